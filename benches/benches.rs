@@ -42,6 +42,24 @@ fn bench_levenshtein(bencher: &mut Bencher) {
     })
 }
 
+#[bench]
+fn bench_bounded_levenshtein(bencher: &mut Bencher) {
+    let a = "Philosopher Friedrich Nietzsche";
+    let b = "Philosopher Jean-Paul Sartre";
+    bencher.iter(|| {
+        strsim::bounded_levenshtein(a, b, 3);
+    })
+}
+
+#[bench]
+fn bench_levenshtein_long(bencher: &mut Bencher) {
+    let a = "Philosopher Friedrich Nietzsche".repeat(10);
+    let b = "Philosopher Jean-Paul Sartre".repeat(10);
+    bencher.iter(|| {
+        strsim::levenshtein(&a, &b);
+    })
+}
+
 #[bench]
 fn bench_levenshtein_on_u8(bencher: &mut Bencher) {
     bencher.iter(|| {
@@ -93,3 +111,13 @@ fn bench_sorensen_dice(bencher: &mut Bencher) {
         strsim::sorensen_dice(a, b);
     })
 }
+
+#[bench]
+fn bench_cyclic_levenshtein(bencher: &mut Bencher) {
+    // dissimilar strings, for which no rotation can be skipped
+    let a = "Philosopher Friedrich Nietzsche ".repeat(16);
+    let b = "Philosopher Jean-Paul Sartre ".repeat(16);
+    bencher.iter(|| {
+        strsim::cyclic_levenshtein(&a, &b);
+    })
+}
@@ -0,0 +1,1020 @@
+//! The alignment behind the Levenshtein distance, as steps turning the first
+//! string into the second.
+
+use std::collections::BTreeMap;
+use std::mem;
+use std::ops::Range;
+
+use crate::{levenshtein, CharWeights};
+
+/// The kind of an `AlignmentStep`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EditKind {
+    /// The characters are equal and kept.
+    Keep,
+    /// The character of the first string is replaced with the character of
+    /// the second string.
+    Substitute,
+    /// The character of the second string is inserted in front of the
+    /// character at `a_index` of the first string.
+    Insert,
+    /// The character of the first string is deleted.
+    Delete,
+}
+
+/// One step of an alignment. The indices are character indices into both
+/// strings, and the byte offsets are the UTF-8 offsets of the same
+/// characters, so terminal and web frontends can use either without scanning
+/// the strings again. For insertions `a_index` is the index of the character
+/// the insertion happens in front of, for deletions `b_index` is the index of
+/// the character of the second string following the deletion. At the end of
+/// a string the index is its number of characters and the byte offset its
+/// length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AlignmentStep {
+    /// What happens in this step.
+    pub kind: EditKind,
+    /// The index in the first string.
+    pub a_index: usize,
+    /// The index in the second string.
+    pub b_index: usize,
+    /// The byte offset in the first string.
+    pub a_byte: usize,
+    /// The byte offset in the second string.
+    pub b_byte: usize,
+}
+
+#[derive(Debug, Clone)]
+enum Task {
+    Align {
+        a_start: usize,
+        a_end: usize,
+        b_start: usize,
+        b_end: usize,
+    },
+    Step(AlignmentStep),
+}
+
+/// An iterator over the steps of an optimal Levenshtein alignment, returned
+/// by `levenshtein_alignment`.
+///
+/// The steps are computed lazily with Hirschberg's algorithm, which only
+/// needs memory linear in the length of the strings. This way even the
+/// alignment of very long strings can be streamed without storing the
+/// complete dynamic programming matrix or all steps at once. Computing all
+/// steps takes about twice as long as calculating the distance.
+#[derive(Debug, Clone)]
+pub struct Alignment {
+    a: Vec<char>,
+    b: Vec<char>,
+    a_bytes: Vec<usize>,
+    b_bytes: Vec<usize>,
+    tasks: Vec<Task>,
+    forward: Vec<usize>,
+    backward: Vec<usize>,
+}
+
+impl Alignment {
+    fn new(a: &str, b: &str) -> Self {
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        let tasks = vec![Task::Align {
+            a_start: 0,
+            a_end: a.len(),
+            b_start: 0,
+            b_end: b.len(),
+        }];
+        Self {
+            a_bytes: byte_offsets(&a),
+            b_bytes: byte_offsets(&b),
+            a,
+            b,
+            tasks,
+            forward: Vec::new(),
+            backward: Vec::new(),
+        }
+    }
+
+    fn step(&self, kind: EditKind, a_index: usize, b_index: usize) -> AlignmentStep {
+        AlignmentStep {
+            kind,
+            a_index,
+            b_index,
+            a_byte: self.a_bytes[a_index],
+            b_byte: self.b_bytes[b_index],
+        }
+    }
+
+    /* Splits the alignment of a[a_start..a_end] with a length of at least two
+    and b[b_start..b_end] at the middle of the first string */
+    fn split(&mut self, a_start: usize, a_end: usize, b_start: usize, b_end: usize) {
+        let a_mid = (a_start + a_end) / 2;
+        let b_range = &self.b[b_start..b_end];
+
+        last_row(
+            self.a[a_start..a_mid].iter(),
+            b_range.iter(),
+            &mut self.forward,
+        );
+        last_row(
+            self.a[a_mid..a_end].iter().rev(),
+            b_range.iter().rev(),
+            &mut self.backward,
+        );
+
+        let mut best = 0;
+        for k in 1..=b_range.len() {
+            let cost = self.forward[k] + self.backward[b_range.len() - k];
+            if cost < self.forward[best] + self.backward[b_range.len() - best] {
+                best = k;
+            }
+        }
+        let b_mid = b_start + best;
+
+        self.tasks.push(Task::Align {
+            a_start: a_mid,
+            a_end,
+            b_start: b_mid,
+            b_end,
+        });
+        self.tasks.push(Task::Align {
+            a_start,
+            a_end: a_mid,
+            b_start,
+            b_end: b_mid,
+        });
+    }
+
+    /* Aligns a single character of the first string with b[b_start..b_end],
+    which is not empty */
+    fn align_char(&mut self, a_index: usize, b_start: usize, b_end: usize) {
+        let a_char = self.a[a_index];
+        let (b_index, kind) = match self.b[b_start..b_end].iter().position(|&c| c == a_char) {
+            Some(offset) => (b_start + offset, EditKind::Keep),
+            None => (b_start, EditKind::Substitute),
+        };
+
+        self.tasks.push(Task::Align {
+            a_start: a_index + 1,
+            a_end: a_index + 1,
+            b_start: b_index + 1,
+            b_end,
+        });
+        let step = self.step(kind, a_index, b_index);
+        self.tasks.push(Task::Step(step));
+        self.tasks.push(Task::Align {
+            a_start: a_index,
+            a_end: a_index,
+            b_start,
+            b_end: b_index,
+        });
+    }
+}
+
+impl Iterator for Alignment {
+    type Item = AlignmentStep;
+
+    fn next(&mut self) -> Option<AlignmentStep> {
+        loop {
+            let (a_start, a_end, b_start, b_end) = match self.tasks.pop()? {
+                Task::Step(step) => return Some(step),
+                Task::Align {
+                    a_start,
+                    a_end,
+                    b_start,
+                    b_end,
+                } => (a_start, a_end, b_start, b_end),
+            };
+
+            let (kind, rest) = if a_start == a_end {
+                if b_start == b_end {
+                    continue;
+                }
+                (EditKind::Insert, (a_start, a_end, b_start + 1, b_end))
+            } else if b_start == b_end {
+                (EditKind::Delete, (a_start + 1, a_end, b_start, b_end))
+            } else if a_end - a_start == 1 {
+                self.align_char(a_start, b_start, b_end);
+                continue;
+            } else {
+                self.split(a_start, a_end, b_start, b_end);
+                continue;
+            };
+
+            self.tasks.push(Task::Align {
+                a_start: rest.0,
+                a_end: rest.1,
+                b_start: rest.2,
+                b_end: rest.3,
+            });
+            return Some(self.step(kind, a_start, b_start));
+        }
+    }
+}
+
+/* The byte offset of every character of a string and its length at the end */
+fn byte_offsets(chars: &[char]) -> Vec<usize> {
+    let mut offsets = Vec::with_capacity(chars.len() + 1);
+    let mut offset = 0;
+    offsets.push(offset);
+    for c in chars {
+        offset += c.len_utf8();
+        offsets.push(offset);
+    }
+    offsets
+}
+
+/* Calculates the last row of the Levenshtein matrix of `a` and `b` into
+`row`, which holds the distances of `a` to every prefix of `b` */
+fn last_row<'c, A, B>(a: A, b: B, row: &mut Vec<usize>)
+where
+    A: Iterator<Item = &'c char>,
+    B: Iterator<Item = &'c char> + Clone,
+{
+    row.clear();
+    row.extend(0..=b.clone().count());
+
+    for (i, a_char) in a.enumerate() {
+        let mut diagonal = mem::replace(&mut row[0], i + 1);
+        for (j, b_char) in b.clone().enumerate() {
+            let substitution = diagonal + usize::from(a_char != b_char);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j + 1] + 1).min(row[j] + 1);
+        }
+    }
+}
+
+/// Returns an iterator over the steps of an optimal alignment of the strings
+/// under the Levenshtein distance, including the kept characters. The number
+/// of steps which are not `EditKind::Keep` equals `levenshtein(a, b)`.
+///
+/// ```
+/// use strsim::{levenshtein_alignment, EditKind};
+///
+/// let kinds: Vec<EditKind> = levenshtein_alignment("cat", "cut").map(|step| step.kind).collect();
+/// assert_eq!(vec![EditKind::Keep, EditKind::Substitute, EditKind::Keep], kinds);
+/// ```
+pub fn levenshtein_alignment(a: &str, b: &str) -> Alignment {
+    Alignment::new(a, b)
+}
+
+/// Returns the edit operations of an optimal alignment of the strings under
+/// the Levenshtein distance, which are the steps of `levenshtein_alignment`
+/// without the kept characters.
+///
+/// ```
+/// use strsim::{levenshtein_editops, AlignmentStep, EditKind};
+///
+/// assert_eq!(
+///     vec![
+///         AlignmentStep { kind: EditKind::Substitute, a_index: 0, b_index: 0, a_byte: 0, b_byte: 0 },
+///         AlignmentStep { kind: EditKind::Substitute, a_index: 4, b_index: 4, a_byte: 4, b_byte: 4 },
+///         AlignmentStep { kind: EditKind::Insert, a_index: 6, b_index: 6, a_byte: 6, b_byte: 6 },
+///     ],
+///     levenshtein_editops("kitten", "sitting")
+/// );
+///
+/// let step = levenshtein_editops("naïve", "naive")[0];
+/// assert_eq!((2, 2), (step.a_index, step.b_index));
+/// assert_eq!((2, 2), (step.a_byte, step.b_byte));
+/// let step = levenshtein_editops("ïx", "ïy")[0];
+/// assert_eq!((1, 1), (step.a_index, step.b_index));
+/// assert_eq!((2, 2), (step.a_byte, step.b_byte));
+/// ```
+pub fn levenshtein_editops(a: &str, b: &str) -> Vec<AlignmentStep> {
+    levenshtein_alignment(a, b)
+        .filter(|step| step.kind != EditKind::Keep)
+        .collect()
+}
+
+/// The number of edit operations of each kind in an optimal alignment.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct EditCounts {
+    /// The number of inserted characters.
+    pub insertions: usize,
+    /// The number of deleted characters.
+    pub deletions: usize,
+    /// The number of substituted characters.
+    pub substitutions: usize,
+    /// The number of swapped adjacent characters, which are only counted by
+    /// `osa_edit_counts`.
+    pub transpositions: usize,
+}
+
+impl EditCounts {
+    /// The total number of edit operations, which is the distance.
+    pub fn distance(&self) -> usize {
+        self.insertions + self.deletions + self.substitutions + self.transpositions
+    }
+}
+
+/// Counts the insertions, deletions and substitutions of the alignment of
+/// `levenshtein_alignment`, without storing its steps. The counts add up to
+/// `levenshtein(a, b)`.
+///
+/// ```
+/// use strsim::{levenshtein_edit_counts, EditCounts};
+///
+/// assert_eq!(
+///     EditCounts { insertions: 1, deletions: 0, substitutions: 2, transpositions: 0 },
+///     levenshtein_edit_counts("kitten", "sitting")
+/// );
+/// ```
+pub fn levenshtein_edit_counts(a: &str, b: &str) -> EditCounts {
+    let mut counts = EditCounts::default();
+    for step in levenshtein_alignment(a, b) {
+        match step.kind {
+            EditKind::Keep => {}
+            EditKind::Substitute => counts.substitutions += 1,
+            EditKind::Insert => counts.insertions += 1,
+            EditKind::Delete => counts.deletions += 1,
+        }
+    }
+    counts
+}
+
+/// Counts the insertions, deletions, substitutions and transpositions of an
+/// optimal alignment under the optimal string alignment distance. The counts
+/// add up to `osa_distance(a, b)`. Only three rows of counts are kept, so the
+/// memory is linear in the length of `b`.
+///
+/// ```
+/// use strsim::{osa_edit_counts, EditCounts};
+///
+/// assert_eq!(
+///     EditCounts { insertions: 0, deletions: 1, substitutions: 0, transpositions: 1 },
+///     osa_edit_counts("abcde", "bace")
+/// );
+/// ```
+pub fn osa_edit_counts(a: &str, b: &str) -> EditCounts {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let first_row = |j: usize| EditCounts {
+        insertions: j,
+        ..EditCounts::default()
+    };
+    let mut prev_two: Vec<EditCounts> = (0..=b.len()).map(first_row).collect();
+    let mut prev = prev_two.clone();
+    let mut curr = prev_two.clone();
+
+    for (i, &a_char) in a.iter().enumerate() {
+        curr[0] = EditCounts {
+            deletions: i + 1,
+            ..EditCounts::default()
+        };
+        for (j, &b_char) in b.iter().enumerate() {
+            /* on ties the first candidate wins, which prefers keeping and
+            substituting characters */
+            let mut best = prev[j];
+            if a_char != b_char {
+                best.substitutions += 1;
+            }
+            let mut deletion = prev[j + 1];
+            deletion.deletions += 1;
+            let mut insertion = curr[j];
+            insertion.insertions += 1;
+            for candidate in [deletion, insertion] {
+                if candidate.distance() < best.distance() {
+                    best = candidate;
+                }
+            }
+            if i > 0 && j > 0 && a_char != b_char && a_char == b[j - 1] && b_char == a[i - 1] {
+                let mut transposition = prev_two[j - 1];
+                transposition.transpositions += 1;
+                if transposition.distance() < best.distance() {
+                    best = transposition;
+                }
+            }
+            curr[j + 1] = best;
+        }
+        mem::swap(&mut prev_two, &mut prev);
+        mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[b.len()]
+}
+
+/// Counts how often characters are kept, substituted, inserted and deleted
+/// in the alignments of many string pairs, e.g. of OCR output and the true
+/// text. The learned costs can be used with `weighted_levenshtein`.
+///
+/// ```
+/// use strsim::{weighted_levenshtein, CharWeights, ConfusionMatrix};
+///
+/// let mut confusions = ConfusionMatrix::new();
+/// for (truth, ocr) in [("O0O", "000"), ("BOX", "B0X"), ("ROOM", "R00M"), ("OK", "OK")] {
+///     confusions.add_pair(truth, ocr);
+/// }
+/// assert_eq!(5, confusions.substitutions('O', '0'));
+/// assert_eq!(1, confusions.kept('O'));
+///
+/// // confusing `O` with `0` becomes cheap
+/// let costs = confusions.costs(10);
+/// assert_eq!(2, costs.substitution_cost('O', '0'));
+/// assert!(weighted_levenshtein("GOOD", "G00D", &costs) < weighted_levenshtein("GOOD", "GXXD", &costs));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfusionMatrix {
+    kept: BTreeMap<char, usize>,
+    substitutions: BTreeMap<(char, char), usize>,
+    insertions: BTreeMap<char, usize>,
+    deletions: BTreeMap<char, usize>,
+}
+
+impl ConfusionMatrix {
+    /// Creates an empty confusion matrix.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Aligns the strings with `levenshtein_alignment` and adds the steps.
+    pub fn add_pair(&mut self, a: &str, b: &str) {
+        self.add_steps(a, b, levenshtein_alignment(a, b));
+    }
+
+    /// Adds the steps of an alignment of `a` and `b`, like the ones of
+    /// `levenshtein_alignment` or `levenshtein_editops`. The characters of
+    /// `a` which are neither substituted nor deleted count as kept, so the
+    /// `EditKind::Keep` steps are optional.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a step has an index outside of the strings.
+    pub fn add_steps<I>(&mut self, a: &str, b: &str, steps: I)
+    where
+        I: IntoIterator<Item = AlignmentStep>,
+    {
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        let mut edited = vec![false; a.len()];
+        for step in steps {
+            match step.kind {
+                EditKind::Keep => {}
+                EditKind::Substitute => {
+                    *self
+                        .substitutions
+                        .entry((a[step.a_index], b[step.b_index]))
+                        .or_insert(0) += 1;
+                    edited[step.a_index] = true;
+                }
+                EditKind::Insert => *self.insertions.entry(b[step.b_index]).or_insert(0) += 1,
+                EditKind::Delete => {
+                    *self.deletions.entry(a[step.a_index]).or_insert(0) += 1;
+                    edited[step.a_index] = true;
+                }
+            }
+        }
+        for (&c, edited) in a.iter().zip(edited) {
+            if !edited {
+                *self.kept.entry(c).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// How often `c` of the first strings was kept.
+    pub fn kept(&self, c: char) -> usize {
+        self.kept.get(&c).copied().unwrap_or(0)
+    }
+
+    /// How often `a` of the first strings was replaced with `b`.
+    pub fn substitutions(&self, a: char, b: char) -> usize {
+        self.substitutions.get(&(a, b)).copied().unwrap_or(0)
+    }
+
+    /// How often `c` of the second strings was inserted.
+    pub fn insertions(&self, c: char) -> usize {
+        self.insertions.get(&c).copied().unwrap_or(0)
+    }
+
+    /// How often `c` of the first strings was deleted.
+    pub fn deletions(&self, c: char) -> usize {
+        self.deletions.get(&c).copied().unwrap_or(0)
+    }
+
+    /// The substitutions with their counts, ordered by the characters.
+    pub fn substitution_counts(&self) -> impl Iterator<Item = ((char, char), usize)> + '_ {
+        self.substitutions
+            .iter()
+            .map(|(&pair, &count)| (pair, count))
+    }
+
+    /* How often `c` occurred in the first strings */
+    fn occurrences(&self, c: char) -> usize {
+        self.kept(c)
+            + self.deletions(c)
+            + self
+                .substitutions
+                .range((c, '\0')..=(c, char::MAX))
+                .map(|(_, &count)| count)
+                .sum::<usize>()
+    }
+
+    /// Learns edit costs from the counts. An edit costs `scale` reduced by
+    /// its observed frequency: replacing `a` with `b` costs `scale` times
+    /// the share of the occurrences of `a` which were not replaced with `b`,
+    /// deleting `c` costs `scale` times the share of the occurrences of `c`
+    /// which were not deleted, and inserting `c` costs `scale` times the
+    /// share of the second strings' `c`s which were not inserted. Every cost
+    /// is at least 1, and edits which were never observed cost `scale`.
+    pub fn costs(&self, scale: usize) -> ConfusionCosts {
+        /* `scale * count` can overflow usize for large scales, and the share
+        never exceeds `scale` as `count <= total` */
+        let cost = |count: usize, total: usize| match (scale as u128 * count as u128)
+            .checked_div(total as u128)
+        {
+            Some(share) => (scale - share as usize).max(1),
+            None => scale,
+        };
+        let mut produced: BTreeMap<char, usize> = BTreeMap::new();
+        for (&c, &count) in self.kept.iter().chain(&self.insertions) {
+            *produced.entry(c).or_insert(0) += count;
+        }
+        for (&(_, b), &count) in &self.substitutions {
+            *produced.entry(b).or_insert(0) += count;
+        }
+
+        ConfusionCosts {
+            scale,
+            substitutions: self
+                .substitutions
+                .iter()
+                .map(|(&(a, b), &count)| ((a, b), cost(count, self.occurrences(a))))
+                .collect(),
+            insertions: self
+                .insertions
+                .iter()
+                .map(|(&c, &count)| (c, cost(count, produced[&c])))
+                .collect(),
+            deletions: self
+                .deletions
+                .iter()
+                .map(|(&c, &count)| (c, cost(count, self.occurrences(c))))
+                .collect(),
+        }
+    }
+}
+
+/// The edit costs learned by `ConfusionMatrix::costs`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfusionCosts {
+    scale: usize,
+    substitutions: BTreeMap<(char, char), usize>,
+    insertions: BTreeMap<char, usize>,
+    deletions: BTreeMap<char, usize>,
+}
+
+impl CharWeights for ConfusionCosts {
+    fn insertion_cost(&self, c: char) -> usize {
+        self.insertions.get(&c).copied().unwrap_or(self.scale)
+    }
+
+    fn deletion_cost(&self, c: char) -> usize {
+        self.deletions.get(&c).copied().unwrap_or(self.scale)
+    }
+
+    fn substitution_cost(&self, a: char, b: char) -> usize {
+        self.substitutions
+            .get(&(a, b))
+            .copied()
+            .unwrap_or(self.scale)
+    }
+}
+
+/// The byte ranges of the kept and the edited characters of both strings of
+/// an alignment, returned by `highlight_spans`. The ranges of every field are
+/// sorted, don't overlap and cover runs of adjacent characters, so they can
+/// be used to slice the strings directly.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct HighlightSpans {
+    /// The kept characters of the first string.
+    pub a_kept: Vec<Range<usize>>,
+    /// The kept characters of the second string.
+    pub b_kept: Vec<Range<usize>>,
+    /// The substituted and deleted characters of the first string.
+    pub a_edited: Vec<Range<usize>>,
+    /// The substituted and inserted characters of the second string.
+    pub b_edited: Vec<Range<usize>>,
+}
+
+/// Converts the steps of an alignment of `a` and `b`, like the ones of
+/// `levenshtein_alignment` or `levenshtein_editops`, into the byte ranges of
+/// the kept and edited characters in the strings, e.g. to highlight a match
+/// in a user interface. Characters without a step count as kept, so the
+/// `EditKind::Keep` steps are optional.
+///
+/// # Panics
+///
+/// Panics if a step has an index outside of the strings.
+///
+/// ```
+/// use strsim::{highlight_spans, levenshtein_editops};
+///
+/// let (a, b) = ("Käse", "Kase");
+/// let spans = highlight_spans(a, b, levenshtein_editops(a, b));
+/// assert_eq!(vec![0..1, 3..5], spans.a_kept);
+/// assert_eq!(vec![1..3], spans.a_edited);
+/// assert_eq!("ä", &a[spans.a_edited[0].clone()]);
+/// assert_eq!(vec![1..2], spans.b_edited);
+/// ```
+pub fn highlight_spans<I>(a: &str, b: &str, steps: I) -> HighlightSpans
+where
+    I: IntoIterator<Item = AlignmentStep>,
+{
+    let mut a_edited = vec![false; a.chars().count()];
+    let mut b_edited = vec![false; b.chars().count()];
+    for step in steps {
+        match step.kind {
+            EditKind::Keep => {}
+            EditKind::Substitute => {
+                a_edited[step.a_index] = true;
+                b_edited[step.b_index] = true;
+            }
+            EditKind::Insert => b_edited[step.b_index] = true,
+            EditKind::Delete => a_edited[step.a_index] = true,
+        }
+    }
+
+    let (a_kept, a_edited) = byte_runs(a, &a_edited);
+    let (b_kept, b_edited) = byte_runs(b, &b_edited);
+    HighlightSpans {
+        a_kept,
+        b_kept,
+        a_edited,
+        b_edited,
+    }
+}
+
+/* The byte ranges of the runs of characters of `s` which are not flagged
+and of the ones which are */
+fn byte_runs(s: &str, flags: &[bool]) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let mut unflagged: Vec<Range<usize>> = Vec::new();
+    let mut flagged: Vec<Range<usize>> = Vec::new();
+    for ((start, c), &flag) in s.char_indices().zip(flags) {
+        let runs = if flag { &mut flagged } else { &mut unflagged };
+        let end = start + c.len_utf8();
+        match runs.last_mut() {
+            Some(last) if last.end == start => last.end = end,
+            _ => runs.push(start..end),
+        }
+    }
+    (unflagged, flagged)
+}
+
+/// How `consensus` resolves positions where the strings disagree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MergePolicy {
+    /// Takes the variant of most strings. Ties are resolved in favor of the
+    /// center string, then the earlier string.
+    Majority,
+    /// Takes the longest variant, so characters win over deletions and
+    /// insertions are kept, e.g. to repair truncated values. Variants of the
+    /// same length are resolved like `Majority`.
+    PreferLonger,
+}
+
+/// Merges near-duplicate strings into a consensus, e.g. to repair a record
+/// from several of its copies.
+///
+/// The string with the smallest total Levenshtein distance to the others is
+/// the center, and every string is aligned with it using
+/// `levenshtein_alignment`. For every character of the center and every gap
+/// between them, each string contributes a variant: the character it aligns
+/// there, which may be deleted, and the characters it inserts. The
+/// variant chosen by the `MergePolicy` is kept. Returns an empty string
+/// without strings.
+///
+/// ```
+/// use strsim::{consensus, MergePolicy};
+///
+/// let copies = ["Jonathan Smith", "Jonathon Smith", "Jonathan Smyth"];
+/// assert_eq!("Jonathan Smith", consensus(&copies, MergePolicy::Majority));
+///
+/// let truncated = ["Main Street", "Main Str"];
+/// assert_eq!("Main Street", consensus(&truncated, MergePolicy::PreferLonger));
+/// ```
+pub fn consensus<S: AsRef<str>>(strings: &[S], policy: MergePolicy) -> String {
+    let strings: Vec<&str> = strings.iter().map(AsRef::as_ref).collect();
+    let center = match (0..strings.len()).min_by_key(|&i| {
+        strings
+            .iter()
+            .map(|other| levenshtein(strings[i], other))
+            .sum::<usize>()
+    }) {
+        Some(center) => center,
+        None => return String::new(),
+    };
+    let center_len = strings[center].chars().count();
+
+    /* the variants of every string for the gaps in front of the characters
+    of the center, after the last one, and for the characters */
+    let mut gaps = vec![vec![String::new(); strings.len()]; center_len + 1];
+    let mut chars = vec![vec![String::new(); strings.len()]; center_len];
+    for (k, string) in strings.iter().enumerate() {
+        let b: Vec<char> = string.chars().collect();
+        for step in levenshtein_alignment(strings[center], string) {
+            match step.kind {
+                EditKind::Keep | EditKind::Substitute => {
+                    chars[step.a_index][k].push(b[step.b_index]);
+                }
+                EditKind::Insert => gaps[step.a_index][k].push(b[step.b_index]),
+                EditKind::Delete => {}
+            }
+        }
+    }
+
+    let mut merged = String::new();
+    for i in 0..=center_len {
+        merged.push_str(choose_variant(&gaps[i], center, policy));
+        if i < center_len {
+            merged.push_str(choose_variant(&chars[i], center, policy));
+        }
+    }
+    merged
+}
+
+/* Picks one of the variants of the strings for a position */
+fn choose_variant(variants: &[String], center: usize, policy: MergePolicy) -> &str {
+    /* the variants with their ranking, where the larger one wins and the
+    earlier string wins ties */
+    let rank = |variant: &String| {
+        let count = variants.iter().filter(|&other| other == variant).count();
+        let len = match policy {
+            MergePolicy::Majority => 0,
+            MergePolicy::PreferLonger => variant.chars().count(),
+        };
+        (len, count, *variant == variants[center])
+    };
+    let mut best = &variants[0];
+    let mut best_rank = rank(best);
+    for variant in &variants[1..] {
+        let variant_rank = rank(variant);
+        if variant_rank > best_rank {
+            best = variant;
+            best_rank = variant_rank;
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{levenshtein, osa_distance};
+
+    /* Applies the steps to `a` and checks that every character of both
+    strings is visited exactly once, in order, at its byte offset */
+    fn apply(a: &str, b: &str, steps: &[AlignmentStep]) -> String {
+        let byte = |s: &str, i: usize| s.char_indices().nth(i).map_or(s.len(), |(byte, _)| byte);
+        let (a_str, b_str) = (a, b);
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        let (mut i, mut j) = (0, 0);
+        let mut result = String::new();
+        for step in steps {
+            assert_eq!((i, j), (step.a_index, step.b_index));
+            assert_eq!((byte(a_str, i), byte(b_str, j)), (step.a_byte, step.b_byte));
+            match step.kind {
+                EditKind::Keep => {
+                    assert_eq!(a[i], b[j]);
+                    result.push(a[i]);
+                    i += 1;
+                    j += 1;
+                }
+                EditKind::Substitute => {
+                    assert_ne!(a[i], b[j]);
+                    result.push(b[j]);
+                    i += 1;
+                    j += 1;
+                }
+                EditKind::Insert => {
+                    result.push(b[j]);
+                    j += 1;
+                }
+                EditKind::Delete => i += 1,
+            }
+        }
+        assert_eq!((a.len(), b.len()), (i, j));
+        result
+    }
+
+    #[test]
+    fn alignment_empty() {
+        assert_eq!(0, levenshtein_alignment("", "").count());
+        let inserts: Vec<EditKind> = levenshtein_alignment("", "ab").map(|s| s.kind).collect();
+        assert_eq!(vec![EditKind::Insert, EditKind::Insert], inserts);
+        let deletes: Vec<EditKind> = levenshtein_alignment("ab", "").map(|s| s.kind).collect();
+        assert_eq!(vec![EditKind::Delete, EditKind::Delete], deletes);
+    }
+
+    #[test]
+    fn alignment_is_optimal() {
+        for (a, b) in [
+            ("kitten", "sitting"),
+            ("sitting", "kitten"),
+            ("a", "bab"),
+            ("ab", "ba"),
+            ("abcdefg", "xaxcxex"),
+            ("öঙ香", "abc香"),
+            ("levenshtein", "frankenstein"),
+            ("cccc", "c"),
+        ] {
+            let steps: Vec<AlignmentStep> = levenshtein_alignment(a, b).collect();
+            assert_eq!(b, apply(a, b, &steps));
+            assert_eq!(
+                levenshtein(a, b),
+                levenshtein_editops(a, b).len(),
+                "{} {}",
+                a,
+                b
+            );
+        }
+    }
+
+    #[test]
+    fn alignment_long_strings() {
+        let a = "0123456789".repeat(300);
+        let mut b = a.clone();
+        b.insert(5, 'x');
+        b.replace_range(1500..1501, "y");
+        b.truncate(2900);
+
+        let steps: Vec<AlignmentStep> = levenshtein_alignment(&a, &b).collect();
+        assert_eq!(b, apply(&a, &b, &steps));
+        assert_eq!(levenshtein(&a, &b), levenshtein_editops(&a, &b).len());
+    }
+
+    #[test]
+    fn edit_counts_add_up() {
+        for (a, b) in [
+            ("", ""),
+            ("", "abc"),
+            ("abc", ""),
+            ("kitten", "sitting"),
+            ("ab", "ba"),
+            ("abcdef", "badcfe"),
+            ("öঙ香", "abc香"),
+            ("levenshtein", "frankenstein"),
+            ("ca", "abc"),
+        ] {
+            let counts = levenshtein_edit_counts(a, b);
+            assert_eq!(levenshtein(a, b), counts.distance());
+            assert_eq!(0, counts.transpositions);
+            // the insertions and deletions make up for the length difference
+            let a_len = a.chars().count();
+            let b_len = b.chars().count();
+            assert_eq!(a_len + counts.insertions, b_len + counts.deletions);
+
+            let counts = osa_edit_counts(a, b);
+            assert_eq!(osa_distance(a, b), counts.distance(), "{} {}", a, b);
+            assert_eq!(a_len + counts.insertions, b_len + counts.deletions);
+        }
+    }
+
+    #[test]
+    fn osa_edit_counts_transpositions() {
+        let counts = osa_edit_counts("abcdef", "badcfe");
+        assert_eq!(3, counts.transpositions);
+        assert_eq!(3, counts.distance());
+        assert_eq!(EditCounts::default(), osa_edit_counts("same", "same"));
+    }
+
+    #[test]
+    fn confusion_matrix_counts() {
+        let mut confusions = ConfusionMatrix::new();
+        confusions.add_pair("kitten", "sitting");
+        assert_eq!(1, confusions.substitutions('k', 's'));
+        assert_eq!(1, confusions.substitutions('e', 'i'));
+        assert_eq!(1, confusions.insertions('g'));
+        assert_eq!(2, confusions.kept('t'));
+        assert_eq!(0, confusions.deletions('n'));
+
+        // editops without the kept characters give the same counts
+        let mut from_editops = ConfusionMatrix::new();
+        from_editops.add_steps(
+            "kitten",
+            "sitting",
+            levenshtein_editops("kitten", "sitting"),
+        );
+        assert_eq!(confusions, from_editops);
+
+        confusions.add_pair("ab", "");
+        assert_eq!(1, confusions.deletions('a'));
+        assert_eq!(
+            vec![(('e', 'i'), 1), (('k', 's'), 1)],
+            confusions.substitution_counts().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn confusion_costs() {
+        let mut confusions = ConfusionMatrix::new();
+        confusions.add_pair("aaaa", "abab");
+        confusions.add_pair("xy", "x");
+        let costs = confusions.costs(100);
+        // half of the `a`s were replaced with `b`
+        assert_eq!(50, costs.substitution_cost('a', 'b'));
+        assert_eq!(100, costs.substitution_cost('b', 'a'));
+        // the only `y` was deleted
+        assert_eq!(1, costs.deletion_cost('y'));
+        assert_eq!(100, costs.deletion_cost('x'));
+        assert_eq!(100, costs.insertion_cost('z'));
+        assert_eq!(
+            100,
+            ConfusionMatrix::new()
+                .costs(100)
+                .substitution_cost('a', 'b')
+        );
+        // exact for scales whose products overflow usize
+        let costs = confusions.costs(usize::MAX);
+        assert_eq!(
+            usize::MAX - usize::MAX / 2,
+            costs.substitution_cost('a', 'b')
+        );
+        assert_eq!(1, costs.deletion_cost('y'));
+    }
+
+    #[test]
+    fn consensus_majority() {
+        let none: [&str; 0] = [];
+        assert_eq!("", consensus(&none, MergePolicy::Majority));
+        assert_eq!("abc", consensus(&["abc"], MergePolicy::Majority));
+        // each string has one error at a different position
+        let copies = ["xbcdef", "abydef", "abcdez", "abcdef"];
+        assert_eq!("abcdef", consensus(&copies, MergePolicy::Majority));
+        // insertions and deletions by a minority are dropped
+        let copies = ["abcd", "abXcd", "acd", "abcd"];
+        assert_eq!("abcd", consensus(&copies, MergePolicy::Majority));
+    }
+
+    #[test]
+    fn consensus_ties() {
+        // with two strings the center is the first one, and it wins ties
+        assert_eq!(
+            "colour",
+            consensus(&["colour", "color"], MergePolicy::Majority)
+        );
+        assert_eq!(
+            "color",
+            consensus(&["color", "colour"], MergePolicy::Majority)
+        );
+        assert_eq!(
+            "colour",
+            consensus(&["color", "colour"], MergePolicy::PreferLonger)
+        );
+    }
+
+    #[test]
+    fn consensus_prefer_longer() {
+        let copies = ["12 Main St", "12 Main Street", "2 Main St"];
+        assert_eq!("12 Main St", consensus(&copies, MergePolicy::Majority));
+        assert_eq!(
+            "12 Main Street",
+            consensus(&copies, MergePolicy::PreferLonger)
+        );
+        assert_eq!(
+            "ö香x",
+            consensus(&["ö香", "香x"], MergePolicy::PreferLonger)
+        );
+    }
+
+    #[test]
+    fn highlight_spans_cover_strings() {
+        for (a, b) in [
+            ("", ""),
+            ("", "ab"),
+            ("kitten", "sitting"),
+            ("öঙ香", "abc香"),
+            ("naïve café", "naive cafe"),
+        ] {
+            let spans = highlight_spans(a, b, levenshtein_alignment(a, b));
+            assert_eq!(spans, highlight_spans(a, b, levenshtein_editops(a, b)));
+
+            let mut a_ranges: Vec<Range<usize>> = spans
+                .a_kept
+                .iter()
+                .chain(&spans.a_edited)
+                .cloned()
+                .collect();
+            a_ranges.sort_by_key(|range| range.start);
+            assert_eq!(
+                a.len(),
+                a_ranges.iter().map(|range| range.len()).sum::<usize>()
+            );
+            assert!(a_ranges.windows(2).all(|pair| pair[0].end == pair[1].start));
+            let kept: String = spans.a_kept.iter().map(|range| &a[range.clone()]).collect();
+            let b_kept: String = spans.b_kept.iter().map(|range| &b[range.clone()]).collect();
+            assert_eq!(kept, b_kept);
+        }
+    }
+
+    #[test]
+    fn highlight_spans_merge_runs() {
+        let spans = highlight_spans("abcdef", "abXYef", levenshtein_editops("abcdef", "abXYef"));
+        assert_eq!(vec![0..2, 4..6], spans.a_kept);
+        assert_eq!(vec![2..4], spans.a_edited);
+        assert_eq!(vec![2..4], spans.b_edited);
+        let spans = highlight_spans("香", "", levenshtein_editops("香", ""));
+        assert_eq!(vec![0..3], spans.a_edited);
+        assert!(spans.a_kept.is_empty() && spans.b_kept.is_empty() && spans.b_edited.is_empty());
+    }
+}
@@ -0,0 +1,244 @@
+//! Running the long computations of this crate from async code.
+//!
+//! Building an index or scoring many candidates takes far longer than an
+//! async task may run without yielding, and would stall the other tasks of
+//! its executor. `spawn_blocking` runs such a computation on a thread of its
+//! own, like the blocking pool of an async runtime, and returns a `Blocking`
+//! future which resolves to its result. It works with any executor and
+//! doesn't depend on a runtime.
+//!
+//! Dropping the future, e.g. because a request timed out or its client went
+//! away, cancels the computation through its `Control`, so the thread stops
+//! at its next check instead of finishing work nobody waits for.
+//!
+//! ```
+//! use strsim::asynchronous::build_vp_tree;
+//! use strsim::metric_space::{Levenshtein, VpTree};
+//! use strsim::StrSimError;
+//!
+//! async fn load(words: Vec<String>) -> Result<VpTree<Levenshtein>, StrSimError> {
+//!     build_vp_tree(Levenshtein, words).await
+//! }
+//! ```
+
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+use crate::metric_space::{FuzzyIndex, TrueMetric, VpTree};
+use crate::{normalized_levenshtein_many_with_control, Control, StrSimError};
+
+/// A computation running on a thread of its own, which resolves to its
+/// result. Dropping it cancels the computation.
+///
+/// A panic of the computation is resumed when the future is polled.
+pub struct Blocking<T> {
+    shared: Arc<Shared<T>>,
+}
+
+struct Shared<T> {
+    cancelled: AtomicBool,
+    state: Mutex<State<T>>,
+}
+
+struct State<T> {
+    /* the result or the panic of the computation once it finished */
+    result: Option<thread::Result<Result<T, StrSimError>>>,
+    waker: Option<Waker>,
+}
+
+/* The state is never left inconsistent by a panic, so a poisoned lock can be
+used as is */
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Runs `computation` on a new thread and returns a future resolving to its
+/// result. `computation` is called with a `Control` which is cancelled once
+/// the future is dropped, and which it passes on to a `_with_control`
+/// function of this crate.
+///
+/// ```
+/// use strsim::asynchronous::spawn_blocking;
+/// use strsim::record::SimilarityGraph;
+/// use strsim::{jaro_winkler, StrSimError};
+///
+/// async fn graph(names: Vec<String>, pairs: Vec<(usize, usize)>) -> Result<SimilarityGraph, StrSimError> {
+///     spawn_blocking(move |control| {
+///         SimilarityGraph::with_control(&names, &pairs, 0.9, |a, b| jaro_winkler(a, b), control)
+///     })
+///     .await
+/// }
+/// ```
+pub fn spawn_blocking<T, F>(computation: F) -> Blocking<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&Control<'_>) -> Result<T, StrSimError> + Send + 'static,
+{
+    let shared = Arc::new(Shared {
+        cancelled: AtomicBool::new(false),
+        state: Mutex::new(State {
+            result: None,
+            waker: None,
+        }),
+    });
+    let thread_shared = Arc::clone(&shared);
+    thread::spawn(move || {
+        let shared = thread_shared;
+        let result = {
+            let control = Control::new().cancel_flag(&shared.cancelled);
+            panic::catch_unwind(AssertUnwindSafe(|| computation(&control)))
+        };
+        let waker = {
+            let mut state = lock(&shared.state);
+            state.result = Some(result);
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    });
+    Blocking { shared }
+}
+
+impl<T> Future for Blocking<T> {
+    type Output = Result<T, StrSimError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = lock(&self.shared.state);
+        match state.result.take() {
+            Some(Ok(result)) => Poll::Ready(result),
+            Some(Err(payload)) => {
+                drop(state);
+                panic::resume_unwind(payload)
+            }
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T> Drop for Blocking<T> {
+    fn drop(&mut self) {
+        self.shared.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+impl<T> Debug for Blocking<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Blocking")
+            .field("finished", &lock(&self.shared.state).result.is_some())
+            .finish()
+    }
+}
+
+/// Builds a `VpTree` of `items` like `VpTree::with_control`, on a thread of
+/// its own.
+pub fn build_vp_tree<M>(metric: M, items: Vec<String>) -> Blocking<VpTree<M>>
+where
+    M: TrueMetric + Send + 'static,
+{
+    spawn_blocking(move |control| VpTree::with_control(metric, items, control))
+}
+
+/// Builds a `FuzzyIndex` of `items` like `FuzzyIndex::with_control`, on a
+/// thread of its own.
+pub fn build_fuzzy_index<M>(
+    metric: M,
+    items: Vec<String>,
+    max_distance: usize,
+) -> Blocking<FuzzyIndex<M>>
+where
+    M: TrueMetric + Send + 'static,
+{
+    spawn_blocking(move |control| FuzzyIndex::with_control(metric, items, max_distance, control))
+}
+
+/// Calculates the similarities of `query` to the `candidates` like
+/// `normalized_levenshtein_many_with_control`, on a thread of its own.
+pub fn normalized_levenshtein_many(query: String, candidates: Vec<String>) -> Blocking<Vec<f64>> {
+    spawn_blocking(move |control| {
+        normalized_levenshtein_many_with_control(&query, &candidates, control)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metric_space::Levenshtein;
+    use std::sync::mpsc;
+    use std::task::Wake;
+
+    struct Unpark(thread::Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    // the smallest executor, which parks the thread until it is woken
+    fn block_on<F: Future + Unpin>(mut future: F) -> F::Output {
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = Pin::new(&mut future).poll(&mut cx) {
+                return output;
+            }
+            thread::park();
+        }
+    }
+
+    #[test]
+    fn builds_the_same_indexes() {
+        let words: Vec<String> = (0..500).map(|i| format!("word{}", i)).collect();
+        let tree = block_on(build_vp_tree(Levenshtein, words.clone())).unwrap();
+        assert_eq!(
+            VpTree::new(Levenshtein, words.clone()).to_bytes(),
+            tree.to_bytes()
+        );
+        let index = block_on(build_fuzzy_index(Levenshtein, words.clone(), 1)).unwrap();
+        assert_eq!(
+            FuzzyIndex::with_max_distance(Levenshtein, words, 1).to_bytes(),
+            index.to_bytes()
+        );
+        let similarities = block_on(normalized_levenshtein_many(
+            "kitten".to_string(),
+            vec!["kitten".to_string(), "sitting".to_string()],
+        ))
+        .unwrap();
+        assert_eq!(2, similarities.len());
+        assert_delta!(1.0, similarities[0]);
+    }
+
+    #[test]
+    fn dropping_cancels() {
+        let (started, running) = mpsc::channel();
+        let (stopped, result) = mpsc::channel();
+        let future = spawn_blocking(move |control| {
+            started.send(()).unwrap();
+            while !control.is_cancelled() {
+                thread::yield_now();
+            }
+            stopped.send(()).unwrap();
+            Err::<(), _>(StrSimError::Cancelled)
+        });
+        running.recv().unwrap();
+        drop(future);
+        result.recv().unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "computation failed")]
+    fn resumes_panics() {
+        let future = spawn_blocking::<(), _>(|_| panic!("computation failed"));
+        let _ = block_on(future);
+    }
+}
@@ -0,0 +1,299 @@
+//! Autocompletion of prefixes which may contain typos.
+//!
+//! An `Autocomplete` stores words with weights, like their frequency, in a
+//! trie. A query returns the words starting with a prefix within a maximum
+//! number of edits (the Levenshtein distance of the query and the closest
+//! prefix of the word), ranked by the number of edits and their weight.
+//!
+//! ```
+//! use strsim::autocomplete::Autocomplete;
+//!
+//! let mut words = Autocomplete::new();
+//! words.insert("apple", 10);
+//! words.insert("application", 30);
+//! words.insert("banana", 20);
+//!
+//! let completions = words.complete("apl", 1, 10);
+//! assert_eq!(2, completions.len());
+//! assert_eq!("application", completions[0].word);
+//! assert_eq!("apple", completions[1].word);
+//! ```
+
+use std::iter::FromIterator;
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    /* The children sorted by their character */
+    children: Vec<(char, usize)>,
+    weight: Option<u64>,
+}
+
+/// A word found by `Autocomplete::complete`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Completion {
+    /// The stored word.
+    pub word: String,
+    /// The weight the word was stored with.
+    pub weight: u64,
+    /// The number of edits turning the query into a prefix of the word.
+    pub edits: usize,
+}
+
+/// A trie of weighted words for autocompletion, see the module documentation.
+#[derive(Debug, Clone)]
+pub struct Autocomplete {
+    nodes: Vec<Node>,
+    len: usize,
+}
+
+impl Autocomplete {
+    /// Creates an empty `Autocomplete`.
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Node::default()],
+            len: 0,
+        }
+    }
+
+    /// The number of stored words.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether no words are stored.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Stores `word` with `weight`, replacing the weight of a stored word.
+    /// Returns the replaced weight.
+    pub fn insert(&mut self, word: &str, weight: u64) -> Option<u64> {
+        let mut node = 0;
+        for c in word.chars() {
+            node = match self.nodes[node]
+                .children
+                .binary_search_by_key(&c, |&(c, _)| c)
+            {
+                Ok(index) => self.nodes[node].children[index].1,
+                Err(index) => {
+                    let child = self.nodes.len();
+                    self.nodes.push(Node::default());
+                    self.nodes[node].children.insert(index, (c, child));
+                    child
+                }
+            };
+        }
+
+        let replaced = self.nodes[node].weight.replace(weight);
+        if replaced.is_none() {
+            self.len += 1;
+        }
+        replaced
+    }
+
+    /// Returns the weight of `word`, or `None` if it is not stored.
+    pub fn get(&self, word: &str) -> Option<u64> {
+        let mut node = 0;
+        for c in word.chars() {
+            let children = &self.nodes[node].children;
+            let index = children.binary_search_by_key(&c, |&(c, _)| c).ok()?;
+            node = children[index].1;
+        }
+        self.nodes[node].weight
+    }
+
+    /// Returns up to `limit` words which start with `prefix` after at most
+    /// `max_edits` insertions, deletions or substitutions. The words with
+    /// fewer edits come first, words with the same number of edits are
+    /// sorted by descending weight and then alphabetically.
+    ///
+    /// ```
+    /// use strsim::autocomplete::Autocomplete;
+    ///
+    /// let words: Autocomplete = vec![("hello", 5), ("help", 8), ("yellow", 9)].into_iter().collect();
+    ///
+    /// let exact = words.complete("hel", 0, 10);
+    /// assert_eq!(vec!["help", "hello"], exact.iter().map(|c| c.word.as_str()).collect::<Vec<_>>());
+    ///
+    /// let fuzzy = words.complete("hel", 1, 10);
+    /// assert_eq!("yellow", fuzzy[2].word);
+    /// assert_eq!(1, fuzzy[2].edits);
+    /// ```
+    pub fn complete(&self, prefix: &str, max_edits: usize, limit: usize) -> Vec<Completion> {
+        let prefix: Vec<char> = prefix.chars().collect();
+        let row: Vec<usize> = (0..=prefix.len()).collect();
+        let mut completions = Vec::new();
+        let mut word = String::new();
+        self.search(
+            0,
+            &prefix,
+            &row,
+            row[prefix.len()],
+            max_edits,
+            &mut word,
+            &mut completions,
+        );
+
+        completions.sort_by(|a, b| {
+            a.edits
+                .cmp(&b.edits)
+                .then(b.weight.cmp(&a.weight))
+                .then_with(|| a.word.cmp(&b.word))
+        });
+        completions.truncate(limit);
+        completions
+    }
+
+    /* Collects the words below `node` into `completions`. `row` holds the
+    Levenshtein distances of `word`, the characters leading to `node`, to
+    every prefix of `prefix`, and `edits` the lowest distance of any prefix
+    of `word` to the complete `prefix` */
+    #[allow(clippy::too_many_arguments)]
+    fn search(
+        &self,
+        node: usize,
+        prefix: &[char],
+        row: &[usize],
+        edits: usize,
+        max_edits: usize,
+        word: &mut String,
+        completions: &mut Vec<Completion>,
+    ) {
+        if let Some(weight) = self.nodes[node].weight {
+            if edits <= max_edits {
+                completions.push(Completion {
+                    word: word.clone(),
+                    weight,
+                    edits,
+                });
+            }
+        }
+
+        for &(c, child) in &self.nodes[node].children {
+            let mut next = Vec::with_capacity(row.len());
+            next.push(row[0] + 1);
+            for (j, &prefix_char) in prefix.iter().enumerate() {
+                let substitution = row[j] + usize::from(prefix_char != c);
+                next.push(substitution.min(row[j + 1] + 1).min(next[j] + 1));
+            }
+
+            let child_edits = edits.min(next[prefix.len()]);
+            /* once the whole prefix matched, all words below qualify */
+            if child_edits <= max_edits || next.iter().any(|&distance| distance <= max_edits) {
+                word.push(c);
+                self.search(
+                    child,
+                    prefix,
+                    &next,
+                    child_edits,
+                    max_edits,
+                    word,
+                    completions,
+                );
+                word.pop();
+            }
+        }
+    }
+}
+
+impl Default for Autocomplete {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> FromIterator<(&'a str, u64)> for Autocomplete {
+    fn from_iter<I: IntoIterator<Item = (&'a str, u64)>>(iter: I) -> Self {
+        let mut autocomplete = Self::new();
+        for (word, weight) in iter {
+            autocomplete.insert(word, weight);
+        }
+        autocomplete
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(completions: &[Completion]) -> Vec<&str> {
+        completions
+            .iter()
+            .map(|completion| completion.word.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn insert_and_get() {
+        let mut autocomplete = Autocomplete::new();
+        assert!(autocomplete.is_empty());
+        assert_eq!(None, autocomplete.insert("abc", 1));
+        assert_eq!(None, autocomplete.insert("ab", 2));
+        assert_eq!(Some(1), autocomplete.insert("abc", 3));
+        assert_eq!(2, autocomplete.len());
+        assert_eq!(Some(3), autocomplete.get("abc"));
+        assert_eq!(Some(2), autocomplete.get("ab"));
+        assert_eq!(None, autocomplete.get("a"));
+        assert_eq!(None, autocomplete.get("abcd"));
+    }
+
+    #[test]
+    fn complete_exact_prefix() {
+        let autocomplete: Autocomplete = vec![("car", 1), ("cart", 3), ("care", 2), ("cat", 9)]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            vec!["cart", "care", "car"],
+            words(&autocomplete.complete("car", 0, 10))
+        );
+        assert_eq!(
+            vec!["cat", "cart"],
+            words(&autocomplete.complete("ca", 0, 2))
+        );
+        assert!(autocomplete.complete("dog", 0, 10).is_empty());
+    }
+
+    #[test]
+    fn complete_empty_prefix() {
+        let autocomplete: Autocomplete = vec![("b", 1), ("a", 1), ("c", 2)].into_iter().collect();
+        assert_eq!(
+            vec!["c", "a", "b"],
+            words(&autocomplete.complete("", 0, 10))
+        );
+    }
+
+    #[test]
+    fn complete_with_typos() {
+        let autocomplete: Autocomplete = vec![("restaurant", 5), ("rest", 1), ("forest", 2)]
+            .into_iter()
+            .collect();
+
+        // substitution
+        let completions = autocomplete.complete("resy", 1, 10);
+        assert_eq!(vec!["restaurant", "rest"], words(&completions));
+        assert!(completions.iter().all(|completion| completion.edits == 1));
+
+        // deletion and insertion
+        assert_eq!(
+            vec!["restaurant"],
+            words(&autocomplete.complete("restuarant", 2, 10))
+        );
+        assert_eq!(
+            vec!["restaurant"],
+            words(&autocomplete.complete("resttau", 1, 10))
+        );
+
+        // the edits are counted against the closest prefix of the word
+        let completions = autocomplete.complete("frest", 1, 10);
+        assert_eq!(vec!["restaurant", "forest", "rest"], words(&completions));
+    }
+
+    #[test]
+    fn complete_counts_lowest_edits() {
+        let autocomplete: Autocomplete = vec![("abc", 1)].into_iter().collect();
+        let completions = autocomplete.complete("abx", 2, 10);
+        assert_eq!(1, completions[0].edits);
+        assert!(autocomplete.complete("xyz", 2, 10).is_empty());
+        assert_eq!(3, autocomplete.complete("xyz", 3, 10)[0].edits);
+    }
+}
@@ -0,0 +1,201 @@
+//! Stopping long-running computations from another thread and reporting
+//! their progress.
+
+use std::fmt::{self, Debug, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::StrSimError;
+
+/* The number of steps between two checks for cancellation and progress
+reports */
+const CHECK_INTERVAL: usize = 64;
+
+/// Controls a long-running computation, like building an index or scoring
+/// many record pairs, which is checked for cancellation and reports its
+/// progress periodically. A cancelled computation returns
+/// `StrSimError::Cancelled`. The callbacks are `Sync`, so one control can be
+/// shared by computations running in parallel.
+///
+/// ```
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use strsim::metric_space::{Levenshtein, VpTree};
+/// use strsim::{Control, StrSimError};
+///
+/// let words: Vec<String> = (0..1000).map(|i| i.to_string()).collect();
+/// let cancelled = AtomicBool::new(false);
+/// let control = Control::new().cancel_flag(&cancelled);
+/// assert!(VpTree::with_control(Levenshtein, words.clone(), &control).is_ok());
+///
+/// // usually set by another thread, e.g. when a request is dropped
+/// cancelled.store(true, Ordering::Relaxed);
+/// assert_eq!(
+///     Some(StrSimError::Cancelled),
+///     VpTree::with_control(Levenshtein, words, &control).err()
+/// );
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct Control<'a> {
+    flag: Option<&'a AtomicBool>,
+    callback: Option<&'a (dyn Fn() -> bool + Sync)>,
+    progress: Option<&'a (dyn Fn(usize, usize) + Sync)>,
+}
+
+impl<'a> Control<'a> {
+    /// Creates a control which never cancels.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the computation once `flag` is set.
+    pub fn cancel_flag(mut self, flag: &'a AtomicBool) -> Self {
+        self.flag = Some(flag);
+        self
+    }
+
+    /// Cancels the computation once `callback` returns true.
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use strsim::Control;
+    ///
+    /// let deadline = Instant::now() + Duration::from_secs(10);
+    /// let timeout = || Instant::now() > deadline;
+    /// let control = Control::new().cancel_when(&timeout);
+    /// assert!(!control.is_cancelled());
+    /// ```
+    pub fn cancel_when(mut self, callback: &'a (dyn Fn() -> bool + Sync)) -> Self {
+        self.callback = Some(callback);
+        self
+    }
+
+    /// Calls `progress` with the number of steps done and the total number
+    /// of steps of the computation, every few steps and after the last one.
+    /// What a step is depends on the computation, like a scored pair or an
+    /// item added to an index.
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use strsim::record::SimilarityGraph;
+    /// use strsim::{jaro, Control};
+    ///
+    /// let names = vec!["Jon"; 1000];
+    /// let pairs: Vec<(usize, usize)> = (1..1000).map(|j| (0, j)).collect();
+    /// let reports = AtomicUsize::new(0);
+    /// let progress = |_done: usize, total: usize| {
+    ///     assert_eq!(999, total);
+    ///     reports.fetch_add(1, Ordering::Relaxed);
+    /// };
+    /// let control = Control::new().on_progress(&progress);
+    /// SimilarityGraph::with_control(&names, &pairs, 0.9, |a, b| jaro(a, b), &control).unwrap();
+    /// assert_eq!(16, reports.load(Ordering::Relaxed));
+    /// ```
+    pub fn on_progress(mut self, progress: &'a (dyn Fn(usize, usize) + Sync)) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Whether the computation should be cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.flag.map_or(false, |flag| flag.load(Ordering::Relaxed))
+            || self.callback.map_or(false, |callback| callback())
+    }
+
+    /* Called after every step of a computation with the number of steps
+    done so far. Reports the progress and checks for cancellation every
+    `CHECK_INTERVAL` steps and after the last one */
+    pub(crate) fn step(&self, done: usize, total: usize) -> Result<(), StrSimError> {
+        if done % CHECK_INTERVAL == 0 || done == total {
+            if let Some(progress) = self.progress {
+                progress(done, total);
+            }
+            self.cancelled()
+        } else {
+            Ok(())
+        }
+    }
+
+    /* Checks for cancellation every `CHECK_INTERVAL` calls, between the
+    steps of a computation whose single steps take long */
+    pub(crate) fn check(&self, calls: usize) -> Result<(), StrSimError> {
+        if calls % CHECK_INTERVAL == 0 {
+            self.cancelled()
+        } else {
+            Ok(())
+        }
+    }
+
+    fn cancelled(&self) -> Result<(), StrSimError> {
+        if self.is_cancelled() {
+            Err(StrSimError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Debug for Control<'_> {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Control")
+            .field("flag", &self.flag)
+            .field("callback", &self.callback.map(|_| "Fn() -> bool"))
+            .field("progress", &self.progress.map(|_| "Fn(usize, usize)"))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Mutex;
+
+    #[test]
+    fn never_cancelled() {
+        let control = Control::new();
+        assert!(!control.is_cancelled());
+        assert!((0..1000).all(|done| control.step(done, 1000).is_ok()));
+    }
+
+    #[test]
+    fn cancel_flag() {
+        let flag = AtomicBool::new(false);
+        let control = Control::new().cancel_flag(&flag);
+        assert!(!control.is_cancelled());
+        flag.store(true, Ordering::Relaxed);
+        assert!(control.is_cancelled());
+        assert_eq!(Err(StrSimError::Cancelled), control.step(0, 10));
+        assert_eq!(Err(StrSimError::Cancelled), control.check(0));
+    }
+
+    #[test]
+    fn step_checks_periodically() {
+        let checks = AtomicUsize::new(0);
+        let callback = || {
+            checks.fetch_add(1, Ordering::Relaxed);
+            false
+        };
+        let control = Control::new().cancel_when(&callback);
+        for done in 1..=CHECK_INTERVAL * 3 {
+            control.step(done, CHECK_INTERVAL * 3 + 1).unwrap();
+        }
+        assert_eq!(3, checks.load(Ordering::Relaxed));
+        control
+            .step(CHECK_INTERVAL * 3 + 1, CHECK_INTERVAL * 3 + 1)
+            .unwrap();
+        assert_eq!(4, checks.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn progress_reports() {
+        let reports = Mutex::new(Vec::new());
+        let progress = |done, total| reports.lock().unwrap().push((done, total));
+        let control = Control::new().on_progress(&progress);
+        for done in 1..=150 {
+            control.step(done, 150).unwrap();
+        }
+        assert_eq!(
+            vec![(64, 150), (128, 150), (150, 150)],
+            *reports.lock().unwrap()
+        );
+    }
+}
@@ -0,0 +1,205 @@
+//! Estimating the Levenshtein distance of strings too long to compare
+//! exactly, by comparing a sample of aligned chunks.
+
+use std::cmp::max;
+
+use crate::levenshtein;
+
+/// Estimates the normalized Levenshtein similarity of very long strings from
+/// a sample of chunks, with confidence bounds.
+///
+/// Both strings are divided into the same number of chunks of about
+/// `chunk_len` characters, proportionally to their lengths, so the `i`-th
+/// chunk of one string is aligned with the `i`-th chunk of the other. The
+/// sum of the distances of all chunk pairs is the distance of an alignment
+/// of the complete strings. It is an upper bound of their Levenshtein
+/// distance and close to it, as long as the edits don't shift text across
+/// many chunk boundaries. Only `samples` evenly spaced chunk pairs are
+/// compared, and the distance of the remaining pairs is extrapolated from
+/// them. This takes time linear in the length of the strings for a fixed
+/// chunk length and sample count, instead of quadratic.
+///
+/// ```
+/// use strsim::LevenshteinEstimator;
+///
+/// let a = "abcdefghij".repeat(50_000);
+/// let b = a.replace("e", "x");
+/// let estimate = LevenshteinEstimator::default().estimate(&a, &b);
+/// assert!((estimate.similarity - 0.9).abs() < 0.01);
+/// assert!(estimate.lower <= estimate.similarity && estimate.similarity <= estimate.upper);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevenshteinEstimator {
+    chunk_len: usize,
+    samples: usize,
+    z: f64,
+}
+
+/// The result of `LevenshteinEstimator::estimate`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimilarityEstimate {
+    /// The estimated normalized Levenshtein similarity.
+    pub similarity: f64,
+    /// The lower confidence bound of the similarity.
+    pub lower: f64,
+    /// The upper confidence bound of the similarity.
+    pub upper: f64,
+    /// The number of chunk pairs that were compared.
+    pub sampled_chunks: usize,
+    /// The number of chunk pairs the strings were divided into. When all of
+    /// them were compared, the bounds equal the similarity.
+    pub total_chunks: usize,
+}
+
+impl LevenshteinEstimator {
+    /// Creates an estimator comparing `samples` chunk pairs of `chunk_len`
+    /// characters, with 95% confidence bounds. Both values are at least 1.
+    pub fn new(chunk_len: usize, samples: usize) -> Self {
+        Self {
+            chunk_len: max(chunk_len, 1),
+            samples: max(samples, 1),
+            z: 1.96,
+        }
+    }
+
+    /// Sets the number of standard errors between the estimate and its
+    /// bounds, which is 1.96 for 95% confidence by default.
+    pub fn z(mut self, z: f64) -> Self {
+        self.z = z;
+        self
+    }
+
+    /// Estimates the normalized Levenshtein similarity of the strings.
+    pub fn estimate(&self, a: &str, b: &str) -> SimilarityEstimate {
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        let max_len = max(a.len(), b.len());
+        if max_len == 0 {
+            return SimilarityEstimate {
+                similarity: 1.0,
+                lower: 1.0,
+                upper: 1.0,
+                sampled_chunks: 0,
+                total_chunks: 0,
+            };
+        }
+
+        let total_chunks = (max_len + self.chunk_len - 1) / self.chunk_len;
+        let sampled_chunks = self.samples.min(total_chunks);
+        let chunk = |s: &[char], i: usize| -> String {
+            s[i * s.len() / total_chunks..(i + 1) * s.len() / total_chunks]
+                .iter()
+                .collect()
+        };
+
+        let distances: Vec<f64> = (0..sampled_chunks)
+            .map(|j| {
+                /* the middle of the j-th of `sampled_chunks` equal parts */
+                let i = (2 * j + 1) * total_chunks / (2 * sampled_chunks);
+                levenshtein(&chunk(&a, i), &chunk(&b, i)) as f64
+            })
+            .collect();
+
+        let n = sampled_chunks as f64;
+        let total = total_chunks as f64;
+        let mean = distances.iter().sum::<f64>() / n;
+        let standard_error = if sampled_chunks < total_chunks && sampled_chunks > 1 {
+            let variance = distances
+                .iter()
+                .map(|d| (d - mean) * (d - mean))
+                .sum::<f64>()
+                / (n - 1.0);
+            total * (variance / n * (1.0 - n / total)).sqrt()
+        } else {
+            0.0
+        };
+
+        /* the distance is at least the length difference and at most the
+        length of the longer string */
+        let min_dist = (a.len() as f64 - b.len() as f64).abs();
+        let max_dist = max_len as f64;
+        let dist = (total * mean).max(min_dist).min(max_dist);
+        let low_dist = (dist - self.z * standard_error).max(min_dist);
+        let high_dist = (dist + self.z * standard_error).min(max_dist);
+
+        SimilarityEstimate {
+            similarity: 1.0 - dist / max_dist,
+            lower: 1.0 - high_dist / max_dist,
+            upper: 1.0 - low_dist / max_dist,
+            sampled_chunks,
+            total_chunks,
+        }
+    }
+}
+
+impl Default for LevenshteinEstimator {
+    /// Compares 64 chunk pairs of 1000 characters.
+    fn default() -> Self {
+        Self::new(1000, 64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normalized_levenshtein;
+
+    #[test]
+    fn estimate_empty() {
+        let estimate = LevenshteinEstimator::default().estimate("", "");
+        assert_delta!(1.0, estimate.similarity);
+        assert_eq!(0, estimate.total_chunks);
+
+        let estimate = LevenshteinEstimator::default().estimate("", "abc");
+        assert_delta!(0.0, estimate.similarity);
+        assert_delta!(0.0, estimate.upper);
+    }
+
+    #[test]
+    fn estimate_short_strings_is_exact() {
+        for (a, b) in [("kitten", "sitting"), ("abc", "abc"), ("öঙ香", "abc香")] {
+            let estimate = LevenshteinEstimator::default().estimate(a, b);
+            assert_eq!(1, estimate.total_chunks);
+            assert_delta!(normalized_levenshtein(a, b), estimate.similarity);
+            assert_delta!(estimate.similarity, estimate.lower);
+            assert_delta!(estimate.similarity, estimate.upper);
+        }
+    }
+
+    #[test]
+    fn estimate_all_chunks_has_no_error() {
+        let a = "the quick brown fox ".repeat(20);
+        let b = a.replace("quick", "slow");
+        let estimate = LevenshteinEstimator::new(50, 100).estimate(&a, &b);
+        assert_eq!(estimate.total_chunks, estimate.sampled_chunks);
+        assert_delta!(estimate.lower, estimate.upper);
+        // the chunk-wise alignment is close to the optimal one
+        assert!(estimate.similarity <= normalized_levenshtein(&a, &b) + 1e-9);
+        assert!(normalized_levenshtein(&a, &b) - estimate.similarity < 0.05);
+    }
+
+    #[test]
+    fn estimate_bounds_cover_similarity() {
+        let a: String = (0..20_000)
+            .map(|i| char::from(b'a' + (i * 7 % 26) as u8))
+            .collect();
+        let mut b = a.clone();
+        // a dense block of edits in the first half only
+        b.replace_range(1000..3000, &"x".repeat(2000));
+        let exact = normalized_levenshtein(&a, &b);
+
+        let estimate = LevenshteinEstimator::new(200, 20).estimate(&a, &b);
+        assert_eq!(100, estimate.total_chunks);
+        assert_eq!(20, estimate.sampled_chunks);
+        assert!(estimate.lower < estimate.upper);
+        assert!(estimate.lower <= exact && exact <= estimate.upper);
+    }
+
+    #[test]
+    fn estimate_respects_length_difference() {
+        let a = "a".repeat(10_000);
+        let b = "a".repeat(5_000);
+        let estimate = LevenshteinEstimator::new(100, 10).estimate(&a, &b);
+        assert!(estimate.upper <= 0.5 + 1e-9);
+    }
+}
@@ -0,0 +1,942 @@
+//! Selecting the candidates similar to a query.
+//!
+//! The functions of this module return lazy iterators, so the candidates are
+//! only scored while the iterator is consumed. Callers needing only the first
+//! few matches can stop early without scoring every candidate. `top_k` and
+//! `best_match` rank the candidates with a `ScoreCmp`, `score_metrics`
+//! scores them with several metrics at once, and `score_nullable` scores
+//! candidates which may be null.
+
+use std::cmp::{min, Ordering};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hash};
+
+use crate::{
+    damerau_levenshtein, jaro, jaro_winkler_from_jaro, levenshtein, normalized_similarity,
+    sorensen_dice, trim_common_affix, Control, Denominator, StrSimError,
+};
+
+/// Returns a lazy iterator over the candidates whose similarity to `query`
+/// is at least `threshold`, together with their similarity. The candidates
+/// are returned in their original order.
+///
+/// `scorer` is called with the query, a candidate and the threshold and
+/// returns the similarity, or `None` when the similarity is below the
+/// threshold. Scorers like `normalized_levenshtein_cutoff` use the threshold
+/// to stop early for dissimilar candidates. Any similarity metric can be used
+/// with `matches_above`.
+///
+/// ```
+/// use strsim::extract::matches_above_with;
+/// use strsim::normalized_levenshtein_cutoff;
+///
+/// let cities = ["Berlin", "Bern", "Boston", "Bergen"];
+/// let mut matches = matches_above_with("Berln", &cities, 0.7, normalized_levenshtein_cutoff);
+///
+/// let (city, similarity) = matches.next().unwrap();
+/// assert_eq!("Berlin", *city);
+/// assert!((similarity - 0.83333).abs() < 0.00001);
+/// assert_eq!("Bern", *matches.next().unwrap().0);
+/// ```
+pub fn matches_above_with<'a, S, F>(
+    query: &'a str,
+    candidates: &'a [S],
+    threshold: f64,
+    mut scorer: F,
+) -> impl Iterator<Item = (&'a S, f64)> + 'a
+where
+    S: AsRef<str>,
+    F: FnMut(&str, &str, f64) -> Option<f64> + 'a,
+{
+    candidates.iter().filter_map(move |candidate| {
+        scorer(query, candidate.as_ref(), threshold).map(|similarity| (candidate, similarity))
+    })
+}
+
+/// Like `matches_above_with`, but for metrics without a cutoff, which always
+/// calculate the complete similarity.
+///
+/// ```
+/// use strsim::extract::matches_above;
+/// use strsim::jaro_winkler;
+///
+/// let names = vec!["Martha".to_string(), "Marhta".to_string(), "Dwayne".to_string()];
+/// let matches: Vec<&String> = matches_above("Martha", &names, 0.9, jaro_winkler)
+///     .map(|(name, _)| name)
+///     .collect();
+/// assert_eq!(vec!["Martha", "Marhta"], matches);
+/// ```
+pub fn matches_above<'a, S, F>(
+    query: &'a str,
+    candidates: &'a [S],
+    threshold: f64,
+    mut metric: F,
+) -> impl Iterator<Item = (&'a S, f64)> + 'a
+where
+    S: AsRef<str>,
+    F: FnMut(&str, &str) -> f64 + 'a,
+{
+    matches_above_with(query, candidates, threshold, move |a, b, threshold| {
+        Some(metric(a, b)).filter(|&similarity| similarity >= threshold)
+    })
+}
+
+/// How `ScoreCmp` orders candidates with equal scores.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TieBreak {
+    /// The candidate coming first in the candidates comes first.
+    Original,
+    /// The candidates are ordered alphabetically, so the order does not
+    /// depend on the order of the candidates.
+    Alphabetical,
+}
+
+/// Ranks scored candidates deterministically.
+///
+/// Scores are rounded to multiples of `epsilon` before they are compared, so
+/// scores differing only by floating point rounding, which can differ between
+/// platforms and algorithms, rank the same. Candidates with equal scores are
+/// ordered by the `TieBreak`. `NaN` scores rank last.
+///
+/// ```
+/// use strsim::extract::{ScoreCmp, TieBreak};
+///
+/// let scores = |_: &str, candidate: &str| match candidate {
+///     "b" => 0.3,
+///     "a" => 0.1 + 0.2,
+///     _ => 0.0,
+/// };
+/// let candidates = ["b", "c", "a"];
+/// // 0.1 + 0.2 is 0.30000000000000004, which is equal within the epsilon
+/// let ranked = ScoreCmp::default().tie_break(TieBreak::Alphabetical).top_k("", &candidates, 2, scores);
+/// assert_eq!(vec!["a", "b"], ranked.iter().map(|(c, _)| **c).collect::<Vec<_>>());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScoreCmp {
+    epsilon: f64,
+    tie_break: TieBreak,
+}
+
+impl ScoreCmp {
+    /// Sets the precision of the scores, which is `1e-12` by default. With 0.0
+    /// the scores are compared exactly.
+    pub fn epsilon(mut self, epsilon: f64) -> Self {
+        self.epsilon = epsilon.abs();
+        self
+    }
+
+    /// Sets how candidates with equal scores are ordered, which is
+    /// `TieBreak::Original` by default.
+    pub fn tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.tie_break = tie_break;
+        self
+    }
+
+    /* The score rounded to the precision, with NaN below every score */
+    fn key(&self, score: f64) -> f64 {
+        if score.is_nan() {
+            f64::NEG_INFINITY
+        } else if self.epsilon > 0.0 {
+            (score / self.epsilon).round()
+        } else {
+            score
+        }
+    }
+
+    /// Compares two scores, which are equal if they round to the same
+    /// multiple of the epsilon.
+    ///
+    /// ```
+    /// use std::cmp::Ordering;
+    /// use strsim::extract::ScoreCmp;
+    ///
+    /// let cmp = ScoreCmp::default();
+    /// assert_eq!(Ordering::Equal, cmp.cmp_scores(0.1 + 0.2, 0.3));
+    /// assert_eq!(Ordering::Less, cmp.cmp_scores(0.2, 0.3));
+    /// assert_eq!(Ordering::Less, cmp.cmp_scores(f64::NAN, 0.0));
+    /// ```
+    pub fn cmp_scores(&self, a: f64, b: f64) -> Ordering {
+        let (a, b) = (self.key(a), self.key(b));
+        a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+    }
+
+    /// Compares two scored candidates by their rank: `Less` means that `a`
+    /// ranks before `b`, because of its higher score or the tie break.
+    /// `index` is the position of a candidate in the candidates.
+    pub fn cmp_ranks(&self, a: (usize, &str, f64), b: (usize, &str, f64)) -> Ordering {
+        let (a_index, a_candidate, a_score) = a;
+        let (b_index, b_candidate, b_score) = b;
+        self.cmp_scores(b_score, a_score)
+            .then_with(|| match self.tie_break {
+                TieBreak::Original => Ordering::Equal,
+                TieBreak::Alphabetical => a_candidate.cmp(b_candidate),
+            })
+            .then(a_index.cmp(&b_index))
+    }
+
+    /// Returns the `k` candidates with the highest similarity to `query` by
+    /// `metric`, with their similarity, ranked by `cmp_ranks`.
+    pub fn top_k<'a, S, F>(
+        &self,
+        query: &str,
+        candidates: &'a [S],
+        k: usize,
+        mut metric: F,
+    ) -> Vec<(&'a S, f64)>
+    where
+        S: AsRef<str>,
+        F: FnMut(&str, &str) -> f64,
+    {
+        let mut scored: Vec<(usize, f64)> = candidates
+            .iter()
+            .enumerate()
+            .map(|(i, candidate)| (i, metric(query, candidate.as_ref())))
+            .collect();
+        scored.sort_unstable_by(|&(a, a_score), &(b, b_score)| {
+            self.cmp_ranks(
+                (a, candidates[a].as_ref(), a_score),
+                (b, candidates[b].as_ref(), b_score),
+            )
+        });
+        scored
+            .into_iter()
+            .take(k)
+            .map(|(i, score)| (&candidates[i], score))
+            .collect()
+    }
+
+    /// Returns the `k` candidates with the highest similarity to `query`
+    /// like `top_k`, but only verifies the candidates which can still enter
+    /// the top `k`.
+    ///
+    /// `scorer` is called with the query, a candidate and the lowest
+    /// similarity which can still enter the top `k`, which is negative
+    /// infinity until `k` candidates are found. It returns the similarity, or
+    /// `None` when the similarity is certainly below the cutoff, like the
+    /// scorers of `matches_above_with`. Scorers like
+    /// `normalized_levenshtein_cutoff` rule out candidates by their length
+    /// and bound the distance they calculate, and a `QgramSketch` can rule
+    /// out candidates before calling them. As long as the scorer only
+    /// returns `None` below the cutoff, the result is the same as the one of
+    /// `top_k` with the complete metric.
+    pub fn top_k_with<'a, S, F>(
+        &self,
+        query: &str,
+        candidates: &'a [S],
+        k: usize,
+        scorer: F,
+    ) -> Vec<(&'a S, f64)>
+    where
+        S: AsRef<str>,
+        F: FnMut(&str, &str, f64) -> Option<f64>,
+    {
+        self.top_k_with_cache(query, candidates, k, scorer, &mut NoCache)
+    }
+
+    /// Returns the `k` candidates with the highest similarity to `query`
+    /// like `top_k_with`, but looks the candidates up in `cache` by their
+    /// index before calling `scorer`, and caches the similarities `scorer`
+    /// returns. Passing the same cache again with the same query and
+    /// candidates, e.g. to rank them with another `k` or `ScoreCmp`, only
+    /// verifies the candidates which weren't scored before.
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use strsim::extract::ScoreCmp;
+    /// use strsim::normalized_levenshtein_cutoff;
+    ///
+    /// let words = ["kitten", "sitting", "mittens", "bitten"];
+    /// let mut cache = HashMap::new();
+    /// let mut calls = 0;
+    /// let mut scorer = |a: &str, b: &str, cutoff| {
+    ///     calls += 1;
+    ///     normalized_levenshtein_cutoff(a, b, cutoff)
+    /// };
+    /// let best = ScoreCmp::default().top_k_with_cache("kitten", &words, 2, &mut scorer, &mut cache);
+    /// let again = ScoreCmp::default().top_k_with_cache("kitten", &words, 2, &mut scorer, &mut cache);
+    /// assert_eq!(best, again);
+    /// assert_eq!(vec!["kitten", "bitten"], best.iter().map(|(w, _)| **w).collect::<Vec<_>>());
+    /// assert_eq!(4, calls);
+    /// ```
+    pub fn top_k_with_cache<'a, S, F, C>(
+        &self,
+        query: &str,
+        candidates: &'a [S],
+        k: usize,
+        mut scorer: F,
+        cache: &mut C,
+    ) -> Vec<(&'a S, f64)>
+    where
+        S: AsRef<str>,
+        F: FnMut(&str, &str, f64) -> Option<f64>,
+        C: ScoreCache<usize> + ?Sized,
+    {
+        if k == 0 {
+            return Vec::new();
+        }
+        let mut best: Vec<(usize, f64)> = Vec::with_capacity(min(k, candidates.len()) + 1);
+        for (i, candidate) in candidates.iter().enumerate() {
+            /* scores within the epsilon of the last one can still tie with it */
+            let cutoff = match best.get(k - 1) {
+                Some(&(_, score)) if !score.is_nan() => score - self.epsilon,
+                _ => f64::NEG_INFINITY,
+            };
+            let candidate = candidate.as_ref();
+            /* `None` isn't cached, since it only holds for this cutoff */
+            let score = cache.get(&i).or_else(|| {
+                let score = scorer(query, candidate, cutoff)?;
+                cache.insert(i, score);
+                Some(score)
+            });
+            if let Some(score) = score {
+                let position = best.partition_point(|&(j, other)| {
+                    self.cmp_ranks((j, candidates[j].as_ref(), other), (i, candidate, score))
+                        == Ordering::Less
+                });
+                if position < k {
+                    best.insert(position, (i, score));
+                    best.truncate(k);
+                }
+            }
+        }
+        best.into_iter()
+            .map(|(i, score)| (&candidates[i], score))
+            .collect()
+    }
+
+    /// Returns the candidate with the highest similarity to `query` by
+    /// `metric`, with its similarity, or `None` without candidates.
+    pub fn best_match<'a, S, F>(
+        &self,
+        query: &str,
+        candidates: &'a [S],
+        mut metric: F,
+    ) -> Option<(&'a S, f64)>
+    where
+        S: AsRef<str>,
+        F: FnMut(&str, &str) -> f64,
+    {
+        candidates
+            .iter()
+            .enumerate()
+            .map(|(i, candidate)| (i, candidate, metric(query, candidate.as_ref())))
+            .min_by(|&(a, a_candidate, a_score), &(b, b_candidate, b_score)| {
+                self.cmp_ranks(
+                    (a, a_candidate.as_ref(), a_score),
+                    (b, b_candidate.as_ref(), b_score),
+                )
+            })
+            .map(|(_, candidate, score)| (candidate, score))
+    }
+}
+
+impl Default for ScoreCmp {
+    fn default() -> Self {
+        Self {
+            epsilon: 1e-12,
+            tie_break: TieBreak::Original,
+        }
+    }
+}
+
+/// Returns the `k` candidates most similar to `query` with their similarity,
+/// ranked by the default `ScoreCmp`: by descending similarity, and by their
+/// position for similarities equal within `1e-12`.
+///
+/// ```
+/// use strsim::extract::top_k;
+/// use strsim::jaro_winkler;
+///
+/// let names = ["Dwayne", "Martha", "Marhta", "Duane"];
+/// let best = top_k("Martha", &names, 2, jaro_winkler);
+/// assert_eq!(vec!["Martha", "Marhta"], best.iter().map(|(name, _)| **name).collect::<Vec<_>>());
+/// ```
+pub fn top_k<'a, S, F>(query: &str, candidates: &'a [S], k: usize, metric: F) -> Vec<(&'a S, f64)>
+where
+    S: AsRef<str>,
+    F: FnMut(&str, &str) -> f64,
+{
+    ScoreCmp::default().top_k(query, candidates, k, metric)
+}
+
+/// Returns the `k` candidates most similar to `query` like `top_k`, but
+/// skips verifying the candidates which can't enter the top `k` anymore. See
+/// `ScoreCmp::top_k_with`.
+///
+/// ```
+/// use strsim::extract::{top_k, top_k_with};
+/// use strsim::{normalized_levenshtein, normalized_levenshtein_cutoff};
+///
+/// let words = ["kitten", "sitting", "mittens", "kit", "bitten", "smitten"];
+/// let best = top_k_with("kitten", &words, 2, normalized_levenshtein_cutoff);
+/// assert_eq!(top_k("kitten", &words, 2, normalized_levenshtein), best);
+/// assert_eq!(vec!["kitten", "bitten"], best.iter().map(|(w, _)| **w).collect::<Vec<_>>());
+/// ```
+pub fn top_k_with<'a, S, F>(
+    query: &str,
+    candidates: &'a [S],
+    k: usize,
+    scorer: F,
+) -> Vec<(&'a S, f64)>
+where
+    S: AsRef<str>,
+    F: FnMut(&str, &str, f64) -> Option<f64>,
+{
+    ScoreCmp::default().top_k_with(query, candidates, k, scorer)
+}
+
+/// Returns the `k` candidates most similar to `query` like `top_k_with`,
+/// but reuses the similarities in `cache`. See `ScoreCmp::top_k_with_cache`.
+pub fn top_k_with_cache<'a, S, F, C>(
+    query: &str,
+    candidates: &'a [S],
+    k: usize,
+    scorer: F,
+    cache: &mut C,
+) -> Vec<(&'a S, f64)>
+where
+    S: AsRef<str>,
+    F: FnMut(&str, &str, f64) -> Option<f64>,
+    C: ScoreCache<usize> + ?Sized,
+{
+    ScoreCmp::default().top_k_with_cache(query, candidates, k, scorer, cache)
+}
+
+/// A cache of the similarities of candidates, keyed by the index of the
+/// candidate or of the pair of records. `ScoreCmp::top_k_with_cache` and
+/// `SimilarityGraph::with_cache` look candidates up before scoring them, so
+/// candidates which are scored again, like records in several blocks, are
+/// only verified once.
+///
+/// It is implemented for `HashMap` and `BTreeMap`. Bounded caches, like an
+/// LRU cache, can implement it and forget similarities at any time.
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use strsim::extract::ScoreCache;
+///
+/// let mut cache = BTreeMap::new();
+/// ScoreCache::insert(&mut cache, 3, 0.5);
+/// assert_eq!(Some(0.5), ScoreCache::get(&mut cache, &3));
+/// assert_eq!(None, ScoreCache::get(&mut cache, &4));
+/// ```
+pub trait ScoreCache<K> {
+    /// Returns the cached similarity of `key`, if any.
+    fn get(&mut self, key: &K) -> Option<f64>;
+
+    /// Caches the similarity of `key`.
+    fn insert(&mut self, key: K, score: f64);
+}
+
+impl<K: Hash + Eq, H: BuildHasher> ScoreCache<K> for HashMap<K, f64, H> {
+    fn get(&mut self, key: &K) -> Option<f64> {
+        HashMap::get(self, key).copied()
+    }
+
+    fn insert(&mut self, key: K, score: f64) {
+        HashMap::insert(self, key, score);
+    }
+}
+
+impl<K: Ord> ScoreCache<K> for BTreeMap<K, f64> {
+    fn get(&mut self, key: &K) -> Option<f64> {
+        BTreeMap::get(self, key).copied()
+    }
+
+    fn insert(&mut self, key: K, score: f64) {
+        BTreeMap::insert(self, key, score);
+    }
+}
+
+/* The cache of the functions without one, which never holds a similarity */
+pub(crate) struct NoCache;
+
+impl<K> ScoreCache<K> for NoCache {
+    fn get(&mut self, _: &K) -> Option<f64> {
+        None
+    }
+
+    fn insert(&mut self, _: K, _: f64) {}
+}
+
+/// Returns the candidate most similar to `query` with its similarity, ranked
+/// like `top_k`, or `None` without candidates.
+///
+/// ```
+/// use strsim::extract::best_match;
+/// use strsim::normalized_levenshtein;
+///
+/// let cities = ["Bern", "Berlin", "Bergen"];
+/// assert_eq!("Berlin", *best_match("Berln", &cities, normalized_levenshtein).unwrap().0);
+/// ```
+pub fn best_match<'a, S, F>(query: &str, candidates: &'a [S], metric: F) -> Option<(&'a S, f64)>
+where
+    S: AsRef<str>,
+    F: FnMut(&str, &str) -> f64,
+{
+    ScoreCmp::default().best_match(query, candidates, metric)
+}
+
+/// A similarity metric calculated by `score_metrics`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Metric {
+    /// `normalized_levenshtein`
+    NormalizedLevenshtein,
+    /// `normalized_damerau_levenshtein`
+    NormalizedDamerauLevenshtein,
+    /// `jaro`
+    Jaro,
+    /// `jaro_winkler`
+    JaroWinkler,
+    /// `sorensen_dice`
+    SorensenDice,
+}
+
+/// The similarities calculated by `score_metrics`, stored as one column per
+/// metric with one similarity per candidate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricScores {
+    metrics: Vec<Metric>,
+    columns: Vec<Vec<f64>>,
+}
+
+impl MetricScores {
+    /// The metrics in the order they were requested.
+    pub fn metrics(&self) -> &[Metric] {
+        &self.metrics
+    }
+
+    /// The similarities of all candidates by `metric`, in the order of the
+    /// candidates, or `None` if `metric` wasn't requested.
+    pub fn get(&self, metric: Metric) -> Option<&[f64]> {
+        self.metrics
+            .iter()
+            .position(|&m| m == metric)
+            .map(|i| self.columns[i].as_slice())
+    }
+
+    /// The number of candidates.
+    pub fn len(&self) -> usize {
+        self.columns.first().map_or(0, Vec::len)
+    }
+
+    /// Whether there are no candidates or no metrics.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Calculates several similarity metrics between `query` and each of the
+/// `candidates` in one pass, e.g. to extract the features of a classifier.
+///
+/// Work shared by the metrics is only done once per candidate: the character
+/// counts, removing the common prefix and suffix before the edit distances,
+/// and the Jaro similarity, which `Metric::JaroWinkler` boosts. The results
+/// are the same as the ones of the single metrics. Requesting a metric twice
+/// calculates it once.
+///
+/// ```
+/// use strsim::extract::{score_metrics, Metric};
+/// use strsim::{jaro_winkler, normalized_levenshtein};
+///
+/// let candidates = ["Martha", "Marhta", "Mark"];
+/// let scores = score_metrics("Martha", &candidates, &[Metric::NormalizedLevenshtein, Metric::JaroWinkler]);
+/// assert_eq!(3, scores.len());
+/// let levenshtein = scores.get(Metric::NormalizedLevenshtein).unwrap();
+/// assert_eq!(normalized_levenshtein("Martha", "Marhta"), levenshtein[1]);
+/// let jaro_winkler_scores = scores.get(Metric::JaroWinkler).unwrap();
+/// assert_eq!(jaro_winkler("Martha", "Mark"), jaro_winkler_scores[2]);
+/// assert_eq!(None, scores.get(Metric::Jaro));
+/// ```
+pub fn score_metrics<S: AsRef<str>>(
+    query: &str,
+    candidates: &[S],
+    metrics: &[Metric],
+) -> MetricScores {
+    match score_metrics_with_control(query, candidates, metrics, &Control::new()) {
+        Ok(scores) => scores,
+        Err(_) => unreachable!("the scoring is never cancelled"),
+    }
+}
+
+/// Calculates the metrics like `score_metrics`, unless the scoring is
+/// cancelled through `control`. The progress is the number of scored
+/// candidates.
+///
+/// ```
+/// use std::sync::atomic::AtomicBool;
+/// use strsim::extract::{score_metrics_with_control, Metric};
+/// use strsim::{Control, StrSimError};
+///
+/// let cancelled = AtomicBool::new(true);
+/// let control = Control::new().cancel_flag(&cancelled);
+/// let scores = score_metrics_with_control("Martha", &["Marhta"], &[Metric::Jaro], &control);
+/// assert_eq!(Some(StrSimError::Cancelled), scores.err());
+/// ```
+pub fn score_metrics_with_control<S: AsRef<str>>(
+    query: &str,
+    candidates: &[S],
+    metrics: &[Metric],
+    control: &Control<'_>,
+) -> Result<MetricScores, StrSimError> {
+    let mut unique: Vec<Metric> = Vec::with_capacity(metrics.len());
+    for &metric in metrics {
+        if !unique.contains(&metric) {
+            unique.push(metric);
+        }
+    }
+    let mut columns = vec![Vec::with_capacity(candidates.len()); unique.len()];
+    let query_len = query.chars().count();
+
+    for (scored, candidate) in candidates.iter().enumerate() {
+        let candidate = candidate.as_ref();
+        let candidate_len = candidate.chars().count();
+        let (query_rest, candidate_rest) = trim_common_affix(query, candidate);
+        let mut jaro_similarity = None;
+
+        for (&metric, column) in unique.iter().zip(&mut columns) {
+            let similarity = match metric {
+                Metric::NormalizedLevenshtein => normalized_similarity(
+                    levenshtein(query_rest, candidate_rest),
+                    query_len,
+                    candidate_len,
+                    Denominator::Max,
+                ),
+                Metric::NormalizedDamerauLevenshtein => normalized_similarity(
+                    damerau_levenshtein(query_rest, candidate_rest),
+                    query_len,
+                    candidate_len,
+                    Denominator::Max,
+                ),
+                Metric::Jaro => *jaro_similarity.get_or_insert_with(|| jaro(query, candidate)),
+                Metric::JaroWinkler => {
+                    let sim = *jaro_similarity.get_or_insert_with(|| jaro(query, candidate));
+                    jaro_winkler_from_jaro(sim, query, candidate)
+                }
+                Metric::SorensenDice => sorensen_dice(query, candidate),
+            };
+            column.push(similarity);
+        }
+        control.step(scored + 1, candidates.len())?;
+    }
+
+    Ok(MetricScores {
+        metrics: unique,
+        columns,
+    })
+}
+
+/// How `score_nullable` scores null values, i.e. `None`, of the query or of
+/// a candidate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NullPolicy {
+    /// The similarity to a null value is null as well. Filtering the nulls
+    /// out of the result skips the null candidates.
+    Propagate,
+    /// The similarity to a null value is the given score.
+    Score(f64),
+    /// A null value is compared like an empty string.
+    Empty,
+}
+
+/// Calculates the similarity of `query` to each of the `candidates` with
+/// `metric`, where the query and the candidates may be null, like the values
+/// of a data frame column. The nulls are scored according to `nulls`. Empty
+/// strings aren't null and are compared with the metric.
+///
+/// ```
+/// use strsim::extract::{score_nullable, NullPolicy};
+/// use strsim::normalized_levenshtein;
+///
+/// let column = [Some("Berlin"), None, Some("")];
+/// assert_eq!(
+///     vec![Some(1.0), None, Some(0.0)],
+///     score_nullable(Some("Berlin"), &column, NullPolicy::Propagate, normalized_levenshtein)
+/// );
+/// assert_eq!(
+///     vec![Some(0.0), Some(1.0), Some(1.0)],
+///     score_nullable(None, &column, NullPolicy::Empty, normalized_levenshtein)
+/// );
+/// ```
+pub fn score_nullable<S, F>(
+    query: Option<&str>,
+    candidates: &[Option<S>],
+    nulls: NullPolicy,
+    mut metric: F,
+) -> Vec<Option<f64>>
+where
+    S: AsRef<str>,
+    F: FnMut(&str, &str) -> f64,
+{
+    candidates
+        .iter()
+        .map(
+            |candidate| match (query, candidate.as_ref().map(AsRef::as_ref), nulls) {
+                (Some(query), Some(candidate), _) => Some(metric(query, candidate)),
+                (_, _, NullPolicy::Propagate) => None,
+                (_, _, NullPolicy::Score(score)) => Some(score),
+                (query, candidate, NullPolicy::Empty) => {
+                    Some(metric(query.unwrap_or(""), candidate.unwrap_or("")))
+                }
+            },
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{normalized_levenshtein, normalized_levenshtein_cutoff};
+    use std::cmp::max;
+    use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+    #[test]
+    fn score_metrics_match_single_metrics() {
+        let candidates = [
+            "kitten",
+            "sitting",
+            "",
+            "öঙ香",
+            "kitchen sink",
+            "ktitne",
+            "k",
+        ];
+        let metrics = [
+            Metric::SorensenDice,
+            Metric::NormalizedLevenshtein,
+            Metric::NormalizedDamerauLevenshtein,
+            Metric::JaroWinkler,
+            Metric::Jaro,
+        ];
+        for query in ["kitten", "", "öঙ"] {
+            let scores = score_metrics(query, &candidates, &metrics);
+            assert_eq!(&metrics, scores.metrics());
+            for (i, candidate) in candidates.iter().enumerate() {
+                let single = [
+                    crate::sorensen_dice(query, candidate),
+                    crate::normalized_levenshtein(query, candidate),
+                    crate::normalized_damerau_levenshtein(query, candidate),
+                    crate::jaro_winkler(query, candidate),
+                    crate::jaro(query, candidate),
+                ];
+                for (&metric, &similarity) in metrics.iter().zip(&single) {
+                    assert_eq!(similarity, scores.get(metric).unwrap()[i]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn score_metrics_duplicates_and_empty() {
+        let scores = score_metrics("a", &["a", "b"], &[Metric::Jaro, Metric::Jaro]);
+        assert_eq!(&[Metric::Jaro], scores.metrics());
+        assert_eq!(Some(&[1.0, 0.0][..]), scores.get(Metric::Jaro));
+        assert_eq!(2, scores.len());
+
+        let none: [&str; 0] = [];
+        assert!(score_metrics("a", &none, &[Metric::Jaro]).is_empty());
+        assert!(score_metrics("a", &["a"], &[]).is_empty());
+    }
+
+    #[test]
+    fn score_metrics_with_control_reports_candidates() {
+        let reports = AtomicUsize::new(0);
+        let progress = |done: usize, total: usize| {
+            assert_eq!(100, total);
+            reports.fetch_add(done, AtomicOrdering::Relaxed);
+        };
+        let control = Control::new().on_progress(&progress);
+        let candidates = vec!["b"; 100];
+        let scores = score_metrics_with_control("a", &candidates, &[Metric::Jaro], &control);
+        assert_eq!(100, scores.unwrap().len());
+        assert_eq!(64 + 100, reports.load(AtomicOrdering::Relaxed));
+    }
+
+    #[test]
+    fn score_nullable_policies() {
+        let candidates = [Some("ab".to_string()), None, Some(String::new())];
+        let metric = |a: &str, b: &str| (a.len() + b.len()) as f64;
+        assert_eq!(
+            vec![Some(4.0), None, Some(2.0)],
+            score_nullable(Some("ab"), &candidates, NullPolicy::Propagate, metric)
+        );
+        assert_eq!(
+            vec![Some(4.0), Some(-1.0), Some(2.0)],
+            score_nullable(Some("ab"), &candidates, NullPolicy::Score(-1.0), metric)
+        );
+        assert_eq!(
+            vec![Some(4.0), Some(2.0), Some(2.0)],
+            score_nullable(Some("ab"), &candidates, NullPolicy::Empty, metric)
+        );
+        assert_eq!(
+            vec![None, None, None],
+            score_nullable(None, &candidates, NullPolicy::Propagate, metric)
+        );
+        assert_eq!(
+            vec![Some(2.0), Some(0.0), Some(0.0)],
+            score_nullable(None, &candidates, NullPolicy::Empty, metric)
+        );
+    }
+
+    #[test]
+    fn score_cmp_epsilon() {
+        let cmp = ScoreCmp::default();
+        assert_eq!(Ordering::Equal, cmp.cmp_scores(1.0, 1.0 - 1e-15));
+        assert_eq!(Ordering::Greater, cmp.cmp_scores(1.0, 1.0 - 1e-9));
+        let exact = ScoreCmp::default().epsilon(0.0);
+        assert_eq!(Ordering::Greater, exact.cmp_scores(1.0, 1.0 - 1e-15));
+        let coarse = ScoreCmp::default().epsilon(0.1);
+        assert_eq!(Ordering::Equal, coarse.cmp_scores(0.51, 0.54));
+        assert_eq!(Ordering::Equal, cmp.cmp_scores(f64::NAN, f64::NAN));
+    }
+
+    #[test]
+    fn score_cmp_tie_break() {
+        let candidates = ["b", "a", "c", "a"];
+        let constant = |_: &str, _: &str| 0.5;
+        let original = ScoreCmp::default().top_k("", &candidates, 4, constant);
+        assert_eq!(
+            vec!["b", "a", "c", "a"],
+            original.iter().map(|(c, _)| **c).collect::<Vec<_>>()
+        );
+        let alphabetical = ScoreCmp::default().tie_break(TieBreak::Alphabetical).top_k(
+            "",
+            &candidates,
+            4,
+            constant,
+        );
+        // equal candidates keep their order
+        assert!(std::ptr::eq(alphabetical[0].0, &candidates[1]));
+        assert!(std::ptr::eq(alphabetical[1].0, &candidates[3]));
+        assert_eq!("b", *alphabetical[2].0);
+    }
+
+    #[test]
+    fn top_k_with_matches_top_k() {
+        let candidates = [
+            "kitten", "sitting", "mitten", "knitting", "", "kit", "mitten", "bitten", "kitchen",
+            "smitten", "b",
+        ];
+        for k in 0..13 {
+            for cmp in [
+                ScoreCmp::default(),
+                ScoreCmp::default().tie_break(TieBreak::Alphabetical),
+                ScoreCmp::default().epsilon(0.1),
+            ] {
+                assert_eq!(
+                    cmp.top_k("bitten", &candidates, k, normalized_levenshtein),
+                    cmp.top_k_with("bitten", &candidates, k, normalized_levenshtein_cutoff)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn top_k_with_cache_reuses_scores() {
+        let candidates = ["kitten", "sitting", "mitten", "bitten", "kit", "smitten"];
+        let mut cache = HashMap::new();
+        for k in [1, 3, 6] {
+            assert_eq!(
+                top_k_with("bitten", &candidates, k, normalized_levenshtein_cutoff),
+                top_k_with_cache(
+                    "bitten",
+                    &candidates,
+                    k,
+                    normalized_levenshtein_cutoff,
+                    &mut cache
+                )
+            );
+        }
+        assert_eq!(candidates.len(), cache.len());
+
+        // cached scores are trusted without calling the scorer
+        let mut cache = BTreeMap::new();
+        cache.insert(4, 2.0);
+        let best = top_k_with_cache("bitten", &candidates, 1, |_, _, _| Some(0.0), &mut cache);
+        assert_eq!(vec![(&"kit", 2.0)], best);
+    }
+
+    #[test]
+    fn top_k_with_skips_verification() {
+        let mut candidates = vec!["new york".to_string(), "newark".to_string()];
+        candidates.extend((0..100).map(|i| format!("springfield {}", i)));
+        let mut verified = 0;
+        let top = top_k_with("new yorc", &candidates, 1, |a, b, cutoff| {
+            let limit = Denominator::Max.max_distance(cutoff, 8, b.chars().count());
+            if max(8, b.chars().count()) - min(8, b.chars().count()) > limit {
+                return None;
+            }
+            verified += 1;
+            Some(normalized_levenshtein(a, b)).filter(|&similarity| similarity >= cutoff)
+        });
+        assert_eq!("new york", top[0].0);
+        assert_eq!(1, verified);
+    }
+
+    #[test]
+    fn top_k_and_best_match_agree() {
+        let candidates = [
+            "kitten", "sitting", "mitten", "knitting", "", "kit", "mitten",
+        ];
+        for k in 0..10 {
+            let top = top_k("bitten", &candidates, k, normalized_levenshtein);
+            assert_eq!(k.min(candidates.len()), top.len());
+            assert!(top.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        }
+        let top = top_k("bitten", &candidates, 1, normalized_levenshtein);
+        assert_eq!(
+            top.first().copied(),
+            best_match("bitten", &candidates, normalized_levenshtein)
+        );
+        assert_eq!("kitten", *top[0].0);
+        let none: [&str; 0] = [];
+        assert_eq!(None, best_match("a", &none, normalized_levenshtein));
+    }
+
+    #[test]
+    fn nan_scores_rank_last() {
+        let candidates = ["nan", "low"];
+        let scores = |_: &str, candidate: &str| if candidate == "nan" { f64::NAN } else { 0.1 };
+        assert_eq!("low", *best_match("", &candidates, scores).unwrap().0);
+    }
+
+    #[test]
+    fn matches_above_with_and_without_cutoff_agree() {
+        let candidates = ["kitten", "sitting", "mitten", "knitting", "", "kit"];
+        for threshold in [0.0, 0.3, 0.5, 0.8, 1.0] {
+            let bounded: Vec<_> = matches_above_with(
+                "kitten",
+                &candidates,
+                threshold,
+                normalized_levenshtein_cutoff,
+            )
+            .collect();
+            let unbounded: Vec<_> =
+                matches_above("kitten", &candidates, threshold, normalized_levenshtein).collect();
+            assert_eq!(unbounded, bounded);
+        }
+    }
+
+    #[test]
+    fn matches_above_is_lazy() {
+        let candidates = ["a", "b", "a", "a"];
+        let mut scored = 0;
+        let first: Vec<_> = matches_above("a", &candidates, 1.0, |a, b| {
+            scored += 1;
+            normalized_levenshtein(a, b)
+        })
+        .take(2)
+        .collect();
+        assert_eq!(2, first.len());
+        assert_eq!(3, scored);
+    }
+
+    #[test]
+    fn matches_above_empty() {
+        let candidates: [&str; 0] = [];
+        assert_eq!(
+            0,
+            matches_above("a", &candidates, 0.0, normalized_levenshtein).count()
+        );
+    }
+}
@@ -0,0 +1,195 @@
+//! Scorers returning integer scores between 0 and 100, compatible with the
+//! `fuzz` module of the Python libraries `fuzzywuzzy` and `rapidfuzz`.
+//!
+//! The scores match `fuzzywuzzy` (backed by `python-Levenshtein`) down to the
+//! integer, including its rounding of ties to the nearest even number. This
+//! keeps thresholds tuned for the Python libraries valid.
+
+use std::collections::BTreeSet;
+
+use crate::normalized_indel;
+
+/* Rounds like Python's `round`, which rounds ties to the nearest even number */
+fn round_half_even(x: f64) -> f64 {
+    let rounded = x.round();
+    if (x - x.trunc()).abs() == 0.5 {
+        2.0 * (x / 2.0).round()
+    } else {
+        rounded
+    }
+}
+
+fn to_score(similarity: f64) -> u8 {
+    round_half_even(100.0 * similarity) as u8
+}
+
+/* The preprocessing `fuzzywuzzy` applies by default: non ASCII characters are
+removed, everything except letters, digits and underscores is replaced with a
+space, and the result is lowercased and trimmed */
+fn full_process(s: &str) -> String {
+    let processed: String = s
+        .chars()
+        .filter(char::is_ascii)
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c.to_ascii_lowercase()
+            } else {
+                ' '
+            }
+        })
+        .collect();
+    processed.trim().to_string()
+}
+
+/// Calculates the normalized Indel similarity (Levenshtein with substitutions
+/// counting twice) scaled to 0 to 100. Equal strings score 100, and a single
+/// empty string scores 0. Like in `fuzzywuzzy`, the strings are not
+/// preprocessed.
+///
+/// ```
+/// use strsim::fuzz;
+///
+/// assert_eq!(100, fuzz::ratio("this is a test", "this is a test"));
+/// assert_eq!(97, fuzz::ratio("this is a test", "this is a test!"));
+/// assert_eq!(0, fuzz::ratio("", "test"));
+/// ```
+pub fn ratio(a: &str, b: &str) -> u8 {
+    if a == b {
+        return 100;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0;
+    }
+    to_score(normalized_indel(a, b))
+}
+
+/// Like `ratio`, but compares the strings after preprocessing them and sorting
+/// their tokens alphabetically. The preprocessing removes non ASCII
+/// characters, replaces punctuation with spaces and lowercases the strings.
+///
+/// ```
+/// use strsim::fuzz;
+///
+/// assert_eq!(100, fuzz::token_sort_ratio("fuzzy wuzzy was a bear", "wuzzy fuzzy was a bear"));
+/// assert_eq!(84, fuzz::token_sort_ratio("fuzzy was a bear", "fuzzy fuzzy was a bear"));
+/// ```
+pub fn token_sort_ratio(a: &str, b: &str) -> u8 {
+    let sort = |s: &str| {
+        let processed = full_process(s);
+        let mut tokens: Vec<&str> = processed.split_whitespace().collect();
+        tokens.sort_unstable();
+        tokens.join(" ")
+    };
+    ratio(&sort(a), &sort(b))
+}
+
+/// Like `ratio`, but compares the preprocessed strings based on their common
+/// tokens. The sorted common tokens are compared with the sorted common tokens
+/// followed by the remaining tokens of either string, and the best score is
+/// returned. Strings that are empty after preprocessing score 0.
+///
+/// ```
+/// use strsim::fuzz;
+///
+/// assert_eq!(100, fuzz::token_set_ratio("fuzzy was a bear", "fuzzy fuzzy was a bear"));
+/// assert_eq!(0, fuzz::token_set_ratio("!!!", "fuzzy"));
+/// ```
+pub fn token_set_ratio(a: &str, b: &str) -> u8 {
+    let a = full_process(a);
+    let b = full_process(b);
+    if a.is_empty() || b.is_empty() {
+        return 0;
+    }
+
+    let a_tokens: BTreeSet<&str> = a.split_whitespace().collect();
+    let b_tokens: BTreeSet<&str> = b.split_whitespace().collect();
+
+    let join = |tokens: Vec<&str>| tokens.join(" ");
+    let common = join(a_tokens.intersection(&b_tokens).copied().collect());
+    let a_rest = join(a_tokens.difference(&b_tokens).copied().collect());
+    let b_rest = join(b_tokens.difference(&a_tokens).copied().collect());
+
+    let a_combined = format!("{} {}", common, a_rest);
+    let b_combined = format!("{} {}", common, b_rest);
+    let (common, a_combined, b_combined) = (common.trim(), a_combined.trim(), b_combined.trim());
+
+    ratio(common, a_combined)
+        .max(ratio(common, b_combined))
+        .max(ratio(a_combined, b_combined))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_half_even_ties() {
+        assert_eq!(0.0, round_half_even(0.5));
+        assert_eq!(2.0, round_half_even(1.5));
+        assert_eq!(2.0, round_half_even(2.5));
+        assert_eq!(4.0, round_half_even(3.5));
+        assert_eq!(3.0, round_half_even(2.6));
+        assert_eq!(2.0, round_half_even(2.4));
+    }
+
+    #[test]
+    fn full_process_like_fuzzywuzzy() {
+        assert_eq!("new york  mets", full_process("  New York, Mets! "));
+        assert_eq!("snake_case", full_process("snake_case"));
+        assert_eq!("caf", full_process("Café"));
+    }
+
+    #[test]
+    fn ratio_equal_and_empty() {
+        assert_eq!(100, ratio("", ""));
+        assert_eq!(0, ratio("", "a"));
+        assert_eq!(0, ratio("a", ""));
+        assert_eq!(100, ratio("test", "test"));
+    }
+
+    #[test]
+    fn ratio_matches_fuzzywuzzy() {
+        assert_eq!(97, ratio("this is a test", "this is a test!"));
+        assert_eq!(
+            91,
+            ratio("fuzzy wuzzy was a bear", "wuzzy fuzzy was a bear")
+        );
+        assert_eq!(60, ratio("new york mets", "new york meats and the yankees"));
+    }
+
+    #[test]
+    fn ratio_rounds_ties_to_even() {
+        // 2 * 7 / 16 = 0.875
+        assert_eq!(88, ratio("abcdefgh", "abcdefgx"));
+        // 2 * 1 / 16 = 0.125
+        assert_eq!(12, ratio("abcdefgh", "axxxxxxx"));
+    }
+
+    #[test]
+    fn ratio_is_case_sensitive() {
+        assert_eq!(0, ratio("ABC", "abc"));
+    }
+
+    #[test]
+    fn token_sort_ratio_matches_fuzzywuzzy() {
+        assert_eq!(
+            100,
+            token_sort_ratio("fuzzy wuzzy was a bear", "wuzzy fuzzy was a bear")
+        );
+        assert_eq!(100, token_sort_ratio("New York Mets", "mets, new york"));
+        assert_eq!(100, token_sort_ratio("!!!", "???"));
+    }
+
+    #[test]
+    fn token_set_ratio_matches_fuzzywuzzy() {
+        assert_eq!(
+            100,
+            token_set_ratio("fuzzy was a bear", "fuzzy fuzzy was a bear")
+        );
+        assert_eq!(
+            100,
+            token_set_ratio("new york mets", "new york mets vs atlanta braves")
+        );
+        assert_eq!(0, token_set_ratio("", ""));
+    }
+}
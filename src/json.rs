@@ -0,0 +1,265 @@
+//! Reading the JSON configurations of `record::SchemaConfig`.
+
+/* Nesting deeper than this is rejected instead of overflowing the stack */
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    /* the members in their order, which may contain duplicate keys */
+    Object(Vec<(String, Value)>),
+}
+
+/* Parses a JSON document, or returns `None` if it isn't valid JSON */
+pub(crate) fn parse(text: &str) -> Option<Value> {
+    let mut parser = Parser { text, pos: 0 };
+    let value = parser.value(0)?;
+    parser.skip_whitespace();
+    if parser.pos == text.len() {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    /* Skips whitespace and `byte`, if it comes next */
+    fn eat(&mut self, byte: u8) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Option<Value> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        self.skip_whitespace();
+        match self.peek()? {
+            b'n' => self.literal("null", Value::Null),
+            b't' => self.literal("true", Value::Bool(true)),
+            b'f' => self.literal("false", Value::Bool(false)),
+            b'"' => self.string().map(Value::String),
+            b'[' => {
+                self.pos += 1;
+                let mut values = Vec::new();
+                if self.eat(b']') {
+                    return Some(Value::Array(values));
+                }
+                loop {
+                    values.push(self.value(depth + 1)?);
+                    if self.eat(b']') {
+                        return Some(Value::Array(values));
+                    }
+                    if !self.eat(b',') {
+                        return None;
+                    }
+                }
+            }
+            b'{' => {
+                self.pos += 1;
+                let mut members = Vec::new();
+                if self.eat(b'}') {
+                    return Some(Value::Object(members));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let key = self.string()?;
+                    if !self.eat(b':') {
+                        return None;
+                    }
+                    members.push((key, self.value(depth + 1)?));
+                    if self.eat(b'}') {
+                        return Some(Value::Object(members));
+                    }
+                    if !self.eat(b',') {
+                        return None;
+                    }
+                }
+            }
+            _ => self.number().map(Value::Number),
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Value) -> Option<Value> {
+        if self.text[self.pos..].starts_with(word) {
+            self.pos += word.len();
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        if self.peek() != Some(b'"') {
+            return None;
+        }
+        self.pos += 1;
+        let mut string = String::new();
+        let mut chars = self.text[self.pos..].char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += i + 1;
+                    return Some(string);
+                }
+                '\\' => {
+                    let escaped = match chars.next()?.1 {
+                        '"' => '"',
+                        '\\' => '\\',
+                        '/' => '/',
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'u' => {
+                            let high = hex_code_unit(&mut chars)?;
+                            let code = if (0xd800..0xdc00).contains(&high) {
+                                /* a surrogate pair */
+                                if chars.next()?.1 != '\\' || chars.next()?.1 != 'u' {
+                                    return None;
+                                }
+                                let low = hex_code_unit(&mut chars)?;
+                                if !(0xdc00..0xe000).contains(&low) {
+                                    return None;
+                                }
+                                0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                            } else {
+                                high
+                            };
+                            char::from_u32(code)?
+                        }
+                        _ => return None,
+                    };
+                    string.push(escaped);
+                }
+                c if u32::from(c) < 0x20 => return None,
+                c => string.push(c),
+            }
+        }
+        None
+    }
+
+    /* A number in the JSON grammar `-?(0|[1-9][0-9]*)(.[0-9]+)?([eE][+-]?[0-9]+)?` */
+    fn number(&mut self) -> Option<f64> {
+        let start = self.pos;
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        match self.peek()? {
+            b'0' => self.pos += 1,
+            b'1'..=b'9' => self.digits(),
+            _ => return None,
+        }
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            if !self.peek()?.is_ascii_digit() {
+                return None;
+            }
+            self.digits();
+        }
+        if let Some(b'e' | b'E') = self.peek() {
+            self.pos += 1;
+            if let Some(b'+' | b'-') = self.peek() {
+                self.pos += 1;
+            }
+            if !self.peek()?.is_ascii_digit() {
+                return None;
+            }
+            self.digits();
+        }
+        self.text[start..self.pos].parse().ok()
+    }
+
+    fn digits(&mut self) {
+        while self.peek().map_or(false, |byte| byte.is_ascii_digit()) {
+            self.pos += 1;
+        }
+    }
+}
+
+/* The four hexadecimal digits of a `\u` escape */
+fn hex_code_unit(chars: &mut std::str::CharIndices<'_>) -> Option<u32> {
+    let mut code = 0;
+    for _ in 0..4 {
+        code = code * 16 + chars.next()?.1.to_digit(16)?;
+    }
+    Some(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_values() {
+        assert_eq!(Some(Value::Null), parse(" null "));
+        assert_eq!(Some(Value::Bool(false)), parse("false"));
+        assert_eq!(Some(Value::Number(-12.5e1)), parse("-12.5e1"));
+        assert_eq!(Some(Value::Number(0.0)), parse("0"));
+        assert_eq!(
+            Some(Value::String("a\"\n\u{e9}\u{1f600}/".to_string())),
+            parse(r#""a\"\n\u00e9\ud83d\ude00\/""#)
+        );
+        assert_eq!(
+            Some(Value::Object(vec![
+                ("a".to_string(), Value::Array(vec![])),
+                (
+                    "b".to_string(),
+                    Value::Array(vec![Value::Number(1.0), Value::Object(vec![])])
+                ),
+            ])),
+            parse(r#"{"a": [], "b": [1, {}]}"#)
+        );
+    }
+
+    #[test]
+    fn reject_invalid_json() {
+        for text in [
+            "",
+            "nul",
+            "01",
+            "1.",
+            ".5",
+            "+1",
+            "1e",
+            "[1,]",
+            "[1 2]",
+            "{\"a\" 1}",
+            "{\"a\":1,}",
+            "{1:1}",
+            "\"a",
+            "\"\\x\"",
+            "\"\\ud83d\"",
+            "\"\t\"",
+            "[] []",
+        ] {
+            assert_eq!(None, parse(text), "{}", text);
+        }
+        assert_eq!(None, parse(&"[".repeat(100_000)));
+    }
+}
@@ -1,4 +1,16 @@
 //! This library implements string similarity metrics.
+//!
+//! All scorers and indexes are `Send` and `Sync`. Their queries take `&self`
+//! and keep their scratch space on the stack or allocate it per call, so one
+//! instance can be shared between threads, e.g. in an `Arc`, without locks.
+//!
+//! All scores are bit-identical on every platform and at every optimization
+//! level. They are calculated from integer counts with the basic floating
+//! point operations and square roots, which IEEE 754 defines exactly, and
+//! Rust neither fuses nor reorders floating point operations. The logarithms
+//! of `record::FellegiSunter` are computed the same way instead of with the
+//! math library of the platform. Only targets without SSE2, like `i586`,
+//! can round differently, because they calculate with extended precision.
 
 #![forbid(unsafe_code)]
 #![warn(rust_2018_idioms)]
@@ -21,23 +33,110 @@
 
 use std::char;
 use std::cmp::{max, min};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::error::Error;
 use std::fmt::{self, Display, Formatter};
 use std::hash::Hash;
+use std::iter::Copied;
 use std::mem;
+use std::ops::{Add, Div, Mul, Sub};
+
+use std::slice;
 use std::str::Chars;
+#[cfg(feature = "unicode-segmentation")]
+use unicode_segmentation::UnicodeSegmentation;
+
+#[cfg(test)]
+macro_rules! assert_delta {
+    ($x:expr, $y:expr) => {
+        assert_delta!($x, $y, 1e-5);
+    };
+    ($x:expr, $y:expr, $d:expr) => {
+        if ($x - $y).abs() > $d {
+            panic!(
+                "assertion failed: actual: `{}`, expected: `{}`: \
+                actual not within < {} of expected",
+                $x, $y, $d
+            );
+        }
+    };
+}
+
+mod alignment;
+#[cfg(feature = "async")]
+pub mod asynchronous;
+pub mod autocomplete;
+mod control;
+mod estimate;
+pub mod extract;
+pub mod fuzz;
+mod json;
+pub mod matchers;
+#[cfg(feature = "matrix")]
+mod matrix;
+mod metric_id;
+pub mod metric_space;
+pub mod phonetic;
+pub mod preprocess;
+pub mod record;
+mod sketch;
+mod token;
+pub mod tree;
+
+pub use alignment::{
+    consensus, highlight_spans, levenshtein_alignment, levenshtein_edit_counts,
+    levenshtein_editops, osa_edit_counts, Alignment, AlignmentStep, ConfusionCosts,
+    ConfusionMatrix, EditCounts, EditKind, HighlightSpans, MergePolicy,
+};
+pub use control::Control;
+pub use estimate::{LevenshteinEstimator, SimilarityEstimate};
+#[cfg(feature = "matrix")]
+pub use matrix::{
+    levenshtein_matrix, osa_matrix, positional_weighted_levenshtein_matrix,
+    weighted_edit_distance_matrix, weighted_levenshtein_matrix, DistanceMatrix,
+};
+pub use metric_id::MetricId;
+pub use sketch::QgramSketch;
+pub use token::{
+    token_match_similarity, token_set_similarity, token_sort_similarity, tokenize,
+    IdentityNormalizer, LowercaseNormalizer, TokenMatch, TokenMatchExplanation, TokenNormalizer,
+    TokenScorer, TokenSetExplanation,
+};
+#[cfg(feature = "stopwords")]
+pub use token::{ENGLISH_STOPWORDS, FRENCH_STOPWORDS, GERMAN_STOPWORDS, SPANISH_STOPWORDS};
 
 #[derive(Debug, PartialEq)]
+#[non_exhaustive]
 pub enum StrSimError {
     DifferentLengthArgs,
+    /// The computation was cancelled through its `Control`.
+    Cancelled,
+    /// A tree in the bracket notation of `tree::BracketTree` couldn't be
+    /// parsed.
+    MalformedTree,
+    /// A `record::SchemaConfig` couldn't be parsed.
+    MalformedConfig,
+    /// A metric isn't part of the `record::MetricRegistry`.
+    UnknownMetric,
+    /// A serialized index like `metric_space::VpTree::to_bytes` returns
+    /// couldn't be read.
+    MalformedIndex,
+    /// A serialized index was built with another metric, other parameters
+    /// or an incompatible version of the metric, see `MetricId`.
+    MetricMismatch,
 }
 
 impl Display for StrSimError {
     fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), fmt::Error> {
         let text = match self {
             StrSimError::DifferentLengthArgs => "Differing length arguments provided",
+            StrSimError::Cancelled => "The computation was cancelled",
+            StrSimError::MalformedTree => "Malformed tree in bracket notation",
+            StrSimError::MalformedConfig => "Malformed schema configuration",
+            StrSimError::UnknownMetric => "Unknown metric",
+            StrSimError::MalformedIndex => "Malformed serialized index",
+            StrSimError::MetricMismatch => "The index was built with another metric",
         };
 
         write!(fmt, "{}", text)
@@ -48,6 +147,261 @@ impl Error for StrSimError {}
 
 pub type HammingResult = Result<usize, StrSimError>;
 
+/// The length used to normalize a raw count into a similarity between 0.0 and
+/// 1.0.
+///
+/// The normalized edit distances like `normalized_levenshtein`,
+/// `normalized_damerau_levenshtein` and `normalized_levenshtein_cutoff` use
+/// `Max`, and `fuzz::ratio` divides the insertions and deletions by the
+/// `Sum`. `max_distance` and `similarity` convert between the similarities
+/// and the distances of these metrics.
+///
+/// The `_with` functions (`normalized_levenshtein_with`,
+/// `normalized_damerau_levenshtein_with`, `normalized_osa_with`,
+/// `normalized_indel_with` and `normalized_lcs_with`) take the denominator as
+/// an argument. The `_f32`, `_ppm`, `_cutoff` and `partial_` variants keep the
+/// normalization of the metric they are derived from; normalize their
+/// distances with `similarity` or `similarity_ppm` to use another
+/// denominator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Denominator {
+    /// The length of the shorter string.
+    Min,
+    /// The length of the longer string.
+    Max,
+    /// The mean length of both strings.
+    Mean,
+    /// The sum of the lengths of both strings, like in Sørensen-Dice.
+    Sum,
+}
+
+impl Denominator {
+    /// Returns the largest distance between strings of `a_len` and `b_len`
+    /// characters whose similarity `1 - distance / denominator` is at least
+    /// `cutoff`, which can be passed as the limit of a bounded distance like
+    /// `bounded_levenshtein`. Returns `usize::MAX` if every distance reaches
+    /// the cutoff.
+    ///
+    /// ```
+    /// use strsim::{bounded_levenshtein, normalized_levenshtein, Denominator};
+    ///
+    /// // "kitten" and "sitting" reach a similarity of 0.5 within 3 edits
+    /// assert_eq!(3, Denominator::Max.max_distance(0.5, 6, 7));
+    /// assert_eq!(Some(3), bounded_levenshtein("kitten", "sitting", 3));
+    /// // a cutoff of exactly the similarity of a distance allows that distance
+    /// let similarity = normalized_levenshtein("kitten", "sitting");
+    /// assert_eq!(3, Denominator::Max.max_distance(similarity, 6, 7));
+    /// assert_eq!(0, Denominator::Max.max_distance(1.0, 6, 7));
+    /// assert_eq!(usize::MAX, Denominator::Max.max_distance(0.0, 6, 7));
+    /// ```
+    pub fn max_distance(self, cutoff: f64, a_len: usize, b_len: usize) -> usize {
+        if cutoff <= 0.0 {
+            return usize::MAX;
+        }
+        /* the mean is counted in halves, so the denominator is an integer and
+        the distance is halved accordingly */
+        let (denominator, halves) = match self {
+            Denominator::Min => (min(a_len, b_len), false),
+            Denominator::Max => (max(a_len, b_len), false),
+            Denominator::Mean => (a_len + b_len, true),
+            Denominator::Sum => (a_len + b_len, false),
+        };
+        /* without a denominator the similarity doesn't depend on the
+        distance, and it is 1.0 for any distance between two empty strings */
+        if denominator == 0 {
+            return 0;
+        }
+
+        /* the floor of `(1 - cutoff) * denominator` is off by at most one
+        through rounding. The similarity falls with the distance, so comparing
+        it with the cutoff moves the estimate to the largest distance which
+        reaches the cutoff */
+        let scaled = ((1.0 - cutoff) * denominator as f64).floor() as usize;
+        let mut distance = if halves { scaled / 2 } else { scaled };
+        while self.similarity(distance + 1, a_len, b_len) >= cutoff {
+            distance += 1;
+        }
+        while distance > 0 && self.similarity(distance, a_len, b_len) < cutoff {
+            distance -= 1;
+        }
+        distance
+    }
+
+    /// Returns the similarity `1 - distance / denominator` of a distance
+    /// between strings of `a_len` and `b_len` characters, clamped to 0.0. Two
+    /// empty strings have a similarity of 1.0.
+    ///
+    /// ```
+    /// use strsim::{levenshtein, normalized_levenshtein, Denominator};
+    ///
+    /// let distance = levenshtein("kitten", "sitting");
+    /// assert_eq!(normalized_levenshtein("kitten", "sitting"), Denominator::Max.similarity(distance, 6, 7));
+    /// assert_eq!(0.0, Denominator::Min.similarity(9, 6, 7));
+    /// ```
+    pub fn similarity(self, distance: usize, a_len: usize, b_len: usize) -> f64 {
+        normalized_similarity(distance, a_len, b_len, self)
+    }
+
+    /// Like `similarity`, but returns the similarity in parts per million,
+    /// calculated exactly with integers and rounded to the nearest integer.
+    ///
+    /// ```
+    /// use strsim::Denominator;
+    ///
+    /// assert_eq!(571_429, Denominator::Max.similarity_ppm(3, 6, 7));
+    /// assert_eq!(538_462, Denominator::Mean.similarity_ppm(3, 6, 7));
+    /// assert_eq!(1_000_000, Denominator::Min.similarity_ppm(0, 0, 0));
+    /// ```
+    pub fn similarity_ppm(self, distance: usize, a_len: usize, b_len: usize) -> u32 {
+        if a_len == 0 && b_len == 0 {
+            return 1_000_000;
+        }
+        let (a_len, b_len, distance) = (a_len as u128, b_len as u128, distance as u128);
+        /* the mean is taken as a fraction with the sum as numerator and the
+        distance is doubled accordingly */
+        let (denominator, distance) = match self {
+            Denominator::Min => (min(a_len, b_len), distance),
+            Denominator::Max => (max(a_len, b_len), distance),
+            Denominator::Mean => (a_len + b_len, 2 * distance),
+            Denominator::Sum => (a_len + b_len, distance),
+        };
+        if denominator == 0 || distance >= denominator {
+            return 0;
+        }
+        ratio_ppm(denominator - distance, denominator)
+    }
+
+    fn value<F: Float>(self, a_len: usize, b_len: usize) -> F {
+        match self {
+            Denominator::Min => F::from_usize(min(a_len, b_len)),
+            Denominator::Max => F::from_usize(max(a_len, b_len)),
+            Denominator::Mean => F::from_usize(a_len + b_len) / F::from_usize(2),
+            Denominator::Sum => F::from_usize(a_len + b_len),
+        }
+    }
+}
+
+/* The floating point types similarities are calculated in, so the `_f32`
+variants don't have to convert from `f64` */
+trait Float:
+    Copy
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn from_usize(value: usize) -> Self;
+    fn from_f64(value: f64) -> Self;
+}
+
+impl Float for f64 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+
+    fn from_usize(value: usize) -> Self {
+        value as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+}
+
+impl Float for f32 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+
+    fn from_usize(value: usize) -> Self {
+        value as f32
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+}
+
+/* The similarity of the distance returned by `bounded` if it reaches
+`cutoff`. `bounded` gets the largest distance reaching the cutoff and may
+return `None` for larger distances. The edit distances are at least the
+length difference, so too different lengths are rejected right away */
+fn normalized_cutoff<B>(
+    a_len: usize,
+    b_len: usize,
+    denominator: Denominator,
+    cutoff: f64,
+    bounded: B,
+) -> Option<f64>
+where
+    B: FnOnce(usize) -> Option<usize>,
+{
+    let similarity = if a_len == 0 && b_len == 0 {
+        1.0
+    } else {
+        let limit = denominator.max_distance(cutoff, a_len, b_len);
+        if max(a_len, b_len) - min(a_len, b_len) > limit {
+            return None;
+        }
+        normalized_similarity(bounded(limit)?, a_len, b_len, denominator)
+    };
+    Some(similarity).filter(|&similarity| similarity >= cutoff)
+}
+
+/* Converts the fraction `numerator / denominator` between 0 and 1 into parts
+per million, rounding half up */
+fn ratio_ppm(numerator: u128, denominator: u128) -> u32 {
+    ((2 * numerator * 1_000_000 + denominator) / (2 * denominator)) as u32
+}
+
+/// Converts a similarity between 0.0 and 1.0 into parts per million, rounded
+/// to the nearest integer, e.g. for the scores of `TokenScorer` or the
+/// matchers. Similarities outside of that range are clamped and `NaN` becomes
+/// 0. The `_ppm` functions like `normalized_levenshtein_ppm` calculate the
+/// result without floating point numbers instead.
+///
+/// ```
+/// use strsim::{to_ppm, token_sort_similarity};
+///
+/// assert_eq!(1_000_000, to_ppm(token_sort_similarity("new york", "york new")));
+/// assert_eq!(333_333, to_ppm(1.0 / 3.0));
+/// assert_eq!(0, to_ppm(-0.5));
+/// ```
+pub fn to_ppm(similarity: f64) -> u32 {
+    if similarity.is_nan() {
+        0
+    } else {
+        (similarity.clamp(0.0, 1.0) * 1_000_000.0).round() as u32
+    }
+}
+
+/* Turns a distance into a similarity between 0.0 and 1.0. Denominators
+smaller than the maximum distance are clamped to 0.0 */
+fn normalized_similarity<F: Float>(
+    dist: usize,
+    a_len: usize,
+    b_len: usize,
+    denominator: Denominator,
+) -> F {
+    if a_len == 0 && b_len == 0 {
+        return F::ONE;
+    }
+
+    let denominator: F = denominator.value(a_len, b_len);
+    if denominator == F::ZERO {
+        return F::ZERO;
+    }
+    let sim = F::ONE - F::from_usize(dist) / denominator;
+    if sim < F::ZERO {
+        F::ZERO
+    } else {
+        sim
+    }
+}
+
 /// Calculates the number of positions in the two sequences where the elements
 /// differ. Returns an error if the sequences have different lengths.
 pub fn generic_hamming<Iter1, Iter2, Elem1, Elem2>(a: Iter1, b: Iter2) -> HammingResult
@@ -55,22 +409,60 @@ where
     Iter1: IntoIterator<Item = Elem1>,
     Iter2: IntoIterator<Item = Elem2>,
     Elem1: PartialEq<Elem2>,
+{
+    hamming_impl(a, b, |_, _, _| false)
+}
+
+fn hamming_impl<Iter1, Iter2, Elem1, Elem2, Ignore>(
+    a: Iter1,
+    b: Iter2,
+    mut ignore: Ignore,
+) -> HammingResult
+where
+    Iter1: IntoIterator<Item = Elem1>,
+    Iter2: IntoIterator<Item = Elem2>,
+    Elem1: PartialEq<Elem2>,
+    Ignore: FnMut(usize, &Elem1, &Elem2) -> bool,
 {
     let (mut ita, mut itb) = (a.into_iter(), b.into_iter());
     let mut count = 0;
+    let mut i = 0;
     loop {
         match (ita.next(), itb.next()) {
             (Some(x), Some(y)) => {
-                if x != y {
+                if x != y && !ignore(i, &x, &y) {
                     count += 1;
                 }
             }
             (None, None) => return Ok(count),
             _ => return Err(StrSimError::DifferentLengthArgs),
         }
+        i += 1;
     }
 }
 
+/// Like `generic_hamming`, but ignores the positions for which `mask` returns
+/// `true`. Returns an error if the sequences have different lengths.
+///
+/// ```
+/// use strsim::generic_hamming_masked;
+///
+/// assert_eq!(Ok(1), generic_hamming_masked(&[1, 2, 3, 4], &[0, 2, 0, 4], |i| i == 0));
+/// ```
+pub fn generic_hamming_masked<Iter1, Iter2, Elem1, Elem2, Mask>(
+    a: Iter1,
+    b: Iter2,
+    mut mask: Mask,
+) -> HammingResult
+where
+    Iter1: IntoIterator<Item = Elem1>,
+    Iter2: IntoIterator<Item = Elem2>,
+    Elem1: PartialEq<Elem2>,
+    Mask: FnMut(usize) -> bool,
+{
+    hamming_impl(a, b, |i, _, _| mask(i))
+}
+
 /// Calculates the number of positions in the two strings where the characters
 /// differ. Returns an error if the strings have different lengths.
 ///
@@ -85,9 +477,158 @@ pub fn hamming(a: &str, b: &str) -> HammingResult {
     generic_hamming(a.chars(), b.chars())
 }
 
+/// Returns the positions in the two sequences where the elements differ, in
+/// increasing order. Returns an error if the sequences have different lengths.
+///
+/// ```
+/// use strsim::generic_hamming_positions;
+///
+/// assert_eq!(Ok(vec![0, 2]), generic_hamming_positions(&[1, 2, 3, 4], &[0, 2, 0, 4]));
+/// ```
+pub fn generic_hamming_positions<Iter1, Iter2, Elem1, Elem2>(
+    a: Iter1,
+    b: Iter2,
+) -> Result<Vec<usize>, StrSimError>
+where
+    Iter1: IntoIterator<Item = Elem1>,
+    Iter2: IntoIterator<Item = Elem2>,
+    Elem1: PartialEq<Elem2>,
+{
+    let (mut ita, mut itb) = (a.into_iter(), b.into_iter());
+    let mut positions = Vec::new();
+    let mut i = 0;
+    loop {
+        match (ita.next(), itb.next()) {
+            (Some(x), Some(y)) => {
+                if x != y {
+                    positions.push(i);
+                }
+            }
+            (None, None) => return Ok(positions),
+            _ => return Err(StrSimError::DifferentLengthArgs),
+        }
+        i += 1;
+    }
+}
+
+/// Returns the character indices at which the two strings differ, in
+/// increasing order. Returns an error if the strings have different lengths.
+///
+/// ```
+/// use strsim::{hamming_positions, StrSimError::DifferentLengthArgs};
+///
+/// assert_eq!(Ok(vec![4, 5, 6]), hamming_positions("hamming", "hammers"));
+///
+/// assert_eq!(Err(DifferentLengthArgs), hamming_positions("hamming", "ham"));
+/// ```
+pub fn hamming_positions(a: &str, b: &str) -> Result<Vec<usize>, StrSimError> {
+    generic_hamming_positions(a.chars(), b.chars())
+}
+
+/// Like `hamming`, but `wildcard` in either string matches any character.
+/// Returns an error if the strings have different lengths.
+///
+/// ```
+/// use strsim::hamming_wildcard;
+///
+/// assert_eq!(Ok(2), hamming_wildcard("ham??ng", "hammers", '?'));
+/// ```
+pub fn hamming_wildcard(a: &str, b: &str, wildcard: char) -> HammingResult {
+    hamming_ignoring(a, b, |c| c == wildcard)
+}
+
+/// Like `hamming`, but ignores the character positions for which `mask`
+/// returns `true`. This is useful for fixed-format codes where some fields
+/// should not be compared. Returns an error if the strings have different
+/// lengths.
+///
+/// ```
+/// use strsim::hamming_masked;
+///
+/// // ignore the check digit at the end
+/// assert_eq!(Ok(0), hamming_masked("4006381333931", "4006381333937", |i| i == 12));
+/// ```
+pub fn hamming_masked<Mask>(a: &str, b: &str, mask: Mask) -> HammingResult
+where
+    Mask: FnMut(usize) -> bool,
+{
+    generic_hamming_masked(a.chars(), b.chars(), mask)
+}
+
+/// Like `hamming`, but ignores the positions where the character of either
+/// string is part of the class described by `ignore`. Returns an error if the
+/// strings have different lengths.
+///
+/// ```
+/// use strsim::hamming_ignoring;
+///
+/// assert_eq!(Ok(1), hamming_ignoring("2024-01-XX", "2024-02-15", |c| c == 'X'));
+/// ```
+pub fn hamming_ignoring<Ignore>(a: &str, b: &str, mut ignore: Ignore) -> HammingResult
+where
+    Ignore: FnMut(char) -> bool,
+{
+    hamming_impl(a.chars(), b.chars(), |_, &x, &y| ignore(x) || ignore(y))
+}
+
 /// Calculates the Jaro similarity between two sequences. The returned value
 /// is between 0.0 and 1.0 (higher value means more similar).
+///
+/// Unlike the edit distances, Jaro can't skip a common prefix or suffix: the
+/// shared elements count as matches, and the length of the sequences
+/// determines the size of the search window.
 pub fn generic_jaro<'a, 'b, Iter1, Iter2, Elem1, Elem2>(a: &'a Iter1, b: &'b Iter2) -> f64
+where
+    &'a Iter1: IntoIterator<Item = Elem1>,
+    &'b Iter2: IntoIterator<Item = Elem2>,
+    Elem1: PartialEq<Elem2>,
+{
+    jaro_counts(a, b).similarity()
+}
+
+/* The intermediate counts of the Jaro algorithm, kept around for the
+adjustments that need more than the final similarity */
+struct JaroCounts {
+    a_len: usize,
+    b_len: usize,
+    matches: usize,
+    transpositions: usize,
+}
+
+impl JaroCounts {
+    /* The similarity as the numerator and denominator of a fraction */
+    fn ratio(&self) -> (u128, u128) {
+        if self.a_len == 0 && self.b_len == 0 {
+            (1, 1)
+        } else if self.matches == 0 {
+            (0, 1)
+        } else {
+            let (a_len, b_len) = (self.a_len as u128, self.b_len as u128);
+            let matches = self.matches as u128;
+            let transpositions = self.transpositions as u128;
+            (
+                matches * matches * (a_len + b_len) + (matches - transpositions) * a_len * b_len,
+                3 * a_len * b_len * matches,
+            )
+        }
+    }
+
+    fn similarity<F: Float>(&self) -> F {
+        if self.a_len == 0 && self.b_len == 0 {
+            F::ONE
+        } else if self.matches == 0 {
+            F::ZERO
+        } else {
+            let matches = F::from_usize(self.matches);
+            ((matches / F::from_usize(self.a_len))
+                + (matches / F::from_usize(self.b_len))
+                + (F::from_usize(self.matches - self.transpositions) / matches))
+                / F::from_usize(3)
+        }
+    }
+}
+
+fn jaro_counts<'a, 'b, Iter1, Iter2, Elem1, Elem2>(a: &'a Iter1, b: &'b Iter2) -> JaroCounts
 where
     &'a Iter1: IntoIterator<Item = Elem1>,
     &'b Iter2: IntoIterator<Item = Elem2>,
@@ -96,10 +637,13 @@ where
     let a_len = a.into_iter().count();
     let b_len = b.into_iter().count();
 
-    if a_len == 0 && b_len == 0 {
-        return 1.0;
-    } else if a_len == 0 || b_len == 0 {
-        return 0.0;
+    if a_len == 0 || b_len == 0 {
+        return JaroCounts {
+            a_len,
+            b_len,
+            matches: 0,
+            transpositions: 0,
+        };
     }
 
     let mut search_range = max(a_len, b_len) / 2;
@@ -113,11 +657,7 @@ where
 
     for (i, a_elem) in a.into_iter().enumerate() {
         // prevent integer wrapping
-        let min_bound = if i > search_range {
-            i - search_range
-        } else {
-            0
-        };
+        let min_bound = i.saturating_sub(search_range);
 
         let max_bound = min(b_len, i + search_range + 1);
 
@@ -153,19 +693,152 @@ where
     }
     transpositions /= 2;
 
-    if matches == 0 {
-        0.0
-    } else {
-        ((matches as f64 / a_len as f64)
-            + (matches as f64 / b_len as f64)
-            + ((matches - transpositions) as f64 / matches as f64))
-            / 3.0
+    JaroCounts {
+        a_len,
+        b_len,
+        matches,
+        transpositions,
+    }
+}
+
+/* Bit-parallel version of `jaro_counts` for bytes. Every bit of a word stands
+for one position in `b`, so the search window is scanned 64 positions at a
+time instead of element by element */
+fn jaro_counts_bytes(a: &[u8], b: &[u8]) -> JaroCounts {
+    let a_len = a.len();
+    let b_len = b.len();
+
+    if a_len == 0 || b_len == 0 {
+        return JaroCounts {
+            a_len,
+            b_len,
+            matches: 0,
+            transpositions: 0,
+        };
+    }
+
+    let search_range = (max(a_len, b_len) / 2).saturating_sub(1);
+    let words = (b_len + 63) / 64;
+
+    if a_len <= 64 && b_len <= 64 {
+        return jaro_counts_bytes_short(a, b, search_range);
+    }
+
+    // positions of every byte value in b, `words` entries per byte value
+    let mut pattern = vec![0_u64; 256 * words];
+    for (j, &b_elem) in b.iter().enumerate() {
+        pattern[usize::from(b_elem) * words + j / 64] |= 1 << (j % 64);
+    }
+
+    let mut a_flags = vec![0_u64; (a_len + 63) / 64];
+    let mut b_flags = vec![0_u64; words];
+    let mut matches = 0_usize;
+
+    for (i, &a_elem) in a.iter().enumerate() {
+        let min_bound = i.saturating_sub(search_range);
+        let max_bound = min(b_len, i + search_range + 1);
+        if min_bound >= max_bound {
+            break;
+        }
+
+        for word in min_bound / 64..=(max_bound - 1) / 64 {
+            let mut candidates = pattern[usize::from(a_elem) * words + word] & !b_flags[word];
+            let word_start = word * 64;
+            if min_bound > word_start {
+                candidates &= u64::MAX << (min_bound - word_start);
+            }
+            if max_bound - word_start < 64 {
+                candidates &= (1 << (max_bound - word_start)) - 1;
+            }
+
+            if candidates != 0 {
+                // the lowest bit is the first unmatched position in the window
+                b_flags[word] |= candidates & candidates.wrapping_neg();
+                a_flags[i / 64] |= 1 << (i % 64);
+                matches += 1;
+                break;
+            }
+        }
+    }
+
+    let mut transpositions = 0_usize;
+    for (i, j) in set_bits(&a_flags).zip(set_bits(&b_flags)) {
+        if a[i] != b[j] {
+            transpositions += 1;
+        }
+    }
+    transpositions /= 2;
+
+    JaroCounts {
+        a_len,
+        b_len,
+        matches,
+        transpositions,
+    }
+}
+
+/* `jaro_counts_bytes` for strings of at most 64 bytes, which keeps everything
+on the stack */
+fn jaro_counts_bytes_short(a: &[u8], b: &[u8], search_range: usize) -> JaroCounts {
+    let mut pattern = [0_u64; 256];
+    for (j, &b_elem) in b.iter().enumerate() {
+        pattern[usize::from(b_elem)] |= 1 << j;
+    }
+
+    let mut a_flags = 0_u64;
+    let mut b_flags = 0_u64;
+    let mut matches = 0_usize;
+
+    for (i, &a_elem) in a.iter().enumerate() {
+        let min_bound = i.saturating_sub(search_range);
+        let max_bound = min(b.len(), i + search_range + 1);
+        if min_bound >= max_bound {
+            break;
+        }
+
+        let window = (u64::MAX << min_bound) & (u64::MAX >> (64 - max_bound));
+        let candidates = pattern[usize::from(a_elem)] & !b_flags & window;
+        if candidates != 0 {
+            b_flags |= candidates & candidates.wrapping_neg();
+            a_flags |= 1 << i;
+            matches += 1;
+        }
     }
+
+    let mut transpositions = 0_usize;
+    for (i, j) in set_bits(&[a_flags]).zip(set_bits(&[b_flags])) {
+        if a[i] != b[j] {
+            transpositions += 1;
+        }
+    }
+    transpositions /= 2;
+
+    JaroCounts {
+        a_len: a.len(),
+        b_len: b.len(),
+        matches,
+        transpositions,
+    }
+}
+
+/* Iterates over the positions of the set bits in a bit vector */
+fn set_bits(words: &[u64]) -> impl Iterator<Item = usize> + '_ {
+    words.iter().enumerate().flat_map(|(word_index, &word)| {
+        let mut remaining = word;
+        std::iter::from_fn(move || {
+            if remaining == 0 {
+                return None;
+            }
+            let bit = remaining.trailing_zeros() as usize;
+            remaining &= remaining - 1;
+            Some(word_index * 64 + bit)
+        })
+    })
 }
 
 struct StringWrapper<'a>(&'a str);
 
-impl<'a, 'b> IntoIterator for &'a StringWrapper<'b> {
+impl<'b> IntoIterator for &StringWrapper<'b> {
     type Item = char;
     type IntoIter = Chars<'b>;
 
@@ -184,7 +857,100 @@ impl<'a, 'b> IntoIterator for &'a StringWrapper<'b> {
 ///         0.001);
 /// ```
 pub fn jaro(a: &str, b: &str) -> f64 {
-    generic_jaro(&StringWrapper(a), &StringWrapper(b))
+    jaro_impl(a, b)
+}
+
+/// Like `jaro`, but calculates the similarity as `f32`.
+///
+/// ```
+/// use strsim::jaro_f32;
+///
+/// assert!((0.392 - jaro_f32("Friedrich Nietzsche", "Jean-Paul Sartre")).abs() <
+///         0.001);
+/// ```
+pub fn jaro_f32(a: &str, b: &str) -> f32 {
+    jaro_impl(a, b)
+}
+
+fn jaro_impl<F: Float>(a: &str, b: &str) -> F {
+    jaro_counts_str(a, b).similarity()
+}
+
+fn jaro_counts_str(a: &str, b: &str) -> JaroCounts {
+    if a.is_ascii() && b.is_ascii() {
+        jaro_counts_bytes(a.as_bytes(), b.as_bytes())
+    } else {
+        jaro_counts(&StringWrapper(a), &StringWrapper(b))
+    }
+}
+
+/// Like `jaro`, but returns the similarity in parts per million, calculated
+/// exactly with integers and rounded to the nearest integer.
+///
+/// ```
+/// use strsim::jaro_ppm;
+///
+/// assert_eq!(944_444, jaro_ppm("martha", "marhta"));
+/// assert_eq!(1_000_000, jaro_ppm("", ""));
+/// ```
+pub fn jaro_ppm(a: &str, b: &str) -> u32 {
+    let (numerator, denominator) = jaro_counts_str(a, b).ratio();
+    ratio_ppm(numerator, denominator)
+}
+
+/// Like `jaro_winkler`, but returns the similarity in parts per million,
+/// calculated exactly with integers and rounded to the nearest integer.
+///
+/// ```
+/// use strsim::jaro_winkler_ppm;
+///
+/// assert_eq!(961_111, jaro_winkler_ppm("martha", "marhta"));
+/// ```
+pub fn jaro_winkler_ppm(a: &str, b: &str) -> u32 {
+    let (numerator, denominator) = jaro_counts_str(a, b).ratio();
+    /* the boost only applies to similarities above 0.7 */
+    if 10 * numerator <= 7 * denominator {
+        return ratio_ppm(numerator, denominator);
+    }
+    let prefix_length = a
+        .chars()
+        .take(4)
+        .zip(b.chars())
+        .take_while(|(a_char, b_char)| a_char == b_char)
+        .count() as u128;
+    /* sim + l / 10 * (1 - sim) */
+    ratio_ppm(
+        numerator * (10 - prefix_length) + prefix_length * denominator,
+        10 * denominator,
+    )
+}
+
+/// Like `jaro`, but `wildcard` in either string matches any character.
+///
+/// ```
+/// use strsim::jaro_wildcard;
+///
+/// assert_eq!(1.0, jaro_wildcard("m?rtha", "martha", '?'));
+/// ```
+pub fn jaro_wildcard(a: &str, b: &str, wildcard: char) -> f64 {
+    generic_jaro(
+        &WildcardWrapper { s: a, wildcard },
+        &WildcardWrapper { s: b, wildcard },
+    )
+}
+
+/// Calculates the Jaro similarity between two byte strings, such as ASCII
+/// text. Instead of comparing the bytes one at a time, the matches within the
+/// search window are found with bitwise operations on 64 positions at once.
+/// `jaro` uses this automatically when both strings are ASCII.
+///
+/// ```
+/// use strsim::jaro_bytes;
+///
+/// assert!((0.944 - jaro_bytes(b"martha", b"marhta")).abs() < 0.001);
+/// ```
+pub fn jaro_bytes(a: &[u8], b: &[u8]) -> f64 {
+    jaro_counts_bytes(a, b).similarity()
 }
 
 /// Like Jaro but gives a boost to sequences that have a common prefix.
@@ -204,12 +970,18 @@ where
             .take_while(|(a_elem, b_elem)| a_elem == b_elem)
             .count();
 
-        sim + 0.1 * prefix_length as f64 * (1.0 - sim)
+        winkler_boost(sim, prefix_length)
     } else {
         sim
     }
 }
 
+/* Boosts a Jaro similarity for `affix_length` (at most 4) elements shared
+at one end of both sequences */
+fn winkler_boost<F: Float>(sim: F, affix_length: usize) -> F {
+    sim + F::from_f64(0.1) * F::from_usize(affix_length) * (F::ONE - sim)
+}
+
 /// Like Jaro but gives a boost to strings that have a common prefix.
 ///
 /// ```
@@ -219,7 +991,279 @@ where
 ///         0.001);
 /// ```
 pub fn jaro_winkler(a: &str, b: &str) -> f64 {
-    generic_jaro_winkler(&StringWrapper(a), &StringWrapper(b))
+    jaro_winkler_impl(a, b)
+}
+
+/// Like `jaro_winkler`, but calculates the similarity as `f32`.
+///
+/// ```
+/// use strsim::jaro_winkler_f32;
+///
+/// assert!((0.866 - jaro_winkler_f32("cheeseburger", "cheese fries")).abs() <
+///         0.001);
+/// ```
+pub fn jaro_winkler_f32(a: &str, b: &str) -> f32 {
+    jaro_winkler_impl(a, b)
+}
+
+fn jaro_winkler_impl<F: Float>(a: &str, b: &str) -> F {
+    jaro_winkler_from_jaro(jaro_impl(a, b), a, b)
+}
+
+/* Applies the prefix boost of `jaro_winkler` to the Jaro similarity `sim` of
+`a` and `b`, for callers which already calculated it */
+pub(crate) fn jaro_winkler_from_jaro<F: Float>(sim: F, a: &str, b: &str) -> F {
+    if sim > F::from_f64(0.7) {
+        winkler_boost(sim, jaro_winkler_prefix_len(a, b))
+    } else {
+        sim
+    }
+}
+
+/* The length of the common prefix considered by `jaro_winkler`, at most 4 */
+fn jaro_winkler_prefix_len(a: &str, b: &str) -> usize {
+    a.chars()
+        .take(4)
+        .zip(b.chars())
+        .take_while(|(a_char, b_char)| a_char == b_char)
+        .count()
+}
+
+/// The intermediate values of `jaro_winkler`, to explain how a similarity
+/// came about.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JaroWinklerExplanation {
+    /// The number of matching characters within the search window.
+    pub matches: usize,
+    /// The number of matching characters in a different order, counted as
+    /// half transpositions like in the Jaro similarity.
+    pub transpositions: usize,
+    /// The Jaro similarity before the boost.
+    pub jaro: f64,
+    /// The length of the common prefix, capped at 4 characters.
+    pub prefix_length: usize,
+    /// The boost added for the common prefix. It is only applied when the
+    /// Jaro similarity is above 0.7.
+    pub prefix_boost: f64,
+    /// The final Jaro-Winkler similarity, the same value `jaro_winkler`
+    /// returns.
+    pub similarity: f64,
+}
+
+/// Like `jaro_winkler`, but returns the intermediate values of the
+/// calculation alongside the similarity.
+///
+/// ```
+/// use strsim::explain_jaro_winkler;
+///
+/// let explanation = explain_jaro_winkler("martha", "marhta");
+/// assert_eq!(6, explanation.matches);
+/// assert_eq!(1, explanation.transpositions);
+/// assert_eq!(3, explanation.prefix_length);
+/// assert!((0.944 - explanation.jaro).abs() < 0.001);
+/// assert!((0.961 - explanation.similarity).abs() < 0.001);
+/// ```
+pub fn explain_jaro_winkler(a: &str, b: &str) -> JaroWinklerExplanation {
+    let counts = if a.is_ascii() && b.is_ascii() {
+        jaro_counts_bytes(a.as_bytes(), b.as_bytes())
+    } else {
+        jaro_counts(&StringWrapper(a), &StringWrapper(b))
+    };
+    let jaro: f64 = counts.similarity();
+    let prefix_length = jaro_winkler_prefix_len(a, b);
+    let similarity = jaro_winkler_from_jaro(jaro, a, b);
+
+    JaroWinklerExplanation {
+        matches: counts.matches,
+        transpositions: counts.transpositions,
+        jaro,
+        prefix_length,
+        prefix_boost: similarity - jaro,
+        similarity,
+    }
+}
+
+/// Like `jaro_winkler`, but `wildcard` in either string matches any
+/// character, including within the common prefix.
+///
+/// ```
+/// use strsim::jaro_winkler_wildcard;
+///
+/// assert!((0.961 - jaro_winkler_wildcard("?arhta", "martha", '?')).abs() <
+///         0.001);
+/// ```
+pub fn jaro_winkler_wildcard(a: &str, b: &str, wildcard: char) -> f64 {
+    generic_jaro_winkler(
+        &WildcardWrapper { s: a, wildcard },
+        &WildcardWrapper { s: b, wildcard },
+    )
+}
+
+/// Like `jaro_bytes` but gives a boost to byte strings that have a common
+/// prefix. `jaro_winkler` uses this automatically when both strings are ASCII.
+///
+/// ```
+/// use strsim::jaro_winkler_bytes;
+///
+/// assert!((0.961 - jaro_winkler_bytes(b"martha", b"marhta")).abs() < 0.001);
+/// ```
+pub fn jaro_winkler_bytes(a: &[u8], b: &[u8]) -> f64 {
+    let sim = jaro_bytes(a, b);
+
+    if sim > 0.7 {
+        let prefix_length = a
+            .iter()
+            .take(4)
+            .zip(b)
+            .take_while(|(a_elem, b_elem)| a_elem == b_elem)
+            .count();
+
+        winkler_boost(sim, prefix_length)
+    } else {
+        sim
+    }
+}
+
+/// The end of the sequences whose common elements are boosted by
+/// `jaro_winkler_with_boost`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WinklerBoost {
+    /// Boosts a common prefix, like `jaro_winkler`.
+    Prefix,
+    /// Boosts a common suffix, like `jaro_winkler_suffix`.
+    Suffix,
+    /// Boosts a common prefix, and then a common suffix of the boosted
+    /// similarity.
+    Both,
+}
+
+/// Like Jaro-Winkler but gives the boost to sequences that have a common
+/// prefix, a common suffix or both, depending on `boost`.
+pub fn generic_jaro_winkler_with_boost<'a, 'b, Iter1, Iter2, Elem1, Elem2>(
+    a: &'a Iter1,
+    b: &'b Iter2,
+    boost: WinklerBoost,
+) -> f64
+where
+    &'a Iter1: IntoIterator<Item = Elem1>,
+    &'b Iter2: IntoIterator<Item = Elem2>,
+    <&'a Iter1 as IntoIterator>::IntoIter: DoubleEndedIterator,
+    <&'b Iter2 as IntoIterator>::IntoIter: DoubleEndedIterator,
+    Elem1: PartialEq<Elem2>,
+{
+    let mut sim = generic_jaro(a, b);
+
+    if sim > 0.7 {
+        if boost != WinklerBoost::Suffix {
+            let prefix_length = a
+                .into_iter()
+                .take(4)
+                .zip(b)
+                .take_while(|(a_elem, b_elem)| a_elem == b_elem)
+                .count();
+            sim = winkler_boost(sim, prefix_length);
+        }
+        if boost != WinklerBoost::Prefix {
+            let suffix_length = a
+                .into_iter()
+                .rev()
+                .take(4)
+                .zip(b.into_iter().rev())
+                .take_while(|(a_elem, b_elem)| a_elem == b_elem)
+                .count();
+            sim = winkler_boost(sim, suffix_length);
+        }
+    }
+    sim
+}
+
+/// Like Jaro-Winkler but gives the boost to strings that have a common
+/// prefix, a common suffix or both, depending on `boost`. Boosting both ends
+/// suits strings that differ in the middle, like names with a changed
+/// middle initial.
+///
+/// ```
+/// use strsim::{jaro_winkler, jaro_winkler_suffix, jaro_winkler_with_boost, WinklerBoost};
+///
+/// let (a, b) = ("john f. kennedy", "john k. kennedy");
+/// assert_eq!(jaro_winkler(a, b), jaro_winkler_with_boost(a, b, WinklerBoost::Prefix));
+/// assert_eq!(jaro_winkler_suffix(a, b), jaro_winkler_with_boost(a, b, WinklerBoost::Suffix));
+/// assert!(jaro_winkler_with_boost(a, b, WinklerBoost::Both) > jaro_winkler(a, b));
+/// ```
+pub fn jaro_winkler_with_boost(a: &str, b: &str, boost: WinklerBoost) -> f64 {
+    generic_jaro_winkler_with_boost(&StringWrapper(a), &StringWrapper(b), boost)
+}
+
+/// Like Jaro-Winkler but gives the boost to sequences that have a common
+/// suffix instead of a common prefix.
+pub fn generic_jaro_winkler_suffix<'a, 'b, Iter1, Iter2, Elem1, Elem2>(
+    a: &'a Iter1,
+    b: &'b Iter2,
+) -> f64
+where
+    &'a Iter1: IntoIterator<Item = Elem1>,
+    &'b Iter2: IntoIterator<Item = Elem2>,
+    <&'a Iter1 as IntoIterator>::IntoIter: DoubleEndedIterator,
+    <&'b Iter2 as IntoIterator>::IntoIter: DoubleEndedIterator,
+    Elem1: PartialEq<Elem2>,
+{
+    generic_jaro_winkler_with_boost(a, b, WinklerBoost::Suffix)
+}
+
+/// Like Jaro-Winkler but gives the boost to strings that have a common suffix
+/// instead of a common prefix. This suits strings that share their endings,
+/// like file extensions or surname suffixes.
+///
+/// ```
+/// use strsim::jaro_winkler_suffix;
+///
+/// assert!((0.902 - jaro_winkler_suffix("henderson", "anderson")).abs() <
+///         0.001);
+/// ```
+pub fn jaro_winkler_suffix(a: &str, b: &str) -> f64 {
+    generic_jaro_winkler_suffix(&StringWrapper(a), &StringWrapper(b))
+}
+
+/// Like Jaro-Winkler but additionally applies Winkler's adjustment for long
+/// strings from the `strcmp95` reference implementation. Once the prefix boost
+/// was applied, strings longer than 4 characters that agree on at least two
+/// more characters beyond the prefix, and on more than half of the remaining
+/// characters, are boosted further. Like in `strcmp95`, strings starting with
+/// a digit are not adjusted.
+///
+/// ```
+/// use strsim::{jaro_winkler, jaro_winkler_long_strings};
+///
+/// assert!((0.869 - jaro_winkler_long_strings("dwayne", "duane")).abs() <
+///         0.001);
+/// assert!(jaro_winkler_long_strings("dwayne", "duane") > jaro_winkler("dwayne", "duane"));
+/// ```
+pub fn jaro_winkler_long_strings(a: &str, b: &str) -> f64 {
+    let counts = jaro_counts(&StringWrapper(a), &StringWrapper(b));
+    let mut sim = counts.similarity();
+
+    if sim > 0.7 {
+        let prefix_length = a
+            .chars()
+            .take(4)
+            .zip(b.chars())
+            .take_while(|(a_elem, b_elem)| a_elem == b_elem)
+            .count();
+        sim = winkler_boost(sim, prefix_length);
+
+        let min_len = min(counts.a_len, counts.b_len);
+        let starts_with_digit = a.chars().next().map_or(false, |c| c.is_ascii_digit());
+        if min_len > 4
+            && counts.matches > prefix_length + 1
+            && 2 * counts.matches >= min_len + prefix_length
+            && !starts_with_digit
+        {
+            sim += (1.0 - sim) * (counts.matches - prefix_length - 1) as f64
+                / (counts.a_len + counts.b_len - 2 * prefix_length + 2) as f64;
+        }
+    }
+
+    sim
 }
 
 /// Calculates the minimum number of insertions, deletions, and substitutions
@@ -238,24 +1282,28 @@ where
 {
     let b_len = b.into_iter().count();
 
-    let mut cache: Vec<usize> = (1..b_len + 1).collect();
+    with_buffer(b_len, |cache: &mut [usize]| {
+        for (j, dist) in cache.iter_mut().enumerate() {
+            *dist = j + 1;
+        }
 
-    let mut result = b_len;
+        let mut result = b_len;
 
-    for (i, a_elem) in a.into_iter().enumerate() {
-        result = i + 1;
-        let mut distance_b = i;
+        for (i, a_elem) in a.into_iter().enumerate() {
+            result = i + 1;
+            let mut distance_b = i;
 
-        for (j, b_elem) in b.into_iter().enumerate() {
-            let cost = usize::from(a_elem != b_elem);
-            let distance_a = distance_b + cost;
-            distance_b = cache[j];
-            result = min(result + 1, min(distance_a, distance_b + 1));
-            cache[j] = result;
+            for (j, b_elem) in b.into_iter().enumerate() {
+                let cost = usize::from(a_elem != b_elem);
+                let distance_a = distance_b + cost;
+                distance_b = cache[j];
+                result = min(result + 1, min(distance_a, distance_b + 1));
+                cache[j] = result;
+            }
         }
-    }
 
-    result
+        result
+    })
 }
 
 /// Calculates the minimum number of insertions, deletions, and substitutions
@@ -267,791 +1315,4289 @@ where
 /// assert_eq!(3, levenshtein("kitten", "sitting"));
 /// ```
 pub fn levenshtein(a: &str, b: &str) -> usize {
-    generic_levenshtein(&StringWrapper(a), &StringWrapper(b))
+    levenshtein_with_backend(a, b, LevenshteinBackend::Auto)
 }
 
-/// Calculates a normalized score of the Levenshtein algorithm between 0.0 and
-/// 1.0 (inclusive), where 1.0 means the strings are the same.
+/// The algorithm used to calculate the Levenshtein distance. All of them
+/// return the same results, but their performance depends on the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevenshteinBackend {
+    /// Picks one of the other backends based on the string lengths, the
+    /// distance limit and the alphabet.
+    Auto,
+    /// The classic dynamic programming algorithm, which needs `O(N*M)` time.
+    Classic,
+    /// The bit-parallel algorithm by Myers and Hyyrö, which needs
+    /// `O(N*M/64)` time.
+    BitParallel,
+    /// Only considers alignments within the distance limit around the
+    /// diagonal, which needs `O(N*limit)` time. Behaves like `BitParallel`
+    /// without a limit.
+    Banded,
+    /// The `mbleven` algorithm, which enumerates the possible edit operations
+    /// and needs `O(N)` time. Only supports limits up to 3; larger limits and
+    /// missing limits fall back to `Auto`.
+    Mbleven,
+}
+
+/// Like `levenshtein`, but uses the algorithm selected by `backend`.
 ///
 /// ```
-/// use strsim::normalized_levenshtein;
+/// use strsim::{levenshtein_with_backend, LevenshteinBackend};
 ///
-/// assert!((normalized_levenshtein("kitten", "sitting") - 0.57142).abs() < 0.00001);
-/// assert!((normalized_levenshtein("", "") - 1.0).abs() < 0.00001);
-/// assert!(normalized_levenshtein("", "second").abs() < 0.00001);
-/// assert!(normalized_levenshtein("first", "").abs() < 0.00001);
-/// assert!((normalized_levenshtein("string", "string") - 1.0).abs() < 0.00001);
+/// assert_eq!(3, levenshtein_with_backend("kitten", "sitting", LevenshteinBackend::Classic));
+/// assert_eq!(3, levenshtein_with_backend("kitten", "sitting", LevenshteinBackend::BitParallel));
 /// ```
-pub fn normalized_levenshtein(a: &str, b: &str) -> f64 {
-    if a.is_empty() && b.is_empty() {
-        return 1.0;
-    }
-    1.0 - (levenshtein(a, b) as f64) / (a.chars().count().max(b.chars().count()) as f64)
+pub fn levenshtein_with_backend(a: &str, b: &str, backend: LevenshteinBackend) -> usize {
+    levenshtein_dispatch(a, b, usize::MAX, backend)
 }
 
-/// Like Levenshtein but allows for adjacent transpositions. Each substring can
-/// only be edited once.
+/// Calculates the Levenshtein distance if it is at most `limit`, and returns
+/// `None` otherwise. A small limit allows for considerably faster algorithms.
 ///
 /// ```
-/// use strsim::osa_distance;
+/// use strsim::bounded_levenshtein;
 ///
-/// assert_eq!(3, osa_distance("ab", "bca"));
+/// assert_eq!(Some(3), bounded_levenshtein("kitten", "sitting", 3));
+/// assert_eq!(None, bounded_levenshtein("kitten", "sitting", 2));
 /// ```
-pub fn osa_distance(a: &str, b: &str) -> usize {
-    let b_len = b.chars().count();
-    // 0..=b_len behaves like 0..b_len.saturating_add(1) which could be a different size
-    // this leads to significantly worse code gen when swapping the vectors below
-    let mut prev_two_distances: Vec<usize> = (0..b_len + 1).collect();
-    let mut prev_distances: Vec<usize> = (0..b_len + 1).collect();
-    let mut curr_distances: Vec<usize> = vec![0; b_len + 1];
-
-    let mut prev_a_char = char::MAX;
-    let mut prev_b_char = char::MAX;
-
-    for (i, a_char) in a.chars().enumerate() {
-        curr_distances[0] = i + 1;
-
-        for (j, b_char) in b.chars().enumerate() {
-            let cost = usize::from(a_char != b_char);
-            curr_distances[j + 1] = min(
-                curr_distances[j] + 1,
-                min(prev_distances[j + 1] + 1, prev_distances[j] + cost),
-            );
-            if i > 0 && j > 0 && a_char != b_char && a_char == prev_b_char && b_char == prev_a_char
-            {
-                curr_distances[j + 1] = min(curr_distances[j + 1], prev_two_distances[j - 1] + 1);
-            }
-
-            prev_b_char = b_char;
-        }
+pub fn bounded_levenshtein(a: &str, b: &str, limit: usize) -> Option<usize> {
+    bounded_levenshtein_with_backend(a, b, limit, LevenshteinBackend::Auto)
+}
 
-        mem::swap(&mut prev_two_distances, &mut prev_distances);
-        mem::swap(&mut prev_distances, &mut curr_distances);
-        prev_a_char = a_char;
+/// Like `bounded_levenshtein`, but uses the algorithm selected by `backend`.
+///
+/// ```
+/// use strsim::{bounded_levenshtein_with_backend, LevenshteinBackend};
+///
+/// assert_eq!(Some(1), bounded_levenshtein_with_backend("test", "tent", 1, LevenshteinBackend::Mbleven));
+/// assert_eq!(None, bounded_levenshtein_with_backend("test", "tent", 0, LevenshteinBackend::Banded));
+/// ```
+pub fn bounded_levenshtein_with_backend(
+    a: &str,
+    b: &str,
+    limit: usize,
+    backend: LevenshteinBackend,
+) -> Option<usize> {
+    let dist = levenshtein_dispatch(a, b, limit, backend);
+    if dist <= limit {
+        Some(dist)
+    } else {
+        None
     }
+}
 
-    // access prev_distances instead of curr_distances since we swapped
-    // them above. In case a is empty this would still contain the correct value
-    // from initializing the last element to b_len
-    prev_distances[b_len]
+/// Like `levenshtein`, but takes the first string as decoded characters, e.g.
+/// the characters stored in an index, so only the second string is decoded.
+///
+/// ```
+/// use strsim::levenshtein_chars;
+///
+/// let kitten: Vec<char> = "kitten".chars().collect();
+/// assert_eq!(3, levenshtein_chars(&kitten, "sitting"));
+/// ```
+pub fn levenshtein_chars(a: &[char], b: &str) -> usize {
+    levenshtein_chars_dispatch(a, b, usize::MAX)
 }
 
-/* Returns the final index for a value in a single vector that represents a fixed
-2d grid */
-fn flat_index(i: usize, j: usize, width: usize) -> usize {
-    j * width + i
+/// Like `bounded_levenshtein`, but takes the first string as decoded
+/// characters.
+///
+/// ```
+/// use strsim::bounded_levenshtein_chars;
+///
+/// let kitten: Vec<char> = "kitten".chars().collect();
+/// assert_eq!(Some(3), bounded_levenshtein_chars(&kitten, "sitting", 3));
+/// assert_eq!(None, bounded_levenshtein_chars(&kitten, "sitting", 2));
+/// ```
+pub fn bounded_levenshtein_chars(a: &[char], b: &str, limit: usize) -> Option<usize> {
+    let dist = levenshtein_chars_dispatch(a, b, limit);
+    if dist <= limit {
+        Some(dist)
+    } else {
+        None
+    }
 }
 
-/// Like optimal string alignment, but substrings can be edited an unlimited
-/// number of times, and the triangle inequality holds.
+/// Like `normalized_levenshtein`, but takes the first string as decoded
+/// characters.
 ///
 /// ```
-/// use strsim::generic_damerau_levenshtein;
+/// use strsim::normalized_levenshtein_chars;
 ///
-/// assert_eq!(2, generic_damerau_levenshtein(&[1,2], &[2,3,1]));
+/// let kitten: Vec<char> = "kitten".chars().collect();
+/// assert!((normalized_levenshtein_chars(&kitten, "sitting") - 0.57142).abs() < 0.00001);
 /// ```
-pub fn generic_damerau_levenshtein<Elem>(a_elems: &[Elem], b_elems: &[Elem]) -> usize
-where
-    Elem: Eq + Hash + Clone,
-{
-    let a_len = a_elems.len();
-    let b_len = b_elems.len();
+pub fn normalized_levenshtein_chars(a: &[char], b: &str) -> f64 {
+    normalized_similarity(
+        levenshtein_chars(a, b),
+        a.len(),
+        b.chars().count(),
+        Denominator::Max,
+    )
+}
 
-    if a_len == 0 {
-        return b_len;
-    }
-    if b_len == 0 {
-        return a_len;
+/* `levenshtein_dispatch` with the automatically selected backend for decoded
+characters and a string */
+fn levenshtein_chars_dispatch(a: &[char], b: &str, limit: usize) -> usize {
+    let (a, b) = trim_common_affix(a, b);
+    let a_len = a.len();
+    let b_len = b.chars().count();
+    let backend = LevenshteinBackend::Auto;
+    if a_len >= b_len {
+        levenshtein_backends(a.iter().copied(), a_len, b.chars(), b_len, limit, backend)
+    } else {
+        levenshtein_backends(b.chars(), b_len, a.iter().copied(), a_len, limit, backend)
     }
+}
 
-    let width = a_len + 2;
-    let mut distances = vec![0; (a_len + 2) * (b_len + 2)];
-    let max_distance = a_len + b_len;
-    distances[0] = max_distance;
+/* A string or decoded characters, from which `trim_common_affix` removes
+characters at either end */
+trait CharSequence: Sized {
+    type Chars: DoubleEndedIterator<Item = char>;
 
-    for i in 0..(a_len + 1) {
-        distances[flat_index(i + 1, 0, width)] = max_distance;
-        distances[flat_index(i + 1, 1, width)] = i;
-    }
+    fn sequence_chars(&self) -> Self::Chars;
 
-    for j in 0..(b_len + 1) {
-        distances[flat_index(0, j + 1, width)] = max_distance;
-        distances[flat_index(1, j + 1, width)] = j;
-    }
+    /* Removes `count` characters taking up `bytes` bytes from the start */
+    fn trim_start(self, count: usize, bytes: usize) -> Self;
 
-    let mut elems: HashMap<Elem, usize> = HashMap::with_capacity(64);
+    /* Removes `count` characters taking up `bytes` bytes from the end */
+    fn trim_end(self, count: usize, bytes: usize) -> Self;
+}
 
-    for i in 1..(a_len + 1) {
-        let mut db = 0;
+impl<'a> CharSequence for &'a str {
+    type Chars = Chars<'a>;
 
-        for j in 1..(b_len + 1) {
-            let k = match elems.get(&b_elems[j - 1]) {
-                Some(&value) => value,
-                None => 0,
-            };
+    fn sequence_chars(&self) -> Chars<'a> {
+        self.chars()
+    }
 
-            let insertion_cost = distances[flat_index(i, j + 1, width)] + 1;
-            let deletion_cost = distances[flat_index(i + 1, j, width)] + 1;
-            let transposition_cost =
-                distances[flat_index(k, db, width)] + (i - k - 1) + 1 + (j - db - 1);
+    fn trim_start(self, _count: usize, bytes: usize) -> Self {
+        &self[bytes..]
+    }
 
-            let mut substitution_cost = distances[flat_index(i, j, width)] + 1;
-            if a_elems[i - 1] == b_elems[j - 1] {
-                db = j;
-                substitution_cost -= 1;
-            }
+    fn trim_end(self, _count: usize, bytes: usize) -> Self {
+        &self[..self.len() - bytes]
+    }
+}
 
-            distances[flat_index(i + 1, j + 1, width)] = min(
-                substitution_cost,
-                min(insertion_cost, min(deletion_cost, transposition_cost)),
-            );
-        }
+impl<'a> CharSequence for &'a [char] {
+    type Chars = Copied<slice::Iter<'a, char>>;
 
-        elems.insert(a_elems[i - 1].clone(), i);
+    fn sequence_chars(&self) -> Self::Chars {
+        self.iter().copied()
     }
 
-    distances[flat_index(a_len + 1, b_len + 1, width)]
-}
+    fn trim_start(self, count: usize, _bytes: usize) -> Self {
+        &self[count..]
+    }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
-struct RowId {
-    val: isize,
+    fn trim_end(self, count: usize, _bytes: usize) -> Self {
+        &self[..self.len() - count]
+    }
 }
 
-impl Default for RowId {
-    fn default() -> Self {
-        Self { val: -1 }
+/* Removes the common prefix and suffix, which never change the Levenshtein,
+OSA or Damerau-Levenshtein distance. Shared characters have the same UTF-8
+length, so the byte offsets are the same in both strings */
+fn trim_common_affix<A: CharSequence, B: CharSequence>(a: A, b: B) -> (A, B) {
+    /* the number of characters and bytes shared by both iterators */
+    fn shared<A: Iterator<Item = char>, B: Iterator<Item = char>>(a: A, b: B) -> (usize, usize) {
+        a.zip(b)
+            .take_while(|(a_char, b_char)| a_char == b_char)
+            .fold((0, 0), |(count, bytes), (a_char, _)| {
+                (count + 1, bytes + a_char.len_utf8())
+            })
     }
-}
 
-#[derive(Default, Clone)]
-struct GrowingHashmapMapElemChar<ValueType> {
-    key: u32,
-    value: ValueType,
+    let (count, bytes) = shared(a.sequence_chars(), b.sequence_chars());
+    let (a, b) = (a.trim_start(count, bytes), b.trim_start(count, bytes));
+    let (count, bytes) = shared(a.sequence_chars().rev(), b.sequence_chars().rev());
+    (a.trim_end(count, bytes), b.trim_end(count, bytes))
 }
 
-/// specialized hashmap to store user provided types
-/// this implementation relies on a couple of base assumptions in order to simplify the implementation
-/// - the hashmap does not have an upper limit of included items
-/// - the default value for the `ValueType` can be used as a dummy value to indicate an empty cell
-/// - elements can't be removed
-/// - only allocates memory on first write access.
-///   This improves performance for hashmaps that are never written to
-struct GrowingHashmapChar<ValueType> {
-    used: i32,
-    fill: i32,
-    mask: i32,
-    map: Option<Vec<GrowingHashmapMapElemChar<ValueType>>>,
+/* Returns the Levenshtein distance, or any value above `limit` when the
+distance exceeds it */
+fn levenshtein_dispatch(a: &str, b: &str, limit: usize, backend: LevenshteinBackend) -> usize {
+    // the classic backend doesn't remove the common affix, so it stays a
+    // reference implementation for the other backends
+    if backend == LevenshteinBackend::Classic {
+        return generic_levenshtein(&StringWrapper(a), &StringWrapper(b));
+    }
+
+    let (a, b) = trim_common_affix(a, b);
+    let a_len = a.chars().count();
+    let b_len = b.chars().count();
+    // keep the longer string in `a`, the shorter one is used as the pattern
+    if a_len >= b_len {
+        levenshtein_backends(a.chars(), a_len, b.chars(), b_len, limit, backend)
+    } else {
+        levenshtein_backends(b.chars(), b_len, a.chars(), a_len, limit, backend)
+    }
 }
 
-impl<ValueType> Default for GrowingHashmapChar<ValueType>
+/* Runs the backend for strings without a common prefix or suffix, with the
+longer string in `a` */
+fn levenshtein_backends<A, B>(
+    a: A,
+    a_len: usize,
+    b: B,
+    b_len: usize,
+    limit: usize,
+    backend: LevenshteinBackend,
+) -> usize
 where
-    ValueType: Default + Clone + Eq,
+    A: Iterator<Item = char> + Clone,
+    B: Iterator<Item = char> + Clone,
 {
-    fn default() -> Self {
-        Self {
-            used: 0,
-            fill: 0,
-            mask: -1,
-            map: None,
+    if a_len - b_len > limit {
+        return limit.saturating_add(1);
+    }
+    if b_len == 0 {
+        return a_len;
+    }
+
+    let words = (b_len + 63) / 64;
+    let backend = match backend {
+        LevenshteinBackend::Auto | LevenshteinBackend::Mbleven if limit < 4 => {
+            LevenshteinBackend::Mbleven
         }
+        LevenshteinBackend::Banded if limit < b_len => LevenshteinBackend::Banded,
+        // one cell of the banded algorithm is about as expensive as computing
+        // half a word of the bit-parallel algorithm
+        LevenshteinBackend::Auto | LevenshteinBackend::Mbleven if words > 1 && limit < words => {
+            LevenshteinBackend::Banded
+        }
+        _ => LevenshteinBackend::BitParallel,
+    };
+
+    match backend {
+        LevenshteinBackend::Mbleven => mbleven(a, a_len, b, b_len, limit),
+        LevenshteinBackend::Banded => banded_levenshtein(a, a_len, b, b_len, limit),
+        _ if words == 1 => levenshtein_hyrroe2003(a, b, b_len),
+        _ => levenshtein_hyrroe2003_block(a, b, b_len),
     }
 }
 
-impl<ValueType> GrowingHashmapChar<ValueType>
+/* The possible edit operations for each combination of limit and length
+difference. Every two bits describe one operation: 1 deletes from the
+longer string, 2 inserts into the longer string and 3 substitutes */
+const MBLEVEN_MATRIX: [&[u8]; 9] = [
+    // limit 1
+    &[0x03], // length difference 0
+    &[0x01], // length difference 1
+    // limit 2
+    &[0x0F, 0x09, 0x06],
+    &[0x0D, 0x07],
+    &[0x05],
+    // limit 3
+    &[0x3F, 0x27, 0x2D, 0x39, 0x36, 0x1E, 0x1B],
+    &[0x3D, 0x37, 0x1F, 0x25, 0x19, 0x16],
+    &[0x35, 0x1D, 0x17],
+    &[0x15],
+];
+
+/* The mbleven algorithm by Fujimoto Seiji. Requires a limit below 4, the
+longer string in `a`, a length difference within the limit and strings without
+a common prefix or suffix */
+fn mbleven<A, B>(a: A, a_len: usize, b: B, b_len: usize, limit: usize) -> usize
 where
-    ValueType: Default + Clone + Eq + Copy,
+    A: Iterator<Item = char> + Clone,
+    B: Iterator<Item = char> + Clone,
 {
-    fn get(&self, key: u32) -> ValueType {
-        self.map
-            .as_ref()
-            .map_or_else(|| Default::default(), |map| map[self.lookup(key)].value)
+    let len_diff = a_len - b_len;
+    if limit == 0 {
+        // the strings differ, since they have no common affix
+        return 1;
+    }
+    if limit == 1 {
+        return if len_diff == 1 || a_len != 1 { 2 } else { 1 };
     }
 
-    fn get_mut(&mut self, key: u32) -> &mut ValueType {
-        if self.map.is_none() {
-            self.allocate();
-        }
-
-        let mut i = self.lookup(key);
-        if self
-            .map
-            .as_ref()
-            .expect("map should have been created above")[i]
-            .value
-            == Default::default()
-        {
-            self.fill += 1;
-            // resize when 2/3 full
-            if self.fill * 3 >= (self.mask + 1) * 2 {
-                self.grow((self.used + 1) * 2);
-                i = self.lookup(key);
+    let ops_index = (limit + limit * limit) / 2 + len_diff - 1;
+    let mut dist = limit + 1;
+    for &possible_ops in MBLEVEN_MATRIX[ops_index] {
+        let mut ops = possible_ops;
+        let mut a_iter = a.clone().peekable();
+        let mut b_iter = b.clone().peekable();
+        let mut cur_dist = 0;
+
+        while let (Some(a_char), Some(b_char)) = (a_iter.peek(), b_iter.peek()) {
+            if a_char == b_char {
+                a_iter.next();
+                b_iter.next();
+            } else {
+                cur_dist += 1;
+                if ops == 0 {
+                    break;
+                }
+                if ops & 1 != 0 {
+                    a_iter.next();
+                }
+                if ops & 2 != 0 {
+                    b_iter.next();
+                }
+                ops >>= 2;
             }
-
-            self.used += 1;
         }
 
-        let elem = &mut self
-            .map
-            .as_mut()
-            .expect("map should have been created above")[i];
-        elem.key = key;
-        &mut elem.value
+        cur_dist += a_iter.count() + b_iter.count();
+        dist = min(dist, cur_dist);
     }
 
-    fn allocate(&mut self) {
-        self.mask = 8 - 1;
-        self.map = Some(vec![GrowingHashmapMapElemChar::default(); 8]);
+    dist
+}
+
+/* Hyyrö's formulation of Myers' bit-parallel algorithm for a `pattern` of at
+most 64 characters */
+fn levenshtein_hyrroe2003(
+    text: impl Iterator<Item = char>,
+    pattern: impl Iterator<Item = char>,
+    pattern_len: usize,
+) -> usize {
+    let mut pattern_masks = HybridGrowingHashmapChar::<u64>::default();
+    for (i, ch) in pattern.enumerate() {
+        *pattern_masks.get_mut(ch) |= 1 << i;
     }
 
-    /// lookup key inside the hashmap using a similar collision resolution
-    /// strategy to `CPython` and `Ruby`
-    fn lookup(&self, key: u32) -> usize {
-        let hash = key;
-        let mut i = hash as usize & self.mask as usize;
+    let mut vp = !0_u64;
+    let mut vn = 0_u64;
+    let mut dist = pattern_len;
+    let last = 1_u64 << (pattern_len - 1);
 
-        let map = self
-            .map
-            .as_ref()
-            .expect("callers have to ensure map is allocated");
+    for ch in text {
+        let pm_j = pattern_masks.get(ch);
+        let x = pm_j;
+        let d0 = (((x & vp).wrapping_add(vp)) ^ vp) | x | vn;
 
-        if map[i].value == Default::default() || map[i].key == key {
-            return i;
+        let mut hp = vn | !(d0 | vp);
+        let mut hn = d0 & vp;
+        if hp & last != 0 {
+            dist += 1;
+        }
+        if hn & last != 0 {
+            dist -= 1;
         }
 
-        let mut perturb = key;
-        loop {
-            i = (i * 5 + perturb as usize + 1) & self.mask as usize;
+        hp = (hp << 1) | 1;
+        hn <<= 1;
+        vp = hn | !(d0 | hp);
+        vn = hp & d0;
+    }
 
-            if map[i].value == Default::default() || map[i].key == key {
-                return i;
-            }
+    dist
+}
 
-            perturb >>= 5;
-        }
+/* The bit-parallel algorithm for patterns of arbitrary length, which splits
+the pattern into blocks of 64 characters and carries the horizontal deltas
+from one block to the next */
+fn levenshtein_hyrroe2003_block(
+    text: impl Iterator<Item = char>,
+    pattern: impl Iterator<Item = char>,
+    pattern_len: usize,
+) -> usize {
+    let words = (pattern_len + 63) / 64;
+    let mut pattern_masks: Vec<HybridGrowingHashmapChar<u64>> = (0..words)
+        .map(|_| HybridGrowingHashmapChar::default())
+        .collect();
+    for (i, ch) in pattern.enumerate() {
+        *pattern_masks[i / 64].get_mut(ch) |= 1 << (i % 64);
     }
 
-    fn grow(&mut self, min_used: i32) {
-        let mut new_size = self.mask + 1;
-        while new_size <= min_used {
-            new_size <<= 1;
-        }
+    let mut vp = vec![!0_u64; words];
+    let mut vn = vec![0_u64; words];
+    let mut dist = pattern_len;
+    let last = 1_u64 << ((pattern_len - 1) % 64);
 
-        self.fill = self.used;
-        self.mask = new_size - 1;
+    for ch in text {
+        let mut hp_carry = 1_u64;
+        let mut hn_carry = 0_u64;
 
-        let old_map = std::mem::replace(
-            self.map
-                .as_mut()
-                .expect("callers have to ensure map is allocated"),
-            vec![GrowingHashmapMapElemChar::<ValueType>::default(); new_size as usize],
-        );
+        for word in 0..words {
+            let pm_j = pattern_masks[word].get(ch);
+            let x = pm_j | hn_carry;
+            let d0 = (((x & vp[word]).wrapping_add(vp[word])) ^ vp[word]) | x | vn[word];
 
-        for elem in old_map {
-            if elem.value != Default::default() {
-                let j = self.lookup(elem.key);
-                let new_elem = &mut self.map.as_mut().expect("map created above")[j];
-                new_elem.key = elem.key;
-                new_elem.value = elem.value;
-                self.used -= 1;
-                if self.used == 0 {
-                    break;
+            let mut hp = vn[word] | !(d0 | vp[word]);
+            let mut hn = d0 & vp[word];
+
+            let hp_carry_in = hp_carry;
+            let hn_carry_in = hn_carry;
+            if word < words - 1 {
+                hp_carry = hp >> 63;
+                hn_carry = hn >> 63;
+            } else {
+                if hp & last != 0 {
+                    dist += 1;
+                }
+                if hn & last != 0 {
+                    dist -= 1;
                 }
             }
+
+            hp = (hp << 1) | hp_carry_in;
+            hn = (hn << 1) | hn_carry_in;
+            vp[word] = hn | !(d0 | hp);
+            vn[word] = hp & d0;
         }
+    }
 
-        self.used = self.fill;
+    dist
+}
+
+#[derive(Clone, Copy)]
+struct WildcardChar {
+    ch: char,
+    wildcard: char,
+}
+
+impl PartialEq for WildcardChar {
+    fn eq(&self, other: &Self) -> bool {
+        self.ch == other.ch || self.ch == self.wildcard || other.ch == other.wildcard
     }
 }
 
-struct HybridGrowingHashmapChar<ValueType> {
-    map: GrowingHashmapChar<ValueType>,
-    extended_ascii: [ValueType; 256],
+struct WildcardWrapper<'a> {
+    s: &'a str,
+    wildcard: char,
 }
 
-impl<ValueType> HybridGrowingHashmapChar<ValueType>
-where
-    ValueType: Default + Clone + Copy + Eq,
-{
-    fn get(&self, key: char) -> ValueType {
-        let value = key as u32;
-        if value <= 255 {
-            let val_u8 = u8::try_from(value).expect("we check the bounds above");
-            self.extended_ascii[usize::from(val_u8)]
-        } else {
-            self.map.get(value)
-        }
+struct WildcardChars<'a> {
+    chars: Chars<'a>,
+    wildcard: char,
+}
+
+impl<'a> Iterator for WildcardChars<'a> {
+    type Item = WildcardChar;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let wildcard = self.wildcard;
+        self.chars.next().map(|ch| WildcardChar { ch, wildcard })
     }
+}
 
-    fn get_mut(&mut self, key: char) -> &mut ValueType {
-        let value = key as u32;
-        if value <= 255 {
-            let val_u8 = u8::try_from(value).expect("we check the bounds above");
-            &mut self.extended_ascii[usize::from(val_u8)]
-        } else {
-            self.map.get_mut(value)
+impl<'b> IntoIterator for &WildcardWrapper<'b> {
+    type Item = WildcardChar;
+    type IntoIter = WildcardChars<'b>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        WildcardChars {
+            chars: self.s.chars(),
+            wildcard: self.wildcard,
         }
     }
 }
 
-impl<ValueType> Default for HybridGrowingHashmapChar<ValueType>
-where
-    ValueType: Default + Clone + Copy + Eq,
-{
-    fn default() -> Self {
-        HybridGrowingHashmapChar {
-            map: GrowingHashmapChar::default(),
-            extended_ascii: [Default::default(); 256],
+/// Like `levenshtein`, but `wildcard` matches any character in the other
+/// string at zero cost. The wildcard itself still takes up one character.
+///
+/// ```
+/// use strsim::levenshtein_wildcard;
+///
+/// assert_eq!(0, levenshtein_wildcard("k?tten", "kitten", '?'));
+/// assert_eq!(1, levenshtein_wildcard("k?tten", "sitten", '?'));
+/// assert_eq!(1, levenshtein_wildcard("k?tten", "ktten", '?'));
+/// ```
+pub fn levenshtein_wildcard(a: &str, b: &str, wildcard: char) -> usize {
+    generic_levenshtein(
+        &WildcardWrapper { s: a, wildcard },
+        &WildcardWrapper { s: b, wildcard },
+    )
+}
+
+/// Calculates the smallest Levenshtein distance between any rotation of `a`
+/// and `b`, for circular strings like plasmid sequences or identifiers whose
+/// start is arbitrary. The result doesn't change when either string is
+/// rotated.
+///
+/// Every rotation of the shorter string corresponds to a path through the
+/// DP matrix of that string repeated twice and the longer string. The
+/// optimal paths of different rotations never need to cross, so following
+/// Maes (1990), the rotation in the middle of two solved ones is only
+/// aligned within the part of the matrix between their paths. This takes
+/// `O(N*M*log(min(N, M)))` time instead of the `O(N*M*min(N, M))` of
+/// comparing every rotation, and `O(N*M)` bytes of memory.
+///
+/// ```
+/// use strsim::{cyclic_levenshtein, levenshtein};
+///
+/// assert_eq!(0, cyclic_levenshtein("abcdef", "defabc"));
+/// assert_eq!(6, levenshtein("abcdef", "defabc"));
+/// assert_eq!(1, cyclic_levenshtein("abcdef", "dxfabc"));
+/// assert_eq!(3, cyclic_levenshtein("", "abc"));
+/// ```
+pub fn cyclic_levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    cyclic_levenshtein_chars(&a, &b).0
+}
+
+/* The cyclic Levenshtein distance of `a` and `b`, with the number of DP
+cells calculated for it */
+fn cyclic_levenshtein_chars(a: &[char], b: &[char]) -> (usize, usize) {
+    /* an alignment of a rotation of one string with the other string can
+    be rotated into an alignment of the first string with a rotation of the
+    other one, so only the shorter string is rotated */
+    let (a, b) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    if a.is_empty() {
+        return (b.len(), 0);
+    }
+
+    let doubled: Vec<char> = a.iter().chain(a).copied().collect();
+    let mut cells = 0;
+    let (mut best, first) = cyclic_alignment(&doubled, b, 0, None, None, &mut cells);
+    /* the rotation starting after all of `a` is the first one again */
+    let last = CyclicPath {
+        start: a.len(),
+        columns: first.columns.clone(),
+    };
+    let mut pending = vec![(first, last)];
+    while let Some((above, below)) = pending.pop() {
+        if below.start - above.start < 2 || best == b.len() - a.len() {
+            continue;
         }
+        let middle = (above.start + below.start) / 2;
+        let (distance, path) =
+            cyclic_alignment(&doubled, b, middle, Some(&above), Some(&below), &mut cells);
+        best = min(best, distance);
+        pending.push((above, path.clone()));
+        pending.push((path, below));
     }
+    (best, cells)
 }
 
-fn damerau_levenshtein_impl<Iter1, Iter2>(s1: Iter1, len1: usize, s2: Iter2, len2: usize) -> usize
-where
-    Iter1: Iterator<Item = char> + Clone,
-    Iter2: Iterator<Item = char> + Clone,
-{
-    // The implementations is based on the paper
-    // `Linear space string correction algorithm using the Damerau-Levenshtein distance`
-    // from Chunchun Zhao and Sartaj Sahni
-    //
-    // It has a runtime complexity of `O(N*M)` and a memory usage of `O(N+M)`.
-    let max_val = max(len1, len2) as isize + 1;
-
-    let mut last_row_id = HybridGrowingHashmapChar::<RowId>::default();
+/* The path of an alignment through the DP matrix of `a + a` and `b`, as the
+first and last column it visits in every row from `start` to `start +
+a.len()` */
+#[derive(Clone)]
+struct CyclicPath {
+    start: usize,
+    columns: Vec<(usize, usize)>,
+}
 
-    let size = len2 + 2;
-    let mut fr = vec![max_val; size];
-    let mut r1 = vec![max_val; size];
-    let mut r: Vec<isize> = (max_val..max_val + 1)
-        .chain(0..(size - 1) as isize)
+/* Aligns the rotation of `a` starting at `start` with `b` in the DP matrix
+of `doubled = a + a` and `b`. The path stays left of the path `above` of an
+earlier rotation and right of the path `below` of a later one, which
+doesn't change the distance */
+fn cyclic_alignment(
+    doubled: &[char],
+    b: &[char],
+    start: usize,
+    above: Option<&CyclicPath>,
+    below: Option<&CyclicPath>,
+    cells: &mut usize,
+) -> (usize, CyclicPath) {
+    const DIAGONAL: u8 = 0;
+    const UP: u8 = 1;
+    const LEFT: u8 = 2;
+
+    let rows = doubled.len() / 2 + 1;
+    let bounds: Vec<(usize, usize)> = (start..start + rows)
+        .map(|i| {
+            let first = below.map_or(0, |path| match i.checked_sub(path.start) {
+                Some(row) => path.columns[row].0,
+                None => 0,
+            });
+            let last = above.map_or(b.len(), |path| match path.columns.get(i - path.start) {
+                Some(&(_, last)) => last,
+                None => b.len(),
+            });
+            (first, last)
+        })
         .collect();
 
-    for (i, ch1) in s1.enumerate().map(|(i, ch1)| (i + 1, ch1)) {
-        mem::swap(&mut r, &mut r1);
-        let mut last_col_id: isize = -1;
-        let mut last_i2l1 = r[1];
-        r[1] = i as isize;
-        let mut t = max_val;
-
-        for (j, ch2) in s2.clone().enumerate().map(|(j, ch2)| (j + 1, ch2)) {
-            let diag = r1[j] + isize::from(ch1 != ch2);
-            let left = r[j] + 1;
-            let up = r1[j + 1] + 1;
-            let mut temp = min(diag, min(left, up));
-
-            if ch1 == ch2 {
-                last_col_id = j as isize; // last occurence of s1_i
-                fr[j + 1] = r1[j - 1]; // save H_k-1,j-2
-                t = last_i2l1; // save H_i-2,l-1
-            } else {
-                let k = last_row_id.get(ch2).val;
-                let l = last_col_id;
-
-                if j as isize - l == 1 {
-                    let transpose = fr[j + 1] + (i as isize - k);
-                    temp = min(temp, transpose);
-                } else if i as isize - k == 1 {
-                    let transpose = t + (j as isize - l);
-                    temp = min(temp, transpose);
-                }
-            }
-
-            last_i2l1 = r[j + 1];
-            r[j + 1] = temp;
+    /* the distances of the previous and the current row, shifted by one so
+    that the column before the first one of a row reads as unreachable */
+    const UNREACHABLE: usize = usize::MAX / 2;
+    let mut prev = vec![UNREACHABLE; b.len() + 2];
+    let mut curr = prev.clone();
+    for (j, distance) in prev[1..bounds[0].1 + 2].iter_mut().enumerate() {
+        *distance = j;
+    }
+    /* the step into every cell, with the offset of each row */
+    let mut steps = vec![LEFT; bounds[0].1 + 1];
+    let mut offsets = vec![0];
+    *cells += steps.len();
+    for row in 1..rows {
+        let (prev_first, prev_last) = bounds[row - 1];
+        let (first, last) = bounds[row];
+        prev[prev_first] = UNREACHABLE;
+        for distance in &mut prev[prev_last + 2..last + 2] {
+            *distance = UNREACHABLE;
+        }
+        let a_char = doubled[start + row - 1];
+        offsets.push(steps.len());
+        let mut left = UNREACHABLE;
+        let mut from = first;
+        if first == 0 {
+            left = prev[1] + 1;
+            curr[1] = left;
+            steps.push(UP);
+            from = 1;
+        }
+        /* the window holds the distances of the diagonal and the upper cell */
+        let cells_above = prev[from..last + 2].windows(2);
+        for ((b_char, above), distance) in b[from.saturating_sub(1)..last]
+            .iter()
+            .zip(cells_above)
+            .zip(&mut curr[from + 1..last + 2])
+        {
+            let diagonal = above[0] + usize::from(a_char != *b_char);
+            let up = above[1] + 1;
+            let vertical = min(up, left + 1);
+            /* branch free, as the choice is hard to predict */
+            steps.push(u8::from(diagonal > vertical) * (UP + u8::from(up > left + 1)));
+            left = min(diagonal, vertical);
+            *distance = left;
         }
-        last_row_id.get_mut(ch1).val = i as isize;
+        *cells += last + 1 - first;
+        mem::swap(&mut prev, &mut curr);
     }
 
-    r[len2 + 1] as usize
+    let distance = prev[b.len() + 1];
+    let mut columns = vec![(usize::MAX, 0); rows];
+    let (mut row, mut j) = (rows - 1, b.len());
+    loop {
+        let (first, last) = &mut columns[row];
+        *first = min(*first, j);
+        *last = max(*last, j);
+        if row == 0 && j == 0 {
+            break;
+        }
+        match steps[offsets[row] + j - bounds[row].0] {
+            DIAGONAL => {
+                row -= 1;
+                j -= 1;
+            }
+            UP => row -= 1,
+            _ => j -= 1,
+        }
+    }
+    (distance, CyclicPath { start, columns })
 }
 
-/// Like optimal string alignment, but substrings can be edited an unlimited
-/// number of times, and the triangle inequality holds.
+/// Calculates a normalized score of the Levenshtein algorithm between 0.0 and
+/// 1.0 (inclusive), where 1.0 means the strings are the same.
 ///
 /// ```
-/// use strsim::damerau_levenshtein;
+/// use strsim::normalized_levenshtein;
 ///
-/// assert_eq!(2, damerau_levenshtein("ab", "bca"));
+/// assert!((normalized_levenshtein("kitten", "sitting") - 0.57142).abs() < 0.00001);
+/// assert!((normalized_levenshtein("", "") - 1.0).abs() < 0.00001);
+/// assert!(normalized_levenshtein("", "second").abs() < 0.00001);
+/// assert!(normalized_levenshtein("first", "").abs() < 0.00001);
+/// assert!((normalized_levenshtein("string", "string") - 1.0).abs() < 0.00001);
 /// ```
-pub fn damerau_levenshtein(a: &str, b: &str) -> usize {
-    damerau_levenshtein_impl(a.chars(), a.chars().count(), b.chars(), b.chars().count())
+pub fn normalized_levenshtein(a: &str, b: &str) -> f64 {
+    normalized_levenshtein_with(a, b, Denominator::Max)
 }
 
-/// Calculates a normalized score of the Damerau–Levenshtein algorithm between
-/// 0.0 and 1.0 (inclusive), where 1.0 means the strings are the same.
+/// Like `normalized_levenshtein`, but returns the similarity in parts per
+/// million, calculated exactly with integers and rounded to the nearest
+/// integer.
 ///
 /// ```
-/// use strsim::normalized_damerau_levenshtein;
+/// use strsim::normalized_levenshtein_ppm;
 ///
-/// assert!((normalized_damerau_levenshtein("levenshtein", "löwenbräu") - 0.27272).abs() < 0.00001);
-/// assert!((normalized_damerau_levenshtein("", "") - 1.0).abs() < 0.00001);
-/// assert!(normalized_damerau_levenshtein("", "flower").abs() < 0.00001);
-/// assert!(normalized_damerau_levenshtein("tree", "").abs() < 0.00001);
-/// assert!((normalized_damerau_levenshtein("sunglasses", "sunglasses") - 1.0).abs() < 0.00001);
+/// assert_eq!(571_429, normalized_levenshtein_ppm("kitten", "sitting"));
+/// assert_eq!(1_000_000, normalized_levenshtein_ppm("", ""));
 /// ```
-pub fn normalized_damerau_levenshtein(a: &str, b: &str) -> f64 {
-    if a.is_empty() && b.is_empty() {
-        return 1.0;
-    }
-
-    let len1 = a.chars().count();
-    let len2 = b.chars().count();
-    let dist = damerau_levenshtein_impl(a.chars(), len1, b.chars(), len2);
-    1.0 - (dist as f64) / (max(len1, len2) as f64)
+pub fn normalized_levenshtein_ppm(a: &str, b: &str) -> u32 {
+    Denominator::Max.similarity_ppm(levenshtein(a, b), a.chars().count(), b.chars().count())
 }
 
-/// Returns an Iterator of char tuples.
-fn bigrams(s: &str) -> impl Iterator<Item = (char, char)> + '_ {
-    s.chars().zip(s.chars().skip(1))
+/// Like `normalized_levenshtein`, but calculates the similarity as `f32`.
+///
+/// ```
+/// use strsim::normalized_levenshtein_f32;
+///
+/// assert!((normalized_levenshtein_f32("kitten", "sitting") - 0.57142).abs() < 0.00001);
+/// assert_eq!(1.0, normalized_levenshtein_f32("", ""));
+/// ```
+pub fn normalized_levenshtein_f32(a: &str, b: &str) -> f32 {
+    normalized_similarity(
+        levenshtein(a, b),
+        a.chars().count(),
+        b.chars().count(),
+        Denominator::Max,
+    )
 }
 
-/// Calculates a Sørensen-Dice similarity distance using bigrams.
-/// See <https://en.wikipedia.org/wiki/S%C3%B8rensen%E2%80%93Dice_coefficient>.
+/// Like `normalized_levenshtein`, but divides the distance by the length
+/// selected by `denominator`. Scores that would fall below 0.0 because the
+/// distance exceeds the denominator are clamped to 0.0.
 ///
 /// ```
-/// use strsim::sorensen_dice;
+/// use strsim::{normalized_levenshtein_with, Denominator};
 ///
-/// assert_eq!(1.0, sorensen_dice("", ""));
-/// assert_eq!(0.0, sorensen_dice("", "a"));
-/// assert_eq!(0.0, sorensen_dice("french", "quebec"));
-/// assert_eq!(1.0, sorensen_dice("ferris", "ferris"));
-/// assert_eq!(0.8888888888888888, sorensen_dice("feris", "ferris"));
+/// assert!((normalized_levenshtein_with("kitten", "sitting", Denominator::Min) - 0.5).abs() < 0.00001);
+/// assert!((normalized_levenshtein_with("kitten", "sitting", Denominator::Sum) - 0.76923).abs() < 0.00001);
+/// assert!((normalized_levenshtein_with("", "", Denominator::Min) - 1.0).abs() < 0.00001);
 /// ```
-pub fn sorensen_dice(a: &str, b: &str) -> f64 {
-    // implementation guided by
-    // https://github.com/aceakash/string-similarity/blob/f83ba3cd7bae874c20c429774e911ae8cff8bced/src/index.js#L6
-
-    let a: String = a.chars().filter(|&x| !char::is_whitespace(x)).collect();
-    let b: String = b.chars().filter(|&x| !char::is_whitespace(x)).collect();
-
-    if a == b {
+pub fn normalized_levenshtein_with(a: &str, b: &str, denominator: Denominator) -> f64 {
+    if a.is_empty() && b.is_empty() {
         return 1.0;
     }
+    normalized_similarity(
+        levenshtein(a, b),
+        a.chars().count(),
+        b.chars().count(),
+        denominator,
+    )
+}
 
-    if a.len() < 2 || b.len() < 2 {
-        return 0.0;
+/// Calculates a normalized score of the Levenshtein algorithm relative to the
+/// shorter string between 0.0 and 1.0 (inclusive), where 1.0 means the
+/// shorter string can be turned into the longer one by insertions alone.
+///
+/// The insertions making up for the difference in length are free, and the
+/// remaining edits are divided by the length of the shorter string. This
+/// way a query scores 1.0 against every title containing it, and its typos
+/// count against its own length instead of the length of the title. Unlike
+/// a windowed partial comparison, the characters of the shorter string don't
+/// have to be adjacent in the longer one, so an abbreviation like `nyc`
+/// scores 1.0 against `new york city`. An empty string only matches another
+/// empty string.
+///
+/// ```
+/// use strsim::{normalized_levenshtein, partial_normalized_levenshtein};
+///
+/// assert_eq!(1.0, partial_normalized_levenshtein("york", "new york city"));
+/// assert_eq!(0.75, partial_normalized_levenshtein("yorx", "new york city"));
+/// assert!(normalized_levenshtein("yorx", "new york city") < 0.3);
+/// assert_eq!(partial_normalized_levenshtein("a", "xyz"), partial_normalized_levenshtein("xyz", "a"));
+/// ```
+pub fn partial_normalized_levenshtein(a: &str, b: &str) -> f64 {
+    let a_len = a.chars().count();
+    let b_len = b.chars().count();
+    if a_len == 0 || b_len == 0 {
+        return if a_len == b_len { 1.0 } else { 0.0 };
     }
 
-    let mut a_bigrams: HashMap<(char, char), usize> = HashMap::new();
+    /* the distance is at least the length difference, which covers the
+    insertions */
+    let length_difference = max(a_len, b_len) - min(a_len, b_len);
+    let edits = levenshtein(a, b) - length_difference;
+    1.0 - edits as f64 / min(a_len, b_len) as f64
+}
 
-    for bigram in bigrams(&a) {
-        *a_bigrams.entry(bigram).or_insert(0) += 1;
-    }
+/// Like `normalized_levenshtein`, but returns `None` as soon as it is clear
+/// that the similarity is below `cutoff`. The distance is only calculated up
+/// to the limit the cutoff allows, which is considerably faster for dissimilar
+/// strings.
+///
+/// ```
+/// use strsim::normalized_levenshtein_cutoff;
+///
+/// assert!((normalized_levenshtein_cutoff("kitten", "sitting", 0.5).unwrap() - 0.57142).abs() < 0.00001);
+/// assert_eq!(None, normalized_levenshtein_cutoff("kitten", "sitting", 0.6));
+/// ```
+pub fn normalized_levenshtein_cutoff(a: &str, b: &str, cutoff: f64) -> Option<f64> {
+    normalized_levenshtein_cutoff_impl(a, a.chars().count(), b, b.chars().count(), cutoff)
+}
 
-    let mut intersection_size = 0_usize;
+/* `normalized_levenshtein_cutoff` for strings with known character counts */
+fn normalized_levenshtein_cutoff_impl(
+    a: &str,
+    a_len: usize,
+    b: &str,
+    b_len: usize,
+    cutoff: f64,
+) -> Option<f64> {
+    normalized_cutoff(a_len, b_len, Denominator::Max, cutoff, |limit| {
+        bounded_levenshtein(a, b, limit)
+    })
+}
 
-    for bigram in bigrams(&b) {
-        a_bigrams.entry(bigram).and_modify(|bi| {
-            if *bi > 0 {
-                *bi -= 1;
-                intersection_size += 1;
-            }
-        });
-    }
+/// Like `normalized_levenshtein_with`, but returns `None` as soon as it is
+/// clear that the similarity is below `cutoff`, like
+/// `normalized_levenshtein_cutoff`.
+///
+/// ```
+/// use strsim::{normalized_levenshtein_with_cutoff, Denominator};
+///
+/// assert_eq!(Some(0.5), normalized_levenshtein_with_cutoff("kitten", "sitting", Denominator::Min, 0.5));
+/// assert_eq!(None, normalized_levenshtein_with_cutoff("kitten", "sitting", Denominator::Min, 0.6));
+/// ```
+pub fn normalized_levenshtein_with_cutoff(
+    a: &str,
+    b: &str,
+    denominator: Denominator,
+    cutoff: f64,
+) -> Option<f64> {
+    normalized_cutoff(
+        a.chars().count(),
+        b.chars().count(),
+        denominator,
+        cutoff,
+        |limit| bounded_levenshtein(a, b, limit),
+    )
+}
 
-    (2 * intersection_size) as f64 / (a.len() + b.len() - 2) as f64
+/// Where a similarity falls relative to a band of scores, e.g. to triage
+/// record pairs into certain non-matches, pairs needing a review and certain
+/// matches. Returned by `normalized_levenshtein_triage`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Triage {
+    /// The similarity is below the band.
+    Reject,
+    /// The similarity is inside the band. The exact similarity is known.
+    Review(f64),
+    /// The similarity is at least the upper end of the band.
+    Accept,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Classifies the `normalized_levenshtein` similarity of two strings by the
+/// band from `reject_below` to `accept_from`: `Triage::Reject` below the
+/// band, `Triage::Accept` from its upper end on and `Triage::Review` with the
+/// similarity in between.
+///
+/// It returns as soon as the classification is certain, like
+/// `normalized_levenshtein_cutoff`. Pairs whose lengths differ too much are
+/// rejected without calculating their distance, and the distance is first
+/// only calculated up to the limit allowed by `accept_from`, which is cheap
+/// for near-duplicates, and then up to the limit allowed by `reject_below`.
+///
+/// ```
+/// use strsim::{normalized_levenshtein_triage, Triage};
+///
+/// assert_eq!(Triage::Accept, normalized_levenshtein_triage("kitten", "kitten!", 0.5, 0.8));
+/// assert_eq!(Triage::Reject, normalized_levenshtein_triage("kitten", "kit", 0.6, 0.8));
+/// match normalized_levenshtein_triage("kitten", "sitting", 0.5, 0.8) {
+///     Triage::Review(similarity) => assert!((similarity - 0.57142).abs() < 0.00001),
+///     _ => unreachable!(),
+/// }
+/// ```
+pub fn normalized_levenshtein_triage(
+    a: &str,
+    b: &str,
+    reject_below: f64,
+    accept_from: f64,
+) -> Triage {
+    normalized_levenshtein_with_triage(a, b, Denominator::Max, reject_below, accept_from)
+}
 
-    macro_rules! assert_delta {
-        ($x:expr, $y:expr) => {
-            assert_delta!($x, $y, 1e-5);
-        };
-        ($x:expr, $y:expr, $d:expr) => {
-            if ($x - $y).abs() > $d {
-                panic!(
-                    "assertion failed: actual: `{}`, expected: `{}`: \
-                    actual not within < {} of expected",
-                    $x, $y, $d
-                );
+/// Like `normalized_levenshtein_triage`, but normalizes the distance by the
+/// given `Denominator` like `normalized_levenshtein_with`.
+///
+/// ```
+/// use strsim::{normalized_levenshtein_with_triage, Denominator, Triage};
+///
+/// assert_eq!(Triage::Review(0.5), normalized_levenshtein_with_triage("kitten", "sitting", Denominator::Min, 0.5, 0.8));
+/// assert_eq!(Triage::Reject, normalized_levenshtein_with_triage("kitten", "sitting", Denominator::Min, 0.6, 0.8));
+/// ```
+pub fn normalized_levenshtein_with_triage(
+    a: &str,
+    b: &str,
+    denominator: Denominator,
+    reject_below: f64,
+    accept_from: f64,
+) -> Triage {
+    let a_len = a.chars().count();
+    let b_len = b.chars().count();
+    let triage = |similarity: f64| {
+        if similarity >= accept_from {
+            Triage::Accept
+        } else if similarity >= reject_below {
+            Triage::Review(similarity)
+        } else {
+            Triage::Reject
+        }
+    };
+    if a_len == 0 && b_len == 0 {
+        return triage(1.0);
+    }
+
+    /* the distance is at least the length difference */
+    let length_difference = max(a_len, b_len) - min(a_len, b_len);
+    let accept_limit = denominator.max_distance(accept_from, a_len, b_len);
+    if length_difference <= accept_limit {
+        if let Some(distance) = bounded_levenshtein(a, b, accept_limit) {
+            return triage(normalized_similarity(distance, a_len, b_len, denominator));
+        }
+    }
+    let reject_limit = denominator.max_distance(reject_below, a_len, b_len);
+    if length_difference > reject_limit {
+        return Triage::Reject;
+    }
+    match bounded_levenshtein(a, b, reject_limit) {
+        Some(distance) => triage(normalized_similarity(distance, a_len, b_len, denominator)),
+        None => Triage::Reject,
+    }
+}
+
+/// Calculates the `normalized_levenshtein` similarity of `query` to each of
+/// the `candidates`, counting the characters of the query only once.
+///
+/// ```
+/// use strsim::normalized_levenshtein_many;
+///
+/// let similarities = normalized_levenshtein_many("kitten", &["kitten", "sitting", ""]);
+/// assert_eq!(1.0, similarities[0]);
+/// assert!((similarities[1] - 0.57142).abs() < 0.00001);
+/// assert_eq!(0.0, similarities[2]);
+/// ```
+pub fn normalized_levenshtein_many<S: AsRef<str>>(query: &str, candidates: &[S]) -> Vec<f64> {
+    match normalized_levenshtein_many_with_control(query, candidates, &Control::new()) {
+        Ok(similarities) => similarities,
+        Err(_) => unreachable!("the scoring is never cancelled"),
+    }
+}
+
+/// Calculates the similarities like `normalized_levenshtein_many`, unless the
+/// scoring is cancelled through `control`. The progress is the number of
+/// scored candidates.
+///
+/// ```
+/// use std::sync::atomic::AtomicBool;
+/// use strsim::{normalized_levenshtein_many_with_control, Control, StrSimError};
+///
+/// let cancelled = AtomicBool::new(true);
+/// let control = Control::new().cancel_flag(&cancelled);
+/// assert_eq!(
+///     Err(StrSimError::Cancelled),
+///     normalized_levenshtein_many_with_control("kitten", &["sitting"], &control)
+/// );
+/// ```
+pub fn normalized_levenshtein_many_with_control<S: AsRef<str>>(
+    query: &str,
+    candidates: &[S],
+    control: &Control<'_>,
+) -> Result<Vec<f64>, StrSimError> {
+    let query_len = query.chars().count();
+    let mut similarities = Vec::with_capacity(candidates.len());
+    for candidate in candidates {
+        let candidate = candidate.as_ref();
+        similarities.push(normalized_similarity(
+            levenshtein(query, candidate),
+            query_len,
+            candidate.chars().count(),
+            Denominator::Max,
+        ));
+        control.step(similarities.len(), candidates.len())?;
+    }
+    Ok(similarities)
+}
+
+/// Like `normalized_levenshtein_many`, but returns `None` for candidates with
+/// a similarity below `cutoff`, like `normalized_levenshtein_cutoff`.
+/// Candidates whose length differs too much from the length of the query are
+/// rejected without calculating their distance.
+///
+/// ```
+/// use strsim::normalized_levenshtein_many_cutoff;
+///
+/// let candidates = ["sitting", "kitchen", "kit", "mittens"];
+/// let similarities = normalized_levenshtein_many_cutoff("kitten", &candidates, 0.6);
+/// assert_eq!(None, similarities[0]);
+/// assert!((similarities[1].unwrap() - 0.71428).abs() < 0.00001);
+/// assert_eq!(None, similarities[2]);
+/// assert!(similarities[3].is_some());
+/// ```
+pub fn normalized_levenshtein_many_cutoff<S: AsRef<str>>(
+    query: &str,
+    candidates: &[S],
+    cutoff: f64,
+) -> Vec<Option<f64>> {
+    let query_len = query.chars().count();
+    candidates
+        .iter()
+        .map(|candidate| {
+            let candidate = candidate.as_ref();
+            normalized_levenshtein_cutoff_impl(
+                query,
+                query_len,
+                candidate,
+                candidate.chars().count(),
+                cutoff,
+            )
+        })
+        .collect()
+}
+
+/// Calculates the length of the longest common subsequence of two sequences:
+/// the largest number of elements found in both in the same order, but not
+/// necessarily next to each other. Only one row over `b` is kept in memory.
+///
+/// ```
+/// use strsim::generic_lcs_seq;
+///
+/// assert_eq!(3, generic_lcs_seq(&[1,2,3,4], &[2,5,3,4]));
+/// ```
+pub fn generic_lcs_seq<'a, 'b, Iter1, Iter2, Elem1, Elem2>(a: &'a Iter1, b: &'b Iter2) -> usize
+where
+    &'a Iter1: IntoIterator<Item = Elem1>,
+    &'b Iter2: IntoIterator<Item = Elem2>,
+    Elem1: PartialEq<Elem2>,
+{
+    let b_len = b.into_iter().count();
+
+    with_buffer(b_len + 1, |cache: &mut [usize]| {
+        for a_elem in a {
+            let mut lcs_diag = 0;
+            for (j, b_elem) in b.into_iter().enumerate() {
+                let lcs_up = cache[j + 1];
+                cache[j + 1] = if a_elem == b_elem {
+                    lcs_diag + 1
+                } else {
+                    max(lcs_up, cache[j])
+                };
+                lcs_diag = lcs_up;
+            }
+        }
+
+        cache[b_len]
+    })
+}
+
+/// Calculates the length of the longest common subsequence of two strings,
+/// counted in characters. Only one row over the shorter string is kept in
+/// memory.
+///
+/// ```
+/// use strsim::lcs_seq;
+///
+/// assert_eq!(4, lcs_seq("kitten", "sitting"));
+/// assert_eq!(0, lcs_seq("", "abc"));
+/// ```
+pub fn lcs_seq(a: &str, b: &str) -> usize {
+    if a.chars().count() < b.chars().count() {
+        generic_lcs_seq(&StringWrapper(b), &StringWrapper(a))
+    } else {
+        generic_lcs_seq(&StringWrapper(a), &StringWrapper(b))
+    }
+}
+
+/// Calculates a normalized score of the longest common subsequence between
+/// 0.0 and 1.0 (inclusive), where 1.0 means the strings are the same: the
+/// length of the subsequence divided by the length of the longer string,
+/// both counted in characters like in `normalized_levenshtein`. Swapped
+/// characters aren't rewarded, since only one of them can be part of the
+/// subsequence.
+///
+/// ```
+/// use strsim::normalized_lcs;
+///
+/// assert_eq!(1.0, normalized_lcs("", ""));
+/// assert_eq!(0.0, normalized_lcs("", "second"));
+/// assert_eq!(1.0, normalized_lcs("string", "string"));
+/// assert_eq!(0.5, normalized_lcs("ab", "ba"));
+/// assert!((normalized_lcs("kitten", "sitting") - 0.57142).abs() < 0.00001);
+/// ```
+pub fn normalized_lcs(a: &str, b: &str) -> f64 {
+    normalized_lcs_with(a, b, Denominator::Max)
+}
+
+/// Like `normalized_lcs`, but divides the length of the subsequence by the
+/// length selected by `denominator`. With `Denominator::Min` a string scores
+/// 1.0 against every string containing it as a subsequence, and with
+/// `Denominator::Mean` the score is the Dice coefficient of the strings.
+///
+/// ```
+/// use strsim::{normalized_lcs_with, Denominator};
+///
+/// assert_eq!(1.0, normalized_lcs_with("nyc", "new york city", Denominator::Min));
+/// assert_eq!(0.5, normalized_lcs_with("ab", "ba", Denominator::Mean));
+/// assert_eq!(1.0, normalized_lcs_with("", "", Denominator::Sum));
+/// ```
+pub fn normalized_lcs_with(a: &str, b: &str, denominator: Denominator) -> f64 {
+    shared_similarity(
+        lcs_seq(a, b),
+        a.chars().count(),
+        b.chars().count(),
+        denominator,
+    )
+}
+
+/// Calculates the minimum number of insertions and deletions required to
+/// change one string into the other, which is Levenshtein without
+/// substitutions. It is the sum of the lengths minus twice the length of the
+/// longest common subsequence.
+///
+/// ```
+/// use strsim::indel_distance;
+///
+/// assert_eq!(5, indel_distance("kitten", "sitting"));
+/// assert_eq!(2, indel_distance("ab", "ba"));
+/// ```
+pub fn indel_distance(a: &str, b: &str) -> usize {
+    a.chars().count() + b.chars().count() - 2 * lcs_seq(a, b)
+}
+
+/// Calculates a normalized score of the Indel distance between 0.0 and 1.0
+/// (inclusive), where 1.0 means the strings are the same. The distance is
+/// divided by the sum of the lengths, which is the largest possible
+/// distance, like with `Denominator::Sum`. It is the similarity behind
+/// `fuzz::ratio`.
+///
+/// ```
+/// use strsim::normalized_indel;
+///
+/// assert_eq!(1.0, normalized_indel("", ""));
+/// assert_eq!(0.0, normalized_indel("", "second"));
+/// assert_eq!(0.5, normalized_indel("ab", "ba"));
+/// assert!((normalized_indel("kitten", "sitting") - 0.61538).abs() < 0.00001);
+/// ```
+pub fn normalized_indel(a: &str, b: &str) -> f64 {
+    let len_sum = a.chars().count() + b.chars().count();
+    if len_sum == 0 {
+        return 1.0;
+    }
+    (len_sum - indel_distance(a, b)) as f64 / len_sum as f64
+}
+
+/// Like `normalized_indel`, but divides the distance by the length selected
+/// by `denominator`. Scores that would fall below 0.0 because the distance
+/// exceeds the denominator are clamped to 0.0.
+///
+/// ```
+/// use strsim::{normalized_indel, normalized_indel_with, Denominator};
+///
+/// assert_eq!(normalized_indel("ab", "ba"), normalized_indel_with("ab", "ba", Denominator::Sum));
+/// assert_eq!(0.0, normalized_indel_with("ab", "ba", Denominator::Max));
+/// assert!((normalized_indel_with("kitten", "sitting", Denominator::Max) - 0.28571).abs() < 0.00001);
+/// ```
+pub fn normalized_indel_with(a: &str, b: &str, denominator: Denominator) -> f64 {
+    normalized_similarity(
+        indel_distance(a, b),
+        a.chars().count(),
+        b.chars().count(),
+        denominator,
+    )
+}
+
+/// Calculates the Needleman-Wunsch score of the best global alignment of two
+/// sequences. Every pair of aligned elements adds `match_score` if they are
+/// equal and subtracts `mismatch_penalty` otherwise, and every element
+/// aligned to a gap subtracts `gap_penalty`. Only one row over `b` is kept
+/// in memory.
+///
+/// ```
+/// use strsim::generic_needleman_wunsch;
+///
+/// assert_eq!(1, generic_needleman_wunsch(&[1, 2, 3, 4], &[2, 3, 4, 5], 1, 1, 1));
+/// ```
+pub fn generic_needleman_wunsch<'a, 'b, Iter1, Iter2, Elem1, Elem2>(
+    a: &'a Iter1,
+    b: &'b Iter2,
+    match_score: i64,
+    mismatch_penalty: i64,
+    gap_penalty: i64,
+) -> i64
+where
+    &'a Iter1: IntoIterator<Item = Elem1>,
+    &'b Iter2: IntoIterator<Item = Elem2>,
+    Elem1: PartialEq<Elem2>,
+{
+    let b_len = b.into_iter().count();
+
+    with_buffer(b_len + 1, |cache: &mut [i64]| {
+        for (j, score) in cache.iter_mut().enumerate() {
+            *score = -(j as i64) * gap_penalty;
+        }
+
+        for (i, a_elem) in a.into_iter().enumerate() {
+            let mut score_diag = cache[0];
+            cache[0] = -(i as i64 + 1) * gap_penalty;
+            for (j, b_elem) in b.into_iter().enumerate() {
+                let score_up = cache[j + 1];
+                let aligned = if a_elem == b_elem {
+                    score_diag + match_score
+                } else {
+                    score_diag - mismatch_penalty
+                };
+                cache[j + 1] = max(aligned, max(score_up, cache[j]) - gap_penalty);
+                score_diag = score_up;
+            }
+        }
+
+        cache[b_len]
+    })
+}
+
+/// Calculates the Needleman-Wunsch score of the best global alignment of two
+/// strings, like `generic_needleman_wunsch` over their characters. Higher
+/// scores mean more similar strings. With a `match_score` of 0 and penalties
+/// of 1, the score is the negated Levenshtein distance.
+///
+/// ```
+/// use strsim::{levenshtein, needleman_wunsch};
+///
+/// assert_eq!(0, needleman_wunsch("", "", 1, 1, 1));
+/// assert_eq!(-6, needleman_wunsch("abc", "", 1, 1, 2));
+/// assert_eq!(0, needleman_wunsch("GATTACA", "GCATGCU", 1, 1, 1));
+/// assert_eq!(-(levenshtein("kitten", "sitting") as i64), needleman_wunsch("kitten", "sitting", 0, 1, 1));
+/// ```
+pub fn needleman_wunsch(
+    a: &str,
+    b: &str,
+    match_score: i64,
+    mismatch_penalty: i64,
+    gap_penalty: i64,
+) -> i64 {
+    generic_needleman_wunsch(
+        &StringWrapper(a),
+        &StringWrapper(b),
+        match_score,
+        mismatch_penalty,
+        gap_penalty,
+    )
+}
+
+/// Calculates the Ratcliff/Obershelp similarity of two strings, with the
+/// same result as `ratio()` of `difflib.SequenceMatcher` in Python: twice
+/// the number of matching characters divided by the sum of the lengths.
+///
+/// The matching characters are found by taking the longest common substring,
+/// and repeating this for the parts left and right of it. Like `difflib`,
+/// characters making up more than 1% of a second string of at least 200
+/// characters can't start a match, which avoids spending much time on
+/// frequent characters like spaces. Unlike `normalized_indel`, the matches
+/// aren't necessarily the longest common subsequence.
+///
+/// ```
+/// use strsim::ratcliff_obershelp;
+///
+/// assert_eq!(1.0, ratcliff_obershelp("", ""));
+/// assert_eq!(0.0, ratcliff_obershelp("abc", ""));
+/// assert_eq!(0.75, ratcliff_obershelp("abcd", "bcde"));
+/// assert!((ratcliff_obershelp("GESTALT PATTERN MATCHING", "GESTALT PRACTICE") - 0.6).abs() < 1e-12);
+/// ```
+pub fn ratcliff_obershelp(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let len_sum = a.len() + b.len();
+    if len_sum == 0 {
+        return 1.0;
+    }
+
+    /* the positions of every character in b, without the popular ones */
+    let mut b_positions: HashMap<char, Vec<usize>> = HashMap::new();
+    for (j, &c) in b.iter().enumerate() {
+        b_positions.entry(c).or_default().push(j);
+    }
+    if b.len() >= 200 {
+        let popular = b.len() / 100 + 1;
+        b_positions.retain(|_, positions| positions.len() <= popular);
+    }
+
+    let mut matches = 0;
+    let mut ranges = vec![(0, a.len(), 0, b.len())];
+    while let Some((a_lo, a_hi, b_lo, b_hi)) = ranges.pop() {
+        let (i, j, len) = longest_match(&a, &b, &b_positions, (a_lo, a_hi, b_lo, b_hi));
+        if len > 0 {
+            matches += len;
+            if a_lo < i && b_lo < j {
+                ranges.push((a_lo, i, b_lo, j));
             }
+            if i + len < a_hi && j + len < b_hi {
+                ranges.push((i + len, a_hi, j + len, b_hi));
+            }
+        }
+    }
+
+    2.0 * matches as f64 / len_sum as f64
+}
+
+/* The longest common substring of `a[a_lo..a_hi]` and `b[b_lo..b_hi]` as
+the start in both and its length, like `find_longest_match` of `difflib`.
+The earliest one in `a`, and then in `b`, wins. It is searched among the
+characters of `b_positions` and then extended by equal neighbors */
+fn longest_match(
+    a: &[char],
+    b: &[char],
+    b_positions: &HashMap<char, Vec<usize>>,
+    (a_lo, a_hi, b_lo, b_hi): (usize, usize, usize, usize),
+) -> (usize, usize, usize) {
+    let (mut best_i, mut best_j, mut best_len) = (a_lo, b_lo, 0);
+    /* the length of the match ending at every position of b for the
+    previous character of a */
+    let mut lengths: HashMap<usize, usize> = HashMap::new();
+    let mut next_lengths: HashMap<usize, usize> = HashMap::new();
+
+    for (i, c) in a.iter().enumerate().take(a_hi).skip(a_lo) {
+        next_lengths.clear();
+        if let Some(positions) = b_positions.get(c) {
+            for &j in positions.iter().skip_while(|&&j| j < b_lo) {
+                if j >= b_hi {
+                    break;
+                }
+                let len = j
+                    .checked_sub(1)
+                    .and_then(|k| lengths.get(&k))
+                    .map_or(1, |len| len + 1);
+                next_lengths.insert(j, len);
+                if len > best_len {
+                    best_i = i + 1 - len;
+                    best_j = j + 1 - len;
+                    best_len = len;
+                }
+            }
+        }
+        mem::swap(&mut lengths, &mut next_lengths);
+    }
+
+    while best_i > a_lo && best_j > b_lo && a[best_i - 1] == b[best_j - 1] {
+        best_i -= 1;
+        best_j -= 1;
+        best_len += 1;
+    }
+    while best_i + best_len < a_hi
+        && best_j + best_len < b_hi
+        && a[best_i + best_len] == b[best_j + best_len]
+    {
+        best_len += 1;
+    }
+    (best_i, best_j, best_len)
+}
+
+/// Lower and upper bounds of an edit distance. The distance is known exactly
+/// when both bounds are the same.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DistanceBounds {
+    /// The smallest possible distance.
+    pub lower: usize,
+    /// The largest possible distance.
+    pub upper: usize,
+}
+
+impl DistanceBounds {
+    /// Returns the distance if it is known exactly.
+    pub fn exact(&self) -> Option<usize> {
+        if self.lower == self.upper {
+            Some(self.lower)
+        } else {
+            None
+        }
+    }
+}
+
+/* Returns the lengths of the common prefix and the common suffix. The suffix
+never overlaps the prefix */
+fn common_affix_lengths<Elem: PartialEq>(a: &[Elem], b: &[Elem]) -> (usize, usize) {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    (prefix, suffix)
+}
+
+/* Levenshtein distance restricted to alignments within `band` of the main
+diagonal (Ukkonen). Requires `band >= |a_len - b_len|`. The result is exact
+whenever it does not exceed `band`, and an upper bound of the distance
+otherwise. The two rows of the band are the only allocation, so it uses
+O(band) memory */
+fn banded_levenshtein<A, B>(a: A, a_len: usize, b: B, b_len: usize, band: usize) -> usize
+where
+    A: Iterator<Item = char>,
+    B: Iterator<Item = char> + Clone,
+{
+    let width = 2 * band + 1;
+    // large enough to never win a minimum, small enough to never overflow
+    let unreachable = a_len + b_len + 1;
+
+    // prev[d] holds the distance for column `j = i - 1 + d - band` of row i - 1
+    let mut prev = vec![unreachable; width];
+    let mut curr = vec![unreachable; width];
+    for (d, cell) in prev.iter_mut().enumerate().skip(band) {
+        let j = d - band;
+        if j <= b_len {
+            *cell = j;
+        }
+    }
+
+    /* the characters of b from index `b_start`, the first one compared in
+    the current row */
+    let mut b_window = b;
+    let mut b_start = 0;
+    for (i, a_char) in a.enumerate().map(|(i, ch)| (i + 1, ch)) {
+        let row_start = i.saturating_sub(band + 1);
+        while b_start < row_start {
+            b_window.next();
+            b_start += 1;
+        }
+        let mut b_chars = b_window.clone();
+
+        for d in 0..width {
+            curr[d] = unreachable;
+            let j = match (i + d).checked_sub(band) {
+                Some(j) if j <= b_len => j,
+                _ => continue,
+            };
+
+            if j == 0 {
+                curr[d] = i;
+                continue;
+            }
+
+            // columns are visited in order, so this is b[j - 1]
+            let b_char = match b_chars.next() {
+                Some(b_char) => b_char,
+                None => break,
+            };
+            let substitution = prev[d] + usize::from(a_char != b_char);
+            let deletion = if d + 1 < width {
+                prev[d + 1] + 1
+            } else {
+                unreachable
+            };
+            let insertion = if d > 0 { curr[d - 1] + 1 } else { unreachable };
+            curr[d] = min(substitution, min(deletion, insertion));
+        }
+        mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[b_len + band - a_len]
+}
+
+/// Calculates the Levenshtein distance of very large strings with bounded
+/// memory and runtime. After removing the common prefix and suffix, only
+/// alignments within a band around the diagonal are considered. The band
+/// starts out narrow and is widened up to `max_band` until the distance is
+/// known exactly. The strings aren't copied or decoded up front, so this
+/// only needs `O(max_band)` memory and `O(max_band * N)` time.
+///
+/// When the distance exceeds `max_band`, the returned bounds are guaranteed to
+/// contain it, but are not exact.
+///
+/// ```
+/// use strsim::{levenshtein_banded, DistanceBounds};
+///
+/// assert_eq!(Some(3), levenshtein_banded("kitten", "sitting", 8).exact());
+///
+/// let bounds = levenshtein_banded("abcdefgh", "hgfedcba", 2);
+/// assert_eq!(DistanceBounds { lower: 3, upper: 8 }, bounds);
+/// assert_eq!(None, bounds.exact());
+/// ```
+pub fn levenshtein_banded(a: &str, b: &str, max_band: usize) -> DistanceBounds {
+    let (a, b) = trim_common_affix(a, b);
+    let (a_len, b_len) = (a.chars().count(), b.chars().count());
+
+    let len_diff = max(a_len, b_len) - min(a_len, b_len);
+    let max_len = max(a_len, b_len);
+    if a_len == 0 || b_len == 0 {
+        return DistanceBounds {
+            lower: max_len,
+            upper: max_len,
+        };
+    }
+
+    if max_band < len_diff {
+        return DistanceBounds {
+            lower: len_diff,
+            upper: max_len,
         };
     }
 
+    let mut band = max(len_diff, 1);
+    loop {
+        let dist = banded_levenshtein(a.chars(), a_len, b.chars(), b_len, band);
+        if dist <= band || band >= max_len {
+            return DistanceBounds {
+                lower: dist,
+                upper: dist,
+            };
+        }
+
+        if band >= max_band {
+            return DistanceBounds {
+                lower: band + 1,
+                upper: dist,
+            };
+        }
+        band = min(band.saturating_mul(2), max_band);
+    }
+}
+
+/// Provides the per-character costs used by `weighted_levenshtein`. Every
+/// cost defaults to 1, so implementors only override what they need.
+pub trait CharWeights {
+    /// The cost of inserting `c` into the first string.
+    fn insertion_cost(&self, _c: char) -> usize {
+        1
+    }
+
+    /// The cost of deleting `c` from the first string.
+    fn deletion_cost(&self, _c: char) -> usize {
+        1
+    }
+
+    /// The cost of replacing `a` with `b`. Only called for differing
+    /// characters.
+    fn substitution_cost(&self, _a: char, _b: char) -> usize {
+        1
+    }
+}
+
+/// A lookup table of per-character insertion and deletion costs, e.g. to make
+/// vowels and whitespace cheap to insert or delete and digits expensive.
+/// Characters without an entry use the default costs.
+///
+/// ```
+/// use strsim::{weighted_levenshtein, CharWeightTable};
+///
+/// let weights = CharWeightTable::new(2, 2, 2).with_indel_cost(' ', 1);
+/// assert_eq!(1, weighted_levenshtein("new york", "newyork", &weights));
+/// assert_eq!(2, weighted_levenshtein("york", "yorks", &weights));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharWeightTable {
+    insertion: HashMap<char, usize>,
+    deletion: HashMap<char, usize>,
+    default_insertion: usize,
+    default_deletion: usize,
+    substitution: usize,
+}
+
+impl CharWeightTable {
+    /// Creates a table using the given default insertion, deletion and
+    /// substitution costs.
+    pub fn new(insertion: usize, deletion: usize, substitution: usize) -> Self {
+        Self {
+            insertion: HashMap::new(),
+            deletion: HashMap::new(),
+            default_insertion: insertion,
+            default_deletion: deletion,
+            substitution,
+        }
+    }
+
+    /// Sets the cost of inserting `c`.
+    pub fn with_insertion_cost(mut self, c: char, cost: usize) -> Self {
+        self.insertion.insert(c, cost);
+        self
+    }
+
+    /// Sets the cost of deleting `c`.
+    pub fn with_deletion_cost(mut self, c: char, cost: usize) -> Self {
+        self.deletion.insert(c, cost);
+        self
+    }
+
+    /// Sets both the cost of inserting and of deleting `c`.
+    pub fn with_indel_cost(self, c: char, cost: usize) -> Self {
+        self.with_insertion_cost(c, cost)
+            .with_deletion_cost(c, cost)
+    }
+}
+
+impl Default for CharWeightTable {
+    fn default() -> Self {
+        Self::new(1, 1, 1)
+    }
+}
+
+impl CharWeights for CharWeightTable {
+    fn insertion_cost(&self, c: char) -> usize {
+        self.insertion
+            .get(&c)
+            .copied()
+            .unwrap_or(self.default_insertion)
+    }
+
+    fn deletion_cost(&self, c: char) -> usize {
+        self.deletion
+            .get(&c)
+            .copied()
+            .unwrap_or(self.default_deletion)
+    }
+
+    fn substitution_cost(&self, _a: char, _b: char) -> usize {
+        self.substitution
+    }
+}
+
+/// Calculates the minimum total cost of insertions, deletions, and
+/// substitutions required to change one string into the other, using the
+/// per-character costs provided by `weights`.
+///
+/// ```
+/// use strsim::{weighted_levenshtein, CharWeightTable};
+///
+/// assert_eq!(3, weighted_levenshtein("kitten", "sitting", &CharWeightTable::default()));
+///
+/// let weights = CharWeightTable::new(1, 1, 10).with_indel_cost('7', 5);
+/// assert_eq!(2, weighted_levenshtein("A17", "A1", &CharWeightTable::new(2, 2, 2)));
+/// assert_eq!(5, weighted_levenshtein("A17", "A1", &weights));
+/// ```
+pub fn weighted_levenshtein<W>(a: &str, b: &str, weights: &W) -> usize
+where
+    W: CharWeights + ?Sized,
+{
+    positional_weighted_levenshtein(a, b, weights, |_| 1)
+}
+
+/// Like `weighted_levenshtein`, but every cost is multiplied by
+/// `position_factor(i)`, where `i` is the position in the first string at
+/// which the edit happens. Deletions and substitutions happen at the index of
+/// the edited character, insertions at the index of the character they are
+/// inserted in front of.
+///
+/// ```
+/// use strsim::{positional_weighted_levenshtein, CharWeightTable};
+///
+/// // typos within the first 3 characters cost double
+/// let early_typos = |i: usize| if i < 3 { 2 } else { 1 };
+/// let weights = CharWeightTable::default();
+///
+/// assert_eq!(2, positional_weighted_levenshtein("strsim", "sxrsim", &weights, early_typos));
+/// assert_eq!(1, positional_weighted_levenshtein("strsim", "strsxm", &weights, early_typos));
+/// ```
+pub fn positional_weighted_levenshtein<W, F>(
+    a: &str,
+    b: &str,
+    weights: &W,
+    position_factor: F,
+) -> usize
+where
+    W: CharWeights + ?Sized,
+    F: Fn(usize) -> usize,
+{
+    weighted_edit_distance(
+        a,
+        b,
+        &PositionalWeights {
+            weights,
+            position_factor,
+        },
+    )
+}
+
+/// Provides the costs of the edit operations used by `weighted_edit_distance`,
+/// given the edited characters and the position in the first string at which
+/// the edit happens (see `positional_weighted_levenshtein`). Every operation
+/// costs 1 by default and transpositions are not allowed, which results in the
+/// Levenshtein distance.
+///
+/// Every `CharWeights` is a `CostModel` ignoring the positions, so learned
+/// costs or confusion statistics can be plugged into the same algorithms.
+///
+/// ```
+/// use strsim::{weighted_edit_distance, CostModel};
+///
+/// // swapped characters are a common typo and cost less than two edits
+/// struct Typos;
+///
+/// impl CostModel for Typos {
+///     fn substitute_cost(&self, _a: char, _b: char, _position: usize) -> usize {
+///         2
+///     }
+///
+///     fn transpose_cost(&self, _a: char, _b: char, _position: usize) -> Option<usize> {
+///         Some(1)
+///     }
+/// }
+///
+/// assert_eq!(1, weighted_edit_distance("strsim", "srtsim", &Typos));
+/// assert_eq!(2, weighted_edit_distance("strsim", "stxsim", &Typos));
+/// ```
+pub trait CostModel {
+    /// The cost of inserting `c` in front of the character at `position`.
+    fn insert_cost(&self, _c: char, _position: usize) -> usize {
+        1
+    }
+
+    /// The cost of deleting the character `c` at `position`.
+    fn delete_cost(&self, _c: char, _position: usize) -> usize {
+        1
+    }
+
+    /// The cost of replacing the character `a` at `position` with `b`. Only
+    /// called for differing characters.
+    fn substitute_cost(&self, _a: char, _b: char, _position: usize) -> usize {
+        1
+    }
+
+    /// The cost of swapping the adjacent characters `a` at `position` and `b`
+    /// following it, or `None` if transpositions are not allowed. Like in
+    /// `osa_distance`, transposed characters can't be edited further.
+    fn transpose_cost(&self, _a: char, _b: char, _position: usize) -> Option<usize> {
+        None
+    }
+}
+
+impl<W: CharWeights + ?Sized> CostModel for W {
+    fn insert_cost(&self, c: char, _position: usize) -> usize {
+        self.insertion_cost(c)
+    }
+
+    fn delete_cost(&self, c: char, _position: usize) -> usize {
+        self.deletion_cost(c)
+    }
+
+    fn substitute_cost(&self, a: char, b: char, _position: usize) -> usize {
+        self.substitution_cost(a, b)
+    }
+}
+
+/* The costs of `positional_weighted_levenshtein`: the character costs scaled by
+the factor of the position */
+pub(crate) struct PositionalWeights<'w, W: ?Sized, F> {
+    pub(crate) weights: &'w W,
+    pub(crate) position_factor: F,
+}
+
+impl<W, F> CostModel for PositionalWeights<'_, W, F>
+where
+    W: CharWeights + ?Sized,
+    F: Fn(usize) -> usize,
+{
+    fn insert_cost(&self, c: char, position: usize) -> usize {
+        self.weights.insertion_cost(c) * (self.position_factor)(position)
+    }
+
+    fn delete_cost(&self, c: char, position: usize) -> usize {
+        self.weights.deletion_cost(c) * (self.position_factor)(position)
+    }
+
+    fn substitute_cost(&self, a: char, b: char, position: usize) -> usize {
+        self.weights.substitution_cost(a, b) * (self.position_factor)(position)
+    }
+}
+
+/// Calculates the minimum total cost of insertions, deletions, substitutions
+/// and, if the cost model allows them, transpositions of adjacent characters
+/// required to change one string into the other.
+///
+/// ```
+/// use strsim::{weighted_edit_distance, CharWeightTable};
+///
+/// assert_eq!(3, weighted_edit_distance("kitten", "sitting", &CharWeightTable::default()));
+/// ```
+pub fn weighted_edit_distance<C>(a: &str, b: &str, costs: &C) -> usize
+where
+    C: CostModel + ?Sized,
+{
+    let a_chars: Vec<char> = a.chars().collect();
+    let b_chars: Vec<char> = b.chars().collect();
+
+    let mut prev_two_rows: Vec<usize> = vec![0; b_chars.len() + 1];
+    let mut prev_row: Vec<usize> = Vec::with_capacity(b_chars.len() + 1);
+    prev_row.push(0);
+    for (j, &b_char) in b_chars.iter().enumerate() {
+        prev_row.push(prev_row[j] + costs.insert_cost(b_char, 0));
+    }
+    let mut cur_row = vec![0; b_chars.len() + 1];
+
+    for (i, &a_char) in a_chars.iter().enumerate() {
+        let deletion_cost = costs.delete_cost(a_char, i);
+        cur_row[0] = prev_row[0] + deletion_cost;
+
+        for (j, &b_char) in b_chars.iter().enumerate() {
+            let substitution = if a_char == b_char {
+                prev_row[j]
+            } else {
+                prev_row[j] + costs.substitute_cost(a_char, b_char, i)
+            };
+            let mut dist = min(
+                substitution,
+                min(
+                    prev_row[j + 1] + deletion_cost,
+                    cur_row[j] + costs.insert_cost(b_char, i + 1),
+                ),
+            );
+
+            if i > 0 && j > 0 && a_char != b_char {
+                let a_prev = a_chars[i - 1];
+                if a_char == b_chars[j - 1] && a_prev == b_char {
+                    if let Some(cost) = costs.transpose_cost(a_prev, a_char, i - 1) {
+                        dist = min(dist, prev_two_rows[j - 1] + cost);
+                    }
+                }
+            }
+            cur_row[j + 1] = dist;
+        }
+
+        mem::swap(&mut prev_two_rows, &mut prev_row);
+        mem::swap(&mut prev_row, &mut cur_row);
+    }
+
+    prev_row[b_chars.len()]
+}
+
+/* Rows for strings up to this length are stored on the stack */
+const MAX_STACK_BUFFER_LEN: usize = 128;
+
+/* Calls `f` with a zeroed buffer of `len` elements. Short buffers are placed on
+the stack, which avoids the heap allocation for the common case of comparing
+short strings. There are two stack sizes, so short strings don't pay for
+zeroing the larger buffer */
+pub(crate) fn with_buffer<T, R, F>(len: usize, f: F) -> R
+where
+    T: Copy + Default,
+    F: FnOnce(&mut [T]) -> R,
+{
+    if len <= 32 {
+        with_stack_buffer::<T, R, F, 32>(len, f)
+    } else if len <= MAX_STACK_BUFFER_LEN {
+        with_stack_buffer::<T, R, F, MAX_STACK_BUFFER_LEN>(len, f)
+    } else {
+        f(&mut vec![T::default(); len])
+    }
+}
+
+fn with_stack_buffer<T, R, F, const N: usize>(len: usize, f: F) -> R
+where
+    T: Copy + Default,
+    F: FnOnce(&mut [T]) -> R,
+{
+    let mut buffer = [T::default(); N];
+    f(&mut buffer[..len])
+}
+
+/// Like Levenshtein but allows for adjacent transpositions. Each substring can
+/// only be edited once.
+///
+/// ```
+/// use strsim::osa_distance;
+///
+/// assert_eq!(3, osa_distance("ab", "bca"));
+/// ```
+pub fn osa_distance(a: &str, b: &str) -> usize {
+    let (a, b) = trim_common_affix(a, b);
+    osa_distance_impl(a, b)
+}
+
+/// Calculates a normalized score of the Optimal String Alignment distance
+/// between 0.0 and 1.0 (inclusive), where 1.0 means the strings are the same.
+///
+/// ```
+/// use strsim::normalized_osa;
+///
+/// assert_eq!(0.5, normalized_osa("ab", "ba"));
+/// assert_eq!(1.0, normalized_osa("", ""));
+/// ```
+pub fn normalized_osa(a: &str, b: &str) -> f64 {
+    normalized_osa_with(a, b, Denominator::Max)
+}
+
+/// Like `normalized_osa`, but divides the distance by the length selected by
+/// `denominator`. Scores that would fall below 0.0 because the distance
+/// exceeds the denominator are clamped to 0.0.
+///
+/// ```
+/// use strsim::{normalized_osa_with, Denominator};
+///
+/// assert_eq!(0.75, normalized_osa_with("ab", "ba", Denominator::Sum));
+/// assert_eq!(0.0, normalized_osa_with("ab", "bca", Denominator::Min));
+/// ```
+pub fn normalized_osa_with(a: &str, b: &str, denominator: Denominator) -> f64 {
+    normalized_similarity(
+        osa_distance(a, b),
+        a.chars().count(),
+        b.chars().count(),
+        denominator,
+    )
+}
+
+fn osa_distance_impl(a: &str, b: &str) -> usize {
+    let row_len = b.chars().count() + 1;
+    with_buffer(3 * row_len, |buffer| osa_distance_rows(a, b, buffer))
+}
+
+/* OSA distance using `buffer` for the three rows of the matrix it keeps */
+fn osa_distance_rows(a: &str, b: &str, buffer: &mut [usize]) -> usize {
+    let b_len = buffer.len() / 3 - 1;
+    let (mut prev_two_distances, rest) = buffer.split_at_mut(b_len + 1);
+    let (mut prev_distances, mut curr_distances) = rest.split_at_mut(b_len + 1);
+    for (j, (prev_two, prev)) in prev_two_distances
+        .iter_mut()
+        .zip(prev_distances.iter_mut())
+        .enumerate()
+    {
+        *prev_two = j;
+        *prev = j;
+    }
+
+    let mut prev_a_char = char::MAX;
+    let mut prev_b_char = char::MAX;
+
+    for (i, a_char) in a.chars().enumerate() {
+        curr_distances[0] = i + 1;
+
+        for (j, b_char) in b.chars().enumerate() {
+            let cost = usize::from(a_char != b_char);
+            curr_distances[j + 1] = min(
+                curr_distances[j] + 1,
+                min(prev_distances[j + 1] + 1, prev_distances[j] + cost),
+            );
+            if i > 0 && j > 0 && a_char != b_char && a_char == prev_b_char && b_char == prev_a_char
+            {
+                curr_distances[j + 1] = min(curr_distances[j + 1], prev_two_distances[j - 1] + 1);
+            }
+
+            prev_b_char = b_char;
+        }
+
+        mem::swap(&mut prev_two_distances, &mut prev_distances);
+        mem::swap(&mut prev_distances, &mut curr_distances);
+        prev_a_char = a_char;
+    }
+
+    // access prev_distances instead of curr_distances since we swapped
+    // them above. In case a is empty this would still contain the correct value
+    // from initializing the last element to b_len
+    prev_distances[b_len]
+}
+
+/// Like `osa_distance`, but a transposition can swap two characters up to
+/// `window` positions apart, as long as the characters between them are
+/// unchanged. Swapping them costs 1, like swapping adjacent characters. A
+/// window of 1 is `osa_distance`, and a window of 0 is `levenshtein`.
+///
+/// Wider windows suit noisy typing, where two characters a few positions
+/// apart are swapped, e.g. "abcd" typed as "dbca" is one transposition with a
+/// window of 3.
+///
+/// Runs in `O(N*M)` time independent of the window. There is no variant
+/// allowing the characters between the swapped ones to be edited, like
+/// `damerau_levenshtein` does for adjacent characters: the cost of a wide
+/// transposition would then include the edit distance between the characters
+/// in between, which can't be derived from the last occurrences of the
+/// swapped characters.
+///
+/// ```
+/// use strsim::{osa_distance, osa_distance_window};
+///
+/// assert_eq!(osa_distance("ab", "bca"), osa_distance_window("ab", "bca", 1));
+/// assert_eq!(2, osa_distance_window("abcd", "dbca", 1));
+/// assert_eq!(1, osa_distance_window("abcd", "dbca", 3));
+/// assert_eq!(2, osa_distance_window("abc", "cba", 0));
+/// ```
+pub fn osa_distance_window(a: &str, b: &str, window: usize) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let row_len = b.len() + 1;
+    /* a transposition spanning `k` positions reads the row `k + 1` rows up */
+    let rows = min(window, a.len()) + 2;
+    let mut buffer = vec![0; rows * row_len];
+    let cell = |i: usize, j: usize| (i % rows) * row_len + j;
+    /* the number of equal characters ending at `a[i - 1]` and `b[j - 1]` in the
+    current and the previous row */
+    let mut runs = vec![0; row_len];
+    let mut prev_runs = vec![0; row_len];
+
+    for (j, distance) in buffer[..row_len].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        mem::swap(&mut runs, &mut prev_runs);
+        buffer[cell(i, 0)] = i;
+        for j in 1..row_len {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = min(
+                buffer[cell(i, j - 1)] + 1,
+                min(
+                    buffer[cell(i - 1, j)] + 1,
+                    buffer[cell(i - 1, j - 1)] + cost,
+                ),
+            );
+            if cost == 1 {
+                /* the characters between the swapped ones have to be equal, and
+                the character before these can't be equal as well, since it
+                would have to match both swapped characters. So only the
+                transposition right before the equal characters is possible */
+                let k = prev_runs[j - 1] + 1;
+                if k <= window
+                    && k < min(i, j)
+                    && a[i - 1] == b[j - 1 - k]
+                    && a[i - 1 - k] == b[j - 1]
+                {
+                    distance = min(distance, buffer[cell(i - k - 1, j - k - 1)] + 1);
+                }
+                runs[j] = 0;
+            } else {
+                runs[j] = prev_runs[j - 1] + 1;
+            }
+            buffer[cell(i, j)] = distance;
+        }
+    }
+
+    buffer[cell(a.len(), b.len())]
+}
+
+/// Like optimal string alignment, but substrings can be edited an unlimited
+/// number of times, and the triangle inequality holds.
+///
+/// ```
+/// use strsim::generic_damerau_levenshtein;
+///
+/// assert_eq!(2, generic_damerau_levenshtein(&[1,2], &[2,3,1]));
+/// ```
+///
+/// The elements are told apart by their value, which includes the last row
+/// each element was seen in for transpositions. Strings split into grapheme
+/// clusters, e.g. with the `unicode-segmentation` crate, are thus compared
+/// cluster by cluster: swapping two flags or emoji made of several `char`s
+/// is a single transposition, while `damerau_levenshtein` counts the edits
+/// of their `char`s. With the `unicode-segmentation` feature,
+/// `damerau_levenshtein_graphemes` splits the strings this way.
+///
+/// ```
+/// use strsim::{damerau_levenshtein, generic_damerau_levenshtein};
+///
+/// // the flags of Germany and France, each a pair of regional indicators
+/// let a = ["🇩🇪", "🇫🇷", "!"];
+/// let b = ["🇫🇷", "🇩🇪", "!"];
+/// assert_eq!(1, generic_damerau_levenshtein(&a, &b));
+/// assert_eq!(4, damerau_levenshtein(&a.concat(), &b.concat()));
+/// ```
+pub fn generic_damerau_levenshtein<Elem>(a_elems: &[Elem], b_elems: &[Elem]) -> usize
+where
+    Elem: Eq + Hash + Clone,
+{
+    let mut ids: HashMap<&Elem, usize> = HashMap::with_capacity(64);
+    let mut all_ids: Vec<usize> = a_elems
+        .iter()
+        .chain(b_elems.iter())
+        .map(|elem| {
+            let next_id = ids.len();
+            *ids.entry(elem).or_insert(next_id)
+        })
+        .collect();
+    let b_ids = all_ids.split_off(a_elems.len());
+    let a_ids = all_ids;
+
+    damerau_levenshtein_ids(&a_ids, &b_ids, ids.len())
+}
+
+/// Like `damerau_levenshtein`, but compares the extended grapheme clusters of
+/// the strings instead of their `char`s. A character with a combining accent
+/// or an emoji made of several `char`s is edited or swapped as a whole.
+///
+/// ```
+/// use strsim::{damerau_levenshtein, damerau_levenshtein_graphemes};
+///
+/// // the flags of Germany and France, each a pair of regional indicators
+/// assert_eq!(1, damerau_levenshtein_graphemes("🇩🇪🇫🇷", "🇫🇷🇩🇪"));
+/// assert_eq!(4, damerau_levenshtein("🇩🇪🇫🇷", "🇫🇷🇩🇪"));
+/// // "é" as "e" followed by a combining acute accent
+/// assert_eq!(1, damerau_levenshtein_graphemes("cafe\u{301}", "cafe"));
+/// ```
+#[cfg(feature = "unicode-segmentation")]
+pub fn damerau_levenshtein_graphemes(a: &str, b: &str) -> usize {
+    let a: Vec<&str> = a.graphemes(true).collect();
+    let b: Vec<&str> = b.graphemes(true).collect();
+    generic_damerau_levenshtein(&a, &b)
+}
+
+/// Like `generic_damerau_levenshtein`, but only requires the elements to be
+/// ordered instead of hashable. The elements are sorted once to map them to
+/// dense ids, so expensive comparisons are not repeated in the inner loop.
+///
+/// ```
+/// use strsim::generic_damerau_levenshtein_ord;
+///
+/// assert_eq!(2, generic_damerau_levenshtein_ord(&[1, 2], &[2, 3, 1]));
+/// assert_eq!(1, generic_damerau_levenshtein_ord(&["a", "b"], &["b", "a"]));
+/// ```
+pub fn generic_damerau_levenshtein_ord<Elem>(a_elems: &[Elem], b_elems: &[Elem]) -> usize
+where
+    Elem: Ord,
+{
+    let mut alphabet: Vec<&Elem> = a_elems.iter().chain(b_elems.iter()).collect();
+    alphabet.sort_unstable();
+    alphabet.dedup();
+
+    let to_ids = |elems: &[Elem]| -> Vec<usize> {
+        elems
+            .iter()
+            .map(|elem| {
+                alphabet
+                    .binary_search(&elem)
+                    .expect("every element is part of the alphabet")
+            })
+            .collect()
+    };
+    let a_ids = to_ids(a_elems);
+    let b_ids = to_ids(b_elems);
+
+    damerau_levenshtein_ids(&a_ids, &b_ids, alphabet.len())
+}
+
+/* Damerau-Levenshtein distance of sequences of dense ids below `alphabet_size`,
+which allows storing the last row of every id in a plain vector */
+fn damerau_levenshtein_ids(a: &[usize], b: &[usize], alphabet_size: usize) -> usize {
+    let (prefix, suffix) = common_affix_lengths(a, b);
+    let a = &a[prefix..a.len() - suffix];
+    let b = &b[prefix..b.len() - suffix];
+
+    damerau_levenshtein_impl(
+        a.iter().copied(),
+        a.len(),
+        b.iter().copied(),
+        b.len(),
+        vec![RowId::default(); alphabet_size],
+    )
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct RowId {
+    val: isize,
+}
+
+impl Default for RowId {
+    fn default() -> Self {
+        Self { val: -1 }
+    }
+}
+
+#[derive(Default, Clone)]
+struct GrowingHashmapMapElemChar<ValueType> {
+    key: u32,
+    value: ValueType,
+}
+
+/// specialized hashmap to store user provided types
+/// this implementation relies on a couple of base assumptions in order to simplify the implementation
+/// - the hashmap does not have an upper limit of included items
+/// - the default value for the `ValueType` can be used as a dummy value to indicate an empty cell
+/// - elements can't be removed
+/// - only allocates memory on first write access.
+///   This improves performance for hashmaps that are never written to
+struct GrowingHashmapChar<ValueType> {
+    used: i32,
+    fill: i32,
+    mask: i32,
+    map: Option<Vec<GrowingHashmapMapElemChar<ValueType>>>,
+}
+
+impl<ValueType> Default for GrowingHashmapChar<ValueType>
+where
+    ValueType: Default + Clone + Eq,
+{
+    fn default() -> Self {
+        Self {
+            used: 0,
+            fill: 0,
+            mask: -1,
+            map: None,
+        }
+    }
+}
+
+impl<ValueType> GrowingHashmapChar<ValueType>
+where
+    ValueType: Default + Clone + Eq + Copy,
+{
+    fn get(&self, key: u32) -> ValueType {
+        self.map
+            .as_ref()
+            .map_or_else(|| Default::default(), |map| map[self.lookup(key)].value)
+    }
+
+    fn get_mut(&mut self, key: u32) -> &mut ValueType {
+        if self.map.is_none() {
+            self.allocate();
+        }
+
+        let mut i = self.lookup(key);
+        if self
+            .map
+            .as_ref()
+            .expect("map should have been created above")[i]
+            .value
+            == Default::default()
+        {
+            self.fill += 1;
+            // resize when 2/3 full
+            if self.fill * 3 >= (self.mask + 1) * 2 {
+                self.grow((self.used + 1) * 2);
+                i = self.lookup(key);
+            }
+
+            self.used += 1;
+        }
+
+        let elem = &mut self
+            .map
+            .as_mut()
+            .expect("map should have been created above")[i];
+        elem.key = key;
+        &mut elem.value
+    }
+
+    fn allocate(&mut self) {
+        self.mask = 8 - 1;
+        self.map = Some(vec![GrowingHashmapMapElemChar::default(); 8]);
+    }
+
+    /// lookup key inside the hashmap using a similar collision resolution
+    /// strategy to `CPython` and `Ruby`
+    fn lookup(&self, key: u32) -> usize {
+        let hash = key;
+        let mut i = hash as usize & self.mask as usize;
+
+        let map = self
+            .map
+            .as_ref()
+            .expect("callers have to ensure map is allocated");
+
+        if map[i].value == Default::default() || map[i].key == key {
+            return i;
+        }
+
+        let mut perturb = key;
+        loop {
+            i = (i * 5 + perturb as usize + 1) & self.mask as usize;
+
+            if map[i].value == Default::default() || map[i].key == key {
+                return i;
+            }
+
+            perturb >>= 5;
+        }
+    }
+
+    fn grow(&mut self, min_used: i32) {
+        let mut new_size = self.mask + 1;
+        while new_size <= min_used {
+            new_size <<= 1;
+        }
+
+        self.fill = self.used;
+        self.mask = new_size - 1;
+
+        let old_map = std::mem::replace(
+            self.map
+                .as_mut()
+                .expect("callers have to ensure map is allocated"),
+            vec![GrowingHashmapMapElemChar::<ValueType>::default(); new_size as usize],
+        );
+
+        for elem in old_map {
+            if elem.value != Default::default() {
+                let j = self.lookup(elem.key);
+                let new_elem = &mut self.map.as_mut().expect("map created above")[j];
+                new_elem.key = elem.key;
+                new_elem.value = elem.value;
+                self.used -= 1;
+                if self.used == 0 {
+                    break;
+                }
+            }
+        }
+
+        self.used = self.fill;
+    }
+}
+
+struct HybridGrowingHashmapChar<ValueType> {
+    map: GrowingHashmapChar<ValueType>,
+    extended_ascii: [ValueType; 256],
+}
+
+impl<ValueType> HybridGrowingHashmapChar<ValueType>
+where
+    ValueType: Default + Clone + Copy + Eq,
+{
+    fn get(&self, key: char) -> ValueType {
+        let value = key as u32;
+        if value <= 255 {
+            let val_u8 = u8::try_from(value).expect("we check the bounds above");
+            self.extended_ascii[usize::from(val_u8)]
+        } else {
+            self.map.get(value)
+        }
+    }
+
+    fn get_mut(&mut self, key: char) -> &mut ValueType {
+        let value = key as u32;
+        if value <= 255 {
+            let val_u8 = u8::try_from(value).expect("we check the bounds above");
+            &mut self.extended_ascii[usize::from(val_u8)]
+        } else {
+            self.map.get_mut(value)
+        }
+    }
+}
+
+impl<ValueType> Default for HybridGrowingHashmapChar<ValueType>
+where
+    ValueType: Default + Clone + Copy + Eq,
+{
+    fn default() -> Self {
+        HybridGrowingHashmapChar {
+            map: GrowingHashmapChar::default(),
+            extended_ascii: [Default::default(); 256],
+        }
+    }
+}
+
+/* Lookup of the last row an element occurred in */
+trait LastRowIds<Elem> {
+    fn get(&self, elem: Elem) -> RowId;
+    fn get_mut(&mut self, elem: Elem) -> &mut RowId;
+}
+
+impl LastRowIds<char> for HybridGrowingHashmapChar<RowId> {
+    fn get(&self, elem: char) -> RowId {
+        HybridGrowingHashmapChar::get(self, elem)
+    }
+
+    fn get_mut(&mut self, elem: char) -> &mut RowId {
+        HybridGrowingHashmapChar::get_mut(self, elem)
+    }
+}
+
+impl LastRowIds<usize> for Vec<RowId> {
+    fn get(&self, elem: usize) -> RowId {
+        self[elem]
+    }
+
+    fn get_mut(&mut self, elem: usize) -> &mut RowId {
+        &mut self[elem]
+    }
+}
+
+fn damerau_levenshtein_impl<Iter1, Iter2, Elem, Ids>(
+    s1: Iter1,
+    len1: usize,
+    s2: Iter2,
+    len2: usize,
+    mut last_row_id: Ids,
+) -> usize
+where
+    Iter1: Iterator<Item = Elem> + Clone,
+    Iter2: Iterator<Item = Elem> + Clone,
+    Elem: PartialEq + Copy,
+    Ids: LastRowIds<Elem>,
+{
+    // The implementations is based on the paper
+    // `Linear space string correction algorithm using the Damerau-Levenshtein distance`
+    // from Chunchun Zhao and Sartaj Sahni
+    //
+    // It has a runtime complexity of `O(N*M)` and a memory usage of `O(N+M)`.
+    let max_val = max(len1, len2) as isize + 1;
+
+    let size = len2 + 2;
+    with_buffer(3 * size, |buffer: &mut [isize]| {
+        let (fr, rest) = buffer.split_at_mut(size);
+        let (mut r1, mut r) = rest.split_at_mut(size);
+        fr.fill(max_val);
+        r1.fill(max_val);
+        r[0] = max_val;
+        for (j, dist) in r.iter_mut().enumerate().skip(1) {
+            *dist = j as isize - 1;
+        }
+
+        for (i, ch1) in s1.enumerate().map(|(i, ch1)| (i + 1, ch1)) {
+            mem::swap(&mut r, &mut r1);
+            let mut last_col_id: isize = -1;
+            let mut last_i2l1 = r[1];
+            r[1] = i as isize;
+            let mut t = max_val;
+
+            for (j, ch2) in s2.clone().enumerate().map(|(j, ch2)| (j + 1, ch2)) {
+                let diag = r1[j] + isize::from(ch1 != ch2);
+                let left = r[j] + 1;
+                let up = r1[j + 1] + 1;
+                let mut temp = min(diag, min(left, up));
+
+                if ch1 == ch2 {
+                    last_col_id = j as isize; // last occurence of s1_i
+                    fr[j + 1] = r1[j - 1]; // save H_k-1,j-2
+                    t = last_i2l1; // save H_i-2,l-1
+                } else {
+                    let k = last_row_id.get(ch2).val;
+                    let l = last_col_id;
+
+                    if j as isize - l == 1 {
+                        let transpose = fr[j + 1] + (i as isize - k);
+                        temp = min(temp, transpose);
+                    } else if i as isize - k == 1 {
+                        let transpose = t + (j as isize - l);
+                        temp = min(temp, transpose);
+                    }
+                }
+
+                last_i2l1 = r[j + 1];
+                r[j + 1] = temp;
+            }
+            last_row_id.get_mut(ch1).val = i as isize;
+        }
+
+        r[len2 + 1] as usize
+    })
+}
+
+/// Like optimal string alignment, but substrings can be edited an unlimited
+/// number of times, and the triangle inequality holds.
+///
+/// ```
+/// use strsim::damerau_levenshtein;
+///
+/// assert_eq!(2, damerau_levenshtein("ab", "bca"));
+/// ```
+pub fn damerau_levenshtein(a: &str, b: &str) -> usize {
+    let (a, b) = trim_common_affix(a, b);
+    damerau_levenshtein_impl(
+        a.chars(),
+        a.chars().count(),
+        b.chars(),
+        b.chars().count(),
+        HybridGrowingHashmapChar::default(),
+    )
+}
+
+/// Calculates a normalized score of the Damerau–Levenshtein algorithm between
+/// 0.0 and 1.0 (inclusive), where 1.0 means the strings are the same.
+///
+/// ```
+/// use strsim::normalized_damerau_levenshtein;
+///
+/// assert!((normalized_damerau_levenshtein("levenshtein", "löwenbräu") - 0.27272).abs() < 0.00001);
+/// assert!((normalized_damerau_levenshtein("", "") - 1.0).abs() < 0.00001);
+/// assert!(normalized_damerau_levenshtein("", "flower").abs() < 0.00001);
+/// assert!(normalized_damerau_levenshtein("tree", "").abs() < 0.00001);
+/// assert!((normalized_damerau_levenshtein("sunglasses", "sunglasses") - 1.0).abs() < 0.00001);
+/// ```
+pub fn normalized_damerau_levenshtein(a: &str, b: &str) -> f64 {
+    normalized_damerau_levenshtein_with(a, b, Denominator::Max)
+}
+
+/// Like `normalized_damerau_levenshtein`, but returns the similarity in
+/// parts per million, calculated exactly with integers and rounded to the
+/// nearest integer.
+///
+/// ```
+/// use strsim::normalized_damerau_levenshtein_ppm;
+///
+/// assert_eq!(272_727, normalized_damerau_levenshtein_ppm("levenshtein", "löwenbräu"));
+/// ```
+pub fn normalized_damerau_levenshtein_ppm(a: &str, b: &str) -> u32 {
+    Denominator::Max.similarity_ppm(
+        damerau_levenshtein(a, b),
+        a.chars().count(),
+        b.chars().count(),
+    )
+}
+
+/// Like `normalized_damerau_levenshtein`, but calculates the similarity as
+/// `f32`.
+///
+/// ```
+/// use strsim::normalized_damerau_levenshtein_f32;
+///
+/// assert!((normalized_damerau_levenshtein_f32("levenshtein", "löwenbräu") - 0.27272).abs() < 0.00001);
+/// ```
+pub fn normalized_damerau_levenshtein_f32(a: &str, b: &str) -> f32 {
+    normalized_similarity(
+        damerau_levenshtein(a, b),
+        a.chars().count(),
+        b.chars().count(),
+        Denominator::Max,
+    )
+}
+
+/// Like `normalized_damerau_levenshtein`, but divides the distance by the
+/// length selected by `denominator`. Scores that would fall below 0.0 because
+/// the distance exceeds the denominator are clamped to 0.0.
+///
+/// ```
+/// use strsim::{normalized_damerau_levenshtein_with, Denominator};
+///
+/// assert!((normalized_damerau_levenshtein_with("levenshtein", "löwenbräu", Denominator::Mean) - 0.2).abs() < 0.00001);
+/// assert!(normalized_damerau_levenshtein_with("tree", "", Denominator::Min).abs() < 0.00001);
+/// ```
+pub fn normalized_damerau_levenshtein_with(a: &str, b: &str, denominator: Denominator) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+
+    let len1 = a.chars().count();
+    let len2 = b.chars().count();
+    let dist = damerau_levenshtein(a, b);
+    normalized_similarity(dist, len1, len2, denominator)
+}
+
+/// Like `normalized_damerau_levenshtein`, but returns `None` if the
+/// similarity is below `cutoff`. Strings whose length difference alone
+/// exceeds the distance the cutoff allows are rejected without calculating
+/// their distance.
+///
+/// ```
+/// use strsim::normalized_damerau_levenshtein_cutoff;
+///
+/// assert_eq!(Some(0.75), normalized_damerau_levenshtein_cutoff("abcd", "abdc", 0.7));
+/// assert_eq!(None, normalized_damerau_levenshtein_cutoff("abcd", "abdc", 0.8));
+/// assert_eq!(None, normalized_damerau_levenshtein_cutoff("ab", "abcdefgh", 0.5));
+/// ```
+pub fn normalized_damerau_levenshtein_cutoff(a: &str, b: &str, cutoff: f64) -> Option<f64> {
+    normalized_cutoff(
+        a.chars().count(),
+        b.chars().count(),
+        Denominator::Max,
+        cutoff,
+        |limit| Some(damerau_levenshtein(a, b)).filter(|&dist| dist <= limit),
+    )
+}
+
+/// Returns an Iterator of char tuples.
+fn bigrams(s: &str) -> impl Iterator<Item = (char, char)> + '_ {
+    s.chars().zip(s.chars().skip(1))
+}
+
+/* Counts how often every bigram occurs */
+fn bigram_counts(s: &str) -> HashMap<(char, char), usize> {
+    let mut counts = HashMap::new();
+    for bigram in bigrams(s) {
+        *counts.entry(bigram).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Calculates a Sørensen-Dice similarity distance using bigrams.
+/// See <https://en.wikipedia.org/wiki/S%C3%B8rensen%E2%80%93Dice_coefficient>.
+///
+/// ```
+/// use strsim::sorensen_dice;
+///
+/// assert_eq!(1.0, sorensen_dice("", ""));
+/// assert_eq!(0.0, sorensen_dice("", "a"));
+/// assert_eq!(0.0, sorensen_dice("french", "quebec"));
+/// assert_eq!(1.0, sorensen_dice("ferris", "ferris"));
+/// assert_eq!(0.8888888888888888, sorensen_dice("feris", "ferris"));
+/// ```
+pub fn sorensen_dice(a: &str, b: &str) -> f64 {
+    sorensen_dice_impl(a, b)
+}
+
+/// Like `sorensen_dice`, but calculates the similarity as `f32`.
+///
+/// ```
+/// use strsim::sorensen_dice_f32;
+///
+/// assert!((sorensen_dice_f32("feris", "ferris") - 0.8888889).abs() < 0.00001);
+/// ```
+pub fn sorensen_dice_f32(a: &str, b: &str) -> f32 {
+    sorensen_dice_impl(a, b)
+}
+
+fn sorensen_dice_impl<F: Float>(a: &str, b: &str) -> F {
+    // implementation guided by
+    // https://github.com/aceakash/string-similarity/blob/f83ba3cd7bae874c20c429774e911ae8cff8bced/src/index.js#L6
+
+    let a: String = a.chars().filter(|&x| !char::is_whitespace(x)).collect();
+    let b: String = b.chars().filter(|&x| !char::is_whitespace(x)).collect();
+
+    if a == b {
+        return F::ONE;
+    }
+
+    if a.len() < 2 || b.len() < 2 {
+        return F::ZERO;
+    }
+
+    let mut a_bigrams = bigram_counts(&a);
+    let mut intersection_size = 0_usize;
+
+    for bigram in bigrams(&b) {
+        a_bigrams.entry(bigram).and_modify(|bi| {
+            if *bi > 0 {
+                *bi -= 1;
+                intersection_size += 1;
+            }
+        });
+    }
+
+    F::from_usize(2 * intersection_size) / F::from_usize(a.len() + b.len() - 2)
+}
+
+/// Calculates the Tversky index of the bigrams of two strings, which weighs
+/// the bigrams only found in `a` by `alpha` and the ones only found in `b` by
+/// `beta`:
+/// `common / (common + alpha * only_in_a + beta * only_in_b)`.
+///
+/// The bigrams are counted like in `sorensen_dice`, which is the Tversky
+/// index with `alpha` and `beta` of 0.5. Both of 1.0 is the Jaccard index of
+/// the bigram counts. Asymmetric weights compare a query with a document,
+/// e.g. an `alpha` of 1.0 and a `beta` of 0.0 is the share of the bigrams of
+/// the query found in the document. The weights shouldn't be negative.
+/// See <https://en.wikipedia.org/wiki/Tversky_index>.
+///
+/// ```
+/// use strsim::{sorensen_dice, tversky};
+///
+/// assert_eq!(1.0, tversky("", "", 0.5, 0.5));
+/// assert_eq!(sorensen_dice("feris", "ferris"), tversky("feris", "ferris", 0.5, 0.5));
+/// assert_eq!(0.8, tversky("feris", "ferris", 1.0, 1.0));
+/// assert_eq!(1.0, tversky("york", "new york city", 1.0, 0.0));
+/// ```
+pub fn tversky(a: &str, b: &str, alpha: f64, beta: f64) -> f64 {
+    let a: String = a.chars().filter(|&x| !char::is_whitespace(x)).collect();
+    let b: String = b.chars().filter(|&x| !char::is_whitespace(x)).collect();
+
+    if a == b {
+        return 1.0;
+    }
+
+    let a_bigrams = bigram_counts(&a);
+    let b_bigrams = bigram_counts(&b);
+    let common: usize = a_bigrams
+        .iter()
+        .filter_map(|(bigram, &count)| b_bigrams.get(bigram).map(|&other| min(count, other)))
+        .sum();
+    if common == 0 {
+        return 0.0;
+    }
+
+    let only_in_a = a_bigrams.values().sum::<usize>() - common;
+    let only_in_b = b_bigrams.values().sum::<usize>() - common;
+    common as f64 / (common as f64 + alpha * only_in_a as f64 + beta * only_in_b as f64)
+}
+
+/// Calculates the overlap coefficient (Szymkiewicz-Simpson) of the bigrams of
+/// two strings: the number of common bigrams divided by the number of bigrams
+/// of the shorter string. The bigrams are counted like in `sorensen_dice`.
+/// Unlike `sorensen_dice`, a string contained in the other one is a perfect
+/// match.
+/// See <https://en.wikipedia.org/wiki/Overlap_coefficient>.
+///
+/// ```
+/// use strsim::overlap_coefficient;
+///
+/// assert_eq!(1.0, overlap_coefficient("", ""));
+/// assert_eq!(0.0, overlap_coefficient("", "a"));
+/// assert_eq!(0.0, overlap_coefficient("french", "quebec"));
+/// assert_eq!(1.0, overlap_coefficient("york", "new york city"));
+/// assert_eq!(0.8, overlap_coefficient("healed", "sealed"));
+/// ```
+pub fn overlap_coefficient(a: &str, b: &str) -> f64 {
+    let a: String = a.chars().filter(|&x| !char::is_whitespace(x)).collect();
+    let b: String = b.chars().filter(|&x| !char::is_whitespace(x)).collect();
+
+    if a == b {
+        return 1.0;
+    }
+
+    let a_bigrams = bigram_counts(&a);
+    let b_bigrams = bigram_counts(&b);
+    if a_bigrams.is_empty() || b_bigrams.is_empty() {
+        return 0.0;
+    }
+
+    let common: usize = a_bigrams
+        .iter()
+        .filter_map(|(bigram, &count)| b_bigrams.get(bigram).map(|&other| min(count, other)))
+        .sum();
+    let a_size: usize = a_bigrams.values().sum();
+    let b_size: usize = b_bigrams.values().sum();
+    common as f64 / min(a_size, b_size) as f64
+}
+
+/// Calculates the cosine similarity of the bigram frequency vectors of two
+/// strings: the dot product of the vectors divided by the product of their
+/// lengths. Like `sorensen_dice`, whitespace is ignored, but a bigram repeated
+/// in both strings adds more to the similarity than one occurring once.
+/// See <https://en.wikipedia.org/wiki/Cosine_similarity>.
+///
+/// ```
+/// use strsim::cosine;
+///
+/// assert_eq!(1.0, cosine("", ""));
+/// assert_eq!(0.0, cosine("", "a"));
+/// assert_eq!(0.0, cosine("french", "quebec"));
+/// assert_eq!(1.0, cosine("ferris", "ferris"));
+/// assert!((cosine("feris", "ferris") - 0.8944271909999159).abs() < 1e-12);
+/// ```
+pub fn cosine(a: &str, b: &str) -> f64 {
+    let a: String = a.chars().filter(|&x| !char::is_whitespace(x)).collect();
+    let b: String = b.chars().filter(|&x| !char::is_whitespace(x)).collect();
+
+    if a == b {
+        return 1.0;
+    }
+
+    let a_bigrams = bigram_counts(&a);
+    let b_bigrams = bigram_counts(&b);
+    if a_bigrams.is_empty() || b_bigrams.is_empty() {
+        return 0.0;
+    }
+
+    let dot_product: usize = a_bigrams
+        .iter()
+        .filter_map(|(bigram, &count)| b_bigrams.get(bigram).map(|&other| count * other))
+        .sum();
+    let squared_norm = |counts: &HashMap<(char, char), usize>| -> usize {
+        counts.values().map(|&count| count * count).sum()
+    };
+
+    /* one square root of the exact product rounds once */
+    dot_product as f64 / (squared_norm(&a_bigrams) as f64 * squared_norm(&b_bigrams) as f64).sqrt()
+}
+
+/// Calculates the Jaccard similarity of the sets of character n-grams of
+/// length `ngram_size`: the number of distinct n-grams in both strings divided
+/// by the number of distinct n-grams in either. Unlike `sorensen_dice`,
+/// repeated n-grams count once and whitespace is kept. `ngram_size` is at
+/// least 1. Strings shorter than an n-gram are only similar to themselves.
+/// See <https://en.wikipedia.org/wiki/Jaccard_index>.
+///
+/// ```
+/// use strsim::jaccard;
+///
+/// assert_eq!(1.0, jaccard("", "", 2));
+/// assert_eq!(0.0, jaccard("", "a", 2));
+/// assert_eq!(0.0, jaccard("french", "quebec", 2));
+/// assert_eq!(1.0, jaccard("ferris", "ferris", 2));
+/// assert_eq!(0.8, jaccard("feris", "ferris", 2));
+/// assert_eq!(0.5, jaccard("hello", "yellow", 1));
+/// ```
+pub fn jaccard(a: &str, b: &str, ngram_size: usize) -> f64 {
+    let n = max(ngram_size, 1);
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    if a == b {
+        return 1.0;
+    }
+
+    if a.len() < n || b.len() < n {
+        return 0.0;
+    }
+
+    let a_ngrams: HashSet<&[char]> = a.windows(n).collect();
+    let b_ngrams: HashSet<&[char]> = b.windows(n).collect();
+    let intersection_size = a_ngrams.intersection(&b_ngrams).count();
+
+    intersection_size as f64 / (a_ngrams.len() + b_ngrams.len() - intersection_size) as f64
+}
+
+/* Counts the set bits of the combined words of two bit vectors of the same
+length */
+fn combined_popcount<F>(a: &[u64], b: &[u64], combine: F) -> Result<usize, StrSimError>
+where
+    F: Fn(u64, u64) -> u64,
+{
+    if a.len() != b.len() {
+        return Err(StrSimError::DifferentLengthArgs);
+    }
+    Ok(a.iter()
+        .zip(b)
+        .map(|(&a, &b)| combine(a, b).count_ones() as usize)
+        .sum())
+}
+
+/// Calculates the number of differing bits of two bit vectors stored as
+/// words of 64 bits.
+///
+/// ```
+/// use strsim::{bit_hamming, StrSimError::DifferentLengthArgs};
+///
+/// assert_eq!(Ok(3), bit_hamming(&[0b1011, 0], &[0b0010, 1 << 63]));
+/// assert_eq!(Err(DifferentLengthArgs), bit_hamming(&[0], &[0, 0]));
+/// ```
+pub fn bit_hamming(a: &[u64], b: &[u64]) -> HammingResult {
+    combined_popcount(a, b, |a, b| a ^ b)
+}
+
+/// Calculates the Tanimoto (Jaccard) similarity of two bit vectors, like
+/// molecular fingerprints or sketches, stored as words of 64 bits: the number
+/// of bits set in both vectors divided by the number of bits set in either.
+/// The returned value is between 0.0 and 1.0 (inclusive), where 1.0 means the
+/// vectors are the same. Two vectors without set bits are the same.
+///
+/// ```
+/// use strsim::{tanimoto, StrSimError::DifferentLengthArgs};
+///
+/// assert_eq!(Ok(0.5), tanimoto(&[0b1110, 0], &[0b0110, 1]));
+/// assert_eq!(Ok(1.0), tanimoto(&[0], &[0]));
+/// assert_eq!(Err(DifferentLengthArgs), tanimoto(&[0], &[]));
+/// ```
+pub fn tanimoto(a: &[u64], b: &[u64]) -> Result<f64, StrSimError> {
+    let union = combined_popcount(a, b, |a, b| a | b)?;
+    if union == 0 {
+        return Ok(1.0);
+    }
+    let intersection = combined_popcount(a, b, |a, b| a & b)?;
+    Ok(intersection as f64 / union as f64)
+}
+
+/* Normalizes the length of a common affix or subsequence. Two empty strings
+are identical, while a zero length denominator otherwise means nothing can be
+shared */
+fn shared_similarity(
+    shared_len: usize,
+    a_len: usize,
+    b_len: usize,
+    denominator: Denominator,
+) -> f64 {
+    if a_len == 0 && b_len == 0 {
+        return 1.0;
+    }
+
+    let denominator: f64 = denominator.value(a_len, b_len);
+    if denominator == 0.0 {
+        0.0
+    } else {
+        shared_len as f64 / denominator
+    }
+}
+
+/// The length of a common prefix or suffix of two strings, returned by
+/// `common_prefix_len` and `common_suffix_len`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub struct AffixLen {
+    /// The number of characters.
+    pub chars: usize,
+    /// The number of bytes, which is the same for both strings and always at
+    /// a character boundary, so it can be used to slice either string.
+    pub bytes: usize,
+    /// The number of extended grapheme clusters the strings have in common.
+    /// A cluster only counts if it is complete in both strings, so `"é"`
+    /// written as `e` and a combining accent shares no cluster with `"e"`,
+    /// although both start with the same `char`.
+    #[cfg(feature = "unicode-segmentation")]
+    pub graphemes: usize,
+}
+
+/// Calculates the length of the common prefix of the two strings.
+///
+/// ```
+/// use strsim::common_prefix_len;
+///
+/// let len = common_prefix_len("großartig", "großmutter");
+/// assert_eq!(4, len.chars);
+/// assert_eq!(5, len.bytes);
+/// assert_eq!("groß", &"großartig"[..len.bytes]);
+/// ```
+pub fn common_prefix_len(a: &str, b: &str) -> AffixLen {
+    let (chars, bytes) = a
+        .chars()
+        .zip(b.chars())
+        .take_while(|(a_char, b_char)| a_char == b_char)
+        .fold((0, 0), |(chars, bytes), (c, _)| {
+            (chars + 1, bytes + c.len_utf8())
+        });
+    AffixLen {
+        chars,
+        bytes,
+        #[cfg(feature = "unicode-segmentation")]
+        graphemes: a
+            .graphemes(true)
+            .zip(b.graphemes(true))
+            .take_while(|(a_grapheme, b_grapheme)| a_grapheme == b_grapheme)
+            .count(),
+    }
+}
+
+/// Calculates the length of the common suffix of the two strings.
+///
+/// ```
+/// use strsim::common_suffix_len;
+///
+/// let len = common_suffix_len("naïve", "knave");
+/// assert_eq!(2, len.chars);
+/// assert_eq!("ve", &"naïve"["naïve".len() - len.bytes..]);
+/// ```
+pub fn common_suffix_len(a: &str, b: &str) -> AffixLen {
+    let (chars, bytes) = a
+        .chars()
+        .rev()
+        .zip(b.chars().rev())
+        .take_while(|(a_char, b_char)| a_char == b_char)
+        .fold((0, 0), |(chars, bytes), (c, _)| {
+            (chars + 1, bytes + c.len_utf8())
+        });
+    AffixLen {
+        chars,
+        bytes,
+        #[cfg(feature = "unicode-segmentation")]
+        graphemes: a
+            .graphemes(true)
+            .rev()
+            .zip(b.graphemes(true).rev())
+            .take_while(|(a_grapheme, b_grapheme)| a_grapheme == b_grapheme)
+            .count(),
+    }
+}
+
+/// Calculates the length of the common prefix of the two strings divided by
+/// the chosen `denominator`. The returned value is between 0.0 and 1.0
+/// (inclusive), where 1.0 means one string is a prefix of the other (for
+/// `Denominator::Min`) or the strings are the same (for `Denominator::Max`
+/// and `Denominator::Mean`).
+///
+/// ```
+/// use strsim::{prefix_similarity, Denominator};
+///
+/// assert_eq!(0.5, prefix_similarity("abcd", "abxy", Denominator::Mean));
+/// assert_eq!(1.0, prefix_similarity("pre", "prefix", Denominator::Min));
+/// assert_eq!(0.5, prefix_similarity("pre", "prefix", Denominator::Max));
+/// assert_eq!(1.0, prefix_similarity("", "", Denominator::Mean));
+/// ```
+pub fn prefix_similarity(a: &str, b: &str, denominator: Denominator) -> f64 {
+    shared_similarity(
+        common_prefix_len(a, b).chars,
+        a.chars().count(),
+        b.chars().count(),
+        denominator,
+    )
+}
+
+/// Calculates the length of the common suffix of the two strings divided by
+/// the chosen `denominator`. The returned value is between 0.0 and 1.0
+/// (inclusive), where 1.0 means one string is a suffix of the other (for
+/// `Denominator::Min`) or the strings are the same (for `Denominator::Max`
+/// and `Denominator::Mean`).
+///
+/// ```
+/// use strsim::{suffix_similarity, Denominator};
+///
+/// assert_eq!(0.4, suffix_similarity("report.txt", "notes.txt", Denominator::Max));
+/// assert_eq!(1.0, suffix_similarity("ing", "testing", Denominator::Min));
+/// assert_eq!(0.0, suffix_similarity("", "testing", Denominator::Min));
+/// ```
+pub fn suffix_similarity(a: &str, b: &str, denominator: Denominator) -> f64 {
+    shared_similarity(
+        common_suffix_len(a, b).chars,
+        a.chars().count(),
+        b.chars().count(),
+        denominator,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+    #[test]
+    fn bigrams_iterator() {
+        let mut bi = bigrams("abcde");
+
+        assert_eq!(Some(('a', 'b')), bi.next());
+        assert_eq!(Some(('b', 'c')), bi.next());
+        assert_eq!(Some(('c', 'd')), bi.next());
+        assert_eq!(Some(('d', 'e')), bi.next());
+        assert_eq!(None, bi.next());
+    }
+
+    fn assert_hamming_dist(dist: usize, str1: &str, str2: &str) {
+        assert_eq!(Ok(dist), hamming(str1, str2));
+    }
+
+    #[test]
+    fn hamming_empty() {
+        assert_hamming_dist(0, "", "")
+    }
+
+    #[test]
+    fn hamming_same() {
+        assert_hamming_dist(0, "hamming", "hamming")
+    }
+
+    #[test]
+    fn hamming_numbers() {
+        assert_eq!(Ok(1), generic_hamming(&[1, 2, 4], &[1, 2, 3]));
+    }
+
+    #[test]
+    fn hamming_diff() {
+        assert_hamming_dist(3, "hamming", "hammers")
+    }
+
+    #[test]
+    fn hamming_diff_multibyte() {
+        assert_hamming_dist(2, "hamming", "h香mmüng");
+    }
+
+    #[test]
+    fn hamming_unequal_length() {
+        assert_eq!(
+            Err(StrSimError::DifferentLengthArgs),
+            generic_hamming("ham".chars(), "hamming".chars())
+        );
+    }
+
+    #[test]
+    fn hamming_masked_empty() {
+        assert_eq!(Ok(0), hamming_masked("", "", |_| true));
+    }
+
+    #[test]
+    fn hamming_masked_positions() {
+        assert_eq!(Ok(3), hamming_masked("hamming", "hammers", |_| false));
+        assert_eq!(Ok(1), hamming_masked("hamming", "hammers", |i| i >= 5));
+        assert_eq!(Ok(0), hamming_masked("hamming", "hammers", |_| true));
+    }
+
+    #[test]
+    fn hamming_masked_multibyte() {
+        assert_eq!(Ok(1), hamming_masked("hamming", "h香mmüng", |i| i == 1));
+    }
+
+    #[test]
+    fn hamming_masked_unequal_length() {
+        assert_eq!(
+            Err(StrSimError::DifferentLengthArgs),
+            hamming_masked("ham", "hamming", |_| true)
+        );
+    }
+
+    #[test]
+    fn generic_hamming_masked_numbers() {
+        assert_eq!(
+            Ok(0),
+            generic_hamming_masked(&[1, 2, 4], &[1, 2, 3], |i| i == 2)
+        );
+    }
+
+    #[test]
+    fn hamming_ignoring_class() {
+        assert_eq!(
+            Ok(0),
+            hamming_ignoring("12/34/56", "12-34-56", |c| !c.is_ascii_digit())
+        );
+        assert_eq!(Ok(2), hamming_ignoring("a?c", "xbz", |c| c == '?'));
+    }
+
+    #[test]
+    fn hamming_wildcard_matches_anything() {
+        assert_eq!(Ok(0), hamming_wildcard("h?mm?ng", "hamming", '?'));
+        assert_eq!(Ok(0), hamming_wildcard("hamming", "h?mm?ng", '?'));
+        assert_eq!(Ok(2), hamming_wildcard("h?mm?ng", "hummers", '?'));
+    }
+
+    #[test]
+    fn hamming_names() {
+        assert_hamming_dist(14, "Friedrich Nietzs", "Jean-Paul Sartre")
+    }
+
+    #[test]
+    fn hamming_positions_empty() {
+        assert_eq!(Ok(vec![]), hamming_positions("", ""));
+    }
+
+    #[test]
+    fn hamming_positions_same() {
+        assert_eq!(Ok(vec![]), hamming_positions("hamming", "hamming"));
+    }
+
+    #[test]
+    fn hamming_positions_multibyte() {
+        // character indices, not byte offsets
+        assert_eq!(Ok(vec![1, 3]), hamming_positions("香ö香a", "香a香ö"));
+    }
+
+    #[test]
+    fn hamming_positions_unequal_length() {
+        assert_eq!(
+            Err(StrSimError::DifferentLengthArgs),
+            hamming_positions("ham", "hamming")
+        );
+    }
+
+    #[test]
+    fn hamming_positions_match_count() {
+        let a = "Friedrich Nietzs";
+        let b = "Jean-Paul Sartre";
+        assert_eq!(
+            hamming(a, b).unwrap(),
+            hamming_positions(a, b).unwrap().len()
+        );
+    }
+
+    #[test]
+    fn jaro_both_empty() {
+        assert_eq!(1.0, jaro("", ""));
+    }
+
+    #[test]
+    fn jaro_first_empty() {
+        assert_eq!(0.0, jaro("", "jaro"));
+    }
+
+    #[test]
+    fn jaro_second_empty() {
+        assert_eq!(0.0, jaro("distance", ""));
+    }
+
+    #[test]
+    fn jaro_same() {
+        assert_eq!(1.0, jaro("jaro", "jaro"));
+    }
+
+    #[test]
+    fn jaro_multibyte() {
+        assert_delta!(0.818, jaro("testabctest", "testöঙ香test"), 0.001);
+        assert_delta!(0.818, jaro("testöঙ香test", "testabctest"), 0.001);
+    }
+
+    #[test]
+    fn jaro_diff_short() {
+        assert_delta!(0.767, jaro("dixon", "dicksonx"), 0.001);
+    }
+
+    #[test]
+    fn jaro_diff_one_character() {
+        assert_eq!(0.0, jaro("a", "b"));
+    }
+
+    #[test]
+    fn jaro_same_one_character() {
+        assert_eq!(1.0, jaro("a", "a"));
+    }
+
+    #[test]
+    fn generic_jaro_diff() {
+        assert_eq!(0.0, generic_jaro(&[1, 2], &[3, 4]));
+    }
+
+    #[test]
+    fn jaro_diff_one_and_two() {
+        assert_delta!(0.83, jaro("a", "ab"), 0.01);
+    }
+
+    #[test]
+    fn jaro_diff_two_and_one() {
+        assert_delta!(0.83, jaro("ab", "a"), 0.01);
+    }
+
+    #[test]
+    fn jaro_diff_no_transposition() {
+        assert_delta!(0.822, jaro("dwayne", "duane"), 0.001);
+    }
+
+    #[test]
+    fn jaro_diff_with_transposition() {
+        assert_delta!(0.944, jaro("martha", "marhta"), 0.001);
+        assert_delta!(0.6, jaro("a jke", "jane a k"), 0.001);
+    }
+
+    #[test]
+    fn jaro_names() {
+        assert_delta!(
+            0.392,
+            jaro("Friedrich Nietzsche", "Jean-Paul Sartre"),
+            0.001
+        );
+    }
+
+    #[test]
+    fn jaro_bytes_empty() {
+        assert_eq!(1.0, jaro_bytes(b"", b""));
+        assert_eq!(0.0, jaro_bytes(b"", b"jaro"));
+        assert_eq!(0.0, jaro_bytes(b"jaro", b""));
+    }
+
+    #[test]
+    fn jaro_bytes_non_ascii() {
+        let a = "Friedrich Nietzsche".as_bytes().to_vec();
+        let b = "Jean-Paul Sartre".as_bytes().to_vec();
+        assert_delta!(generic_jaro(&a, &b), jaro_bytes(&a, &b));
+        assert_delta!(0.0, jaro_bytes(&[0xff, 0xfe], &[0x00, 0x01]));
+    }
+
+    #[test]
+    fn jaro_bytes_matches_generic_jaro() {
+        // long strings cover search windows spanning multiple words
+        let mut strings = random_strings(30, 20);
+        strings.extend(random_strings(30, 300));
+        for a in &strings {
+            for b in &strings {
+                let a = a.as_bytes().to_vec();
+                let b = b.as_bytes().to_vec();
+                assert_delta!(generic_jaro(&a, &b), jaro_bytes(&a, &b));
+                assert_delta!(generic_jaro_winkler(&a, &b), jaro_winkler_bytes(&a, &b));
+            }
+        }
+    }
+
+    #[test]
+    fn jaro_wildcard_matches_anything() {
+        assert_eq!(1.0, jaro_wildcard("???", "abc", '?'));
+        assert_eq!(1.0, jaro_wildcard("d?xon", "dixon", '?'));
+        assert_delta!(
+            jaro("dixon", "dicksonx"),
+            jaro_wildcard("dixon", "dicksonx", '?')
+        );
+    }
+
+    #[test]
+    fn jaro_wildcard_empty() {
+        assert_eq!(1.0, jaro_wildcard("", "", '?'));
+        assert_eq!(0.0, jaro_wildcard("?", "", '?'));
+    }
+
+    #[test]
+    fn jaro_winkler_wildcard_boosts_prefix() {
+        assert_delta!(
+            jaro_winkler("martha", "marhta"),
+            jaro_winkler_wildcard("m?rtha", "marhta", '?')
+        );
+    }
+
+    #[test]
+    fn explain_jaro_winkler_matches_jaro_winkler() {
+        let mut strings = random_strings(20, 10);
+        strings.push("cheeseburger".to_string());
+        strings.push("cheese fries".to_string());
+        for a in &strings {
+            for b in &strings {
+                let explanation = explain_jaro_winkler(a, b);
+                assert_eq!(jaro(a, b), explanation.jaro);
+                assert_eq!(jaro_winkler(a, b), explanation.similarity);
+                assert_delta!(
+                    explanation.similarity,
+                    explanation.jaro + explanation.prefix_boost
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn explain_jaro_winkler_below_threshold() {
+        let explanation = explain_jaro_winkler("abcdwxyz", "abcdqrst");
+        assert!(explanation.jaro <= 0.7);
+        assert_eq!(4, explanation.prefix_length);
+        assert_eq!(0.0, explanation.prefix_boost);
+    }
+
+    #[test]
+    fn jaro_winkler_both_empty() {
+        assert_eq!(1.0, jaro_winkler("", ""));
+    }
+
+    #[test]
+    fn jaro_winkler_first_empty() {
+        assert_eq!(0.0, jaro_winkler("", "jaro-winkler"));
+    }
+
+    #[test]
+    fn jaro_winkler_second_empty() {
+        assert_eq!(0.0, jaro_winkler("distance", ""));
+    }
+
+    #[test]
+    fn jaro_winkler_same() {
+        assert_eq!(1.0, jaro_winkler("Jaro-Winkler", "Jaro-Winkler"));
+    }
+
+    #[test]
+    fn jaro_winkler_multibyte() {
+        assert_delta!(0.89, jaro_winkler("testabctest", "testöঙ香test"), 0.001);
+        assert_delta!(0.89, jaro_winkler("testöঙ香test", "testabctest"), 0.001);
+    }
+
+    #[test]
+    fn jaro_winkler_diff_short() {
+        assert_delta!(0.813, jaro_winkler("dixon", "dicksonx"), 0.001);
+        assert_delta!(0.813, jaro_winkler("dicksonx", "dixon"), 0.001);
+    }
+
+    #[test]
+    fn jaro_winkler_diff_one_character() {
+        assert_eq!(0.0, jaro_winkler("a", "b"));
+    }
+
+    #[test]
+    fn jaro_winkler_same_one_character() {
+        assert_eq!(1.0, jaro_winkler("a", "a"));
+    }
+
+    #[test]
+    fn jaro_winkler_diff_no_transposition() {
+        assert_delta!(0.84, jaro_winkler("dwayne", "duane"), 0.001);
+    }
+
+    #[test]
+    fn jaro_winkler_diff_with_transposition() {
+        assert_delta!(0.961, jaro_winkler("martha", "marhta"), 0.001);
+        assert_delta!(0.6, jaro_winkler("a jke", "jane a k"), 0.001);
+    }
+
+    #[test]
+    fn jaro_winkler_names() {
+        assert_delta!(
+            0.452,
+            jaro_winkler("Friedrich Nietzsche", "Fran-Paul Sartre"),
+            0.001
+        );
+    }
+
+    #[test]
+    fn jaro_winkler_long_prefix() {
+        assert_delta!(0.866, jaro_winkler("cheeseburger", "cheese fries"), 0.001);
+    }
+
+    #[test]
+    fn jaro_winkler_more_names() {
+        assert_delta!(0.868, jaro_winkler("Thorkel", "Thorgier"), 0.001);
+    }
+
+    #[test]
+    fn jaro_winkler_length_of_one() {
+        assert_delta!(0.738, jaro_winkler("Dinsdale", "D"), 0.001);
+    }
+
+    #[test]
+    fn jaro_winkler_very_long_prefix() {
+        assert_delta!(
+            0.98519,
+            jaro_winkler("thequickbrownfoxjumpedoverx", "thequickbrownfoxjumpedovery")
+        );
+    }
+
+    #[test]
+    fn jaro_winkler_suffix_both_empty() {
+        assert_eq!(1.0, jaro_winkler_suffix("", ""));
+    }
+
+    #[test]
+    fn jaro_winkler_suffix_first_empty() {
+        assert_eq!(0.0, jaro_winkler_suffix("", "jaro-winkler"));
+    }
+
+    #[test]
+    fn jaro_winkler_suffix_same() {
+        assert_eq!(1.0, jaro_winkler_suffix("Jaro-Winkler", "Jaro-Winkler"));
+    }
+
+    #[test]
+    fn jaro_winkler_suffix_common_suffix() {
+        assert_delta!(0.902, jaro_winkler_suffix("henderson", "anderson"), 0.001);
+        assert_delta!(0.975, jaro_winkler_suffix("ethanol", "methanol"), 0.001);
+    }
+
+    #[test]
+    fn jaro_winkler_suffix_no_common_suffix() {
+        assert_delta!(0.767, jaro_winkler_suffix("dixon", "dicksonx"), 0.001);
+    }
+
+    #[test]
+    fn jaro_winkler_with_boost_both_ends() {
+        let (a, b) = ("dixon", "dicksonx");
+        let jaro_sim = jaro(a, b);
+        let prefix = jaro_winkler(a, b);
+        assert_delta!(prefix, jaro_winkler_with_boost(a, b, WinklerBoost::Prefix));
+        assert_delta!(
+            jaro_sim,
+            jaro_winkler_with_boost(a, b, WinklerBoost::Suffix)
+        );
+        assert_delta!(prefix, jaro_winkler_with_boost(a, b, WinklerBoost::Both));
+
+        // three shared characters at the start and four at the end
+        let (a, b) = ("ab_xyz_cde", "ab_uvw_cde");
+        let jaro_sim = jaro(a, b);
+        let prefix_boosted = jaro_sim + 0.3 * (1.0 - jaro_sim);
+        assert_delta!(
+            prefix_boosted + 0.4 * (1.0 - prefix_boosted),
+            jaro_winkler_with_boost(a, b, WinklerBoost::Both)
+        );
+        assert_eq!(
+            1.0,
+            jaro_winkler_with_boost("abc", "abc", WinklerBoost::Both)
+        );
+        assert_eq!(
+            0.0,
+            jaro_winkler_with_boost("abc", "xyz", WinklerBoost::Both)
+        );
+        assert_eq!(1.0, jaro_winkler_with_boost("", "", WinklerBoost::Both));
+    }
+
+    #[test]
+    fn jaro_winkler_suffix_multibyte() {
+        assert_delta!(
+            0.89,
+            jaro_winkler_suffix("testabctest", "testöঙ香test"),
+            0.001
+        );
+    }
+
+    #[test]
+    fn jaro_winkler_long_strings_both_empty() {
+        assert_eq!(1.0, jaro_winkler_long_strings("", ""));
+    }
+
+    #[test]
+    fn jaro_winkler_long_strings_first_empty() {
+        assert_eq!(0.0, jaro_winkler_long_strings("", "jaro-winkler"));
+    }
+
+    #[test]
+    fn jaro_winkler_long_strings_same() {
+        assert_eq!(
+            1.0,
+            jaro_winkler_long_strings("Jaro-Winkler", "Jaro-Winkler")
+        );
+    }
+
+    #[test]
+    fn jaro_winkler_long_strings_adjusted() {
+        assert_delta!(0.869, jaro_winkler_long_strings("dwayne", "duane"), 0.001);
+        assert_delta!(0.971, jaro_winkler_long_strings("martha", "marhta"), 0.001);
+        assert_delta!(0.830, jaro_winkler_long_strings("dixon", "dicksonx"), 0.001);
+    }
+
+    #[test]
+    fn jaro_winkler_long_strings_short_strings_unchanged() {
+        assert_delta!(
+            jaro_winkler("dave", "dvae"),
+            jaro_winkler_long_strings("dave", "dvae")
+        );
+    }
+
+    #[test]
+    fn jaro_winkler_long_strings_leading_digit_unchanged() {
+        assert_delta!(
+            jaro_winkler("12345", "12354"),
+            jaro_winkler_long_strings("12345", "12354")
+        );
+    }
+
+    #[test]
+    fn generic_jaro_winkler_suffix_numbers() {
+        assert_delta!(
+            jaro_winkler_suffix("abcd", "xbcd"),
+            generic_jaro_winkler_suffix(&[1, 2, 3, 4], &[5, 2, 3, 4])
+        );
+    }
+
+    #[test]
+    fn levenshtein_empty() {
+        assert_eq!(0, levenshtein("", ""));
+    }
+
+    #[test]
+    fn levenshtein_same() {
+        assert_eq!(0, levenshtein("levenshtein", "levenshtein"));
+    }
+
     #[test]
-    fn bigrams_iterator() {
-        let mut bi = bigrams("abcde");
+    fn levenshtein_diff_short() {
+        assert_eq!(3, levenshtein("kitten", "sitting"));
+    }
 
-        assert_eq!(Some(('a', 'b')), bi.next());
-        assert_eq!(Some(('b', 'c')), bi.next());
-        assert_eq!(Some(('c', 'd')), bi.next());
-        assert_eq!(Some(('d', 'e')), bi.next());
-        assert_eq!(None, bi.next());
+    #[test]
+    fn levenshtein_diff_with_space() {
+        assert_eq!(5, levenshtein("hello, world", "bye, world"));
     }
 
-    fn assert_hamming_dist(dist: usize, str1: &str, str2: &str) {
-        assert_eq!(Ok(dist), hamming(str1, str2));
+    #[test]
+    fn levenshtein_diff_multibyte() {
+        assert_eq!(3, levenshtein("öঙ香", "abc"));
+        assert_eq!(3, levenshtein("abc", "öঙ香"));
     }
 
     #[test]
-    fn hamming_empty() {
-        assert_hamming_dist(0, "", "")
+    fn levenshtein_diff_longer() {
+        let a = "The quick brown fox jumped over the angry dog.";
+        let b = "Lorem ipsum dolor sit amet, dicta latine an eam.";
+        assert_eq!(37, levenshtein(a, b));
     }
 
     #[test]
-    fn hamming_same() {
-        assert_hamming_dist(0, "hamming", "hamming")
+    fn levenshtein_first_empty() {
+        assert_eq!(7, levenshtein("", "sitting"));
     }
 
     #[test]
-    fn hamming_numbers() {
-        assert_eq!(Ok(1), generic_hamming(&[1, 2, 4], &[1, 2, 3]));
+    fn levenshtein_second_empty() {
+        assert_eq!(6, levenshtein("kitten", ""));
     }
 
     #[test]
-    fn hamming_diff() {
-        assert_hamming_dist(3, "hamming", "hammers")
+    fn levenshtein_wildcard_matches_anything() {
+        assert_eq!(0, levenshtein_wildcard("", "", '?'));
+        assert_eq!(0, levenshtein_wildcard("?????", "abcde", '?'));
+        assert_eq!(0, levenshtein_wildcard("abcde", "a???e", '?'));
+        assert_eq!(2, levenshtein_wildcard("a?", "abcd", '?'));
+        assert_eq!(3, levenshtein_wildcard("kitten", "sitting", '*'));
+        assert_eq!(2, levenshtein_wildcard("k*tt*n", "sitting", '*'));
     }
 
     #[test]
-    fn hamming_diff_multibyte() {
-        assert_hamming_dist(2, "hamming", "h香mmüng");
+    fn levenshtein_wildcard_multibyte() {
+        assert_eq!(2, levenshtein_wildcard("ö?香", "abc", '?'));
+        assert_eq!(0, levenshtein_wildcard("öঙ香", "ö香香", '香'));
     }
 
+    /* Deterministic pseudo random strings over a small alphabet, which makes
+    matches between the strings likely */
+    fn random_strings(count: usize, max_len: usize) -> Vec<String> {
+        let alphabet = ['a', 'b', 'c', 'd', 'ö', '香'];
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        (0..count)
+            .map(|_| {
+                let len = next() as usize % (max_len + 1);
+                (0..len)
+                    .map(|_| alphabet[next() as usize % alphabet.len()])
+                    .collect()
+            })
+            .collect()
+    }
+
+    const BACKENDS: [LevenshteinBackend; 5] = [
+        LevenshteinBackend::Auto,
+        LevenshteinBackend::Classic,
+        LevenshteinBackend::BitParallel,
+        LevenshteinBackend::Banded,
+        LevenshteinBackend::Mbleven,
+    ];
+
     #[test]
-    fn hamming_unequal_length() {
-        assert_eq!(
-            Err(StrSimError::DifferentLengthArgs),
-            generic_hamming("ham".chars(), "hamming".chars())
-        );
+    fn levenshtein_backends_agree() {
+        let strings = random_strings(30, 130);
+        for a in &strings {
+            for b in &strings {
+                let expected = generic_levenshtein(&StringWrapper(a), &StringWrapper(b));
+                for &backend in BACKENDS.iter() {
+                    assert_eq!(
+                        expected,
+                        levenshtein_with_backend(a, b, backend),
+                        "{:?} {:?} {:?}",
+                        backend,
+                        a,
+                        b
+                    );
+                }
+            }
+        }
     }
 
     #[test]
-    fn hamming_names() {
-        assert_hamming_dist(14, "Friedrich Nietzs", "Jean-Paul Sartre")
+    fn bounded_levenshtein_backends_agree() {
+        let strings = random_strings(30, 130);
+        for a in &strings {
+            for b in &strings {
+                let dist = generic_levenshtein(&StringWrapper(a), &StringWrapper(b));
+                for &limit in [0, 1, 2, 3, 4, 8, 20, 64, 100].iter() {
+                    let expected = if dist <= limit { Some(dist) } else { None };
+                    for &backend in BACKENDS.iter() {
+                        assert_eq!(
+                            expected,
+                            bounded_levenshtein_with_backend(a, b, limit, backend),
+                            "{:?} {} {:?} {:?}",
+                            backend,
+                            limit,
+                            a,
+                            b
+                        );
+                    }
+                }
+            }
+        }
     }
 
     #[test]
-    fn jaro_both_empty() {
-        assert_eq!(1.0, jaro("", ""));
+    fn levenshtein_bit_parallel_block_boundaries() {
+        for &len in [63, 64, 65, 127, 128, 129].iter() {
+            let a = "a".repeat(len);
+            let b = format!("b{}", "a".repeat(len - 1));
+            assert_eq!(
+                1,
+                levenshtein_with_backend(&a, &b, LevenshteinBackend::BitParallel)
+            );
+            assert_eq!(
+                len,
+                levenshtein_with_backend(&a, &"b".repeat(len), LevenshteinBackend::BitParallel)
+            );
+        }
     }
 
     #[test]
-    fn jaro_first_empty() {
-        assert_eq!(0.0, jaro("", "jaro"));
+    fn bounded_levenshtein_empty() {
+        assert_eq!(Some(0), bounded_levenshtein("", "", 0));
+        assert_eq!(None, bounded_levenshtein("", "abc", 2));
+        assert_eq!(Some(3), bounded_levenshtein("abc", "", 3));
     }
 
     #[test]
-    fn jaro_second_empty() {
-        assert_eq!(0.0, jaro("distance", ""));
+    fn trim_common_affix_multibyte() {
+        assert_eq!(("", ""), trim_common_affix("香香", "香香"));
+        assert_eq!(("ö", "ü"), trim_common_affix("香ö香", "香ü香"));
+        assert_eq!(("", "a"), trim_common_affix("aa", "aaa"));
+        let chars: Vec<char> = "香ö香".chars().collect();
+        assert_eq!((&['ö'][..], "ü"), trim_common_affix(&chars[..], "香ü香"));
+        assert_eq!((&[][..], "a"), trim_common_affix(&['a', 'a'][..], "aaa"));
     }
 
     #[test]
-    fn jaro_same() {
-        assert_eq!(1.0, jaro("jaro", "jaro"));
+    fn normalized_levenshtein_diff_short() {
+        assert_delta!(0.57142, normalized_levenshtein("kitten", "sitting"));
     }
 
     #[test]
-    fn jaro_multibyte() {
-        assert_delta!(0.818, jaro("testabctest", "testöঙ香test"), 0.001);
-        assert_delta!(0.818, jaro("testöঙ香test", "testabctest"), 0.001);
+    fn normalized_levenshtein_for_empty_strings() {
+        assert_delta!(1.0, normalized_levenshtein("", ""));
     }
 
     #[test]
-    fn jaro_diff_short() {
-        assert_delta!(0.767, jaro("dixon", "dicksonx"), 0.001);
+    fn normalized_levenshtein_first_empty() {
+        assert_delta!(0.0, normalized_levenshtein("", "second"));
     }
 
     #[test]
-    fn jaro_diff_one_character() {
-        assert_eq!(0.0, jaro("a", "b"));
+    fn normalized_levenshtein_second_empty() {
+        assert_delta!(0.0, normalized_levenshtein("first", ""));
     }
 
     #[test]
-    fn jaro_same_one_character() {
-        assert_eq!(1.0, jaro("a", "a"));
+    fn normalized_levenshtein_identical_strings() {
+        assert_delta!(1.0, normalized_levenshtein("identical", "identical"));
     }
 
     #[test]
-    fn generic_jaro_diff() {
-        assert_eq!(0.0, generic_jaro(&[1, 2], &[3, 4]));
+    fn normalized_levenshtein_with_max() {
+        assert_delta!(
+            normalized_levenshtein("kitten", "sitting"),
+            normalized_levenshtein_with("kitten", "sitting", Denominator::Max)
+        );
     }
 
     #[test]
-    fn jaro_diff_one_and_two() {
-        assert_delta!(0.83, jaro("a", "ab"), 0.01);
+    fn normalized_levenshtein_with_other_denominators() {
+        assert_delta!(
+            0.5,
+            normalized_levenshtein_with("kitten", "sitting", Denominator::Min)
+        );
+        assert_delta!(
+            0.53846,
+            normalized_levenshtein_with("kitten", "sitting", Denominator::Mean)
+        );
+        assert_delta!(
+            0.76923,
+            normalized_levenshtein_with("kitten", "sitting", Denominator::Sum)
+        );
     }
 
     #[test]
-    fn jaro_diff_two_and_one() {
-        assert_delta!(0.83, jaro("ab", "a"), 0.01);
+    fn normalized_levenshtein_with_clamps_to_zero() {
+        assert_delta!(
+            0.0,
+            normalized_levenshtein_with("a", "bcd", Denominator::Min)
+        );
     }
 
     #[test]
-    fn jaro_diff_no_transposition() {
-        assert_delta!(0.822, jaro("dwayne", "duane"), 0.001);
+    fn normalized_levenshtein_cutoff_matches_unbounded() {
+        for (a, b) in [
+            ("kitten", "sitting"),
+            ("", "abc"),
+            ("abc", "abc"),
+            ("öঙ香", "abc香"),
+        ] {
+            let similarity = normalized_levenshtein(a, b);
+            assert_eq!(Some(similarity), normalized_levenshtein_cutoff(a, b, 0.0));
+            assert_eq!(
+                Some(similarity),
+                normalized_levenshtein_cutoff(a, b, similarity)
+            );
+            assert_eq!(None, normalized_levenshtein_cutoff(a, b, similarity + 0.01));
+        }
     }
 
     #[test]
-    fn jaro_diff_with_transposition() {
-        assert_delta!(0.944, jaro("martha", "marhta"), 0.001);
-        assert_delta!(0.6, jaro("a jke", "jane a k"), 0.001);
+    fn normalized_levenshtein_many_matches_pairs() {
+        let candidates = [
+            "kitten",
+            "sitting",
+            "",
+            "öঙ香",
+            "a much longer candidate",
+            "kit",
+        ];
+        for query in ["kitten", "", "öঙ"] {
+            let many = normalized_levenshtein_many(query, &candidates);
+            for (candidate, similarity) in candidates.iter().zip(many) {
+                assert_eq!(normalized_levenshtein(query, candidate), similarity);
+            }
+            for cutoff in [0.0, 0.3, 0.5, 0.9, 1.0] {
+                let many = normalized_levenshtein_many_cutoff(query, &candidates, cutoff);
+                for (candidate, similarity) in candidates.iter().zip(many) {
+                    assert_eq!(
+                        normalized_levenshtein_cutoff(query, candidate, cutoff),
+                        similarity
+                    );
+                }
+            }
+        }
     }
 
     #[test]
-    fn jaro_names() {
-        assert_delta!(
-            0.392,
-            jaro("Friedrich Nietzsche", "Jean-Paul Sartre"),
-            0.001
+    fn normalized_levenshtein_many_with_control_checks_candidates() {
+        let checks = AtomicUsize::new(0);
+        let callback = || checks.fetch_add(1, AtomicOrdering::Relaxed) == 1;
+        let control = Control::new().cancel_when(&callback);
+        let candidates = vec!["sitting"; 200];
+        assert_eq!(
+            Err(StrSimError::Cancelled),
+            normalized_levenshtein_many_with_control("kitten", &candidates, &control)
         );
+        // cancelled at the second check, after 128 candidates
+        assert_eq!(2, checks.load(AtomicOrdering::Relaxed));
     }
 
     #[test]
-    fn jaro_winkler_both_empty() {
-        assert_eq!(1.0, jaro_winkler("", ""));
+    fn levenshtein_chars_matches_levenshtein() {
+        let strings = [
+            "",
+            "kitten",
+            "sitting",
+            "öঙ香",
+            "abc香",
+            "kitten and mittens",
+            "a",
+            &"abcdefghij".repeat(20),
+            &"abcdefghjj".repeat(20),
+        ];
+        for a in strings.iter() {
+            let chars: Vec<char> = a.chars().collect();
+            for b in strings.iter() {
+                let dist = levenshtein(a, b);
+                assert_eq!(dist, levenshtein_chars(&chars, b), "{} {}", a, b);
+                assert_eq!(
+                    normalized_levenshtein(a, b),
+                    normalized_levenshtein_chars(&chars, b)
+                );
+                for limit in 0..5 {
+                    assert_eq!(
+                        bounded_levenshtein(a, b, limit),
+                        bounded_levenshtein_chars(&chars, b, limit)
+                    );
+                }
+            }
+        }
     }
 
     #[test]
-    fn jaro_winkler_first_empty() {
-        assert_eq!(0.0, jaro_winkler("", "jaro-winkler"));
+    fn triage_matches_unbounded() {
+        let strings = [
+            "",
+            "a",
+            "kitten",
+            "sitting",
+            "öঙ香",
+            "abc香",
+            "kitten and mittens",
+        ];
+        let denominators = [
+            Denominator::Min,
+            Denominator::Max,
+            Denominator::Mean,
+            Denominator::Sum,
+        ];
+        for a in strings.iter() {
+            for b in strings.iter() {
+                for &denominator in denominators.iter() {
+                    let similarity = normalized_levenshtein_with(a, b, denominator);
+                    for &(reject_below, accept_from) in [
+                        (0.0, 1.0),
+                        (similarity, 1.0),
+                        (similarity + 0.01, 1.0),
+                        (0.0, similarity),
+                        (0.0, similarity + 0.01),
+                        (-1.0, 0.0),
+                        (0.5, 0.8),
+                    ]
+                    .iter()
+                    {
+                        let expected = if similarity >= accept_from {
+                            Triage::Accept
+                        } else if similarity >= reject_below {
+                            Triage::Review(similarity)
+                        } else {
+                            Triage::Reject
+                        };
+                        assert_eq!(
+                            expected,
+                            normalized_levenshtein_with_triage(
+                                a,
+                                b,
+                                denominator,
+                                reject_below,
+                                accept_from
+                            ),
+                            "{} {} {:?} {} {}",
+                            a,
+                            b,
+                            denominator,
+                            reject_below,
+                            accept_from
+                        );
+                    }
+                }
+            }
+        }
     }
 
     #[test]
-    fn jaro_winkler_second_empty() {
-        assert_eq!(0.0, jaro_winkler("distance", ""));
+    fn normalized_cutoff_matches_unbounded() {
+        let strings = [
+            "",
+            "a",
+            "kitten",
+            "sitting",
+            "öঙ香",
+            "abc香",
+            "kitten and mittens",
+        ];
+        let denominators = [
+            Denominator::Min,
+            Denominator::Max,
+            Denominator::Mean,
+            Denominator::Sum,
+        ];
+        for a in strings.iter() {
+            for b in strings.iter() {
+                for &denominator in denominators.iter() {
+                    let similarity = normalized_levenshtein_with(a, b, denominator);
+                    for &cutoff in [-1.0, 0.0, similarity, similarity + 0.01, 1.0].iter() {
+                        assert_eq!(
+                            Some(similarity).filter(|&s| s >= cutoff),
+                            normalized_levenshtein_with_cutoff(a, b, denominator, cutoff),
+                            "{} {} {:?} {}",
+                            a,
+                            b,
+                            denominator,
+                            cutoff
+                        );
+                    }
+                }
+                let similarity = normalized_damerau_levenshtein(a, b);
+                for &cutoff in [0.0, similarity, similarity + 0.01].iter() {
+                    assert_eq!(
+                        Some(similarity).filter(|&s| s >= cutoff),
+                        normalized_damerau_levenshtein_cutoff(a, b, cutoff)
+                    );
+                }
+            }
+        }
     }
 
     #[test]
-    fn jaro_winkler_same() {
-        assert_eq!(1.0, jaro_winkler("Jaro-Winkler", "Jaro-Winkler"));
+    fn max_distance_round_trip() {
+        let denominators = [
+            Denominator::Min,
+            Denominator::Max,
+            Denominator::Mean,
+            Denominator::Sum,
+        ];
+        for &denominator in denominators.iter() {
+            for a_len in 0..12 {
+                for b_len in 1..12 {
+                    for distance in 0..=a_len + b_len {
+                        let similarity = denominator.similarity(distance, a_len, b_len);
+                        if similarity <= 0.0 {
+                            continue;
+                        }
+                        let limit = denominator.max_distance(similarity, a_len, b_len);
+                        assert!(limit >= distance);
+                        assert!(denominator.similarity(limit, a_len, b_len) >= similarity);
+                        assert!(denominator.similarity(limit + 1, a_len, b_len) < similarity);
+                        // the next larger cutoff excludes the distance
+                        let above = f64::from_bits(similarity.to_bits() + 1);
+                        if similarity < 1.0 {
+                            let limit = denominator.max_distance(above, a_len, b_len);
+                            assert!(limit < distance);
+                            assert!(denominator.similarity(limit, a_len, b_len) >= above);
+                        }
+                    }
+                }
+            }
+        }
     }
 
     #[test]
-    fn jaro_winkler_multibyte() {
-        assert_delta!(0.89, jaro_winkler("testabctest", "testöঙ香test"), 0.001);
-        assert_delta!(0.89, jaro_winkler("testöঙ香test", "testabctest"), 0.001);
+    fn max_distance_edge_cutoffs() {
+        assert_eq!(usize::MAX, Denominator::Max.max_distance(-0.5, 3, 4));
+        assert_eq!(0, Denominator::Max.max_distance(1.5, 3, 4));
+        assert_eq!(0, Denominator::Max.max_distance(f64::NAN, 3, 4));
+        assert_eq!(0, Denominator::Min.max_distance(0.1, 0, 4));
+        assert_eq!(1, Denominator::Mean.max_distance(0.5, 2, 3));
+        assert_eq!(1.0, Denominator::Sum.similarity(5, 0, 0));
     }
 
     #[test]
-    fn jaro_winkler_diff_short() {
-        assert_delta!(0.813, jaro_winkler("dixon", "dicksonx"), 0.001);
-        assert_delta!(0.813, jaro_winkler("dicksonx", "dixon"), 0.001);
+    fn max_distance_long_inputs() {
+        let len = 1 << 40;
+        for &distance in [1, 12_345, len / 3, len - 1].iter() {
+            let similarity = Denominator::Max.similarity(distance, len, len);
+            assert_eq!(
+                distance,
+                Denominator::Max.max_distance(similarity, len, len)
+            );
+            let similarity = Denominator::Mean.similarity(distance, len, len + 1);
+            assert_eq!(
+                distance,
+                Denominator::Mean.max_distance(similarity, len, len + 1)
+            );
+        }
     }
 
     #[test]
-    fn jaro_winkler_diff_one_character() {
-        assert_eq!(0.0, jaro_winkler("a", "b"));
+    fn ppm_matches_floats() {
+        let strings = [
+            "", "a", "kitten", "sitting", "öঙ香", "abc香", "martha", "marhta", "dixon", "dicksonx",
+        ];
+        let denominators = [
+            Denominator::Min,
+            Denominator::Max,
+            Denominator::Mean,
+            Denominator::Sum,
+        ];
+        for a in strings.iter() {
+            for b in strings.iter() {
+                let pairs = [
+                    (
+                        normalized_levenshtein(a, b),
+                        normalized_levenshtein_ppm(a, b),
+                    ),
+                    (
+                        normalized_damerau_levenshtein(a, b),
+                        normalized_damerau_levenshtein_ppm(a, b),
+                    ),
+                    (jaro(a, b), jaro_ppm(a, b)),
+                    (jaro_winkler(a, b), jaro_winkler_ppm(a, b)),
+                ];
+                for &(similarity, ppm) in pairs.iter() {
+                    assert_eq!(to_ppm(similarity), ppm, "{} {}", a, b);
+                }
+                let distance = levenshtein(a, b);
+                let (a_len, b_len) = (a.chars().count(), b.chars().count());
+                for &denominator in denominators.iter() {
+                    assert_eq!(
+                        to_ppm(denominator.similarity(distance, a_len, b_len)),
+                        denominator.similarity_ppm(distance, a_len, b_len)
+                    );
+                }
+            }
+        }
     }
 
     #[test]
-    fn jaro_winkler_same_one_character() {
-        assert_eq!(1.0, jaro_winkler("a", "a"));
+    fn ratio_ppm_rounds_half_up() {
+        assert_eq!(500_000, ratio_ppm(1, 2));
+        assert_eq!(1, ratio_ppm(1, 2_000_000));
+        assert_eq!(0, ratio_ppm(1, 2_000_001));
+        assert_eq!(1_000_000, ratio_ppm(7, 7));
+        assert_eq!(0, to_ppm(f64::NAN));
+        assert_eq!(1_000_000, to_ppm(2.0));
     }
 
     #[test]
-    fn jaro_winkler_diff_no_transposition() {
-        assert_delta!(0.84, jaro_winkler("dwayne", "duane"), 0.001);
+    fn normalized_levenshtein_cutoff_empty() {
+        assert_eq!(Some(1.0), normalized_levenshtein_cutoff("", "", 1.0));
+        assert_eq!(None, normalized_levenshtein_cutoff("", "", 1.5));
     }
 
     #[test]
-    fn jaro_winkler_diff_with_transposition() {
-        assert_delta!(0.961, jaro_winkler("martha", "marhta"), 0.001);
-        assert_delta!(0.6, jaro_winkler("a jke", "jane a k"), 0.001);
+    fn partial_normalized_levenshtein_substrings() {
+        assert_delta!(1.0, partial_normalized_levenshtein("abc", "abc"));
+        assert_delta!(1.0, partial_normalized_levenshtein("bc", "abcd"));
+        assert_delta!(1.0, partial_normalized_levenshtein("abcd", "bc"));
+        assert_delta!(1.0, partial_normalized_levenshtein("öঙ", "xöyঙz"));
     }
 
     #[test]
-    fn jaro_winkler_names() {
+    fn partial_normalized_levenshtein_edits() {
+        // one substitution in the shorter string
+        assert_delta!(0.5, partial_normalized_levenshtein("bx", "abcd"));
+        assert_delta!(0.0, partial_normalized_levenshtein("xy", "abcd"));
         assert_delta!(
-            0.452,
-            jaro_winkler("Friedrich Nietzsche", "Fran-Paul Sartre"),
-            0.001
+            1.0 - 2.0 / 6.0,
+            partial_normalized_levenshtein("kitten", "sitting")
         );
     }
 
     #[test]
-    fn jaro_winkler_long_prefix() {
-        assert_delta!(0.866, jaro_winkler("cheeseburger", "cheese fries"), 0.001);
+    fn partial_normalized_levenshtein_empty() {
+        assert_delta!(1.0, partial_normalized_levenshtein("", ""));
+        assert_delta!(0.0, partial_normalized_levenshtein("", "abc"));
+        assert_delta!(0.0, partial_normalized_levenshtein("abc", ""));
     }
 
     #[test]
-    fn jaro_winkler_more_names() {
-        assert_delta!(0.868, jaro_winkler("Thorkel", "Thorgier"), 0.001);
+    fn normalized_levenshtein_with_empty() {
+        assert_delta!(1.0, normalized_levenshtein_with("", "", Denominator::Min));
+        assert_delta!(
+            0.0,
+            normalized_levenshtein_with("", "second", Denominator::Min)
+        );
+        assert_delta!(
+            0.0,
+            normalized_levenshtein_with("", "second", Denominator::Sum)
+        );
     }
 
     #[test]
-    fn jaro_winkler_length_of_one() {
-        assert_delta!(0.738, jaro_winkler("Dinsdale", "D"), 0.001);
+    fn common_affix_lengths_no_overlap() {
+        assert_eq!((0, 0), common_affix_lengths::<char>(&[], &[]));
+        assert_eq!((3, 0), common_affix_lengths(b"aaa", b"aaaa"));
+        assert_eq!((1, 1), common_affix_lengths(b"abc", b"axc"));
     }
 
     #[test]
-    fn jaro_winkler_very_long_prefix() {
-        assert_delta!(
-            0.98519,
-            jaro_winkler("thequickbrownfoxjumpedoverx", "thequickbrownfoxjumpedovery")
-        );
+    fn levenshtein_banded_exact() {
+        let cases = [
+            ("", ""),
+            ("", "sitting"),
+            ("kitten", ""),
+            ("kitten", "sitting"),
+            ("öঙ香", "abc"),
+            ("hello, world", "bye, world"),
+            (
+                "The quick brown fox jumped over the angry dog.",
+                "Lorem ipsum dolor sit amet, dicta latine an eam.",
+            ),
+        ];
+        for &(a, b) in cases.iter() {
+            let expected = levenshtein(a, b);
+            assert_eq!(Some(expected), levenshtein_banded(a, b, 64).exact());
+            assert_eq!(Some(expected), levenshtein_banded(b, a, 64).exact());
+        }
     }
 
     #[test]
-    fn levenshtein_empty() {
-        assert_eq!(0, levenshtein("", ""));
+    fn levenshtein_banded_contains_distance() {
+        let strings = random_strings(30, 20);
+        for a in &strings {
+            for b in &strings {
+                let expected = levenshtein(a, b);
+                for &max_band in &[0, 1, 3, 8] {
+                    let bounds = levenshtein_banded(a, b, max_band);
+                    assert!(bounds.lower <= expected && expected <= bounds.upper);
+                    if expected <= max_band {
+                        assert_eq!(Some(expected), bounds.exact(), "{} {}", a, b);
+                    }
+                }
+            }
+        }
     }
 
     #[test]
-    fn levenshtein_same() {
-        assert_eq!(0, levenshtein("levenshtein", "levenshtein"));
+    fn levenshtein_banded_narrow_band_bounds() {
+        let a = "The quick brown fox jumped over the angry dog.";
+        let b = "Lorem ipsum dolor sit amet, dicta latine an eam.";
+        let expected = levenshtein(a, b);
+
+        for max_band in 0..40 {
+            let bounds = levenshtein_banded(a, b, max_band);
+            assert!(bounds.lower <= expected, "max_band {}", max_band);
+            assert!(bounds.upper >= expected, "max_band {}", max_band);
+        }
+        assert_eq!(Some(expected), levenshtein_banded(a, b, 40).exact());
     }
 
     #[test]
-    fn levenshtein_diff_short() {
-        assert_eq!(3, levenshtein("kitten", "sitting"));
+    fn levenshtein_banded_length_difference_exceeds_band() {
+        assert_eq!(
+            DistanceBounds { lower: 3, upper: 6 },
+            levenshtein_banded("abc", "defghi", 2)
+        );
     }
 
     #[test]
-    fn levenshtein_diff_with_space() {
-        assert_eq!(5, levenshtein("hello, world", "bye, world"));
+    fn levenshtein_banded_large_input() {
+        let a = "0123456789".repeat(10_000);
+        let mut b = a.clone();
+        b.insert(5, 'x');
+        b.replace_range(50_000..50_001, "y");
+        b.push('z');
+
+        assert_eq!(Some(3), levenshtein_banded(&a, &b, 16).exact());
+    }
+
+    struct VowelWeights;
+
+    impl CharWeights for VowelWeights {
+        fn insertion_cost(&self, c: char) -> usize {
+            if "aeiou".contains(c) {
+                1
+            } else {
+                3
+            }
+        }
+
+        fn deletion_cost(&self, c: char) -> usize {
+            self.insertion_cost(c)
+        }
+
+        fn substitution_cost(&self, _a: char, _b: char) -> usize {
+            2
+        }
     }
 
     #[test]
-    fn levenshtein_diff_multibyte() {
-        assert_eq!(3, levenshtein("öঙ香", "abc"));
-        assert_eq!(3, levenshtein("abc", "öঙ香"));
+    fn weighted_levenshtein_default_weights() {
+        let weights = CharWeightTable::default();
+        assert_eq!(0, weighted_levenshtein("", "", &weights));
+        assert_eq!(7, weighted_levenshtein("", "sitting", &weights));
+        assert_eq!(6, weighted_levenshtein("kitten", "", &weights));
+        assert_eq!(3, weighted_levenshtein("kitten", "sitting", &weights));
+        assert_eq!(3, weighted_levenshtein("öঙ香", "abc", &weights));
     }
 
     #[test]
-    fn levenshtein_diff_longer() {
+    fn weighted_levenshtein_matches_levenshtein() {
         let a = "The quick brown fox jumped over the angry dog.";
         let b = "Lorem ipsum dolor sit amet, dicta latine an eam.";
-        assert_eq!(37, levenshtein(a, b));
+        assert_eq!(
+            levenshtein(a, b),
+            weighted_levenshtein(a, b, &CharWeightTable::default())
+        );
     }
 
     #[test]
-    fn levenshtein_first_empty() {
-        assert_eq!(7, levenshtein("", "sitting"));
+    fn weighted_levenshtein_char_costs() {
+        assert_eq!(1, weighted_levenshtein("colour", "color", &VowelWeights));
+        assert_eq!(3, weighted_levenshtein("cart", "car", &VowelWeights));
+        assert_eq!(2, weighted_levenshtein("cat", "cut", &VowelWeights));
     }
 
     #[test]
-    fn levenshtein_second_empty() {
-        assert_eq!(6, levenshtein("kitten", ""));
+    fn weighted_levenshtein_expensive_substitution() {
+        // replacing is more expensive than deleting and inserting
+        let weights = CharWeightTable::new(1, 1, 5);
+        assert_eq!(2, weighted_levenshtein("a", "b", &weights));
     }
 
     #[test]
-    fn normalized_levenshtein_diff_short() {
-        assert_delta!(0.57142, normalized_levenshtein("kitten", "sitting"));
+    fn weighted_levenshtein_asymmetric() {
+        let weights = CharWeightTable::new(1, 1, 10)
+            .with_insertion_cost('x', 4)
+            .with_deletion_cost('x', 2);
+        assert_eq!(4, weighted_levenshtein("ab", "abx", &weights));
+        assert_eq!(2, weighted_levenshtein("abx", "ab", &weights));
+    }
+
+    struct OsaCosts;
+
+    impl CostModel for OsaCosts {
+        fn transpose_cost(&self, _a: char, _b: char, _position: usize) -> Option<usize> {
+            Some(1)
+        }
+    }
+
+    #[test]
+    fn weighted_edit_distance_default_costs() {
+        struct Unit;
+        impl CostModel for Unit {}
+
+        for (a, b) in [
+            ("kitten", "sitting"),
+            ("", "abc"),
+            ("ab", "ba"),
+            ("öঙ香", "香ö"),
+        ] {
+            assert_eq!(levenshtein(a, b), weighted_edit_distance(a, b, &Unit));
+        }
+    }
+
+    #[test]
+    fn weighted_edit_distance_matches_osa_distance() {
+        for (a, b) in [
+            ("ab", "bca"),
+            ("ca", "abc"),
+            ("a cat", "an abct"),
+            ("abcd", "badc"),
+            ("aa", "aa"),
+        ] {
+            assert_eq!(osa_distance(a, b), weighted_edit_distance(a, b, &OsaCosts));
+        }
+    }
+
+    #[test]
+    fn weighted_edit_distance_positions() {
+        struct LateTranspositions;
+
+        impl CostModel for LateTranspositions {
+            fn transpose_cost(&self, _a: char, _b: char, position: usize) -> Option<usize> {
+                if position >= 2 {
+                    Some(1)
+                } else {
+                    None
+                }
+            }
+        }
+
+        assert_eq!(
+            2,
+            weighted_edit_distance("abcd", "bacd", &LateTranspositions)
+        );
+        assert_eq!(
+            1,
+            weighted_edit_distance("abcd", "abdc", &LateTranspositions)
+        );
     }
 
     #[test]
-    fn normalized_levenshtein_for_empty_strings() {
-        assert_delta!(1.0, normalized_levenshtein("", ""));
+    fn weighted_edit_distance_char_weights() {
+        for (a, b) in [("colour", "color"), ("cart", "car"), ("cat", "cut")] {
+            assert_eq!(
+                weighted_levenshtein(a, b, &VowelWeights),
+                weighted_edit_distance(a, b, &VowelWeights)
+            );
+        }
     }
 
     #[test]
-    fn normalized_levenshtein_first_empty() {
-        assert_delta!(0.0, normalized_levenshtein("", "second"));
+    fn positional_weighted_levenshtein_constant_factor() {
+        let weights = CharWeightTable::default();
+        assert_eq!(
+            3,
+            positional_weighted_levenshtein("kitten", "sitting", &weights, |_| 1)
+        );
+        assert_eq!(
+            6,
+            positional_weighted_levenshtein("kitten", "sitting", &weights, |_| 2)
+        );
+        assert_eq!(0, positional_weighted_levenshtein("", "", &weights, |_| 2));
     }
 
     #[test]
-    fn normalized_levenshtein_second_empty() {
-        assert_delta!(0.0, normalized_levenshtein("first", ""));
+    fn positional_weighted_levenshtein_early_edits() {
+        let weights = CharWeightTable::default();
+        let early = |i: usize| if i < 3 { 2 } else { 1 };
+        // substitution at position 0 and insertion at the end
+        assert_eq!(
+            4,
+            positional_weighted_levenshtein("kitten", "sitting", &weights, early)
+        );
+        assert_eq!(
+            2,
+            positional_weighted_levenshtein("abc", "xbc", &weights, early)
+        );
+        assert_eq!(
+            1,
+            positional_weighted_levenshtein("abcd", "abcx", &weights, early)
+        );
     }
 
     #[test]
-    fn normalized_levenshtein_identical_strings() {
-        assert_delta!(1.0, normalized_levenshtein("identical", "identical"));
+    fn positional_weighted_levenshtein_insertion_positions() {
+        let weights = CharWeightTable::default();
+        let first_only = |i: usize| if i == 0 { 10 } else { 1 };
+        assert_eq!(
+            30,
+            positional_weighted_levenshtein("", "xab", &weights, first_only)
+        );
+        assert_eq!(
+            10,
+            positional_weighted_levenshtein("ab", "xab", &weights, first_only)
+        );
+        assert_eq!(
+            1,
+            positional_weighted_levenshtein("ab", "axb", &weights, first_only)
+        );
+        assert_eq!(
+            1,
+            positional_weighted_levenshtein("ab", "abx", &weights, first_only)
+        );
     }
 
     #[test]
@@ -1220,6 +5766,404 @@ mod tests {
         assert_eq!(3, damerau_levenshtein("a cat", "an abct"));
     }
 
+    #[test]
+    fn generic_damerau_levenshtein_ord_empty() {
+        let empty: [u8; 0] = [];
+        assert_eq!(0, generic_damerau_levenshtein_ord(&empty, &empty));
+        assert_eq!(3, generic_damerau_levenshtein_ord(&empty, &[1, 2, 3]));
+        assert_eq!(2, generic_damerau_levenshtein_ord(&[1, 2], &empty));
+    }
+
+    #[test]
+    fn generic_damerau_levenshtein_ord_without_hash() {
+        #[derive(PartialEq, Eq, PartialOrd, Ord)]
+        struct Word(&'static str);
+
+        let a = [Word("a"), Word("cat")];
+        let b = [Word("cat"), Word("a"), Word("dog")];
+        assert_eq!(2, generic_damerau_levenshtein_ord(&a, &b));
+    }
+
+    #[test]
+    fn generic_damerau_levenshtein_matches_char_version() {
+        let strings = random_strings(40, 12);
+        for a in &strings {
+            for b in &strings {
+                let a_chars: Vec<char> = a.chars().collect();
+                let b_chars: Vec<char> = b.chars().collect();
+                let expected = damerau_levenshtein(a, b);
+                assert_eq!(expected, generic_damerau_levenshtein(&a_chars, &b_chars));
+                assert_eq!(
+                    expected,
+                    generic_damerau_levenshtein_ord(&a_chars, &b_chars)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn osa_distance_window_generalizes_osa() {
+        let strings = [
+            "", "a", "ab", "ba", "abc", "cba", "bca", "abcd", "dbca", "kitten", "sitting", "öঙ香",
+            "香ঙö", "a tc", "ca t",
+        ];
+        for a in strings.iter() {
+            for b in strings.iter() {
+                assert_eq!(levenshtein(a, b), osa_distance_window(a, b, 0));
+                assert_eq!(osa_distance(a, b), osa_distance_window(a, b, 1));
+                for window in 1..6 {
+                    assert!(
+                        osa_distance_window(a, b, window) <= osa_distance_window(a, b, window - 1)
+                    );
+                    assert_eq!(
+                        osa_distance_window(a, b, window),
+                        osa_distance_window(b, a, window)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn osa_distance_window_wide_swaps() {
+        assert_eq!(1, osa_distance_window("abcd", "dbca", 3));
+        assert_eq!(1, osa_distance_window("abcd", "dbca", 100));
+        // the characters between the swapped ones have to be unchanged
+        assert_eq!(3, osa_distance_window("axcd", "dbca", 3));
+        assert_eq!(2, osa_distance_window("abxcd", "dbxca", 3));
+        assert_eq!(1, osa_distance_window("abxcd", "dbxca", 4));
+        assert_eq!(2, osa_distance_window("kitchen", "kctihen", 1));
+        assert_eq!(1, osa_distance_window("kitchen", "kctihen", 2));
+        assert_eq!(2, osa_distance_window("1234 5678", "1834 5672", 6));
+        assert_eq!(1, osa_distance_window("1234 5678", "1834 5672", 7));
+    }
+
+    /* `osa_distance_window` trying every span of the transposition */
+    fn osa_distance_window_scan(a: &str, b: &str, window: usize) -> usize {
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        let mut matrix = vec![vec![0; b.len() + 1]; a.len() + 1];
+        for i in 0..=a.len() {
+            for j in 0..=b.len() {
+                if i == 0 || j == 0 {
+                    matrix[i][j] = i + j;
+                    continue;
+                }
+                let cost = usize::from(a[i - 1] != b[j - 1]);
+                let mut distance = min(
+                    min(matrix[i][j - 1], matrix[i - 1][j]) + 1,
+                    matrix[i - 1][j - 1] + cost,
+                );
+                for k in 1..=min(window, min(i, j) - 1) {
+                    if a[i - 1] == b[j - 1 - k]
+                        && a[i - 1 - k] == b[j - 1]
+                        && a[i - k..i - 1] == b[j - k..j - 1]
+                    {
+                        distance = min(distance, matrix[i - k - 1][j - k - 1] + 1);
+                    }
+                }
+                matrix[i][j] = distance;
+            }
+        }
+        matrix[a.len()][b.len()]
+    }
+
+    #[test]
+    fn osa_distance_window_random_strings() {
+        let strings = random_strings(40, 8);
+        for a in &strings {
+            for b in &strings {
+                for window in 0..6 {
+                    assert_eq!(
+                        osa_distance_window_scan(a, b, window),
+                        osa_distance_window(a, b, window)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cyclic_levenshtein_matches_brute_force() {
+        let strings = [
+            "", "a", "ab", "ba", "abc", "cab", "acb", "abcdef", "defabc", "efxabcd", "kitten",
+            "tenkit", "sitting", "öঙ香", "香öঙ", "aaaa", "aaba",
+        ];
+        for a in strings.iter() {
+            for b in strings.iter() {
+                let chars: Vec<char> = a.chars().collect();
+                let brute_force = (0..max(chars.len(), 1))
+                    .map(|r| {
+                        let rotation: String = chars[r.min(chars.len())..]
+                            .iter()
+                            .chain(&chars[..r.min(chars.len())])
+                            .collect();
+                        levenshtein(&rotation, b)
+                    })
+                    .min()
+                    .unwrap();
+                assert_eq!(brute_force, cyclic_levenshtein(a, b), "{} {}", a, b);
+                assert_eq!(
+                    cyclic_levenshtein(b, a),
+                    cyclic_levenshtein(a, b),
+                    "{} {}",
+                    a,
+                    b
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn cyclic_levenshtein_rotations() {
+        assert_eq!(0, cyclic_levenshtein("ATGCGTA", "GTAATGC"));
+        assert_eq!(1, cyclic_levenshtein("ATGCGTA", "GTAATGG"));
+        assert_eq!(1, cyclic_levenshtein("ATGCGTA", "TAATGC"));
+    }
+
+    #[test]
+    fn cyclic_levenshtein_random_strings() {
+        let strings = random_strings(40, 9);
+        for a in &strings {
+            for b in &strings {
+                let chars: Vec<char> = a.chars().collect();
+                let brute_force = (0..max(chars.len(), 1))
+                    .map(|r| {
+                        let r = min(r, chars.len());
+                        let rotation: String = chars[r..].iter().chain(&chars[..r]).collect();
+                        levenshtein(&rotation, b)
+                    })
+                    .min()
+                    .unwrap();
+                assert_eq!(brute_force, cyclic_levenshtein(a, b), "{} {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn cyclic_levenshtein_cells() {
+        // comparing every rotation would calculate about 500 times as many
+        let a: Vec<char> = random_strings(1, 2000)[0]
+            .chars()
+            .cycle()
+            .take(512)
+            .collect();
+        let b: Vec<char> = "abcdö香".chars().cycle().take(600).collect();
+        let (distance, cells) = cyclic_levenshtein_chars(&a, &b);
+        assert_eq!(distance, cyclic_levenshtein_chars(&b, &a).0);
+        assert!(cells <= 513 * 601 * 12, "{}", cells);
+    }
+
+    #[test]
+    fn cyclic_levenshtein_different_lengths() {
+        let a = "abcdefghij".repeat(300);
+        assert_eq!(a.len() - 3, cyclic_levenshtein(&a, "jab"));
+        assert_eq!(a.len() - 3, cyclic_levenshtein("jab", &a));
+        assert_eq!(a.len() - 1, cyclic_levenshtein(&a, "ax"));
+    }
+
+    #[test]
+    fn indel_distance_without_substitutions() {
+        assert_eq!(0, indel_distance("", ""));
+        assert_eq!(3, indel_distance("abc", ""));
+        assert_eq!(2, indel_distance("a", "b"));
+        assert_eq!(2, indel_distance("öঙ香", "ö香x"));
+        for (a, b) in [("kitten", "sitting"), ("abc", "cab"), ("", "xyz")] {
+            assert_eq!(indel_distance(a, b), indel_distance(b, a));
+            assert!(indel_distance(a, b) >= levenshtein(a, b));
+        }
+        assert_delta!(0.0, normalized_indel("a", "b"));
+        assert_delta!(0.75, normalized_indel("abc", "abcde"));
+        assert_delta!(0.5, normalized_indel("ab", "ba"));
+    }
+
+    #[test]
+    fn needleman_wunsch_special_cases() {
+        let strings = random_strings(30, 40);
+        for a in &strings {
+            for b in &strings {
+                assert_eq!(
+                    -(levenshtein(a, b) as i64),
+                    needleman_wunsch(a, b, 0, 1, 1),
+                    "{} {}",
+                    a,
+                    b
+                );
+                // without gap penalty and with a mismatch penalty of 2 or
+                // more, only the common subsequence scores
+                assert_eq!(lcs_seq(a, b) as i64, needleman_wunsch(a, b, 1, 2, 0));
+                assert_eq!(
+                    needleman_wunsch(a, b, 3, 1, 2),
+                    needleman_wunsch(b, a, 3, 1, 2)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn needleman_wunsch_scores() {
+        assert_eq!(-3, needleman_wunsch("", "abc", 5, 1, 1));
+        assert_eq!(12, needleman_wunsch("abcd", "abcd", 3, 1, 1));
+        // a mismatch costs less than two gaps
+        assert_eq!(5, needleman_wunsch("abcd", "abxd", 2, 1, 4));
+        // two gaps cost less than a mismatch
+        assert_eq!(4, needleman_wunsch("abcd", "abxd", 2, 5, 1));
+        assert_eq!(
+            -4,
+            generic_needleman_wunsch(&[1, 2, 3], &[4, 5, 6, 7], 1, 1, 1)
+        );
+        // negative penalties reward mismatches and gaps
+        assert_eq!(4, needleman_wunsch("ab", "cd", 0, -2, 1));
+        assert_eq!(2, needleman_wunsch("a", "b", 0, 0, -1));
+        assert_eq!(-20, needleman_wunsch("香香", "", 1, 1, 10));
+    }
+
+    #[test]
+    fn ratcliff_obershelp_matches_difflib() {
+        // expected values calculated with difflib.SequenceMatcher(None, a, b).ratio()
+        for &(a, b, ratio) in [
+            ("GESTALT PATTERN MATCHING", "GESTALT PRACTICE", 0.6),
+            ("kitten", "sitting", 0.6153846153846154),
+            ("ab", "ba", 0.5),
+            ("öঙ香x", "香xö", 0.5714285714285714),
+            (
+                "private Thread currentThread;",
+                "private volatile Thread currentThread;",
+                0.8656716417910447,
+            ),
+            (
+                "the quick brown fox",
+                "the quack brown fax",
+                0.8947368421052632,
+            ),
+        ]
+        .iter()
+        {
+            assert_eq!(ratio, ratcliff_obershelp(a, b), "{} {}", a, b);
+        }
+    }
+
+    #[test]
+    fn ratcliff_obershelp_ignores_popular_characters() {
+        let a: String = (0..300)
+            .map(|i| {
+                if i % 5 == 0 {
+                    ' '
+                } else {
+                    char::from(b'a' + (i * 7 % 26) as u8)
+                }
+            })
+            .collect();
+        let b = format!("{}xyz", &a[10..260]);
+        // nearly every character occurs more than 1% of the time in the 253
+        // character string, so like difflib hardly any matches are found
+        assert_eq!(0.003616636528028933, ratcliff_obershelp(&a, &b));
+        assert_eq!(0.003616636528028933, ratcliff_obershelp(&b, &a));
+        assert_eq!(
+            0.858085808580858,
+            ratcliff_obershelp(&a[..150], &format!("{}xyz", &a[20..170]))
+        );
+    }
+
+    #[test]
+    fn normalized_lcs_by_longer_string() {
+        assert_delta!(1.0, normalized_lcs("a", "a"));
+        assert_delta!(0.0, normalized_lcs("a", "b"));
+        assert_delta!(0.0, normalized_lcs("abc", ""));
+        assert_delta!(0.5, normalized_lcs("abc", "abcxyz"));
+        assert_delta!(0.5, normalized_lcs("abcxyz", "abc"));
+        assert_delta!(2.0 / 3.0, normalized_lcs("öঙ香", "ö香"));
+        assert_delta!(2.0 / 3.0, normalized_lcs("abc", "bac"));
+    }
+
+    #[test]
+    fn lcs_seq_lengths() {
+        assert_eq!(0, lcs_seq("", ""));
+        assert_eq!(0, lcs_seq("abc", ""));
+        assert_eq!(0, lcs_seq("abc", "xyz"));
+        assert_eq!(3, lcs_seq("abc", "abc"));
+        assert_eq!(3, lcs_seq("axbycz", "abc"));
+        assert_eq!(3, lcs_seq("abc", "axbycz"));
+        assert_eq!(2, lcs_seq("öঙ香", "ö香"));
+        assert_eq!(4, lcs_seq("ABCBDAB", "BDCABA"));
+        assert_eq!(
+            lcs_seq("BDCABA", "ABCBDAB"),
+            generic_lcs_seq(
+                &['B', 'D', 'C', 'A', 'B', 'A'],
+                &"ABCBDAB".chars().collect::<Vec<_>>()
+            )
+        );
+        // the row moves to the heap for long strings
+        let a = "ab".repeat(200);
+        let b = "ba".repeat(150);
+        assert_eq!(300, lcs_seq(&a, &b));
+        assert_eq!(100, indel_distance(&a, &b));
+    }
+
+    #[test]
+    fn with_buffer_is_zeroed() {
+        for len in [0, 1, 32, 33, 128, 129, 1000] {
+            let buffer_len = with_buffer(len, |buffer: &mut [usize]| {
+                assert!(buffer.iter().all(|&x| x == 0));
+                buffer.len()
+            });
+            assert_eq!(len, buffer_len);
+        }
+    }
+
+    #[test]
+    fn stack_and_heap_buffers() {
+        // covers the lengths where the rows move to the larger stack buffer
+        // and to the heap
+        for len in 0..140 {
+            let a = "a".repeat(len);
+            let b = "b".repeat(len);
+            assert_eq!(len, osa_distance_impl(&a, &b));
+            assert_eq!(
+                len,
+                generic_levenshtein(&StringWrapper(&a), &StringWrapper(&b))
+            );
+            let damerau = damerau_levenshtein_impl(
+                a.chars(),
+                len,
+                b.chars(),
+                len,
+                HybridGrowingHashmapChar::default(),
+            );
+            assert_eq!(len, damerau);
+        }
+    }
+
+    #[test]
+    fn affix_trimming_keeps_distances() {
+        let strings = random_strings(20, 8);
+        let affixes = ["", "a", "ab", "ba", "香ö"];
+        for a in &strings {
+            for b in &strings {
+                for prefix in &affixes {
+                    for suffix in &affixes {
+                        let a = format!("{}{}{}", prefix, a, suffix);
+                        let b = format!("{}{}{}", prefix, b, suffix);
+                        let (a_len, b_len) = (a.chars().count(), b.chars().count());
+                        let a_chars: Vec<char> = a.chars().collect();
+                        let b_chars: Vec<char> = b.chars().collect();
+
+                        assert_eq!(osa_distance_impl(&a, &b), osa_distance(&a, &b));
+                        let damerau = damerau_levenshtein_impl(
+                            a.chars(),
+                            a_len,
+                            b.chars(),
+                            b_len,
+                            HybridGrowingHashmapChar::default(),
+                        );
+                        assert_eq!(damerau, damerau_levenshtein(&a, &b));
+                        assert_eq!(damerau, generic_damerau_levenshtein(&a_chars, &b_chars));
+                    }
+                }
+            }
+        }
+    }
+
     #[test]
     fn normalized_damerau_levenshtein_diff_short() {
         assert_delta!(
@@ -1251,6 +6195,317 @@ mod tests {
         );
     }
 
+    #[test]
+    fn generic_damerau_levenshtein_clusters() {
+        // a family emoji joined with zero width joiners, a flag, and an `e`
+        // with a combining accent
+        let family = "👨\u{200d}👩\u{200d}👧";
+        let a = [family, "🇯🇵", "e\u{301}", "x"];
+        let b = ["🇯🇵", family, "x", "e\u{301}"];
+        assert_eq!(2, generic_damerau_levenshtein(&a, &b));
+        // repeated clusters keep the transposition bookkeeping apart
+        let a = [family, "🇯🇵", family, "🇯🇵"];
+        let b = ["🇯🇵", family, "🇯🇵", family];
+        assert_eq!(2, generic_damerau_levenshtein(&a, &b));
+        assert_eq!(0, generic_damerau_levenshtein(&a, &a));
+    }
+
+    #[cfg(feature = "unicode-segmentation")]
+    #[test]
+    fn damerau_levenshtein_graphemes_splits_clusters() {
+        let family = "👨\u{200d}👩\u{200d}👧";
+        let a = [family, "🇯🇵", "e\u{301}", "x"];
+        let b = ["🇯🇵", family, "x", "e\u{301}"];
+        assert_eq!(
+            generic_damerau_levenshtein(&a, &b),
+            damerau_levenshtein_graphemes(&a.concat(), &b.concat())
+        );
+        assert_eq!(0, damerau_levenshtein_graphemes("", ""));
+        assert_eq!(3, damerau_levenshtein_graphemes(family, "abc"));
+        for a in ["", "ab", "abc", "kitten", "öঙ香"] {
+            for b in ["", "ba", "bca", "sitting", "香ঙö"] {
+                assert_eq!(
+                    damerau_levenshtein(a, b),
+                    damerau_levenshtein_graphemes(a, b)
+                );
+            }
+        }
+    }
+
+    /* The number of characters and bytes of an affix */
+    fn chars_and_bytes(len: AffixLen) -> (usize, usize) {
+        (len.chars, len.bytes)
+    }
+
+    #[test]
+    fn common_prefix_len_counts() {
+        assert_eq!(AffixLen::default(), common_prefix_len("", "abc"));
+        assert_eq!((3, 3), chars_and_bytes(common_prefix_len("abc", "abc")));
+        assert_eq!((2, 5), chars_and_bytes(common_prefix_len("öঙ香", "öঙa")));
+        // different characters sharing a leading byte are no common prefix
+        assert_eq!(AffixLen::default(), common_prefix_len("ö", "ü"));
+    }
+
+    #[test]
+    fn common_suffix_len_counts() {
+        assert_eq!(AffixLen::default(), common_suffix_len("abc", ""));
+        assert_eq!((1, 3), chars_and_bytes(common_suffix_len("a香", "öঙ香")));
+        assert_eq!((2, 2), chars_and_bytes(common_suffix_len("aa", "aaa")));
+    }
+
+    #[cfg(feature = "unicode-segmentation")]
+    #[test]
+    fn common_affix_len_graphemes() {
+        // "é" as "e" followed by a combining acute accent
+        let len = common_prefix_len("e\u{301}te", "ete");
+        assert_eq!((1, 1, 0), (len.chars, len.bytes, len.graphemes));
+        let len = common_prefix_len(
+            "\u{1f1e9}\u{1f1ea}\u{1f1eb}\u{1f1f7}",
+            "\u{1f1e9}\u{1f1ea}\u{1f1eb}",
+        );
+        assert_eq!((3, 12, 1), (len.chars, len.bytes, len.graphemes));
+        let len = common_suffix_len("cafe\u{301}", "the\u{301}");
+        assert_eq!((2, 3, 1), (len.chars, len.bytes, len.graphemes));
+        let len = common_suffix_len("cafe", "cafe\u{301}");
+        assert_eq!((0, 0, 0), (len.chars, len.bytes, len.graphemes));
+    }
+
+    #[test]
+    fn prefix_similarity_empty() {
+        assert_eq!(1.0, prefix_similarity("", "", Denominator::Min));
+        assert_eq!(1.0, prefix_similarity("", "", Denominator::Max));
+        assert_eq!(1.0, prefix_similarity("", "", Denominator::Mean));
+    }
+
+    #[test]
+    fn prefix_similarity_one_empty() {
+        assert_eq!(0.0, prefix_similarity("", "prefix", Denominator::Min));
+        assert_eq!(0.0, prefix_similarity("prefix", "", Denominator::Max));
+        assert_eq!(0.0, prefix_similarity("prefix", "", Denominator::Mean));
+    }
+
+    #[test]
+    fn prefix_similarity_denominators() {
+        assert_delta!(1.0, prefix_similarity("pre", "prefix", Denominator::Min));
+        assert_delta!(0.5, prefix_similarity("pre", "prefix", Denominator::Max));
+        assert_delta!(
+            0.66666,
+            prefix_similarity("pre", "prefix", Denominator::Mean)
+        );
+    }
+
+    #[test]
+    fn prefix_similarity_multibyte() {
+        assert_delta!(0.5, prefix_similarity("öঙ香a", "öঙb香", Denominator::Max));
+    }
+
+    #[test]
+    fn suffix_similarity_empty() {
+        assert_eq!(1.0, suffix_similarity("", "", Denominator::Min));
+    }
+
+    #[test]
+    fn suffix_similarity_one_empty() {
+        assert_eq!(0.0, suffix_similarity("suffix", "", Denominator::Min));
+    }
+
+    #[test]
+    fn suffix_similarity_denominators() {
+        assert_delta!(1.0, suffix_similarity("ing", "testing", Denominator::Min));
+        assert_delta!(
+            0.42857,
+            suffix_similarity("ing", "testing", Denominator::Max)
+        );
+        assert_delta!(0.6, suffix_similarity("ing", "testing", Denominator::Mean));
+    }
+
+    #[test]
+    fn suffix_similarity_no_common_suffix() {
+        assert_eq!(0.0, suffix_similarity("abc", "abd", Denominator::Min));
+    }
+
+    #[test]
+    fn normalized_damerau_levenshtein_with_denominators() {
+        assert_delta!(
+            normalized_damerau_levenshtein("levenshtein", "löwenbräu"),
+            normalized_damerau_levenshtein_with("levenshtein", "löwenbräu", Denominator::Max)
+        );
+        assert_delta!(
+            0.11111,
+            normalized_damerau_levenshtein_with("levenshtein", "löwenbräu", Denominator::Min)
+        );
+        assert_delta!(
+            0.6,
+            normalized_damerau_levenshtein_with("levenshtein", "löwenbräu", Denominator::Sum)
+        );
+    }
+
+    #[test]
+    fn normalized_osa_with_denominators() {
+        assert_delta!(
+            normalized_osa("ab", "bca"),
+            normalized_osa_with("ab", "bca", Denominator::Max)
+        );
+        assert_delta!(
+            0.66666,
+            normalized_osa_with("abc", "acb", Denominator::Mean)
+        );
+        assert_delta!(1.0, normalized_osa_with("", "", Denominator::Min));
+        assert_delta!(0.0, normalized_osa_with("", "abc", Denominator::Min));
+    }
+
+    #[test]
+    fn normalized_indel_with_denominators() {
+        for (a, b) in [("kitten", "sitting"), ("", ""), ("ab", "")] {
+            assert_delta!(
+                normalized_indel(a, b),
+                normalized_indel_with(a, b, Denominator::Sum)
+            );
+        }
+        assert_delta!(0.0, normalized_indel_with("ab", "ba", Denominator::Min));
+    }
+
+    #[test]
+    fn normalized_lcs_with_denominators() {
+        assert_delta!(
+            normalized_lcs("kitten", "sitting"),
+            normalized_lcs_with("kitten", "sitting", Denominator::Max)
+        );
+        assert_delta!(
+            0.66666,
+            normalized_lcs_with("kitten", "sitting", Denominator::Min)
+        );
+        assert_delta!(0.0, normalized_lcs_with("", "abc", Denominator::Min));
+        assert_delta!(1.0, normalized_lcs_with("", "", Denominator::Mean));
+    }
+
+    #[test]
+    fn prefix_similarity_sum() {
+        assert_delta!(0.5, prefix_similarity("same", "same", Denominator::Sum));
+    }
+
+    #[test]
+    fn f32_variants_match_f64() {
+        let mut strings = random_strings(20, 10);
+        strings.push("Friedrich Nietzsche".to_string());
+        strings.push("Jean-Paul Sartre".to_string());
+        for a in &strings {
+            for b in &strings {
+                assert_delta!(
+                    normalized_levenshtein(a, b) as f32,
+                    normalized_levenshtein_f32(a, b),
+                    1e-6
+                );
+                assert_delta!(
+                    normalized_damerau_levenshtein(a, b) as f32,
+                    normalized_damerau_levenshtein_f32(a, b),
+                    1e-6
+                );
+                assert_delta!(jaro(a, b) as f32, jaro_f32(a, b), 1e-6);
+                assert_delta!(jaro_winkler(a, b) as f32, jaro_winkler_f32(a, b), 1e-6);
+                assert_delta!(sorensen_dice(a, b) as f32, sorensen_dice_f32(a, b), 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn f32_variants_empty() {
+        assert_eq!(1.0, normalized_levenshtein_f32("", ""));
+        assert_eq!(0.0, normalized_damerau_levenshtein_f32("", "flower"));
+        assert_eq!(1.0, jaro_f32("", ""));
+        assert_eq!(0.0, jaro_winkler_f32("", "jaro"));
+        assert_eq!(1.0, sorensen_dice_f32("", ""));
+    }
+
+    #[test]
+    fn bit_hamming_counts_bits() {
+        assert_eq!(Ok(0), bit_hamming(&[], &[]));
+        assert_eq!(Ok(128), bit_hamming(&[u64::MAX, 0], &[0, u64::MAX]));
+        assert_eq!(
+            Err(StrSimError::DifferentLengthArgs),
+            bit_hamming(&[], &[0])
+        );
+    }
+
+    #[test]
+    fn tanimoto_fingerprints() {
+        assert_eq!(Ok(1.0), tanimoto(&[], &[]));
+        assert_eq!(Ok(1.0), tanimoto(&[0b101, 7], &[0b101, 7]));
+        assert_eq!(Ok(0.0), tanimoto(&[0b01], &[0b10]));
+        assert_eq!(Ok(0.0), tanimoto(&[0], &[1]));
+        assert_eq!(Ok(0.25), tanimoto(&[0b1111, 0], &[0b0001, 0]));
+        assert_eq!(
+            Err(StrSimError::DifferentLengthArgs),
+            tanimoto(&[0, 0], &[0])
+        );
+    }
+
+    #[test]
+    fn overlap_coefficient_smaller_set() {
+        assert_delta!(1.0, overlap_coefficient("a", "a"));
+        assert_delta!(0.0, overlap_coefficient("a", "ab"));
+        assert_delta!(1.0, overlap_coefficient("ab", "xaby"));
+        assert_delta!(1.0, overlap_coefficient("iphone", "iphone x"));
+        assert_delta!(1.0, overlap_coefficient("aa", "aaaa"));
+        // "aa" occurs twice in "aaa" but once in "aab"
+        assert_delta!(0.5, overlap_coefficient("aaa", "aab"));
+        assert_delta!(0.5, overlap_coefficient("abcde", "xxxcdex"));
+        assert_delta!(
+            overlap_coefficient("pineapple", "apple"),
+            overlap_coefficient("apple", "pineapple")
+        );
+    }
+
+    #[test]
+    fn cosine_bigram_frequencies() {
+        assert_delta!(1.0, cosine("a", "a"));
+        assert_delta!(0.0, cosine("a", "b"));
+        assert_delta!(0.0, cosine("a", "ab"));
+        assert_delta!(1.0, cosine("apple event", "apple    event"));
+        // repeated bigrams weigh more: "aa" occurs 3 times in "aaaa"
+        assert_delta!(1.0, cosine("aaaa", "aa"));
+        assert_delta!(4.0 / 20f64.sqrt(), cosine("aaaab", "aab"));
+        assert_delta!(0.0, cosine("french", "quebec"));
+        assert_delta!(0.8, cosine("healed", "sealed"));
+    }
+
+    #[test]
+    fn tversky_weights() {
+        assert_delta!(1.0, tversky("a", "a", 0.5, 0.5));
+        assert_delta!(0.0, tversky("a", "b", 0.5, 0.5));
+        assert_delta!(0.0, tversky("", "ab", 0.0, 0.0));
+        assert_delta!(0.0, tversky("french", "quebec", 1.0, 1.0));
+        for (a, b) in [
+            ("healed", "sealed"),
+            ("iphone", "iphone x"),
+            ("aaaa", "aa"),
+            ("web applications", "applications of the web"),
+        ] {
+            assert_delta!(sorensen_dice(a, b), tversky(a, b, 0.5, 0.5));
+        }
+        // "aa" occurs 3 times in "aaaa" and once in "aa"
+        assert_delta!(1.0 / 3.0, tversky("aaaa", "aa", 1.0, 1.0));
+        assert_delta!(1.0, tversky("aaaa", "aa", 0.0, 1.0));
+        // only the bigrams of the query count
+        assert_delta!(1.0, tversky("apple", "pineapple", 1.0, 0.0));
+        assert_delta!(0.5, tversky("pineapple", "apple", 1.0, 0.0));
+    }
+
+    #[test]
+    fn jaccard_ngram_sets() {
+        assert_delta!(1.0, jaccard("a", "a", 2));
+        assert_delta!(0.0, jaccard("a", "b", 2));
+        assert_delta!(0.0, jaccard("ab", "abc", 4));
+        // repeated n-grams count once
+        assert_delta!(1.0, jaccard("aaaa", "aa", 2));
+        assert_delta!(0.5, jaccard("ab", "abc", 2));
+        // the size is at least 1
+        assert_delta!(jaccard("abc", "cab", 1), jaccard("abc", "cab", 0));
+        assert_delta!(1.0, jaccard("abc", "cab", 0));
+        assert_delta!(0.5, jaccard("über", "uber", 2));
+        assert_delta!(0.0, jaccard("a b", "ab", 2));
+    }
+
     #[test]
     fn sorensen_dice_all() {
         // test cases taken from
@@ -0,0 +1,237 @@
+use crate::{jaro_winkler, tokenize, TokenScorer};
+
+/* Common abbreviations of street types, directions and unit designators, with
+their expansions */
+const ABBREVIATIONS: [(&str, &str); 29] = [
+    ("apt", "apartment"),
+    ("av", "avenue"),
+    ("ave", "avenue"),
+    ("bldg", "building"),
+    ("blvd", "boulevard"),
+    ("cir", "circle"),
+    ("ct", "court"),
+    ("dr", "drive"),
+    ("e", "east"),
+    ("fl", "floor"),
+    ("hwy", "highway"),
+    ("ln", "lane"),
+    ("n", "north"),
+    ("ne", "northeast"),
+    ("nw", "northwest"),
+    ("pkwy", "parkway"),
+    ("pl", "place"),
+    ("rd", "road"),
+    ("rm", "room"),
+    ("s", "south"),
+    ("se", "southeast"),
+    ("sq", "square"),
+    ("st", "street"),
+    ("ste", "suite"),
+    ("str", "street"),
+    ("sw", "southwest"),
+    ("ter", "terrace"),
+    ("trl", "trail"),
+    ("w", "west"),
+];
+
+/* Tokens introducing a unit number, which must not be taken as house number */
+const UNIT_DESIGNATORS: [&str; 6] = ["apartment", "building", "floor", "room", "suite", "unit"];
+
+fn expand(token: &str) -> String {
+    let token = token.to_lowercase();
+    match ABBREVIATIONS.iter().find(|(short, _)| *short == token) {
+        Some((_, long)) => long.to_string(),
+        None => token,
+    }
+}
+
+/* A normalized address: the house number and the remaining tokens */
+struct AddressParts {
+    house_number: Option<String>,
+    tokens: Vec<String>,
+}
+
+impl AddressParts {
+    fn parse(address: &str) -> Self {
+        let mut house_number = None;
+        let mut tokens = Vec::new();
+        let mut after_unit_designator = false;
+
+        for token in tokenize(address) {
+            let token = expand(token);
+            let is_number = token.starts_with(|c: char| c.is_ascii_digit());
+            if is_number && house_number.is_none() && !after_unit_designator {
+                house_number = Some(token);
+            } else {
+                after_unit_designator = UNIT_DESIGNATORS.contains(&token.as_str());
+                tokens.push(token);
+            }
+        }
+        Self {
+            house_number,
+            tokens,
+        }
+    }
+}
+
+/* Compares house numbers numerically, so `012` equals `12`, and anything else
+like `12a` by its text */
+fn same_house_number(a: &str, b: &str) -> bool {
+    match (a.parse::<u64>(), b.parse::<u64>()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Compares street addresses by their house number and their remaining tokens.
+///
+/// The addresses are tokenized and lowercased, and common abbreviations like
+/// `St`, `Ave` or `Apt` are expanded. The first token starting with a digit
+/// which doesn't follow a unit designator like `Apartment` is taken as house
+/// number. House numbers either match or not, with `012` matching `12`. The
+/// remaining tokens are scored with the average of `token_set_similarity` and
+/// the Monge-Elkan similarity with `jaro_winkler` for the tokens, see
+/// `TokenScorer::match_similarity`. When only one address has a house number
+/// only the remaining tokens are compared.
+///
+/// `St` is always expanded to `street`, so `St Louis` becomes `street louis`
+/// in both addresses.
+///
+/// ```
+/// use strsim::matchers::AddressScorer;
+///
+/// let scorer = AddressScorer::default();
+/// assert_eq!(1.0, scorer.similarity("12 Main St., Apt 4", "12 main street apartment 4"));
+/// assert!(scorer.similarity("12 Main St", "14 Main St") <= 0.7);
+/// assert!(scorer.similarity("12 Main St", "12 Mian Street") > 0.9);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AddressScorer {
+    house_number_weight: f64,
+}
+
+impl AddressScorer {
+    /// Sets the weight of the house number, between 0.0 and 1.0. The
+    /// remaining tokens are weighted with `1.0 - weight`. The default is 0.3.
+    pub fn house_number_weight(mut self, weight: f64) -> Self {
+        self.house_number_weight = weight.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Normalizes an address as described for `AddressScorer`, joining the
+    /// tokens with spaces.
+    pub fn normalize(&self, address: &str) -> String {
+        tokenize(address)
+            .into_iter()
+            .map(expand)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Calculates the weighted similarity of the house numbers and the
+    /// remaining tokens. The returned value is between 0.0 and 1.0
+    /// (inclusive), where 1.0 means the addresses are the same after
+    /// normalization, apart from the order of the tokens.
+    pub fn similarity(&self, a: &str, b: &str) -> f64 {
+        let a = AddressParts::parse(a);
+        let b = AddressParts::parse(b);
+
+        let a_street = a.tokens.join(" ");
+        let b_street = b.tokens.join(" ");
+        let street = (TokenScorer::default().set_similarity(&a_street, &b_street)
+            + TokenScorer::new(jaro_winkler).match_similarity(&a_street, &b_street))
+            / 2.0;
+
+        match (a.house_number, b.house_number) {
+            (Some(a_number), Some(b_number)) => {
+                let house_number = if same_house_number(&a_number, &b_number) {
+                    1.0
+                } else {
+                    0.0
+                };
+                self.house_number_weight * house_number + (1.0 - self.house_number_weight) * street
+            }
+            _ => street,
+        }
+    }
+}
+
+impl Default for AddressScorer {
+    fn default() -> Self {
+        Self {
+            house_number_weight: 0.3,
+        }
+    }
+}
+
+/// Normalizes an address as described for `AddressScorer`.
+///
+/// ```
+/// use strsim::matchers::normalize_address;
+///
+/// assert_eq!("12 north main street suite 5", normalize_address("12 N. Main St., Ste 5"));
+/// ```
+pub fn normalize_address(address: &str) -> String {
+    AddressScorer::default().normalize(address)
+}
+
+/// Compares street addresses with the default settings of `AddressScorer`.
+/// The returned value is between 0.0 and 1.0 (inclusive).
+///
+/// ```
+/// use strsim::matchers::address_similarity;
+///
+/// assert_eq!(1.0, address_similarity("221B Baker St", "221b baker street"));
+/// ```
+pub fn address_similarity(a: &str, b: &str) -> f64 {
+    AddressScorer::default().similarity(a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_house_number() {
+        let parts = AddressParts::parse("Apt 5, 12 Main St");
+        assert_eq!(Some("12".to_string()), parts.house_number);
+        assert_eq!(vec!["apartment", "5", "main", "street"], parts.tokens);
+
+        let parts = AddressParts::parse("Main Street");
+        assert_eq!(None, parts.house_number);
+    }
+
+    #[test]
+    fn house_numbers_compare_numerically() {
+        assert!(same_house_number("012", "12"));
+        assert!(same_house_number("12a", "12a"));
+        assert!(!same_house_number("12a", "12"));
+        assert!(!same_house_number("12", "21"));
+    }
+
+    #[test]
+    fn similarity_ignores_token_order() {
+        assert_delta!(
+            1.0,
+            address_similarity("12 Main St Apt 4", "12 Apt 4 Main Street")
+        );
+    }
+
+    #[test]
+    fn similarity_different_house_numbers() {
+        assert_delta!(0.7, address_similarity("12 Main St", "14 Main St"));
+        let scorer = AddressScorer::default().house_number_weight(0.5);
+        assert_delta!(0.5, scorer.similarity("12 Main St", "14 Main St"));
+    }
+
+    #[test]
+    fn similarity_missing_house_number() {
+        assert_delta!(1.0, address_similarity("Main St", "12 Main St"));
+    }
+
+    #[test]
+    fn similarity_empty() {
+        assert_delta!(1.0, address_similarity("", ""));
+        assert_delta!(0.0, address_similarity("", "Main St"));
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{tokenize, TokenScorer};
+
+/* Legal forms of companies with their canonical spelling. Dots are removed
+before the lookup, so `S.A.` is found as `sa` */
+const LEGAL_FORMS: [(&str, &str); 33] = [
+    ("ab", "ab"),
+    ("ag", "ag"),
+    ("as", "as"),
+    ("bv", "bv"),
+    ("co", "co"),
+    ("company", "co"),
+    ("corp", "corp"),
+    ("corporation", "corp"),
+    ("gmbh", "gmbh"),
+    ("inc", "inc"),
+    ("incorporated", "inc"),
+    ("kft", "kft"),
+    ("kg", "kg"),
+    ("kk", "kk"),
+    ("limited", "ltd"),
+    ("llc", "llc"),
+    ("llp", "llp"),
+    ("lp", "lp"),
+    ("ltd", "ltd"),
+    ("nv", "nv"),
+    ("oy", "oy"),
+    ("plc", "plc"),
+    ("pte", "pte"),
+    ("pty", "pty"),
+    ("sa", "sa"),
+    ("sarl", "sarl"),
+    ("sas", "sas"),
+    ("se", "se"),
+    ("spa", "spa"),
+    ("srl", "srl"),
+    ("ug", "ug"),
+    ("ulc", "ulc"),
+    ("zrt", "zrt"),
+];
+
+fn legal_form(token: &str) -> Option<&'static str> {
+    LEGAL_FORMS
+        .iter()
+        .find(|(form, _)| *form == token)
+        .map(|(_, canonical)| *canonical)
+}
+
+/// Compares company names after normalizing their legal forms.
+///
+/// The names are lowercased and tokenized, dots are removed so `S.A.` equals
+/// `SA`, a standalone `&` is replaced with `and`, and a leading `the` is
+/// dropped. Legal forms like `Inc`, `GmbH` or `Ltd` at the end of the name are
+/// removed together with an `and` joining them, as in `& Co`, or with
+/// `keep_legal_forms` replaced with their canonical spelling like `ltd` for
+/// `Limited`. A name consisting only of legal forms keeps its
+/// first token. The normalized names are compared with
+/// `token_set_similarity`.
+///
+/// ```
+/// use strsim::matchers::CompanyScorer;
+///
+/// let scorer = CompanyScorer::default();
+/// assert_eq!(1.0, scorer.similarity("Smith & Sons Ltd.", "smith and sons limited"));
+/// assert_eq!(1.0, scorer.similarity("The Acme Corporation", "ACME Inc"));
+///
+/// let strict = CompanyScorer::default().keep_legal_forms(true);
+/// assert!(strict.similarity("The Acme Corporation", "ACME Inc") < 1.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CompanyScorer {
+    keep_legal_forms: bool,
+}
+
+impl CompanyScorer {
+    /// Whether legal forms are kept in their canonical spelling instead of
+    /// being removed, so that `Acme Inc` and `Acme GmbH` differ. Disabled by
+    /// default.
+    pub fn keep_legal_forms(mut self, enabled: bool) -> Self {
+        self.keep_legal_forms = enabled;
+        self
+    }
+
+    /// Normalizes a company name as described for `CompanyScorer`, joining
+    /// the tokens with spaces.
+    pub fn normalize(&self, name: &str) -> String {
+        let name = name.to_lowercase().replace('.', "");
+        let mut tokens: Vec<&str> = name
+            .split_whitespace()
+            .flat_map(|token| {
+                if token == "&" {
+                    vec!["and"]
+                } else {
+                    tokenize(token)
+                }
+            })
+            .collect();
+        if tokens.len() > 1 && tokens[0] == "the" {
+            tokens.remove(0);
+        }
+
+        let mut name_end = tokens.len();
+        while name_end > 1 {
+            let token = tokens[name_end - 1];
+            let joins_legal_forms = token == "and" && name_end < tokens.len();
+            if legal_form(token).is_none() && !joins_legal_forms {
+                break;
+            }
+            name_end -= 1;
+        }
+        if self.keep_legal_forms {
+            for token in &mut tokens[name_end..] {
+                *token = legal_form(token).unwrap_or(token);
+            }
+        } else {
+            tokens.truncate(name_end);
+        }
+        tokens.join(" ")
+    }
+
+    /// Calculates the token set similarity of the normalized names. The
+    /// returned value is between 0.0 and 1.0 (inclusive), where 1.0 means all
+    /// tokens of one normalized name are part of the other.
+    pub fn similarity(&self, a: &str, b: &str) -> f64 {
+        TokenScorer::default().set_similarity(&self.normalize(a), &self.normalize(b))
+    }
+}
+
+/// Normalizes a company name with the default settings of `CompanyScorer`.
+///
+/// ```
+/// use strsim::matchers::normalize_company;
+///
+/// assert_eq!("müller", normalize_company("Müller & Co. GmbH"));
+/// ```
+pub fn normalize_company(name: &str) -> String {
+    CompanyScorer::default().normalize(name)
+}
+
+/// Compares company names with the default settings of `CompanyScorer`. The
+/// returned value is between 0.0 and 1.0 (inclusive).
+///
+/// ```
+/// use strsim::matchers::company_similarity;
+///
+/// assert_eq!(1.0, company_similarity("Nestlé S.A.", "Nestlé"));
+/// ```
+pub fn company_similarity(a: &str, b: &str) -> f64 {
+    CompanyScorer::default().similarity(a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_legal_forms() {
+        assert_eq!("acme", normalize_company("Acme, Inc."));
+        assert_eq!("acme", normalize_company("ACME Pty. Ltd."));
+        assert_eq!("acme holdings", normalize_company("Acme Holdings Corp"));
+    }
+
+    #[test]
+    fn normalize_keeps_legal_forms_inside_name() {
+        assert_eq!("co-op food", normalize_company("Co-op Food"));
+        assert_eq!("inc marketing", normalize_company("Inc. Marketing"));
+        assert_eq!("limited", normalize_company("Limited"));
+        assert_eq!("the", normalize_company("The"));
+    }
+
+    #[test]
+    fn normalize_canonical_legal_forms() {
+        let scorer = CompanyScorer::default().keep_legal_forms(true);
+        assert_eq!("acme co ltd", scorer.normalize("Acme Company Limited"));
+        assert_eq!("at&t inc", scorer.normalize("AT&T Incorporated"));
+        assert_eq!("müller and co kg", scorer.normalize("Müller & Co. KG"));
+    }
+
+    #[test]
+    fn similarity_ampersand() {
+        assert_delta!(
+            1.0,
+            company_similarity("Johnson & Johnson", "johnson and johnson")
+        );
+        assert_delta!(
+            1.0,
+            company_similarity("Procter & Gamble Co.", "Procter and Gamble")
+        );
+    }
+
+    #[test]
+    fn similarity_different_names() {
+        assert!(company_similarity("Acme Inc", "Apex Inc") < 1.0);
+        assert_delta!(0.0, company_similarity("Acme", ""));
+    }
+}
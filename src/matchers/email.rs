@@ -0,0 +1,252 @@
+use crate::{damerau_levenshtein, jaro_winkler, normalized_levenshtein};
+
+/* Domains of large email providers, used to correct typos like `gmial.com` */
+const COMMON_DOMAINS: [&str; 15] = [
+    "aol.com",
+    "gmail.com",
+    "gmx.de",
+    "gmx.net",
+    "googlemail.com",
+    "hotmail.com",
+    "icloud.com",
+    "live.com",
+    "mail.com",
+    "msn.com",
+    "outlook.com",
+    "protonmail.com",
+    "web.de",
+    "yahoo.com",
+    "yandex.ru",
+];
+
+/* Domains of real email providers which are close to a common domain, like
+`ymail.com` to `gmail.com`, and must not be corrected */
+const KNOWN_DOMAINS: [&str; 8] = [
+    "bol.com",
+    "email.com",
+    "gmx.at",
+    "gmx.ch",
+    "gmx.com",
+    "iol.com",
+    "mail.ru",
+    "ymail.com",
+];
+
+/* Replaces a domain one edit away from a common domain with that domain,
+unless it is a known domain itself */
+fn correct_domain(domain: &str) -> &str {
+    if COMMON_DOMAINS.contains(&domain) || KNOWN_DOMAINS.contains(&domain) {
+        return domain;
+    }
+    COMMON_DOMAINS
+        .iter()
+        .find(|common| damerau_levenshtein(domain, common) == 1)
+        .copied()
+        .unwrap_or(domain)
+}
+
+/// Compares email addresses by their local part and their domain, which are
+/// weighted separately.
+///
+/// Before the comparison the addresses are lowercased, tags added with plus
+/// addressing (`jane+news@example.com`) are removed, and typos in the domains
+/// of large email providers (`gmial.com`) are corrected. Domains of other
+/// real providers, like `ymail.com`, are never corrected. Addresses without
+/// an `@` are compared as a whole.
+///
+/// ```
+/// use strsim::matchers::EmailScorer;
+///
+/// let scorer = EmailScorer::default();
+/// assert_eq!(1.0, scorer.similarity("Jane.Doe+news@gmial.com", "jane.doe@gmail.com"));
+/// assert!(scorer.similarity("jane.doe@gmail.com", "jane.doe@yahoo.com") < 0.9);
+///
+/// let local_only = EmailScorer::default().weights(1.0, 0.0);
+/// assert_eq!(1.0, local_only.similarity("jane.doe@gmail.com", "jane.doe@yahoo.com"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EmailScorer {
+    local_weight: f64,
+    domain_weight: f64,
+    plus_addressing: bool,
+    domain_typos: bool,
+}
+
+impl EmailScorer {
+    /// Sets the weights of the local part and of the domain. The local part
+    /// is compared with `jaro_winkler`, the domain with
+    /// `normalized_levenshtein`. The defaults are 0.7 and 0.3.
+    pub fn weights(mut self, local_weight: f64, domain_weight: f64) -> Self {
+        self.local_weight = local_weight;
+        self.domain_weight = domain_weight;
+        self
+    }
+
+    /// Whether tags added with plus addressing are removed. Enabled by
+    /// default.
+    pub fn plus_addressing(mut self, enabled: bool) -> Self {
+        self.plus_addressing = enabled;
+        self
+    }
+
+    /// Whether typos in the domains of large email providers are corrected.
+    /// Enabled by default.
+    pub fn domain_typos(mut self, enabled: bool) -> Self {
+        self.domain_typos = enabled;
+        self
+    }
+
+    /// Normalizes an email address as described for `EmailScorer`.
+    pub fn normalize(&self, email: &str) -> String {
+        let email = email.trim().to_lowercase();
+        let (local, domain) = match email.rfind('@') {
+            Some(at) => (&email[..at], &email[at + 1..]),
+            None => return email,
+        };
+
+        let local = match local.find('+') {
+            Some(plus) if self.plus_addressing => &local[..plus],
+            _ => local,
+        };
+        let domain = if self.domain_typos {
+            correct_domain(domain)
+        } else {
+            domain
+        };
+        format!("{}@{}", local, domain)
+    }
+
+    /// Calculates the weighted similarity of the local parts and the domains
+    /// of the normalized addresses. The returned value is between 0.0 and 1.0
+    /// (inclusive), where 1.0 means the addresses are the same after
+    /// normalization.
+    pub fn similarity(&self, a: &str, b: &str) -> f64 {
+        let a = self.normalize(a);
+        let b = self.normalize(b);
+        if a == b {
+            return 1.0;
+        }
+
+        match (a.rsplit_once('@'), b.rsplit_once('@')) {
+            (Some((a_local, a_domain)), Some((b_local, b_domain))) => {
+                let weight_sum = self.local_weight + self.domain_weight;
+                if weight_sum <= 0.0 {
+                    return 0.0;
+                }
+                (self.local_weight * jaro_winkler(a_local, b_local)
+                    + self.domain_weight * normalized_levenshtein(a_domain, b_domain))
+                    / weight_sum
+            }
+            _ => jaro_winkler(&a, &b),
+        }
+    }
+}
+
+impl Default for EmailScorer {
+    fn default() -> Self {
+        Self {
+            local_weight: 0.7,
+            domain_weight: 0.3,
+            plus_addressing: true,
+            domain_typos: true,
+        }
+    }
+}
+
+/// Normalizes an email address with the default settings of `EmailScorer`.
+///
+/// ```
+/// use strsim::matchers::normalize_email;
+///
+/// assert_eq!("jane@gmail.com", normalize_email(" Jane+Shop@GMAIL.con "));
+/// ```
+pub fn normalize_email(email: &str) -> String {
+    EmailScorer::default().normalize(email)
+}
+
+/// Compares email addresses with the default settings of `EmailScorer`. The
+/// returned value is between 0.0 and 1.0 (inclusive).
+///
+/// ```
+/// use strsim::matchers::email_similarity;
+///
+/// assert_eq!(1.0, email_similarity("john+spam@hotmial.com", "JOHN@hotmail.com"));
+/// ```
+pub fn email_similarity(a: &str, b: &str) -> f64 {
+    EmailScorer::default().similarity(a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_without_at() {
+        assert_eq!("jane+doe", normalize_email(" Jane+Doe "));
+    }
+
+    #[test]
+    fn normalize_uses_last_at() {
+        assert_eq!("\"a@b\"@gmail.com", normalize_email("\"a@b\"@gmail.com"));
+    }
+
+    #[test]
+    fn normalize_keeps_unknown_domains() {
+        assert_eq!("jane@exmaple.com", normalize_email("jane@exmaple.com"));
+        assert_eq!("jane@gmail.com", normalize_email("jane@gmai.com"));
+    }
+
+    #[test]
+    fn normalize_keeps_known_domains() {
+        for domain in KNOWN_DOMAINS.iter() {
+            let email = format!("jane@{}", domain);
+            assert_eq!(email, normalize_email(&email));
+        }
+        assert!(email_similarity("jane@ymail.com", "jane@gmail.com") < 1.0);
+        assert!(email_similarity("jane@email.com", "jane@mail.com") < 1.0);
+        assert!(email_similarity("jane@bol.com", "jane@aol.com") < 1.0);
+    }
+
+    #[test]
+    fn normalize_options() {
+        let scorer = EmailScorer::default()
+            .plus_addressing(false)
+            .domain_typos(false);
+        assert_eq!(
+            "jane+news@gmial.com",
+            scorer.normalize("jane+news@gmial.com")
+        );
+    }
+
+    #[test]
+    fn similarity_weights_parts() {
+        let local = jaro_winkler("jane", "jean");
+        let domain = normalized_levenshtein("example.com", "example.org");
+        assert_delta!(
+            0.7 * local + 0.3 * domain,
+            email_similarity("jane@example.com", "jean@example.org")
+        );
+        let scorer = EmailScorer::default().weights(1.0, 1.0);
+        assert_delta!(
+            (local + domain) / 2.0,
+            scorer.similarity("jane@example.com", "jean@example.org")
+        );
+    }
+
+    #[test]
+    fn similarity_without_weights() {
+        let scorer = EmailScorer::default().weights(0.0, 0.0);
+        assert_delta!(
+            0.0,
+            scorer.similarity("jane@example.com", "jean@example.org")
+        );
+    }
+
+    #[test]
+    fn similarity_invalid_addresses() {
+        assert_delta!(
+            jaro_winkler("jane", "jane@example.com"),
+            email_similarity("jane", "jane@example.com")
+        );
+    }
+}
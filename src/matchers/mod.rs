@@ -0,0 +1,20 @@
+//! Ready-made scorers for common kinds of values, which parse and normalize
+//! the values before comparing their parts with the metrics of this crate.
+
+mod address;
+mod company;
+mod email;
+mod path;
+mod phone;
+mod title;
+mod url;
+mod version;
+
+pub use address::{address_similarity, normalize_address, AddressScorer};
+pub use company::{company_similarity, normalize_company, CompanyScorer};
+pub use email::{email_similarity, normalize_email, EmailScorer};
+pub use path::{normalize_path, path_similarity, PathScorer};
+pub use phone::{normalize_phone, phone_similarity, PhoneScorer};
+pub use title::{is_duplicate_title, normalize_title, TitleScorer};
+pub use url::{normalize_url, url_similarity, UrlScorer};
+pub use version::{normalize_version, version_similarity, VersionScorer};
@@ -0,0 +1,220 @@
+use std::cmp::max;
+use std::mem;
+
+use crate::normalized_levenshtein;
+
+/* The components of a path without empty and `.` components, like the ones
+between repeated or trailing separators */
+fn components(path: &str, case_sensitive: bool) -> Vec<String> {
+    path.split(|c| c == '/' || c == '\\')
+        .filter(|component| !component.is_empty() && *component != ".")
+        .map(|component| {
+            if case_sensitive {
+                component.to_string()
+            } else {
+                component.to_lowercase()
+            }
+        })
+        .collect()
+}
+
+/* The file name and the directories of the components of a path */
+fn split_file_name(components: &[String]) -> (&str, &[String]) {
+    match components.split_last() {
+        Some((file_name, directories)) => (file_name, directories),
+        None => ("", &[]),
+    }
+}
+
+/* The edit distance of two sequences of components, where substituting a
+component costs its dissimilarity, normalized by the longer sequence */
+fn components_similarity(a: &[String], b: &[String]) -> f64 {
+    let len = max(a.len(), b.len());
+    if len == 0 {
+        return 1.0;
+    }
+
+    let mut prev: Vec<f64> = (0..=b.len()).map(|j| j as f64).collect();
+    let mut curr = vec![0.0; b.len() + 1];
+    for (i, a_component) in a.iter().enumerate() {
+        curr[0] = (i + 1) as f64;
+        for (j, b_component) in b.iter().enumerate() {
+            let substitution = prev[j] + 1.0 - normalized_levenshtein(a_component, b_component);
+            curr[j + 1] = substitution.min(prev[j + 1] + 1.0).min(curr[j] + 1.0);
+        }
+        mem::swap(&mut prev, &mut curr);
+    }
+    1.0 - prev[b.len()] / len as f64
+}
+
+/// Compares file paths by their file name and their directories, which are
+/// weighted separately.
+///
+/// The paths are split into components at `/` and `\`, ignoring empty and
+/// `.` components, and compared case insensitively by default. The last
+/// components, the file names, are compared with `normalized_levenshtein`.
+/// The directories are compared with a component-wise edit distance, where
+/// inserting or deleting a directory costs 1 and replacing one costs 1 minus
+/// the `normalized_levenshtein` similarity of the directory names. A
+/// differing file name weighs more than a differing directory, which fits
+/// fuzzy file finders better than the character distance of the full paths.
+///
+/// ```
+/// use strsim::matchers::PathScorer;
+/// use strsim::normalized_levenshtein;
+///
+/// let scorer = PathScorer::default();
+/// assert_eq!(1.0, scorer.similarity("src\\Lib.rs", "./src//lib.rs"));
+///
+/// // the same file in another directory is closer than another file
+/// let query = "src/matchers/path.rs";
+/// let moved = "crates/strsim/src/matchers/path.rs";
+/// let other = "src/matchers/url.rs";
+/// assert!(scorer.similarity(query, moved) > scorer.similarity(query, other));
+/// assert!(normalized_levenshtein(query, moved) < normalized_levenshtein(query, other));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PathScorer {
+    file_name_weight: f64,
+    directories_weight: f64,
+    case_sensitive: bool,
+}
+
+impl PathScorer {
+    /// Sets the weights of the file name and the directories. The defaults
+    /// are 0.7 and 0.3.
+    pub fn weights(mut self, file_name: f64, directories: f64) -> Self {
+        self.file_name_weight = file_name;
+        self.directories_weight = directories;
+        self
+    }
+
+    /// Sets whether the components are compared case sensitively, which is
+    /// false by default.
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Normalizes a path as described for `PathScorer`, joining the
+    /// components with `/`.
+    pub fn normalize(&self, path: &str) -> String {
+        components(path, self.case_sensitive).join("/")
+    }
+
+    /// Calculates the weighted similarity of the file names and directories
+    /// of the paths. The returned value is between 0.0 and 1.0 (inclusive),
+    /// where 1.0 means the paths have the same components.
+    pub fn similarity(&self, a: &str, b: &str) -> f64 {
+        let a = components(a, self.case_sensitive);
+        let b = components(b, self.case_sensitive);
+        if a == b {
+            return 1.0;
+        }
+
+        let weight_sum = self.file_name_weight + self.directories_weight;
+        if weight_sum <= 0.0 {
+            return 0.0;
+        }
+        let (a_name, a_directories) = split_file_name(&a);
+        let (b_name, b_directories) = split_file_name(&b);
+        (self.file_name_weight * normalized_levenshtein(a_name, b_name)
+            + self.directories_weight * components_similarity(a_directories, b_directories))
+            / weight_sum
+    }
+}
+
+impl Default for PathScorer {
+    fn default() -> Self {
+        Self {
+            file_name_weight: 0.7,
+            directories_weight: 0.3,
+            case_sensitive: false,
+        }
+    }
+}
+
+/// Normalizes a path as described for `PathScorer`.
+///
+/// ```
+/// use strsim::matchers::normalize_path;
+///
+/// assert_eq!("src/matchers/mod.rs", normalize_path(".\\SRC\\matchers//mod.rs"));
+/// ```
+pub fn normalize_path(path: &str) -> String {
+    PathScorer::default().normalize(path)
+}
+
+/// Compares paths with the default weights of `PathScorer`. The returned
+/// value is between 0.0 and 1.0 (inclusive).
+///
+/// ```
+/// use strsim::matchers::path_similarity;
+///
+/// assert_eq!(1.0, path_similarity("docs/README.md", "docs/readme.md"));
+/// ```
+pub fn path_similarity(a: &str, b: &str) -> f64 {
+    PathScorer::default().similarity(a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn owned(components: &[&str]) -> Vec<String> {
+        components.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn split_components() {
+        assert_eq!(
+            owned(&["usr", "Local", "bin"]),
+            components("/usr/./Local//bin/", true)
+        );
+        assert_eq!(owned(&["c:", "users"]), components("C:\\Users", false));
+        assert!(components("", false).is_empty());
+        assert!(components("/./", false).is_empty());
+    }
+
+    #[test]
+    fn components_edit_distance() {
+        let a = owned(&["src", "matchers"]);
+        assert_delta!(1.0, components_similarity(&[], &[]));
+        assert_delta!(0.0, components_similarity(&a, &[]));
+        assert_delta!(0.5, components_similarity(&a, &owned(&["matchers"])));
+        let partial = normalized_levenshtein("matchers", "matcher");
+        assert_delta!(
+            1.0 - (1.0 - partial) / 2.0,
+            components_similarity(&a, &owned(&["src", "matcher"]))
+        );
+    }
+
+    #[test]
+    fn similarity_weights_file_name() {
+        let a = "a/b/report.txt";
+        let b = "a/c/report.csv";
+        let name = normalized_levenshtein("report.txt", "report.csv");
+        let directories = 1.0 - (1.0 - normalized_levenshtein("b", "c")) / 2.0;
+        assert_delta!(0.7 * name + 0.3 * directories, path_similarity(a, b));
+
+        let name_only = PathScorer::default().weights(1.0, 0.0);
+        assert_delta!(name, name_only.similarity(a, b));
+    }
+
+    #[test]
+    fn similarity_case_sensitive() {
+        let scorer = PathScorer::default().case_sensitive(true);
+        assert!(scorer.similarity("Makefile", "makefile") < 1.0);
+        assert_eq!("A/b", scorer.normalize("A\\b"));
+        assert_delta!(1.0, path_similarity("Makefile", "makefile"));
+    }
+
+    #[test]
+    fn similarity_empty_paths() {
+        assert_delta!(1.0, path_similarity("", "/"));
+        // only the file names differ
+        assert_delta!(0.3, path_similarity("", "a"));
+        let scorer = PathScorer::default().weights(0.0, 0.0);
+        assert_delta!(0.0, scorer.similarity("a", "b"));
+    }
+}
@@ -0,0 +1,175 @@
+use crate::normalized_damerau_levenshtein;
+
+/// Compares phone numbers digit by digit after removing their formatting.
+///
+/// Numbers starting with `+` or the international prefix `00` carry their own
+/// country code, and a trunk prefix written as `(0)` is removed from them.
+/// Other numbers are national numbers: a leading trunk prefix `0` is removed
+/// and the default country code is added if one is set. Everything after a
+/// letter, like the extensions `x12` and `ext. 12`, is ignored.
+///
+/// Without a default country code an international number can still match a
+/// national number, since up to three leading digits of the international
+/// number are skipped to line up their lengths. The digits are compared with
+/// `normalized_damerau_levenshtein`, so swapped digits count as one edit.
+///
+/// ```
+/// use strsim::matchers::PhoneScorer;
+///
+/// let scorer = PhoneScorer::default().country_code(49);
+/// assert_eq!("+49301234567", scorer.normalize("030 / 123 45 67"));
+/// assert_eq!(1.0, scorer.similarity("030 1234567", "+49 (30) 123-4567"));
+/// assert!(scorer.similarity("030 1234567", "0049 30 1234657") > 0.9);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PhoneScorer {
+    country_code: Option<u16>,
+}
+
+impl PhoneScorer {
+    /// Sets the country code of national numbers, like `1` for the United
+    /// States or `44` for the United Kingdom.
+    pub fn country_code(mut self, code: u16) -> Self {
+        self.country_code = Some(code);
+        self
+    }
+
+    /// Normalizes a phone number as described for `PhoneScorer`. International
+    /// numbers start with `+` followed by digits, national numbers without a
+    /// default country code only consist of digits.
+    pub fn normalize(&self, number: &str) -> String {
+        let number = number.trim();
+        let number = match number.find(|c: char| c.is_alphabetic()) {
+            Some(extension) => &number[..extension],
+            None => number,
+        };
+        let digits = ascii_digits(number);
+
+        if number.starts_with('+') {
+            format!("+{}", ascii_digits(&number.replace("(0)", "")))
+        } else if let Some(digits) = digits.strip_prefix("00") {
+            let without_trunk = ascii_digits(&number.replace("(0)", ""));
+            format!("+{}", without_trunk.strip_prefix("00").unwrap_or(digits))
+        } else {
+            let national = digits.strip_prefix('0').unwrap_or(&digits);
+            match self.country_code {
+                Some(code) => format!("+{}{}", code, national),
+                None => national.to_string(),
+            }
+        }
+    }
+
+    /// Calculates the similarity of the digits of the normalized numbers. The
+    /// returned value is between 0.0 and 1.0 (inclusive), where 1.0 means the
+    /// numbers are the same after normalization.
+    pub fn similarity(&self, a: &str, b: &str) -> f64 {
+        let a = self.normalize(a);
+        let b = self.normalize(b);
+
+        let (a, b) = match (a.strip_prefix('+'), b.strip_prefix('+')) {
+            (Some(a), None) => (skip_country_code(a, &b), b.as_str()),
+            (None, Some(b)) => (a.as_str(), skip_country_code(b, &a)),
+            (Some(a), Some(b)) => (a, b),
+            (None, None) => (a.as_str(), b.as_str()),
+        };
+        normalized_damerau_levenshtein(a, b)
+    }
+}
+
+fn ascii_digits(s: &str) -> String {
+    s.chars().filter(char::is_ascii_digit).collect()
+}
+
+/* Removes up to three leading digits of an international number, so it is as
+long as the national number it is compared with */
+fn skip_country_code<'a>(international: &'a str, national: &str) -> &'a str {
+    let extra = international.len().saturating_sub(national.len());
+    if (1..=3).contains(&extra) {
+        &international[extra..]
+    } else {
+        international
+    }
+}
+
+/// Normalizes a phone number with the default settings of `PhoneScorer`.
+///
+/// ```
+/// use strsim::matchers::normalize_phone;
+///
+/// assert_eq!("+442079460958", normalize_phone("+44 (0)20 7946 0958"));
+/// ```
+pub fn normalize_phone(number: &str) -> String {
+    PhoneScorer::default().normalize(number)
+}
+
+/// Compares phone numbers with the default settings of `PhoneScorer`. The
+/// returned value is between 0.0 and 1.0 (inclusive).
+///
+/// ```
+/// use strsim::matchers::phone_similarity;
+///
+/// assert_eq!(1.0, phone_similarity("(555) 010-4477 ext. 12", "555.010.4477"));
+/// assert_eq!(1.0, phone_similarity("+1 555 010 4477", "555-010-4477"));
+/// ```
+pub fn phone_similarity(a: &str, b: &str) -> f64 {
+    PhoneScorer::default().similarity(a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_formats() {
+        assert_eq!("5550104477", normalize_phone("(555) 010-4477"));
+        assert_eq!("+15550104477", normalize_phone(" +1-555-010-4477 "));
+        assert_eq!("+15550104477", normalize_phone("001 555 010 4477"));
+        assert_eq!("2079460958", normalize_phone("020 7946 0958"));
+        assert_eq!("", normalize_phone("n/a"));
+    }
+
+    #[test]
+    fn normalize_trunk_prefix() {
+        assert_eq!("+442079460958", normalize_phone("+44 (0)20 7946 0958"));
+        assert_eq!("+442079460958", normalize_phone("0044 (0)20 7946 0958"));
+        // only international numbers drop a trunk prefix in parentheses
+        assert_eq!("5550104477", normalize_phone("555 (0)104477"));
+        assert_eq!("2079460958", normalize_phone("(0)20 7946 0958"));
+        assert!(phone_similarity("555 (0)104477", "555 104477") < 1.0);
+        assert_eq!("+", normalize_phone("(0)0"));
+    }
+
+    #[test]
+    fn normalize_extensions() {
+        assert_eq!("5550104477", normalize_phone("555 010 4477 x123"));
+        assert_eq!("5550104477", normalize_phone("555 010 4477 Ext. 123"));
+    }
+
+    #[test]
+    fn normalize_country_code() {
+        let scorer = PhoneScorer::default().country_code(44);
+        assert_eq!("+442079460958", scorer.normalize("020 7946 0958"));
+        assert_eq!("+15550104477", scorer.normalize("+1 555 010 4477"));
+    }
+
+    #[test]
+    fn similarity_transpositions() {
+        assert_delta!(0.9, phone_similarity("5550104477", "5550104747"));
+        assert_delta!(0.8, phone_similarity("5550104477", "5550104400"));
+    }
+
+    #[test]
+    fn similarity_country_codes() {
+        assert_delta!(1.0, phone_similarity("+44 20 7946 0958", "020 7946 0958"));
+        assert!(phone_similarity("+44 20 7946 0958", "+1 20 7946 0958") < 1.0);
+
+        let scorer = PhoneScorer::default().country_code(1);
+        assert!(scorer.similarity("+44 20 7946 0958", "020 7946 0958") < 1.0);
+    }
+
+    #[test]
+    fn similarity_empty() {
+        assert_delta!(1.0, phone_similarity("", "-"));
+        assert_delta!(0.0, phone_similarity("", "555"));
+    }
+}
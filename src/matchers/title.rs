@@ -0,0 +1,225 @@
+use crate::{normalized_levenshtein, TokenScorer};
+
+/* Words which rarely distinguish titles */
+const TITLE_STOPWORDS: &[&str] = &[
+    "a", "an", "and", "at", "for", "in", "of", "on", "or", "the", "to", "with",
+];
+
+/* Roman numerals from 2 to 10, which number sequels and volumes. `i` is left
+out, since it is more often a pronoun */
+const ROMAN_NUMERALS: &[&str] = &["ii", "iii", "iv", "v", "vi", "vii", "viii", "ix", "x"];
+
+/* Lowercases `title`, removes apostrophes so `don't` becomes `dont`, and
+replaces all other punctuation with spaces */
+fn fold(title: &str) -> String {
+    title
+        .chars()
+        .filter(|&c| c != '\'' && c != '’')
+        .map(|c| {
+            if c.is_alphanumeric() || c.is_whitespace() {
+                c
+            } else {
+                ' '
+            }
+        })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/* Whether a word of a folded title counts as a number */
+fn is_number(word: &str) -> bool {
+    word.chars().all(|c| c.is_ascii_digit()) || ROMAN_NUMERALS.contains(&word)
+}
+
+/* Whether a number is a year, which catalogs often add to titles */
+fn is_year(number: &str) -> bool {
+    number.len() == 4 && number.bytes().all(|b| b.is_ascii_digit())
+}
+
+/* The sorted numbers of a folded title which are years or not */
+fn numbers(title: &str, years: bool) -> Vec<&str> {
+    let mut numbers: Vec<&str> = title
+        .split_whitespace()
+        .filter(|word| is_number(word) && is_year(word) == years)
+        .collect();
+    numbers.sort_unstable();
+    numbers
+}
+
+/* Whether the folded titles can belong to the same work: they contain the
+same numbers, and the same year if both contain one */
+fn same_numbers(a: &str, b: &str) -> bool {
+    let (a_years, b_years) = (numbers(a, true), numbers(b, true));
+    numbers(a, false) == numbers(b, false)
+        && (a_years.is_empty() || b_years.is_empty() || a_years == b_years)
+}
+
+/// Detects duplicate titles of books, movies, songs or products, which
+/// differ in case, punctuation, filler words and the order of their words.
+///
+/// Both titles are lowercased, apostrophes are removed and other punctuation
+/// is replaced with spaces. Common words like `the` and `of` are ignored,
+/// and the remaining words are compared with the token set similarity of
+/// `TokenScorer` based on `normalized_levenshtein`, which is 1.0 if one title
+/// only adds words to the other. Titles with a similarity of at least the
+/// threshold of 0.9 are duplicates, as long as they contain the same numbers
+/// and roman numerals from `ii` to `x`, which tell apart the parts of a
+/// series. Four digit numbers are taken as years and only need to be equal
+/// if both titles contain one, so a title matches itself with the year of
+/// its release added, but not a remake from another year.
+///
+/// ```
+/// use strsim::matchers::TitleScorer;
+///
+/// let scorer = TitleScorer::default();
+/// assert!(scorer.is_duplicate(
+///     "The Lord of the Rings: The Fellowship of the Ring",
+///     "lord of the rings - fellowship of the ring",
+/// ));
+/// assert!(scorer.is_duplicate("Ocean's Eleven", "Oceans Eleven (2001)"));
+/// assert!(!scorer.is_duplicate("King Kong (1933)", "King Kong (2005)"));
+/// assert!(!scorer.is_duplicate("Rocky", "Rocky II"));
+/// assert!(!scorer.is_duplicate("Star Wars", "Star Trek"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TitleScorer<'s> {
+    stopwords: &'s [&'s str],
+    threshold: f64,
+}
+
+impl<'s> TitleScorer<'s> {
+    /// Sets the ignored words, which are compared with the lowercased words
+    /// of the titles. By default common English words like `the` are
+    /// ignored.
+    pub fn stopwords(mut self, stopwords: &'s [&'s str]) -> Self {
+        self.stopwords = stopwords;
+        self
+    }
+
+    /// Sets the similarity from which on titles are duplicates. The default is
+    /// 0.9.
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Normalizes a title as described for `TitleScorer`, joining the
+    /// remaining words with spaces. A title consisting only of stopwords
+    /// keeps them.
+    pub fn normalize(&self, title: &str) -> String {
+        let folded = fold(title);
+        let words: Vec<&str> = folded.split_whitespace().collect();
+        if words.iter().all(|word| self.stopwords.contains(word)) {
+            return words.join(" ");
+        }
+        words
+            .into_iter()
+            .filter(|word| !self.stopwords.contains(word))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Calculates the similarity of the words of the titles, ignoring their
+    /// numbers. The returned value is between 0.0 and 1.0 (inclusive), where
+    /// 1.0 means the titles have the same words after normalization, or one
+    /// title only adds words.
+    pub fn similarity(&self, a: &str, b: &str) -> f64 {
+        self.scorer().set_similarity(&fold(a), &fold(b))
+    }
+
+    /// Whether the titles have a similarity of at least the threshold and
+    /// the same numbers.
+    pub fn is_duplicate(&self, a: &str, b: &str) -> bool {
+        let a = fold(a);
+        let b = fold(b);
+        same_numbers(&a, &b) && self.scorer().set_similarity(&a, &b) >= self.threshold
+    }
+
+    fn scorer(&self) -> TokenScorer<'s> {
+        TokenScorer::new(normalized_levenshtein).stopwords(self.stopwords)
+    }
+}
+
+impl Default for TitleScorer<'_> {
+    fn default() -> Self {
+        Self {
+            stopwords: TITLE_STOPWORDS,
+            threshold: 0.9,
+        }
+    }
+}
+
+/// Normalizes a title as described for `TitleScorer`.
+///
+/// ```
+/// use strsim::matchers::normalize_title;
+///
+/// assert_eq!("spider man far from home", normalize_title("Spider-Man: Far From Home"));
+/// ```
+pub fn normalize_title(title: &str) -> String {
+    TitleScorer::default().normalize(title)
+}
+
+/// Whether the titles are duplicates according to the default settings of
+/// `TitleScorer`.
+///
+/// ```
+/// use strsim::matchers::is_duplicate_title;
+///
+/// assert!(is_duplicate_title("Harry Potter and the Goblet of Fire", "HARRY POTTER: GOBLET OF FIRE"));
+/// assert!(!is_duplicate_title("Toy Story 2", "Toy Story 3"));
+/// ```
+pub fn is_duplicate_title(a: &str, b: &str) -> bool {
+    TitleScorer::default().is_duplicate(a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fold_punctuation() {
+        assert_eq!("dont stop me now ", fold("Don't Stop Me Now!"));
+        assert_eq!("ocean s ", fold("Ocean`s?"));
+        assert_eq!("wall e", fold("WALL·E"));
+        assert_eq!("amélie", fold("Amélie"));
+    }
+
+    #[test]
+    fn numbers_of_titles() {
+        assert!(same_numbers("ii the 2 civil", "2 the ii"));
+        assert!(!same_numbers("ii", "iii"));
+        assert!(same_numbers("i robot", "robot"));
+        assert!(same_numbers("alien 1979", "alien"));
+        assert!(!same_numbers("alien 1979", "alien 1980"));
+        assert!(!same_numbers("alien 1979", "alien 1"));
+    }
+
+    #[test]
+    fn normalize_stopwords() {
+        assert_eq!("lord rings", normalize_title("The Lord of the Rings"));
+        assert_eq!("the the", normalize_title("The The"));
+        let scorer = TitleScorer::default().stopwords(&["lord"]);
+        assert_eq!(
+            "the of the rings",
+            scorer.normalize("The Lord of the Rings")
+        );
+    }
+
+    #[test]
+    fn similarity_ignores_numbers() {
+        let scorer = TitleScorer::default();
+        // the token set similarity ignores the additional words
+        assert_delta!(1.0, scorer.similarity("Alien", "Alien: Director's Cut"));
+        assert_delta!(1.0, scorer.similarity("Rocky", "Rocky II"));
+        assert!(scorer.is_duplicate("Alien", "Alien: Director's Cut"));
+        assert!(!scorer.is_duplicate("Part 1", "Part 2"));
+    }
+
+    #[test]
+    fn threshold() {
+        let scorer = TitleScorer::default().threshold(0.5);
+        assert!(scorer.is_duplicate("Star Wars", "Star Trek"));
+        assert!(!TitleScorer::default().is_duplicate("Star Wars", "Star Trek"));
+    }
+}
@@ -0,0 +1,242 @@
+use std::cmp::max;
+
+use crate::normalized_levenshtein;
+
+/* A version split into its dot separated release segments and its
+pre-release. The build metadata after `+` is dropped, since it doesn't
+order versions */
+#[derive(Debug, PartialEq, Eq)]
+struct Version<'a> {
+    segments: Vec<&'a str>,
+    pre_release: &'a str,
+}
+
+impl<'a> Version<'a> {
+    fn parse(version: &'a str) -> Self {
+        let version = version.trim();
+        let version = version
+            .strip_prefix(|c| c == 'v' || c == 'V')
+            .filter(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+            .unwrap_or(version);
+        let version = match version.find('+') {
+            Some(build) => &version[..build],
+            None => version,
+        };
+        let (release, pre_release) = match version.find('-') {
+            Some(start) => (&version[..start], &version[start + 1..]),
+            None => (version, ""),
+        };
+
+        let mut segments: Vec<&str> = release.split('.').map(trim_zeros).collect();
+        /* missing segments count as 0, so 1.2 equals 1.2.0 */
+        while segments.len() > 1 && segments.last() == Some(&"0") {
+            segments.pop();
+        }
+        Self {
+            segments,
+            pre_release,
+        }
+    }
+
+    fn segment(&self, i: usize) -> &'a str {
+        self.segments.get(i).copied().unwrap_or("0")
+    }
+}
+
+/* Removes the leading zeros of a number */
+fn trim_zeros(segment: &str) -> &str {
+    if segment.len() > 1 && segment.bytes().all(|b| b.is_ascii_digit()) {
+        let trimmed = segment.trim_start_matches('0');
+        if trimmed.is_empty() {
+            "0"
+        } else {
+            trimmed
+        }
+    } else {
+        segment
+    }
+}
+
+/* The closeness of two differing segments, by their numeric difference for
+numbers and their `normalized_levenshtein` similarity otherwise */
+fn segment_similarity(a: &str, b: &str) -> f64 {
+    match (a.parse::<u64>(), b.parse::<u64>()) {
+        (Ok(a), Ok(b)) => {
+            let difference = if a > b { a - b } else { b - a };
+            1.0 / (1.0 + difference as f64)
+        }
+        _ => normalized_levenshtein(a, b),
+    }
+}
+
+/// Compares dotted version strings like `1.0.210` segment by segment, with
+/// more weight on the leading segments.
+///
+/// A leading `v` and the build metadata after `+` are ignored, and missing
+/// segments count as 0, so `v1.2` equals `1.2.0+build.5`. The `i`-th segment
+/// weighs `decay` to the power of `i`, and the pre-release after `-` weighs
+/// like a segment after the last one. Segments before the first difference
+/// match, numeric segments at the first difference score by their distance,
+/// `1 / (1 + |a - b|)`, and all later segments and the pre-release score 0,
+/// since their versions are on different release lines. A difference in the
+/// major version thus costs the most.
+///
+/// ```
+/// use strsim::matchers::VersionScorer;
+///
+/// let scorer = VersionScorer::default();
+/// assert_eq!(1.0, scorer.similarity("v1.2", "1.2.0"));
+/// let query = "1.0.210";
+/// assert!(scorer.similarity(query, "1.0.209") > scorer.similarity(query, "1.1.210"));
+/// assert!(scorer.similarity(query, "1.1.210") > scorer.similarity(query, "2.0.210"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VersionScorer {
+    decay: f64,
+}
+
+impl VersionScorer {
+    /// Sets the factor between the weights of consecutive segments, which is
+    /// 0.5 by default. Values below 1.0 weigh the leading segments more.
+    pub fn decay(mut self, decay: f64) -> Self {
+        self.decay = decay;
+        self
+    }
+
+    /// Normalizes a version as described for `VersionScorer`, without
+    /// trailing zero segments and leading zeros.
+    pub fn normalize(&self, version: &str) -> String {
+        let version = Version::parse(version);
+        let mut normalized = version.segments.join(".");
+        if !version.pre_release.is_empty() {
+            normalized.push('-');
+            normalized.push_str(version.pre_release);
+        }
+        normalized
+    }
+
+    /// Calculates the weighted similarity of the segments of the versions.
+    /// The returned value is between 0.0 and 1.0 (inclusive), where 1.0 means
+    /// the versions are the same after normalization.
+    pub fn similarity(&self, a: &str, b: &str) -> f64 {
+        let a = Version::parse(a);
+        let b = Version::parse(b);
+        if a == b {
+            return 1.0;
+        }
+
+        let segments = max(a.segments.len(), b.segments.len());
+        let mut weight = 1.0;
+        let mut weight_sum = 0.0;
+        let mut score = 0.0;
+        let mut differed = false;
+        for i in 0..segments {
+            let (a_segment, b_segment) = (a.segment(i), b.segment(i));
+            if !differed {
+                if a_segment == b_segment {
+                    score += weight;
+                } else {
+                    score += weight * segment_similarity(a_segment, b_segment);
+                    differed = true;
+                }
+            }
+            weight_sum += weight;
+            weight *= self.decay;
+        }
+        if !a.pre_release.is_empty() || !b.pre_release.is_empty() {
+            if !differed {
+                score += weight * normalized_levenshtein(a.pre_release, b.pre_release);
+            }
+            weight_sum += weight;
+        }
+
+        if weight_sum > 0.0 {
+            score / weight_sum
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Default for VersionScorer {
+    fn default() -> Self {
+        Self { decay: 0.5 }
+    }
+}
+
+/// Normalizes a version as described for `VersionScorer`.
+///
+/// ```
+/// use strsim::matchers::normalize_version;
+///
+/// assert_eq!("1.2-rc.1", normalize_version("v01.2.0-rc.1+sha.5114f85"));
+/// ```
+pub fn normalize_version(version: &str) -> String {
+    VersionScorer::default().normalize(version)
+}
+
+/// Compares versions with the default decay of `VersionScorer`. The returned
+/// value is between 0.0 and 1.0 (inclusive).
+///
+/// ```
+/// use strsim::matchers::version_similarity;
+///
+/// assert_eq!(1.0, version_similarity("2.0", "v2.0.0"));
+/// assert!(version_similarity("2.0.1", "2.0.2") > 0.9);
+/// ```
+pub fn version_similarity(a: &str, b: &str) -> f64 {
+    VersionScorer::default().similarity(a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_segments() {
+        let version = Version::parse(" v1.02.0.0-beta.2+exp.sha ");
+        assert_eq!(vec!["1", "2"], version.segments);
+        assert_eq!("beta.2", version.pre_release);
+        assert_eq!(vec!["0"], Version::parse("0.0").segments);
+        assert_eq!(vec!["1", "x"], Version::parse("1.x").segments);
+        // only a `v` in front of a number is a prefix
+        assert_eq!(vec!["very"], Version::parse("very").segments);
+    }
+
+    #[test]
+    fn similarity_weights_segments() {
+        // 1 + 0.5 / 2 of 1.75
+        assert_delta!(1.25 / 1.75, version_similarity("1.2.9", "1.3.9"));
+        assert_delta!(1.625 / 1.75, version_similarity("1.2.9", "1.2.8"));
+        assert_delta!(0.5 / 1.75, version_similarity("1.2.9", "2.2.9"));
+        assert_delta!(0.25 / 1.75, version_similarity("1.2.9", "4.2.9"));
+
+        // equal weights
+        let flat = VersionScorer::default().decay(1.0);
+        assert_delta!(2.5 / 3.0, flat.similarity("1.2.9", "1.2.8"));
+    }
+
+    #[test]
+    fn similarity_pre_release() {
+        // 1 + 0.5 + 0.25 of 1.875
+        assert_delta!(1.75 / 1.875, version_similarity("1.2.3", "1.2.3-rc1"));
+        let partial = normalized_levenshtein("rc1", "rc2");
+        assert_delta!(
+            (1.75 + 0.125 * partial) / 1.875,
+            version_similarity("1.2.3-rc1", "1.2.3-rc2")
+        );
+        assert_delta!(1.625 / 1.875, version_similarity("1.2.4", "1.2.3-rc1"));
+    }
+
+    #[test]
+    fn similarity_non_numeric_segments() {
+        let partial = normalized_levenshtein("x", "10");
+        assert_delta!(
+            (1.0 + 0.5 * partial) / 1.5,
+            version_similarity("1.x", "1.10")
+        );
+        assert_delta!(1.0, version_similarity("1.x", "v1.x.0"));
+        let scorer = VersionScorer::default().decay(0.0);
+        assert_delta!(1.0, scorer.similarity("1.2", "1.3"));
+    }
+}
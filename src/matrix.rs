@@ -0,0 +1,358 @@
+//! Functions returning the complete dynamic programming matrix of an edit
+//! distance, for teaching and for debugging custom costs. They need `O(N*M)`
+//! memory and are not optimized, so the regular functions should be used to
+//! calculate distances.
+
+use std::cmp::min;
+use std::fmt::{self, Display, Formatter};
+
+use crate::{CharWeights, CostModel, PositionalWeights};
+
+/// The dynamic programming matrix of an edit distance. The cell in row `i`
+/// and column `j` holds the distance between the first `i` characters of the
+/// first string and the first `j` characters of the second string.
+///
+/// The `Display` implementation prints the matrix as a table labeled with the
+/// characters of both strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistanceMatrix {
+    a: Vec<char>,
+    b: Vec<char>,
+    cells: Vec<usize>,
+}
+
+impl DistanceMatrix {
+    fn new(a: &str, b: &str) -> Self {
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        let cells = vec![0; (a.len() + 1) * (b.len() + 1)];
+        Self { a, b, cells }
+    }
+
+    /// The number of rows, which is the length of the first string plus one.
+    pub fn rows(&self) -> usize {
+        self.a.len() + 1
+    }
+
+    /// The number of columns, which is the length of the second string plus
+    /// one.
+    pub fn columns(&self) -> usize {
+        self.b.len() + 1
+    }
+
+    /// Returns the cell in row `i` and column `j`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` or `j` is outside of the matrix.
+    pub fn get(&self, i: usize, j: usize) -> usize {
+        assert!(i < self.rows() && j < self.columns(), "cell out of bounds");
+        self.cells[i * self.columns() + j]
+    }
+
+    /// Returns the distance between the complete strings, which is the value
+    /// of the bottom right cell.
+    pub fn distance(&self) -> usize {
+        self.get(self.a.len(), self.b.len())
+    }
+
+    fn set(&mut self, i: usize, j: usize, value: usize) {
+        let columns = self.columns();
+        self.cells[i * columns + j] = value;
+    }
+}
+
+impl Display for DistanceMatrix {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        let width = self
+            .cells
+            .iter()
+            .map(|cell| cell.to_string().len())
+            .max()
+            .unwrap_or(1);
+
+        write!(fmt, "{:>width$}", "", width = width)?;
+        write!(fmt, " {:>width$}", "", width = width)?;
+        for ch in &self.b {
+            write!(fmt, " {:>width$}", ch, width = width)?;
+        }
+
+        for i in 0..self.rows() {
+            writeln!(fmt)?;
+            let label = if i == 0 { ' ' } else { self.a[i - 1] };
+            write!(fmt, "{:>width$}", label, width = width)?;
+            for j in 0..self.columns() {
+                write!(fmt, " {:>width$}", self.get(i, j), width = width)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the dynamic programming matrix of the Levenshtein distance.
+///
+/// ```
+/// use strsim::levenshtein_matrix;
+///
+/// let matrix = levenshtein_matrix("kitten", "sitting");
+/// assert_eq!(3, matrix.distance());
+/// assert_eq!(1, matrix.get(1, 1));
+/// ```
+pub fn levenshtein_matrix(a: &str, b: &str) -> DistanceMatrix {
+    positional_weighted_levenshtein_matrix(a, b, &UnitWeights, |_| 1)
+}
+
+/// Returns the dynamic programming matrix of the optimal string alignment
+/// distance.
+///
+/// ```
+/// use strsim::osa_matrix;
+///
+/// let matrix = osa_matrix("ab", "bca");
+/// assert_eq!(3, matrix.distance());
+/// assert_eq!(1, matrix.get(2, 1));
+/// ```
+pub fn osa_matrix(a: &str, b: &str) -> DistanceMatrix {
+    let mut matrix = DistanceMatrix::new(a, b);
+    for i in 0..matrix.rows() {
+        matrix.set(i, 0, i);
+    }
+    for j in 0..matrix.columns() {
+        matrix.set(0, j, j);
+    }
+
+    for i in 1..matrix.rows() {
+        for j in 1..matrix.columns() {
+            let a_char = matrix.a[i - 1];
+            let b_char = matrix.b[j - 1];
+            let cost = usize::from(a_char != b_char);
+            let mut dist = min(
+                matrix.get(i - 1, j - 1) + cost,
+                min(matrix.get(i - 1, j) + 1, matrix.get(i, j - 1) + 1),
+            );
+            if i > 1 && j > 1 && a_char == matrix.b[j - 2] && matrix.a[i - 2] == b_char {
+                dist = min(dist, matrix.get(i - 2, j - 2) + 1);
+            }
+            matrix.set(i, j, dist);
+        }
+    }
+    matrix
+}
+
+/// Returns the dynamic programming matrix of `weighted_levenshtein`.
+///
+/// ```
+/// use strsim::{weighted_levenshtein_matrix, CharWeightTable};
+///
+/// let weights = CharWeightTable::new(1, 1, 10).with_indel_cost('7', 5);
+/// let matrix = weighted_levenshtein_matrix("A17", "A1", &weights);
+/// assert_eq!(5, matrix.distance());
+/// assert_eq!(6, matrix.get(3, 1));
+/// ```
+pub fn weighted_levenshtein_matrix<W>(a: &str, b: &str, weights: &W) -> DistanceMatrix
+where
+    W: CharWeights + ?Sized,
+{
+    positional_weighted_levenshtein_matrix(a, b, weights, |_| 1)
+}
+
+/// Returns the dynamic programming matrix of
+/// `positional_weighted_levenshtein`.
+///
+/// ```
+/// use strsim::{positional_weighted_levenshtein_matrix, CharWeightTable};
+///
+/// let early_typos = |i: usize| if i < 3 { 2 } else { 1 };
+/// let matrix =
+///     positional_weighted_levenshtein_matrix("strsim", "sxrsim", &CharWeightTable::default(), early_typos);
+/// assert_eq!(2, matrix.distance());
+/// ```
+pub fn positional_weighted_levenshtein_matrix<W, F>(
+    a: &str,
+    b: &str,
+    weights: &W,
+    position_factor: F,
+) -> DistanceMatrix
+where
+    W: CharWeights + ?Sized,
+    F: Fn(usize) -> usize,
+{
+    weighted_edit_distance_matrix(
+        a,
+        b,
+        &PositionalWeights {
+            weights,
+            position_factor,
+        },
+    )
+}
+
+/// Returns the dynamic programming matrix of `weighted_edit_distance`.
+///
+/// ```
+/// use strsim::{weighted_edit_distance_matrix, CostModel};
+///
+/// struct CheapTranspositions;
+///
+/// impl CostModel for CheapTranspositions {
+///     fn transpose_cost(&self, _a: char, _b: char, _position: usize) -> Option<usize> {
+///         Some(1)
+///     }
+/// }
+///
+/// let matrix = weighted_edit_distance_matrix("ab", "ba", &CheapTranspositions);
+/// assert_eq!(1, matrix.distance());
+/// ```
+pub fn weighted_edit_distance_matrix<C>(a: &str, b: &str, costs: &C) -> DistanceMatrix
+where
+    C: CostModel + ?Sized,
+{
+    let mut matrix = DistanceMatrix::new(a, b);
+
+    for j in 1..matrix.columns() {
+        let cost = costs.insert_cost(matrix.b[j - 1], 0);
+        matrix.set(0, j, matrix.get(0, j - 1) + cost);
+    }
+
+    for i in 1..matrix.rows() {
+        let a_char = matrix.a[i - 1];
+        let deletion_cost = costs.delete_cost(a_char, i - 1);
+        matrix.set(i, 0, matrix.get(i - 1, 0) + deletion_cost);
+
+        for j in 1..matrix.columns() {
+            let b_char = matrix.b[j - 1];
+            let substitution = if a_char == b_char {
+                matrix.get(i - 1, j - 1)
+            } else {
+                matrix.get(i - 1, j - 1) + costs.substitute_cost(a_char, b_char, i - 1)
+            };
+            let deletion = matrix.get(i - 1, j) + deletion_cost;
+            let insertion = matrix.get(i, j - 1) + costs.insert_cost(b_char, i);
+            let mut dist = min(substitution, min(deletion, insertion));
+
+            if i > 1 && j > 1 && a_char != b_char {
+                let a_prev = matrix.a[i - 2];
+                if a_char == matrix.b[j - 2] && a_prev == b_char {
+                    if let Some(cost) = costs.transpose_cost(a_prev, a_char, i - 2) {
+                        dist = min(dist, matrix.get(i - 2, j - 2) + cost);
+                    }
+                }
+            }
+            matrix.set(i, j, dist);
+        }
+    }
+    matrix
+}
+
+/* Weights of the regular Levenshtein distance */
+struct UnitWeights;
+
+impl CharWeights for UnitWeights {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{levenshtein, osa_distance, weighted_levenshtein, CharWeightTable};
+
+    #[test]
+    fn levenshtein_matrix_empty() {
+        let matrix = levenshtein_matrix("", "");
+        assert_eq!(1, matrix.rows());
+        assert_eq!(1, matrix.columns());
+        assert_eq!(0, matrix.distance());
+    }
+
+    #[test]
+    fn levenshtein_matrix_borders() {
+        let matrix = levenshtein_matrix("abc", "de");
+        assert_eq!((4, 3), (matrix.rows(), matrix.columns()));
+        for i in 0..matrix.rows() {
+            assert_eq!(i, matrix.get(i, 0));
+        }
+        for j in 0..matrix.columns() {
+            assert_eq!(j, matrix.get(0, j));
+        }
+    }
+
+    #[test]
+    fn levenshtein_matrix_prefixes() {
+        let a = "kitten";
+        let b = "sitting";
+        let matrix = levenshtein_matrix(a, b);
+        for (i, (a_end, _)) in a.char_indices().chain(Some((a.len(), ' '))).enumerate() {
+            for (j, (b_end, _)) in b.char_indices().chain(Some((b.len(), ' '))).enumerate() {
+                assert_eq!(levenshtein(&a[..a_end], &b[..b_end]), matrix.get(i, j));
+            }
+        }
+    }
+
+    #[test]
+    fn osa_matrix_matches_osa_distance() {
+        for (a, b) in [
+            ("ab", "bca"),
+            ("ca", "abc"),
+            ("a cat", "an abct"),
+            ("香ö", "ö香"),
+        ] {
+            assert_eq!(osa_distance(a, b), osa_matrix(a, b).distance());
+        }
+    }
+
+    #[test]
+    fn weighted_levenshtein_matrix_matches_weighted_levenshtein() {
+        let weights = CharWeightTable::new(2, 3, 4).with_insertion_cost('x', 1);
+        for (a, b) in [
+            ("kitten", "sitting"),
+            ("", "xyz"),
+            ("abc", ""),
+            ("axc", "abxc"),
+        ] {
+            assert_eq!(
+                weighted_levenshtein(a, b, &weights),
+                weighted_levenshtein_matrix(a, b, &weights).distance()
+            );
+        }
+    }
+
+    #[test]
+    fn positional_weighted_levenshtein_matrix_matches() {
+        let weights = CharWeightTable::default();
+        let factor = |i: usize| i + 1;
+        for (a, b) in [("strsim", "sxrsim"), ("strsim", "strsimx"), ("", "ab")] {
+            assert_eq!(
+                crate::positional_weighted_levenshtein(a, b, &weights, factor),
+                positional_weighted_levenshtein_matrix(a, b, &weights, factor).distance()
+            );
+        }
+    }
+
+    #[test]
+    fn weighted_edit_distance_matrix_matches() {
+        struct Transpositions;
+
+        impl CostModel for Transpositions {
+            fn transpose_cost(&self, _a: char, _b: char, position: usize) -> Option<usize> {
+                Some(position + 1)
+            }
+        }
+
+        for (a, b) in [("ab", "ba"), ("xab", "xba"), ("ca", "abc"), ("", "ab")] {
+            assert_eq!(
+                crate::weighted_edit_distance(a, b, &Transpositions),
+                weighted_edit_distance_matrix(a, b, &Transpositions).distance()
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "cell out of bounds")]
+    fn get_out_of_bounds() {
+        levenshtein_matrix("ab", "c").get(3, 0);
+    }
+
+    #[test]
+    fn display_table() {
+        let expected = "    a b\n  0 1 2\nb 1 1 1";
+        assert_eq!(expected, levenshtein_matrix("b", "ab").to_string());
+    }
+}
@@ -0,0 +1,160 @@
+//! Identifying metrics and their parameters in reports and serialized
+//! indexes.
+
+/* FNV-1a, which unlike the hashers of the standard library gives the same
+fingerprint on every platform and with every Rust release */
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// The identity of a metric: a stable name, the semantic version of its
+/// results and a fingerprint of its parameters.
+///
+/// It is recorded in match reports and serialized indexes, so results
+/// computed with another metric, other parameters or an incompatible version
+/// are recognized. A metric increments its major version whenever its results
+/// change, or its minor version before 1.0.0.
+///
+/// ```
+/// use strsim::MetricId;
+///
+/// let weight = 2.5f64.to_le_bytes();
+/// let id = MetricId::new("weighted_levenshtein", "1.2.0").parameter(&weight);
+/// assert_eq!("weighted_levenshtein", id.name());
+///
+/// let patched = MetricId::new("weighted_levenshtein", "1.3.1").parameter(&weight);
+/// assert!(id.is_compatible(&patched));
+/// let changed = MetricId::new("weighted_levenshtein", "2.0.0").parameter(&weight);
+/// assert!(!id.is_compatible(&changed));
+/// let reweighted = MetricId::new("weighted_levenshtein", "1.2.0").parameter(&3.0f64.to_le_bytes());
+/// assert!(!id.is_compatible(&reweighted));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MetricId {
+    name: &'static str,
+    version: &'static str,
+    fingerprint: u64,
+}
+
+impl MetricId {
+    /// Identifies the metric `name` without parameters in the semantic
+    /// `version`, like `"1.0.0"`.
+    pub const fn new(name: &'static str, version: &'static str) -> Self {
+        Self {
+            name,
+            version,
+            fingerprint: FNV_OFFSET_BASIS,
+        }
+    }
+
+    /* An identity whose fingerprint was computed before */
+    pub(crate) fn from_parts(name: &'static str, version: &'static str, fingerprint: u64) -> Self {
+        Self {
+            name,
+            version,
+            fingerprint,
+        }
+    }
+
+    /// Adds a parameter of the metric, encoded as bytes, to the fingerprint.
+    /// The parameters have to be added in the same order every time.
+    pub fn parameter(mut self, value: &[u8]) -> Self {
+        /* the length keeps the parameters "ab", "c" apart from "a", "bc" */
+        let len = (value.len() as u64).to_le_bytes();
+        for &byte in len.iter().chain(value) {
+            self.fingerprint ^= u64::from(byte);
+            self.fingerprint = self.fingerprint.wrapping_mul(FNV_PRIME);
+        }
+        self
+    }
+
+    /// The name of the metric.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The version of the metric.
+    pub fn version(&self) -> &'static str {
+        self.version
+    }
+
+    /// The fingerprint of the parameters of the metric.
+    pub fn fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+
+    /// Whether the metric gives the same results as `other`: both have the
+    /// same name and parameters, and the same major version, or the same
+    /// minor version before 1.0.0.
+    pub fn is_compatible(&self, other: &MetricId) -> bool {
+        self.is_compatible_with(other.name, other.version, other.fingerprint)
+    }
+
+    /* `is_compatible` for an identity read from a serialized index */
+    pub(crate) fn is_compatible_with(&self, name: &str, version: &str, fingerprint: u64) -> bool {
+        self.name == name
+            && self.fingerprint == fingerprint
+            && compatible_versions(self.version, version)
+    }
+}
+
+/* Whether two semantic versions share the components up to the first one
+which isn't 0, i.e. are compatible according to semver */
+fn compatible_versions(a: &str, b: &str) -> bool {
+    let mut a = a.split('.');
+    let mut b = b.split('.');
+    loop {
+        match (a.next(), b.next()) {
+            (Some(a), Some(b)) if a == b => {
+                if a != "0" {
+                    return true;
+                }
+            }
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn semantic_versions() {
+        assert!(compatible_versions("1.0.0", "1.4.2"));
+        assert!(compatible_versions("0.3.0", "0.3.9"));
+        assert!(compatible_versions("0.0.1", "0.0.1"));
+        assert!(!compatible_versions("1.0.0", "2.0.0"));
+        assert!(!compatible_versions("0.3.0", "0.4.0"));
+        assert!(!compatible_versions("0.0.1", "0.0.2"));
+        assert!(!compatible_versions("0", "0.1"));
+    }
+
+    #[test]
+    fn parameters_change_the_fingerprint() {
+        let id = MetricId::new("metric", "1.0.0");
+        assert_eq!(FNV_OFFSET_BASIS, id.fingerprint());
+        assert_ne!(id, id.parameter(&[]));
+        assert_ne!(
+            id.parameter(b"ab").parameter(b"c"),
+            id.parameter(b"a").parameter(b"bc")
+        );
+        assert_ne!(
+            id.parameter(b"a").parameter(b"b"),
+            id.parameter(b"b").parameter(b"a")
+        );
+        assert_eq!(id.parameter(b"a"), id.parameter(b"a"));
+        assert!(!id.is_compatible(&MetricId::new("other", "1.0.0")));
+    }
+
+    #[test]
+    fn stable_fingerprints() {
+        // serialized indexes rely on the fingerprints never changing
+        assert_eq!(
+            0x9c60_01d3_28a9_4690,
+            MetricId::new("metric", "1.0.0")
+                .parameter(b"ab")
+                .fingerprint()
+        );
+    }
+}
@@ -0,0 +1,2031 @@
+//! Utilities for searching with distances satisfying the triangle
+//! inequality, to build custom metric space indexes.
+//!
+//! For a true metric `d`, the distance of a query `q` to a candidate `c` is
+//! bounded by their distances to any pivot `p`:
+//! `|d(q, p) - d(p, c)| <= d(q, c) <= d(q, p) + d(p, c)`. With the distances
+//! of the candidates to a few pivots computed in advance, many candidates can
+//! be ruled out by computing only the distances of the query to the pivots.
+//!
+//! `Pivots` only accepts distances implementing `TrueMetric`. The optimal
+//! string alignment distance is no true metric: `osa_distance("ca", "abc")`
+//! is 3, while `osa_distance("ca", "ac")` and `osa_distance("ac", "abc")` are
+//! both 1. It therefore only implements `Distance`.
+//!
+//! ```
+//! use strsim::metric_space::{can_prune, Levenshtein, Pivots};
+//!
+//! let words = ["kitten", "sitting", "mitten", "fitting", "smitten"];
+//! let pivots = Pivots::new(Levenshtein, vec!["kitten".to_string(), "fitting".to_string()]);
+//! let table: Vec<Vec<usize>> = words.iter().map(|word| pivots.distances(word)).collect();
+//!
+//! let query = pivots.distances("bitten");
+//! let candidates: Vec<&str> = words
+//!     .iter()
+//!     .zip(&table)
+//!     .filter(|(_, distances)| !can_prune(&query, distances, 1))
+//!     .map(|(word, _)| *word)
+//!     .collect();
+//! // sitting and fitting are ruled out without comparing them to the query
+//! assert_eq!(vec!["kitten", "mitten", "smitten"], candidates);
+//! ```
+//!
+//! `VpTree` is an index built on this, and `FuzzyIndex` picks between it and
+//! a linear scan. Strings can be inserted and removed after building them,
+//! a `QueryBudget` limits the work of a single query, and `to_bytes` and
+//! `from_bytes` store an index built offline. A `VpTreeView` queries a
+//! stored tree without reading it into memory. They can all be queried from
+//! many threads at once:
+//!
+//! ```
+//! use std::sync::Arc;
+//! use std::thread;
+//! use strsim::metric_space::{FuzzyIndex, Levenshtein};
+//!
+//! let words: Vec<String> = (0..1000).map(|i| format!("item{}", i)).collect();
+//! let index = Arc::new(FuzzyIndex::new(Levenshtein, words));
+//! let workers: Vec<_> = ["item1", "item22", "item333"]
+//!     .iter()
+//!     .map(|&query| {
+//!         let index = Arc::clone(&index);
+//!         thread::spawn(move || index.k_nearest(query, 1)[0].0 == query)
+//!     })
+//!     .collect();
+//! assert!(workers.into_iter().all(|worker| worker.join().unwrap()));
+//! ```
+
+use std::cmp::max;
+use std::collections::BinaryHeap;
+use std::mem;
+#[cfg(feature = "parallel")]
+use std::panic;
+#[cfg(feature = "parallel")]
+use std::sync::Arc;
+#[cfg(feature = "parallel")]
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{damerau_levenshtein, levenshtein, osa_distance, Control, MetricId, StrSimError};
+
+/// A distance between strings.
+pub trait Distance {
+    /// Calculates the distance of `a` and `b`.
+    fn distance(&self, a: &str, b: &str) -> usize;
+
+    /// The identity of the distance, including its parameters.
+    ///
+    /// ```
+    /// use strsim::metric_space::{Distance, Levenshtein};
+    /// use strsim::MetricId;
+    ///
+    /// assert_eq!(MetricId::new("levenshtein", "1.0.0"), Levenshtein.id());
+    /// ```
+    fn id(&self) -> MetricId;
+}
+
+/// Marks a `Distance` as a true metric: the distance is 0 exactly for equal
+/// strings, symmetric, and satisfies the triangle inequality
+/// `d(a, c) <= d(a, b) + d(b, c)`. Only true metrics can be used with the
+/// pruning of `Pivots`.
+pub trait TrueMetric: Distance {}
+
+/// The Levenshtein distance, see `levenshtein`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Levenshtein;
+
+impl Distance for Levenshtein {
+    fn distance(&self, a: &str, b: &str) -> usize {
+        levenshtein(a, b)
+    }
+
+    fn id(&self) -> MetricId {
+        MetricId::new("levenshtein", "1.0.0")
+    }
+}
+
+impl TrueMetric for Levenshtein {}
+
+/// The Damerau-Levenshtein distance, see `damerau_levenshtein`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct DamerauLevenshtein;
+
+impl Distance for DamerauLevenshtein {
+    fn distance(&self, a: &str, b: &str) -> usize {
+        damerau_levenshtein(a, b)
+    }
+
+    fn id(&self) -> MetricId {
+        MetricId::new("damerau_levenshtein", "1.0.0")
+    }
+}
+
+impl TrueMetric for DamerauLevenshtein {}
+
+/// The optimal string alignment distance, see `osa_distance`. It violates
+/// the triangle inequality, so it is no `TrueMetric`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Osa;
+
+impl Distance for Osa {
+    fn distance(&self, a: &str, b: &str) -> usize {
+        osa_distance(a, b)
+    }
+
+    fn id(&self) -> MetricId {
+        MetricId::new("osa_distance", "1.0.0")
+    }
+}
+
+/// The bounds of the distance of a query and a candidate given their
+/// distances to the same pivot, as `(lower, upper)`.
+///
+/// ```
+/// use strsim::metric_space::distance_bounds;
+///
+/// assert_eq!((2, 8), distance_bounds(5, 3));
+/// ```
+pub fn distance_bounds(query_to_pivot: usize, pivot_to_candidate: usize) -> (usize, usize) {
+    let lower = if query_to_pivot > pivot_to_candidate {
+        query_to_pivot - pivot_to_candidate
+    } else {
+        pivot_to_candidate - query_to_pivot
+    };
+    (lower, query_to_pivot.saturating_add(pivot_to_candidate))
+}
+
+/// The tightest lower bound of the distance of two strings given their
+/// distances to the same pivots, which is 0 without pivots.
+pub fn lower_bound(a_to_pivots: &[usize], b_to_pivots: &[usize]) -> usize {
+    a_to_pivots
+        .iter()
+        .zip(b_to_pivots)
+        .map(|(&a, &b)| distance_bounds(a, b).0)
+        .max()
+        .unwrap_or(0)
+}
+
+/// The tightest upper bound of the distance of two strings given their
+/// distances to the same pivots, or `None` without pivots.
+pub fn upper_bound(a_to_pivots: &[usize], b_to_pivots: &[usize]) -> Option<usize> {
+    a_to_pivots
+        .iter()
+        .zip(b_to_pivots)
+        .map(|(&a, &b)| distance_bounds(a, b).1)
+        .min()
+}
+
+/// Whether two strings with the given distances to the same pivots are
+/// certainly further apart than `max_distance`.
+///
+/// ```
+/// use strsim::metric_space::can_prune;
+///
+/// assert!(can_prune(&[1, 6], &[2, 2], 3));
+/// assert!(!can_prune(&[1, 6], &[2, 2], 4));
+/// ```
+pub fn can_prune(a_to_pivots: &[usize], b_to_pivots: &[usize], max_distance: usize) -> bool {
+    lower_bound(a_to_pivots, b_to_pivots) > max_distance
+}
+
+/// A set of pivots with the metric used to measure the distances to them.
+#[derive(Debug, Clone)]
+pub struct Pivots<M> {
+    metric: M,
+    pivots: Vec<String>,
+}
+
+impl<M: TrueMetric> Pivots<M> {
+    /// Creates the pivots. Good pivots are far away from each other and
+    /// spread over the data.
+    pub fn new(metric: M, pivots: Vec<String>) -> Self {
+        Self { metric, pivots }
+    }
+
+    /// The pivots.
+    pub fn pivots(&self) -> &[String] {
+        &self.pivots
+    }
+
+    /// The distances of `s` to every pivot.
+    pub fn distances(&self, s: &str) -> Vec<usize> {
+        self.pivots
+            .iter()
+            .map(|pivot| self.metric.distance(s, pivot))
+            .collect()
+    }
+}
+
+/* The items of a subtree still to be built, and the parent node to link
+its root to, as inside or outside child */
+type PendingSubtree = (Vec<usize>, Option<(usize, bool)>);
+
+#[derive(Debug, Clone, Copy)]
+struct VpNode {
+    item: usize,
+    /* the items of the inside subtree are at most `radius` away from `item`,
+    those of the outside subtree at least */
+    radius: usize,
+    inside: Option<usize>,
+    outside: Option<usize>,
+}
+
+/// A vantage-point tree of strings for similarity search with a true
+/// metric.
+///
+/// Every node picks one string as vantage point and splits the remaining
+/// strings into halves at the median of their distances to it. A query only descends
+/// into the halves which can contain results according to the triangle
+/// inequality.
+///
+/// The tree is built from all strings at once. Strings inserted later are
+/// added as leaves, and removed strings are only marked as removed, since
+/// they may still be needed as vantage points. Once the number of
+/// insertions and removals reaches half the number of strings, the tree is
+/// rebuilt to keep it balanced.
+///
+/// ```
+/// use strsim::metric_space::{Levenshtein, VpTree};
+///
+/// let words = vec!["kitten", "sitting", "mitten", "fitting", "smitten", "written"];
+/// let tree = VpTree::new(Levenshtein, words.iter().map(|word| word.to_string()).collect());
+///
+/// assert_eq!(vec![("kitten", 1), ("mitten", 1)], tree.find_within("bitten", 1));
+/// assert_eq!(vec![("sitting", 0), ("fitting", 1)], tree.k_nearest("sitting", 2));
+/// ```
+#[derive(Debug, Clone)]
+pub struct VpTree<M> {
+    metric: M,
+    items: Vec<String>,
+    nodes: Vec<VpNode>,
+    /* whether every item was removed, keeping the indices of the others */
+    removed: Vec<bool>,
+    removed_count: usize,
+    /* the insertions and removals since the tree was built */
+    changes: usize,
+}
+
+impl<M: TrueMetric> VpTree<M> {
+    /// Builds the tree of `items`.
+    pub fn new(metric: M, items: Vec<String>) -> Self {
+        match Self::with_control(metric, items, &Control::new()) {
+            Ok(tree) => tree,
+            Err(_) => unreachable!("the build is never cancelled"),
+        }
+    }
+
+    /// Builds the tree of `items` like `new`, unless the build is cancelled
+    /// through `control`. The progress is the number of items added to the
+    /// tree.
+    pub fn with_control(
+        metric: M,
+        items: Vec<String>,
+        control: &Control<'_>,
+    ) -> Result<Self, StrSimError> {
+        let nodes = build_nodes(&metric, &items, control)?;
+        Ok(Self {
+            metric,
+            removed: vec![false; items.len()],
+            items,
+            nodes,
+            removed_count: 0,
+            changes: 0,
+        })
+    }
+
+    /// Reads a tree serialized with `to_bytes`. Fails with
+    /// `StrSimError::MetricMismatch` if the tree was built with a metric
+    /// which isn't compatible with `metric` according to
+    /// `MetricId::is_compatible`, and with `StrSimError::MalformedIndex` if
+    /// the bytes are no serialized tree.
+    pub fn from_bytes(metric: M, bytes: &[u8]) -> Result<Self, StrSimError> {
+        let layout = IndexLayout::parse(&metric, bytes)?;
+        if layout.kind != IndexKind::VpTree {
+            return Err(StrSimError::MalformedIndex);
+        }
+        Ok(Self::from_layout(metric, &layout))
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<M: TrueMetric + Send + Sync + 'static> VpTree<M> {
+    /// Builds the tree of `items` like `new`, but builds the two halves of
+    /// the upper nodes on separate threads, using up to `threads` threads.
+    /// The tree is the same as the one built by `new`.
+    ///
+    /// ```
+    /// use strsim::metric_space::{Levenshtein, VpTree};
+    ///
+    /// let words: Vec<String> = (0..10_000).map(|i| format!("word{}", i)).collect();
+    /// let tree = VpTree::new_parallel(Levenshtein, words.clone(), 4);
+    /// assert_eq!(VpTree::new(Levenshtein, words).to_bytes(), tree.to_bytes());
+    /// ```
+    pub fn new_parallel(metric: M, items: Vec<String>, threads: usize) -> Self {
+        let metric = Arc::new(metric);
+        let items = Arc::new(items);
+        let nodes = build_parallel(&metric, &items, (0..items.len()).collect(), threads);
+        match (Arc::try_unwrap(metric), Arc::try_unwrap(items)) {
+            (Ok(metric), Ok(items)) => Self {
+                metric,
+                removed: vec![false; items.len()],
+                items,
+                nodes,
+                removed_count: 0,
+                changes: 0,
+            },
+            _ => unreachable!("every thread building the tree was joined"),
+        }
+    }
+}
+
+impl<M> VpTree<M> {
+    fn from_layout(metric: M, layout: &IndexLayout<'_>) -> Self {
+        let removed: Vec<bool> = (0..layout.item_count)
+            .map(|i| layout.is_removed(i))
+            .collect();
+        Self {
+            metric,
+            items: (0..layout.item_count)
+                .map(|i| layout.item(i).to_string())
+                .collect(),
+            nodes: (0..layout.node_count).map(|i| layout.node(i)).collect(),
+            removed_count: removed.iter().filter(|&&removed| removed).count(),
+            removed,
+            changes: layout.changes,
+        }
+    }
+}
+
+impl<M: Distance> VpTree<M> {
+    /// The number of strings in the tree.
+    pub fn len(&self) -> usize {
+        self.items.len() - self.removed_count
+    }
+
+    /// Whether the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Serializes the tree, e.g. to build it offline and read it with
+    /// `from_bytes` at startup. The bytes are the same on every platform and
+    /// record the `MetricId` of the metric.
+    ///
+    /// ```
+    /// use strsim::metric_space::{DamerauLevenshtein, Levenshtein, VpTree};
+    /// use strsim::StrSimError;
+    ///
+    /// let words: Vec<String> = (0..1000).map(|i| format!("word{}", i)).collect();
+    /// let bytes = VpTree::new(Levenshtein, words).to_bytes();
+    ///
+    /// let tree = VpTree::from_bytes(Levenshtein, &bytes).unwrap();
+    /// assert_eq!(vec![("word7", 0), ("word0", 1)], tree.k_nearest("word7", 2));
+    /// assert_eq!(
+    ///     Some(StrSimError::MetricMismatch),
+    ///     VpTree::from_bytes(DamerauLevenshtein, &bytes).err()
+    /// );
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        write_index(
+            IndexKind::VpTree,
+            &self.metric,
+            &self.items,
+            &self.nodes,
+            &self.removed,
+            self.changes,
+        )
+    }
+
+    /// Adds `item` to the tree as a leaf, and rebuilds the tree if it
+    /// changed a lot since it was built.
+    ///
+    /// ```
+    /// use strsim::metric_space::{Levenshtein, VpTree};
+    ///
+    /// let mut tree = VpTree::new(Levenshtein, vec!["kitten".to_string()]);
+    /// tree.insert("mitten".to_string());
+    /// assert!(tree.remove("kitten"));
+    /// assert!(!tree.remove("kitten"));
+    /// assert_eq!(vec![("mitten", 1)], tree.find_within("bitten", 1));
+    /// ```
+    pub fn insert(&mut self, item: String) {
+        let index = self.items.len();
+        self.items.push(item);
+        self.removed.push(false);
+        let leaf = VpNode {
+            item: index,
+            radius: 0,
+            inside: None,
+            outside: None,
+        };
+
+        let mut node = 0;
+        while let Some(current) = self.nodes.get(node) {
+            let distance = self
+                .metric
+                .distance(&self.items[index], &self.items[current.item]);
+            let inside = distance < current.radius;
+            let child = if inside {
+                current.inside
+            } else {
+                current.outside
+            };
+            match child {
+                Some(child) => node = child,
+                None => {
+                    let leaf_node = self.nodes.len();
+                    if inside {
+                        self.nodes[node].inside = Some(leaf_node);
+                    } else {
+                        self.nodes[node].outside = Some(leaf_node);
+                    }
+                    break;
+                }
+            }
+        }
+        self.nodes.push(leaf);
+        self.changed();
+    }
+
+    /// Removes one string equal to `item`, the one added first, and returns
+    /// whether there was one. Rebuilds the tree if it changed a lot since it
+    /// was built.
+    pub fn remove(&mut self, item: &str) -> bool {
+        let found = self
+            .search(item, 0, QueryBudget::new())
+            .0
+            .into_iter()
+            .map(|(_, index)| index)
+            .filter(|&index| self.items[index] == item)
+            .min();
+        match found {
+            Some(index) => {
+                self.removed[index] = true;
+                self.removed_count += 1;
+                self.changed();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /* Rebuilds the tree without the removed items once the changes since
+    the last build reach half of its size, which keeps the amortized cost of
+    a change logarithmic */
+    fn changed(&mut self) {
+        /* small trees are cheap to search even if they are unbalanced */
+        const MIN_REBUILD_CHANGES: usize = 32;
+
+        self.changes += 1;
+        if self.changes < max(self.len() / 2, MIN_REBUILD_CHANGES) {
+            return;
+        }
+        let items = mem::take(&mut self.items);
+        let removed = mem::take(&mut self.removed);
+        self.items = items
+            .into_iter()
+            .zip(removed)
+            .filter(|&(_, removed)| !removed)
+            .map(|(item, _)| item)
+            .collect();
+        self.removed = vec![false; self.items.len()];
+        self.removed_count = 0;
+        self.changes = 0;
+        self.nodes = match build_nodes(&self.metric, &self.items, &Control::new()) {
+            Ok(nodes) => nodes,
+            Err(_) => unreachable!("the build is never cancelled"),
+        };
+    }
+
+    /// Returns the strings within `max_distance` of `query` with their
+    /// distance, sorted by distance. Strings with the same distance are
+    /// ordered as they were added to the tree.
+    pub fn find_within(&self, query: &str, max_distance: usize) -> Vec<(&str, usize)> {
+        self.find_within_with_budget(query, max_distance, QueryBudget::new())
+            .matches
+    }
+
+    /// Like `find_within`, but stops once the `budget` is used up and
+    /// returns the strings found so far.
+    ///
+    /// ```
+    /// use strsim::metric_space::{Levenshtein, QueryBudget, VpTree};
+    ///
+    /// let words: Vec<String> = (0..1000).map(|i| format!("word{}", i)).collect();
+    /// let tree = VpTree::new(Levenshtein, words);
+    ///
+    /// let result = tree.find_within_with_budget("word7", 1, QueryBudget::new());
+    /// assert!(result.complete);
+    /// assert_eq!(tree.find_within("word7", 1), result.matches);
+    ///
+    /// let result = tree.find_within_with_budget("word7", 1, QueryBudget::new().max_candidates(10));
+    /// assert!(!result.complete);
+    /// assert!(result.matches.len() < tree.find_within("word7", 1).len());
+    /// ```
+    pub fn find_within_with_budget(
+        &self,
+        query: &str,
+        max_distance: usize,
+        budget: QueryBudget,
+    ) -> QueryResult<'_> {
+        let (found, complete) = self.search(query, max_distance, budget);
+        QueryResult {
+            matches: self.results(found),
+            complete,
+        }
+    }
+
+    /* The distances and indices of the items within `max_distance` of
+    `query`, unsorted, and whether the search finished within `budget` */
+    fn search(
+        &self,
+        query: &str,
+        max_distance: usize,
+        budget: QueryBudget,
+    ) -> (Vec<(usize, usize)>, bool) {
+        search_nodes(self, &self.metric, query, max_distance, budget)
+    }
+
+    /// Returns the `k` strings closest to `query` with their distance, sorted
+    /// like `find_within`. Of several strings with the same distance, those
+    /// added to the tree first are preferred.
+    pub fn k_nearest(&self, query: &str, k: usize) -> Vec<(&str, usize)> {
+        self.k_nearest_with_budget(query, k, QueryBudget::new())
+            .matches
+    }
+
+    /// Like `k_nearest`, but stops once the `budget` is used up and returns
+    /// the `k` closest strings found so far. The nearer half of every
+    /// subtree is searched first, so these tend to be close to the query.
+    pub fn k_nearest_with_budget(
+        &self,
+        query: &str,
+        k: usize,
+        budget: QueryBudget,
+    ) -> QueryResult<'_> {
+        let (found, complete) = k_nearest_nodes(self, &self.metric, query, k, budget);
+        QueryResult {
+            matches: self.results(found),
+            complete,
+        }
+    }
+
+    fn results(&self, found: Vec<(usize, usize)>) -> Vec<(&str, usize)> {
+        results(found, |item| &self.items[item])
+    }
+}
+
+impl<M> VpNodes for VpTree<M> {
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn node(&self, node: usize) -> VpNode {
+        self.nodes[node]
+    }
+
+    fn item(&self, item: usize) -> &str {
+        &self.items[item]
+    }
+
+    fn is_removed(&self, item: usize) -> bool {
+        self.removed[item]
+    }
+}
+
+/// A `VpTree` serialized with `to_bytes`, queried directly in the bytes
+/// without reading it into memory first, e.g. in a memory mapped file which
+/// is larger than the available memory.
+///
+/// Creating the view checks the bytes once without allocating, so a
+/// corrupted file can't make queries fail. Queries only read the nodes they
+/// visit and return strings borrowed from the bytes. Unlike `VpTree`, the
+/// view can't be changed.
+///
+/// ```
+/// use strsim::metric_space::{Levenshtein, VpTree, VpTreeView};
+///
+/// let words: Vec<String> = (0..1000).map(|i| format!("word{}", i)).collect();
+/// let bytes = VpTree::new(Levenshtein, words).to_bytes();
+///
+/// let view = VpTreeView::new(Levenshtein, &bytes).unwrap();
+/// assert_eq!(1000, view.len());
+/// assert_eq!(vec![("word7", 0), ("word0", 1)], view.k_nearest("word7", 2));
+/// ```
+#[derive(Debug, Clone)]
+pub struct VpTreeView<'a, M> {
+    metric: M,
+    layout: IndexLayout<'a>,
+    len: usize,
+}
+
+impl<'a, M: TrueMetric> VpTreeView<'a, M> {
+    /// Wraps the bytes of a serialized tree. Fails like `VpTree::from_bytes`
+    /// if they were built with another metric or are no serialized tree.
+    pub fn new(metric: M, bytes: &'a [u8]) -> Result<Self, StrSimError> {
+        let layout = IndexLayout::parse(&metric, bytes)?;
+        if layout.kind != IndexKind::VpTree {
+            return Err(StrSimError::MalformedIndex);
+        }
+        let removed_count = layout
+            .removed
+            .iter()
+            .filter(|&&removed| removed == 1)
+            .count();
+        Ok(Self {
+            metric,
+            len: layout.item_count - removed_count,
+            layout,
+        })
+    }
+}
+
+impl<'a, M: Distance> VpTreeView<'a, M> {
+    /// The number of strings in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the strings within `max_distance` of `query`, like
+    /// `VpTree::find_within`.
+    pub fn find_within(&self, query: &str, max_distance: usize) -> Vec<(&'a str, usize)> {
+        self.find_within_with_budget(query, max_distance, QueryBudget::new())
+            .matches
+    }
+
+    /// Returns the strings within `max_distance` of `query` found within the
+    /// `budget`, like `VpTree::find_within_with_budget`.
+    pub fn find_within_with_budget(
+        &self,
+        query: &str,
+        max_distance: usize,
+        budget: QueryBudget,
+    ) -> QueryResult<'a> {
+        let (found, complete) =
+            search_nodes(&self.layout, &self.metric, query, max_distance, budget);
+        QueryResult {
+            matches: results(found, |item| self.layout.item(item)),
+            complete,
+        }
+    }
+
+    /// Returns the `k` strings closest to `query`, like `VpTree::k_nearest`.
+    pub fn k_nearest(&self, query: &str, k: usize) -> Vec<(&'a str, usize)> {
+        self.k_nearest_with_budget(query, k, QueryBudget::new())
+            .matches
+    }
+
+    /// Returns the `k` strings closest to `query` found within the `budget`,
+    /// like `VpTree::k_nearest_with_budget`.
+    pub fn k_nearest_with_budget(
+        &self,
+        query: &str,
+        k: usize,
+        budget: QueryBudget,
+    ) -> QueryResult<'a> {
+        let (found, complete) = k_nearest_nodes(&self.layout, &self.metric, query, k, budget);
+        QueryResult {
+            matches: results(found, |item| self.layout.item(item)),
+            complete,
+        }
+    }
+}
+
+/* The nodes of a vantage-point tree, stored in a `VpTree` or in the bytes of
+a `VpTreeView`. The root is the first node. */
+trait VpNodes {
+    fn node_count(&self) -> usize;
+
+    fn node(&self, node: usize) -> VpNode;
+
+    fn item(&self, item: usize) -> &str;
+
+    fn is_removed(&self, item: usize) -> bool;
+}
+
+/* The distances and indices of the items within `max_distance` of `query`,
+unsorted, and whether the search finished within `budget` */
+fn search_nodes<T: VpNodes, M: Distance>(
+    tree: &T,
+    metric: &M,
+    query: &str,
+    max_distance: usize,
+    budget: QueryBudget,
+) -> (Vec<(usize, usize)>, bool) {
+    let mut budget = budget.start();
+    let mut found: Vec<(usize, usize)> = Vec::new();
+    let mut pending: Vec<usize> = if tree.node_count() == 0 {
+        vec![]
+    } else {
+        vec![0]
+    };
+    while let Some(node) = pending.pop() {
+        if !budget.compare() {
+            return (found, false);
+        }
+        let node = tree.node(node);
+        let distance = metric.distance(query, tree.item(node.item));
+        if distance <= max_distance && !tree.is_removed(node.item) {
+            found.push((distance, node.item));
+        }
+        if let Some(inside) = node.inside {
+            if distance <= node.radius.saturating_add(max_distance) {
+                pending.push(inside);
+            }
+        }
+        if let Some(outside) = node.outside {
+            if distance.saturating_add(max_distance) >= node.radius {
+                pending.push(outside);
+            }
+        }
+    }
+    (found, true)
+}
+
+/* The distances and indices of the `k` items closest to `query` found within
+`budget`, unsorted, and whether the search finished within it */
+fn k_nearest_nodes<T: VpNodes, M: Distance>(
+    tree: &T,
+    metric: &M,
+    query: &str,
+    k: usize,
+    budget: QueryBudget,
+) -> (Vec<(usize, usize)>, bool) {
+    let mut budget = budget.start();
+    let mut complete = true;
+    /* a max-heap of the best candidates so far */
+    let mut best: BinaryHeap<(usize, usize)> = BinaryHeap::new();
+    let mut pending: Vec<usize> = if tree.node_count() == 0 || k == 0 {
+        vec![]
+    } else {
+        vec![0]
+    };
+    while let Some(node) = pending.pop() {
+        if !budget.compare() {
+            complete = false;
+            break;
+        }
+        let node = tree.node(node);
+        let distance = metric.distance(query, tree.item(node.item));
+        if !tree.is_removed(node.item) {
+            best.push((distance, node.item));
+            if best.len() > k {
+                best.pop();
+            }
+        }
+        let tau = if best.len() < k {
+            usize::MAX
+        } else {
+            best.peek().map_or(usize::MAX, |&(distance, _)| distance)
+        };
+
+        /* ties with the current worst candidate can still win by their
+        position, so a subtree is only skipped when all its strings are
+        further away than it */
+        let visit_inside = node
+            .inside
+            .filter(|_| distance <= node.radius.saturating_add(tau));
+        let visit_outside = node
+            .outside
+            .filter(|_| distance.saturating_add(tau) >= node.radius);
+        /* the nearer half is popped first */
+        if distance <= node.radius {
+            pending.extend(visit_outside);
+            pending.extend(visit_inside);
+        } else {
+            pending.extend(visit_inside);
+            pending.extend(visit_outside);
+        }
+    }
+    (best.into_vec(), complete)
+}
+
+/// Limits on the work of a single query of a `VpTree` or `FuzzyIndex`, for
+/// services which need an answer within a latency bound rather than an
+/// exact one. A query stops once it compared `max_candidates` strings with
+/// the query or ran for `time_limit`, and returns the best results found so
+/// far. The maximum distance is already a parameter of every query.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryBudget {
+    max_candidates: Option<usize>,
+    time_limit: Option<Duration>,
+}
+
+impl QueryBudget {
+    /// Creates a budget without limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compares at most `max_candidates` strings with the query.
+    pub fn max_candidates(mut self, max_candidates: usize) -> Self {
+        self.max_candidates = Some(max_candidates);
+        self
+    }
+
+    /// Stops the query once it ran for `time_limit`. The time is checked
+    /// before every comparison, so a query can exceed the limit by the time
+    /// of one comparison.
+    pub fn time_limit(mut self, time_limit: Duration) -> Self {
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    fn start(self) -> BudgetCounter {
+        BudgetCounter {
+            remaining: self.max_candidates,
+            /* a deadline too far in the future to represent is no limit */
+            deadline: self
+                .time_limit
+                .and_then(|time_limit| Instant::now().checked_add(time_limit)),
+        }
+    }
+}
+
+/* The budget left for the current query */
+struct BudgetCounter {
+    remaining: Option<usize>,
+    deadline: Option<Instant>,
+}
+
+impl BudgetCounter {
+    /* Whether another string may be compared with the query, which is then
+    counted */
+    fn compare(&mut self) -> bool {
+        if let Some(remaining) = &mut self.remaining {
+            if *remaining == 0 {
+                return false;
+            }
+            *remaining -= 1;
+        }
+        self.deadline
+            .map_or(true, |deadline| Instant::now() < deadline)
+    }
+}
+
+/// The results of a query with a `QueryBudget`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryResult<'a> {
+    /// The strings found with their distance, sorted like the results of the
+    /// query without a budget.
+    pub matches: Vec<(&'a str, usize)>,
+    /// Whether the query finished within its budget, in which case `matches`
+    /// are the same as without a budget. Otherwise some strings weren't
+    /// compared with the query, so matches can be missing, and the results
+    /// of `k_nearest` queries can be further away than the true nearest
+    /// strings.
+    pub complete: bool,
+}
+
+/* Sorts `(distance, item)` pairs and looks up the items */
+fn results<'a, F>(mut found: Vec<(usize, usize)>, item: F) -> Vec<(&'a str, usize)>
+where
+    F: Fn(usize) -> &'a str,
+{
+    found.sort_unstable();
+    found
+        .into_iter()
+        .map(|(distance, index)| (item(index), distance))
+        .collect()
+}
+
+/* Builds the nodes of a tree of `items`, the root first */
+fn build_nodes<M: Distance>(
+    metric: &M,
+    items: &[String],
+    control: &Control<'_>,
+) -> Result<Vec<VpNode>, StrSimError> {
+    build_subtree(metric, items, (0..items.len()).collect(), control)
+}
+
+/* Builds the nodes of a tree of the items at the indices `subtree`, the
+root first. Every subtree is built before the subtrees after it, inside
+before outside, so the node of every item is the same as in a parallel
+build. */
+fn build_subtree<M: Distance>(
+    metric: &M,
+    items: &[String],
+    subtree: Vec<usize>,
+    control: &Control<'_>,
+) -> Result<Vec<VpNode>, StrSimError> {
+    let mut nodes: Vec<VpNode> = Vec::with_capacity(subtree.len());
+    let total = subtree.len();
+    let mut pending: Vec<PendingSubtree> = Vec::new();
+    if !subtree.is_empty() {
+        pending.push((subtree, None));
+    }
+
+    let mut compared = 0;
+    while let Some((mut subtree, parent)) = pending.pop() {
+        let (item, radius, inside, outside) = split_subtree(metric, items, &mut subtree, || {
+            compared += 1;
+            control.check(compared)
+        })?;
+
+        let node = nodes.len();
+        nodes.push(VpNode {
+            item,
+            radius,
+            inside: None,
+            outside: None,
+        });
+        match parent {
+            Some((parent, true)) => nodes[parent].inside = Some(node),
+            Some((parent, false)) => nodes[parent].outside = Some(node),
+            None => {}
+        }
+        control.step(nodes.len(), total)?;
+
+        if !outside.is_empty() {
+            pending.push((outside, Some((node, false))));
+        }
+        if !inside.is_empty() {
+            pending.push((inside, Some((node, true))));
+        }
+    }
+
+    Ok(nodes)
+}
+
+/* Builds the nodes like `build_subtree`, but builds the outside half of
+every node on another thread while threads are left */
+#[cfg(feature = "parallel")]
+fn build_parallel<M>(
+    metric: &Arc<M>,
+    items: &Arc<Vec<String>>,
+    mut subtree: Vec<usize>,
+    threads: usize,
+) -> Vec<VpNode>
+where
+    M: Distance + Send + Sync + 'static,
+{
+    /* below this, spawning a thread costs more than it saves */
+    const MIN_PARALLEL_LEN: usize = 1024;
+
+    if threads <= 1 || subtree.len() < MIN_PARALLEL_LEN {
+        return match build_subtree(&**metric, items, subtree, &Control::new()) {
+            Ok(nodes) => nodes,
+            Err(_) => unreachable!("the build is never cancelled"),
+        };
+    }
+    let (item, radius, inside, outside) =
+        match split_subtree(&**metric, items, &mut subtree, || Ok(())) {
+            Ok(split) => split,
+            Err(_) => unreachable!("the build is never cancelled"),
+        };
+
+    let outside_threads = threads / 2;
+    let outside = {
+        let metric = Arc::clone(metric);
+        let items = Arc::clone(items);
+        thread::spawn(move || build_parallel(&metric, &items, outside, outside_threads))
+    };
+    let inside = build_parallel(metric, items, inside, threads - outside_threads);
+    let outside = match outside.join() {
+        Ok(nodes) => nodes,
+        Err(payload) => panic::resume_unwind(payload),
+    };
+
+    /* the root, then the inside and the outside subtree like in
+    `build_subtree`, with the indices of their nodes shifted */
+    let outside_root = 1 + inside.len();
+    let mut nodes = Vec::with_capacity(outside_root + outside.len());
+    nodes.push(VpNode {
+        item,
+        radius,
+        inside: Some(1).filter(|_| !inside.is_empty()),
+        outside: Some(outside_root).filter(|_| !outside.is_empty()),
+    });
+    for (subtree, shift) in [(inside, 1), (outside, outside_root)] {
+        nodes.extend(subtree.into_iter().map(|node| VpNode {
+            inside: node.inside.map(|child| child + shift),
+            outside: node.outside.map(|child| child + shift),
+            ..node
+        }));
+    }
+    nodes
+}
+
+/* Picks the first item of a non-empty `subtree` as vantage point and splits
+the others at the median of their distances to it. Returns the vantage
+point, the radius and the items inside and outside of it. `compared` is
+called after every distance. */
+fn split_subtree<M, F>(
+    metric: &M,
+    items: &[String],
+    subtree: &mut Vec<usize>,
+    mut compared: F,
+) -> Result<(usize, usize, Vec<usize>, Vec<usize>), StrSimError>
+where
+    M: Distance,
+    F: FnMut() -> Result<(), StrSimError>,
+{
+    let item = subtree.swap_remove(0);
+    let mut distances = Vec::with_capacity(subtree.len());
+    for &other in subtree.iter() {
+        distances.push((metric.distance(&items[item], &items[other]), other));
+        compared()?;
+    }
+    distances.sort_unstable();
+    /* splitting at the position of the median keeps the tree balanced
+    even if many distances are equal */
+    let split = distances.len() / 2;
+    let radius = distances.get(split).map_or(0, |&(distance, _)| distance);
+    let inside = distances[..split].iter().map(|&(_, other)| other).collect();
+    let outside = distances[split..].iter().map(|&(_, other)| other).collect();
+    Ok((item, radius, inside, outside))
+}
+
+/// The structure backing a `FuzzyIndex`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IndexKind {
+    /// Comparing the query with every string.
+    LinearScan,
+    /// A `VpTree`.
+    VpTree,
+}
+
+#[derive(Debug, Clone)]
+enum Backing<M> {
+    LinearScan { metric: M, items: Vec<String> },
+    VpTree(VpTree<M>),
+}
+
+/// An index of strings for similarity search which picks its backing
+/// structure from the number and length of the strings, the metric and the
+/// expected maximum distance of queries.
+///
+/// A `VpTree` only pays off for many strings, and only if queries are
+/// answered with distances well below the typical distance of the strings,
+/// since otherwise nearly no subtree can be skipped. For few strings, large
+/// distances and distances which are no `TrueMetric`, every string is
+/// compared with the query instead. Both return the same results.
+///
+/// ```
+/// use strsim::metric_space::{FuzzyIndex, IndexKind, Levenshtein, Osa};
+///
+/// let words: Vec<String> = (0..1000).map(|i| format!("word{}", i)).collect();
+/// let index = FuzzyIndex::new(Levenshtein, words.clone());
+/// assert_eq!(IndexKind::VpTree, index.kind());
+/// assert_eq!(vec![("word7", 0), ("word0", 1)], index.k_nearest("word7", 2));
+///
+/// let index = FuzzyIndex::with_max_distance(Levenshtein, words, 5);
+/// assert_eq!(IndexKind::LinearScan, index.kind());
+///
+/// let index = FuzzyIndex::linear_scan(Osa, vec!["abc".to_string(), "acb".to_string()]);
+/// assert_eq!(vec![("acb", 0), ("abc", 1)], index.find_within("acb", 1));
+/// ```
+#[derive(Debug, Clone)]
+pub struct FuzzyIndex<M> {
+    backing: Backing<M>,
+}
+
+impl<M: TrueMetric> FuzzyIndex<M> {
+    /// Builds the index of `items` for queries with small maximum
+    /// distances.
+    pub fn new(metric: M, items: Vec<String>) -> Self {
+        Self::with_max_distance(metric, items, 1)
+    }
+
+    /// Builds the index of `items` for queries with distances up to
+    /// `max_distance`, which also includes `k_nearest` queries whose results
+    /// are about that far away. Larger distances can still be queried.
+    pub fn with_max_distance(metric: M, items: Vec<String>, max_distance: usize) -> Self {
+        match Self::with_control(metric, items, max_distance, &Control::new()) {
+            Ok(index) => index,
+            Err(_) => unreachable!("the build is never cancelled"),
+        }
+    }
+
+    /// Builds the index like `with_max_distance`, unless the build is
+    /// cancelled through `control`.
+    pub fn with_control(
+        metric: M,
+        items: Vec<String>,
+        max_distance: usize,
+        control: &Control<'_>,
+    ) -> Result<Self, StrSimError> {
+        /* below this, building and descending the tree costs more than it
+        saves */
+        const MIN_TREE_LEN: usize = 256;
+
+        let total_len: usize = items.iter().map(|item| item.chars().count()).sum();
+        let mean_len = total_len / max(items.len(), 1);
+        /* the distances of random strings are roughly their length, and a
+        query radius of over half of it prunes next to nothing */
+        let backing = if items.len() < MIN_TREE_LEN || max_distance.saturating_mul(2) > mean_len {
+            Backing::LinearScan { metric, items }
+        } else {
+            Backing::VpTree(VpTree::with_control(metric, items, control)?)
+        };
+        Ok(Self { backing })
+    }
+}
+
+impl<M: Distance> FuzzyIndex<M> {
+    /// Builds an index of `items` which compares every string with the
+    /// query, for any distance.
+    pub fn linear_scan(metric: M, items: Vec<String>) -> Self {
+        Self {
+            backing: Backing::LinearScan { metric, items },
+        }
+    }
+
+    /// The structure backing the index.
+    pub fn kind(&self) -> IndexKind {
+        match self.backing {
+            Backing::LinearScan { .. } => IndexKind::LinearScan,
+            Backing::VpTree(_) => IndexKind::VpTree,
+        }
+    }
+
+    /// The number of strings in the index.
+    pub fn len(&self) -> usize {
+        match &self.backing {
+            Backing::LinearScan { items, .. } => items.len(),
+            Backing::VpTree(tree) => tree.len(),
+        }
+    }
+
+    /// Whether the index is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Serializes the index with its backing structure, like
+    /// `VpTree::to_bytes`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match &self.backing {
+            Backing::LinearScan { metric, items } => write_index(
+                IndexKind::LinearScan,
+                metric,
+                items,
+                &[],
+                &vec![false; items.len()],
+                0,
+            ),
+            Backing::VpTree(tree) => tree.to_bytes(),
+        }
+    }
+
+    /// Reads an index serialized with `to_bytes`, like `VpTree::from_bytes`.
+    ///
+    /// ```
+    /// use strsim::metric_space::{FuzzyIndex, IndexKind, Osa};
+    ///
+    /// let index = FuzzyIndex::linear_scan(Osa, vec!["abc".to_string(), "acb".to_string()]);
+    /// let index = FuzzyIndex::from_bytes(Osa, &index.to_bytes()).unwrap();
+    /// assert_eq!(IndexKind::LinearScan, index.kind());
+    /// assert_eq!(vec![("acb", 0), ("abc", 1)], index.find_within("acb", 1));
+    /// ```
+    pub fn from_bytes(metric: M, bytes: &[u8]) -> Result<Self, StrSimError> {
+        let layout = IndexLayout::parse(&metric, bytes)?;
+        let backing = match layout.kind {
+            IndexKind::LinearScan => Backing::LinearScan {
+                metric,
+                items: (0..layout.item_count)
+                    .map(|i| layout.item(i).to_string())
+                    .collect(),
+            },
+            /* the matching `MetricId` ensures that the metric is the true
+            metric the tree was built with */
+            IndexKind::VpTree => Backing::VpTree(VpTree::from_layout(metric, &layout)),
+        };
+        Ok(Self { backing })
+    }
+
+    /// Adds `item` to the index, like `VpTree::insert`. The backing
+    /// structure is kept.
+    ///
+    /// ```
+    /// use strsim::metric_space::{FuzzyIndex, Levenshtein};
+    ///
+    /// let mut index = FuzzyIndex::new(Levenshtein, vec!["@alice".to_string()]);
+    /// index.insert("@alicia".to_string());
+    /// assert!(index.remove("@alice"));
+    /// assert_eq!(vec![("@alicia", 2)], index.find_within("@alice", 2));
+    /// ```
+    pub fn insert(&mut self, item: String) {
+        match &mut self.backing {
+            Backing::LinearScan { items, .. } => items.push(item),
+            Backing::VpTree(tree) => tree.insert(item),
+        }
+    }
+
+    /// Removes one string equal to `item`, the one added first, and returns
+    /// whether there was one.
+    pub fn remove(&mut self, item: &str) -> bool {
+        match &mut self.backing {
+            Backing::LinearScan { items, .. } => match items.iter().position(|other| other == item)
+            {
+                Some(index) => {
+                    items.remove(index);
+                    true
+                }
+                None => false,
+            },
+            Backing::VpTree(tree) => tree.remove(item),
+        }
+    }
+
+    /// Returns the strings within `max_distance` of `query`, like
+    /// `VpTree::find_within`.
+    pub fn find_within(&self, query: &str, max_distance: usize) -> Vec<(&str, usize)> {
+        self.find_within_with_budget(query, max_distance, QueryBudget::new())
+            .matches
+    }
+
+    /// Returns the strings within `max_distance` of `query` found within the
+    /// `budget`, like `VpTree::find_within_with_budget`. A linear scan
+    /// compares the strings in the order they were added.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use strsim::metric_space::{FuzzyIndex, Levenshtein, QueryBudget};
+    ///
+    /// let words: Vec<String> = (0..1000).map(|i| format!("word{}", i)).collect();
+    /// let index = FuzzyIndex::new(Levenshtein, words);
+    /// let budget = QueryBudget::new().max_candidates(500).time_limit(Duration::from_secs(1));
+    /// let result = index.find_within_with_budget("word7", 0, budget);
+    /// assert_eq!(vec![("word7", 0)], result.matches);
+    /// ```
+    pub fn find_within_with_budget(
+        &self,
+        query: &str,
+        max_distance: usize,
+        budget: QueryBudget,
+    ) -> QueryResult<'_> {
+        match &self.backing {
+            Backing::LinearScan { metric, items } => {
+                let (found, complete) = scan(metric, items, query, budget);
+                let found = found
+                    .into_iter()
+                    .filter(|&(distance, _)| distance <= max_distance)
+                    .collect();
+                QueryResult {
+                    matches: results(found, |item| &items[item]),
+                    complete,
+                }
+            }
+            Backing::VpTree(tree) => tree.find_within_with_budget(query, max_distance, budget),
+        }
+    }
+
+    /// Returns the `k` strings closest to `query`, like
+    /// `VpTree::k_nearest`.
+    pub fn k_nearest(&self, query: &str, k: usize) -> Vec<(&str, usize)> {
+        self.k_nearest_with_budget(query, k, QueryBudget::new())
+            .matches
+    }
+
+    /// Returns the `k` strings closest to `query` found within the `budget`,
+    /// like `VpTree::k_nearest_with_budget`.
+    pub fn k_nearest_with_budget(
+        &self,
+        query: &str,
+        k: usize,
+        budget: QueryBudget,
+    ) -> QueryResult<'_> {
+        match &self.backing {
+            Backing::LinearScan { metric, items } => {
+                let (found, complete) = scan(metric, items, query, budget);
+                let mut matches = results(found, |item| &items[item]);
+                matches.truncate(k);
+                QueryResult { matches, complete }
+            }
+            Backing::VpTree(tree) => tree.k_nearest_with_budget(query, k, budget),
+        }
+    }
+}
+
+/* The distances and indices of the items compared with `query` within
+`budget`, in order, and whether all of them were compared */
+fn scan<M: Distance>(
+    metric: &M,
+    items: &[String],
+    query: &str,
+    budget: QueryBudget,
+) -> (Vec<(usize, usize)>, bool) {
+    let mut budget = budget.start();
+    let mut found = Vec::with_capacity(items.len());
+    for (i, item) in items.iter().enumerate() {
+        if !budget.compare() {
+            return (found, false);
+        }
+        found.push((metric.distance(query, item), i));
+    }
+    (found, true)
+}
+
+/* The layout of serialized indexes, with every integer as little endian:
+the magic bytes, the format version as u32, the `IndexKind` as byte, the
+name and version of the metric each as u32 length and UTF-8 bytes, the
+fingerprint of the metric and then as u64 the insertions and removals since
+the tree was built, the number of items and the number of nodes. The nodes
+follow as `item`, `radius`, `inside` and `outside` each as u64, with
+`NO_CHILD` for a missing child, then a byte per item which is 1 if it was
+removed, the offsets of the items as u64 including the end of the last item,
+and the items as one UTF-8 string.
+
+Every integer is read with `from_le_bytes`, so the bytes can be queried in
+place at any alignment. Children are always stored after their parent, which
+keeps every path through the nodes finite. */
+const INDEX_MAGIC: &[u8; 8] = b"strsimix";
+const INDEX_FORMAT: u32 = 1;
+const NO_CHILD: u64 = u64::MAX;
+const NODE_LEN: usize = 32;
+
+fn write_index<M: Distance>(
+    kind: IndexKind,
+    metric: &M,
+    items: &[String],
+    nodes: &[VpNode],
+    removed: &[bool],
+    changes: usize,
+) -> Vec<u8> {
+    let id = metric.id();
+    let data_len: usize = items.iter().map(String::len).sum();
+    let mut bytes = Vec::with_capacity(
+        64 + id.name().len()
+            + id.version().len()
+            + nodes.len() * NODE_LEN
+            + items.len() * 9
+            + data_len,
+    );
+    let write_u64 =
+        |bytes: &mut Vec<u8>, x: usize| bytes.extend_from_slice(&(x as u64).to_le_bytes());
+    bytes.extend_from_slice(INDEX_MAGIC);
+    bytes.extend_from_slice(&INDEX_FORMAT.to_le_bytes());
+    bytes.push(match kind {
+        IndexKind::LinearScan => 0,
+        IndexKind::VpTree => 1,
+    });
+    for text in [id.name(), id.version()] {
+        bytes.extend_from_slice(&(text.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(text.as_bytes());
+    }
+    bytes.extend_from_slice(&id.fingerprint().to_le_bytes());
+    write_u64(&mut bytes, changes);
+    write_u64(&mut bytes, items.len());
+    write_u64(&mut bytes, nodes.len());
+    for node in nodes {
+        write_u64(&mut bytes, node.item);
+        write_u64(&mut bytes, node.radius);
+        for child in [node.inside, node.outside] {
+            bytes.extend_from_slice(&child.map_or(NO_CHILD, |child| child as u64).to_le_bytes());
+        }
+    }
+    bytes.extend(removed.iter().map(|&removed| u8::from(removed)));
+    let mut offset = 0;
+    write_u64(&mut bytes, offset);
+    for item in items {
+        offset += item.len();
+        write_u64(&mut bytes, offset);
+    }
+    for item in items {
+        bytes.extend_from_slice(item.as_bytes());
+    }
+    bytes
+}
+
+/* The sections of a serialized index, which are checked when it is read */
+#[derive(Debug, Clone, Copy)]
+struct IndexLayout<'a> {
+    kind: IndexKind,
+    changes: usize,
+    item_count: usize,
+    node_count: usize,
+    nodes: &'a [u8],
+    removed: &'a [u8],
+    offsets: &'a [u8],
+    data: &'a str,
+}
+
+impl<'a> IndexLayout<'a> {
+    /* Reads the sections, and fails unless `bytes` are an index built with a
+    metric compatible with `metric` */
+    fn parse<M: Distance>(metric: &M, bytes: &'a [u8]) -> Result<Self, StrSimError> {
+        let mut reader = ByteReader { bytes };
+        if reader.take(INDEX_MAGIC.len())? != INDEX_MAGIC || reader.u32()? != INDEX_FORMAT {
+            return Err(StrSimError::MalformedIndex);
+        }
+        let kind = match reader.take(1)? {
+            [0] => IndexKind::LinearScan,
+            [1] => IndexKind::VpTree,
+            _ => return Err(StrSimError::MalformedIndex),
+        };
+        let name = reader.str()?;
+        let version = reader.str()?;
+        let fingerprint = u64::from_le_bytes(reader.array()?);
+        if !metric.id().is_compatible_with(name, version, fingerprint) {
+            return Err(StrSimError::MetricMismatch);
+        }
+
+        let changes = reader.usize()?;
+        let item_count = reader.usize()?;
+        let node_count = reader.usize()?;
+        let offset_count = item_count
+            .checked_add(1)
+            .ok_or(StrSimError::MalformedIndex)?;
+        let layout = Self {
+            kind,
+            changes,
+            item_count,
+            node_count,
+            nodes: reader.take_array(node_count, NODE_LEN)?,
+            removed: reader.take(item_count)?,
+            offsets: reader.take_array(offset_count, 8)?,
+            data: std::str::from_utf8(reader.bytes).map_err(|_| StrSimError::MalformedIndex)?,
+        };
+        if layout.is_valid() {
+            Ok(layout)
+        } else {
+            Err(StrSimError::MalformedIndex)
+        }
+    }
+
+    /* Whether the sections describe an index which can be queried without
+    reading outside of them or looping forever */
+    fn is_valid(&self) -> bool {
+        let nodes_valid = match self.kind {
+            IndexKind::LinearScan => self.node_count == 0 && self.changes == 0,
+            /* every item is the vantage point of one node */
+            IndexKind::VpTree => self.node_count == self.item_count,
+        };
+        let children_valid = (0..self.node_count).all(|i| {
+            let chunk = &self.nodes[i * NODE_LEN..(i + 1) * NODE_LEN];
+            let item = read_u64(&chunk[..8]);
+            let child_valid = |child: u64| {
+                child == NO_CHILD || (child > i as u64 && child < self.node_count as u64)
+            };
+            item < self.item_count as u64
+                && child_valid(read_u64(&chunk[16..24]))
+                && child_valid(read_u64(&chunk[24..]))
+        });
+        let removed_valid = match self.kind {
+            IndexKind::LinearScan => self.removed.iter().all(|&removed| removed == 0),
+            IndexKind::VpTree => self.removed.iter().all(|&removed| removed <= 1),
+        };
+        let mut previous = 0;
+        let offsets_valid = self.offsets.chunks_exact(8).enumerate().all(|(i, offset)| {
+            let offset = read_u64(offset);
+            let valid = (i > 0 || offset == 0)
+                && offset >= previous
+                && offset <= self.data.len() as u64
+                && self.data.is_char_boundary(offset as usize);
+            previous = offset;
+            valid
+        });
+        nodes_valid
+            && children_valid
+            && removed_valid
+            && offsets_valid
+            && previous == self.data.len() as u64
+    }
+
+    fn item(&self, i: usize) -> &'a str {
+        let start = read_u64(&self.offsets[i * 8..(i + 1) * 8]) as usize;
+        let end = read_u64(&self.offsets[(i + 1) * 8..(i + 2) * 8]) as usize;
+        &self.data[start..end]
+    }
+}
+
+impl<'a> VpNodes for IndexLayout<'a> {
+    fn node_count(&self) -> usize {
+        self.node_count
+    }
+
+    fn node(&self, node: usize) -> VpNode {
+        let chunk = &self.nodes[node * NODE_LEN..(node + 1) * NODE_LEN];
+        let child = |bytes: &[u8]| match read_u64(bytes) {
+            NO_CHILD => None,
+            child => Some(child as usize),
+        };
+        VpNode {
+            item: read_u64(&chunk[..8]) as usize,
+            /* radii beyond usize are never reached by a distance */
+            radius: usize::try_from(read_u64(&chunk[8..16])).unwrap_or(usize::MAX),
+            inside: child(&chunk[16..24]),
+            outside: child(&chunk[24..]),
+        }
+    }
+
+    fn item(&self, item: usize) -> &str {
+        IndexLayout::item(self, item)
+    }
+
+    fn is_removed(&self, item: usize) -> bool {
+        self.removed[item] == 1
+    }
+}
+
+/* Reads the sections of a serialized index from the front */
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], StrSimError> {
+        if len > self.bytes.len() {
+            return Err(StrSimError::MalformedIndex);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    /* `count` entries of `len` bytes */
+    fn take_array(&mut self, count: usize, len: usize) -> Result<&'a [u8], StrSimError> {
+        self.take(count.checked_mul(len).ok_or(StrSimError::MalformedIndex)?)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], StrSimError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn u32(&mut self) -> Result<u32, StrSimError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    /* A count, which has to fit into usize to address the bytes */
+    fn usize(&mut self) -> Result<usize, StrSimError> {
+        usize::try_from(u64::from_le_bytes(self.array()?)).map_err(|_| StrSimError::MalformedIndex)
+    }
+
+    fn str(&mut self) -> Result<&'a str, StrSimError> {
+        let len = usize::try_from(self.u32()?).map_err(|_| StrSimError::MalformedIndex)?;
+        std::str::from_utf8(self.take(len)?).map_err(|_| StrSimError::MalformedIndex)
+    }
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    let mut array = [0; 8];
+    array.copy_from_slice(bytes);
+    u64::from_le_bytes(array)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORDS: [&str; 9] = [
+        "", "a", "ab", "ba", "abc", "ca", "kitten", "sitting", "öঙ香",
+    ];
+
+    #[test]
+    fn distance_bounds_order() {
+        assert_eq!((0, 6), distance_bounds(3, 3));
+        assert_eq!((3, 3), distance_bounds(0, 3));
+        assert_eq!((3, 3), distance_bounds(3, 0));
+        assert_eq!((usize::MAX - 1, usize::MAX), distance_bounds(usize::MAX, 1));
+    }
+
+    #[test]
+    fn bounds_contain_distance() {
+        let pivots = Pivots::new(
+            DamerauLevenshtein,
+            vec!["abc".to_string(), "sitting".to_string()],
+        );
+        for a in &WORDS {
+            for b in &WORDS {
+                let distance = damerau_levenshtein(a, b);
+                let (a_pivots, b_pivots) = (pivots.distances(a), pivots.distances(b));
+                assert!(lower_bound(&a_pivots, &b_pivots) <= distance);
+                assert!(upper_bound(&a_pivots, &b_pivots).unwrap() >= distance);
+                assert!(!can_prune(&a_pivots, &b_pivots, distance));
+            }
+        }
+    }
+
+    #[test]
+    fn bounds_without_pivots() {
+        assert_eq!(0, lower_bound(&[], &[]));
+        assert_eq!(None, upper_bound(&[], &[]));
+    }
+
+    fn words() -> Vec<String> {
+        let mut words: Vec<String> = WORDS.iter().map(|word| word.to_string()).collect();
+        for i in 0..200 {
+            words.push(format!("{:x}", i * 7919 % 4096));
+        }
+        words.push("ab".to_string());
+        words
+    }
+
+    /* The expected results by comparing with every word */
+    fn linear_scan(words: &[String], query: &str) -> Vec<(usize, usize)> {
+        let mut distances: Vec<(usize, usize)> = words
+            .iter()
+            .enumerate()
+            .map(|(i, word)| (levenshtein(query, word), i))
+            .collect();
+        distances.sort_unstable();
+        distances
+    }
+
+    #[test]
+    fn vp_tree_find_within() {
+        let words = words();
+        let tree = VpTree::new(Levenshtein, words.clone());
+        assert_eq!(words.len(), tree.len());
+        for query in ["", "ab", "abc", "1f", "7a3", "kitten", "xyz"] {
+            for max_distance in 0..4 {
+                let expected: Vec<(&str, usize)> = linear_scan(&words, query)
+                    .into_iter()
+                    .filter(|&(distance, _)| distance <= max_distance)
+                    .map(|(distance, i)| (words[i].as_str(), distance))
+                    .collect();
+                assert_eq!(expected, tree.find_within(query, max_distance));
+            }
+        }
+    }
+
+    #[test]
+    fn vp_tree_k_nearest() {
+        let words = words();
+        let tree = VpTree::new(Levenshtein, words.clone());
+        for query in ["", "ab", "1f", "7a3", "kitten"] {
+            for k in [0, 1, 5, 50, 1000] {
+                let expected: Vec<(&str, usize)> = linear_scan(&words, query)
+                    .into_iter()
+                    .take(k)
+                    .map(|(distance, i)| (words[i].as_str(), distance))
+                    .collect();
+                assert_eq!(expected, tree.k_nearest(query, k));
+            }
+        }
+    }
+
+    #[test]
+    fn vp_tree_equal_items() {
+        let tree = VpTree::new(Levenshtein, vec!["a".to_string(); 10_000]);
+        assert_eq!(10_000, tree.find_within("a", 0).len());
+        assert_eq!(vec![("a", 1)], tree.k_nearest("b", 1));
+    }
+
+    #[test]
+    fn vp_tree_cancelled() {
+        let words: Vec<String> = (0..300).map(|i| format!("{:03}", i)).collect();
+        let cancelled = || true;
+        let control = Control::new().cancel_when(&cancelled);
+        assert_eq!(
+            Some(StrSimError::Cancelled),
+            VpTree::with_control(Levenshtein, words.clone(), &control).err()
+        );
+        assert_eq!(
+            Some(StrSimError::Cancelled),
+            FuzzyIndex::with_control(Levenshtein, words.clone(), 1, &control).err()
+        );
+        // a linear scan has nothing to build
+        assert!(FuzzyIndex::with_control(Levenshtein, words, 10, &control).is_ok());
+    }
+
+    #[test]
+    fn vp_tree_insert_and_remove() {
+        let words = words();
+        let mut tree = VpTree::new(Levenshtein, words[..50].to_vec());
+        let mut expected: Vec<String> = words[..50].to_vec();
+        for (i, word) in words[50..].iter().enumerate() {
+            tree.insert(word.clone());
+            expected.push(word.clone());
+            if i % 3 == 0 {
+                let removed = expected.remove(i % expected.len());
+                assert!(tree.remove(&removed));
+            }
+            assert_eq!(expected.len(), tree.len());
+            if i % 10 == 0 {
+                for query in ["", "ab", "1f", "kitten"] {
+                    let scan: Vec<(&str, usize)> = linear_scan(&expected, query)
+                        .into_iter()
+                        .map(|(distance, i)| (expected[i].as_str(), distance))
+                        .collect();
+                    let within: Vec<(&str, usize)> = scan
+                        .iter()
+                        .copied()
+                        .filter(|&(_, distance)| distance <= 2)
+                        .collect();
+                    assert_eq!(within, tree.find_within(query, 2));
+                    assert_eq!(scan[..5].to_vec(), tree.k_nearest(query, 5));
+                }
+            }
+        }
+        assert!(!tree.remove("not in the tree"));
+    }
+
+    #[test]
+    fn vp_tree_remove_duplicates() {
+        let mut tree = VpTree::new(Levenshtein, vec!["a".to_string(), "a".to_string()]);
+        assert!(tree.remove("a"));
+        assert_eq!(vec![("a", 0)], tree.find_within("a", 0));
+        assert!(tree.remove("a"));
+        assert!(tree.is_empty());
+        assert!(!tree.remove("a"));
+        assert!(tree.k_nearest("a", 1).is_empty());
+        tree.insert("b".to_string());
+        assert_eq!(vec![("b", 1)], tree.k_nearest("a", 1));
+    }
+
+    #[test]
+    fn fuzzy_index_insert_and_remove() {
+        let words = words();
+        let mut scan = FuzzyIndex::linear_scan(Levenshtein, words[..100].to_vec());
+        // too few words for FuzzyIndex::new to pick a tree
+        let mut tree = FuzzyIndex {
+            backing: Backing::VpTree(VpTree::new(Levenshtein, words[..100].to_vec())),
+        };
+        for word in &words[100..] {
+            tree.insert(word.clone());
+            scan.insert(word.clone());
+        }
+        for word in &words[..80] {
+            assert_eq!(scan.remove(word), tree.remove(word));
+        }
+        assert!(!scan.remove("not in the index"));
+        assert_eq!(scan.len(), tree.len());
+        for query in ["", "ab", "1f", "7a3", "kitten"] {
+            assert_eq!(scan.find_within(query, 2), tree.find_within(query, 2));
+            assert_eq!(scan.k_nearest(query, 7), tree.k_nearest(query, 7));
+        }
+    }
+
+    #[test]
+    fn query_budget_limits_comparisons() {
+        let words = words();
+        let tree = FuzzyIndex {
+            backing: Backing::VpTree(VpTree::new(Levenshtein, words.clone())),
+        };
+        let scan = FuzzyIndex::linear_scan(Levenshtein, words.clone());
+        for index in [&tree, &scan] {
+            let unlimited = QueryBudget::new().max_candidates(words.len());
+            let result = index.find_within_with_budget("ab", 2, unlimited);
+            assert!(result.complete);
+            assert_eq!(index.find_within("ab", 2), result.matches);
+            let result = index.k_nearest_with_budget("ab", 5, unlimited);
+            assert!(result.complete);
+            assert_eq!(index.k_nearest("ab", 5), result.matches);
+
+            for max_candidates in [0, 1, 10, 100] {
+                let budget = QueryBudget::new().max_candidates(max_candidates);
+                let result = index.find_within_with_budget("ab", 2, budget);
+                assert!(!result.complete);
+                assert!(result.matches.len() <= max_candidates);
+                let all = index.find_within("ab", 2);
+                assert!(result.matches.iter().all(|found| all.contains(found)));
+
+                let result = index.k_nearest_with_budget("ab", 5, budget);
+                assert!(!result.complete);
+                assert_eq!(max_candidates.min(5), result.matches.len());
+                assert!(result
+                    .matches
+                    .iter()
+                    .all(|&(word, distance)| levenshtein("ab", word) == distance));
+                assert!(result.matches.windows(2).all(|w| w[0].1 <= w[1].1));
+            }
+
+            let expired = QueryBudget::new().time_limit(Duration::from_secs(0));
+            assert!(!index.k_nearest_with_budget("ab", 5, expired).complete);
+            let forever = QueryBudget::new().time_limit(Duration::from_secs(u64::MAX));
+            assert!(index.k_nearest_with_budget("ab", 5, forever).complete);
+        }
+
+        let empty = VpTree::new(Levenshtein, Vec::new());
+        let none = QueryBudget::new().max_candidates(0);
+        assert!(empty.find_within_with_budget("a", 1, none).complete);
+        assert!(
+            VpTree::new(Levenshtein, words)
+                .k_nearest_with_budget("a", 0, none)
+                .complete
+        );
+    }
+
+    #[test]
+    fn vp_tree_empty() {
+        let tree = VpTree::new(Levenshtein, Vec::new());
+        assert!(tree.is_empty());
+        assert!(tree.find_within("a", 5).is_empty());
+        assert!(tree.k_nearest("a", 5).is_empty());
+    }
+
+    #[test]
+    fn fuzzy_index_kind() {
+        let words: Vec<String> = (0..300).map(|i| format!("{:03}", i)).collect();
+        assert_eq!(
+            IndexKind::VpTree,
+            FuzzyIndex::new(Levenshtein, words.clone()).kind()
+        );
+        assert_eq!(
+            IndexKind::LinearScan,
+            FuzzyIndex::with_max_distance(Levenshtein, words.clone(), 2).kind()
+        );
+        assert_eq!(
+            IndexKind::LinearScan,
+            FuzzyIndex::new(Levenshtein, words[..10].to_vec()).kind()
+        );
+        assert_eq!(
+            IndexKind::LinearScan,
+            FuzzyIndex::linear_scan(Levenshtein, words).kind()
+        );
+        assert!(FuzzyIndex::new(Levenshtein, Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn fuzzy_index_backings_agree() {
+        let mut words = words();
+        words.extend((0..100).map(|i| format!("{:x}", i * 31)));
+        let tree = FuzzyIndex::new(Levenshtein, words.clone());
+        assert_eq!(IndexKind::VpTree, tree.kind());
+        let scan = FuzzyIndex::linear_scan(Levenshtein, words.clone());
+        assert_eq!(words.len(), scan.len());
+        for query in ["", "ab", "1f", "7a3", "kitten"] {
+            for n in [0, 1, 3, 50] {
+                assert_eq!(tree.find_within(query, n), scan.find_within(query, n));
+                assert_eq!(tree.k_nearest(query, n), scan.k_nearest(query, n));
+            }
+        }
+    }
+
+    /* A Levenshtein distance which only counts every `step`-th edit */
+    struct Scaled {
+        step: usize,
+        version: &'static str,
+    }
+
+    impl Distance for Scaled {
+        fn distance(&self, a: &str, b: &str) -> usize {
+            levenshtein(a, b) / self.step
+        }
+
+        fn id(&self) -> MetricId {
+            MetricId::new("scaled", self.version).parameter(&(self.step as u64).to_le_bytes())
+        }
+    }
+
+    #[test]
+    fn vp_tree_serialized() {
+        let words = words();
+        let mut tree = VpTree::new(Levenshtein, words[..150].to_vec());
+        for word in &words[150..] {
+            tree.insert(word.clone());
+        }
+        for word in &words[..20] {
+            tree.remove(word);
+        }
+        let bytes = tree.to_bytes();
+        let mut read = VpTree::from_bytes(Levenshtein, &bytes).unwrap();
+        assert_eq!(bytes, read.to_bytes());
+        assert_eq!(tree.len(), read.len());
+        for query in ["", "ab", "1f", "7a3", "kitten"] {
+            assert_eq!(tree.find_within(query, 2), read.find_within(query, 2));
+            assert_eq!(tree.k_nearest(query, 7), read.k_nearest(query, 7));
+        }
+        /* the changes since the tree was built are kept */
+        for word in &words[20..40] {
+            assert_eq!(tree.remove(word), read.remove(word));
+            assert_eq!(tree.to_bytes(), read.to_bytes());
+        }
+
+        let empty = VpTree::new(Levenshtein, Vec::new()).to_bytes();
+        assert!(VpTree::from_bytes(Levenshtein, &empty).unwrap().is_empty());
+    }
+
+    #[test]
+    fn vp_tree_view_matches_tree() {
+        let words = words();
+        let mut tree = VpTree::new(Levenshtein, words[..150].to_vec());
+        for word in &words[150..] {
+            tree.insert(word.clone());
+        }
+        for word in &words[..20] {
+            tree.remove(word);
+        }
+        let bytes = tree.to_bytes();
+        let view = VpTreeView::new(Levenshtein, &bytes).unwrap();
+        assert_eq!(tree.len(), view.len());
+        for query in ["", "ab", "1f", "7a3", "kitten"] {
+            for n in [0, 1, 3, 50] {
+                assert_eq!(tree.find_within(query, n), view.find_within(query, n));
+                assert_eq!(tree.k_nearest(query, n), view.k_nearest(query, n));
+            }
+            let budget = QueryBudget::new().max_candidates(10);
+            assert_eq!(
+                tree.k_nearest_with_budget(query, 5, budget),
+                view.k_nearest_with_budget(query, 5, budget)
+            );
+        }
+
+        let empty = VpTree::new(Levenshtein, Vec::new()).to_bytes();
+        let view = VpTreeView::new(Levenshtein, &empty).unwrap();
+        assert!(view.is_empty());
+        assert!(view.k_nearest("a", 1).is_empty());
+        let scan = FuzzyIndex::linear_scan(Levenshtein, vec!["a".to_string()]).to_bytes();
+        assert_eq!(
+            Some(StrSimError::MalformedIndex),
+            VpTreeView::new(Levenshtein, &scan).err()
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn vp_tree_parallel_matches_sequential() {
+        let mut words = words();
+        for i in 0..3000 {
+            words.push(format!("{:x}", i * 104_729 % 65_536));
+        }
+        let tree = VpTree::new(Levenshtein, words.clone());
+        for threads in [0, 1, 2, 3, 8] {
+            let parallel = VpTree::new_parallel(Levenshtein, words.clone(), threads);
+            assert_eq!(tree.to_bytes(), parallel.to_bytes());
+        }
+        assert!(VpTree::new_parallel(Levenshtein, Vec::new(), 4).is_empty());
+    }
+
+    #[test]
+    fn fuzzy_index_serialized() {
+        let words = words();
+        let scan = FuzzyIndex::linear_scan(Levenshtein, words.clone());
+        let tree = FuzzyIndex {
+            backing: Backing::VpTree(VpTree::new(Levenshtein, words)),
+        };
+        for index in [scan, tree] {
+            let read = FuzzyIndex::from_bytes(Levenshtein, &index.to_bytes()).unwrap();
+            assert_eq!(index.kind(), read.kind());
+            for query in ["", "ab", "1f", "kitten"] {
+                assert_eq!(index.find_within(query, 2), read.find_within(query, 2));
+                assert_eq!(index.k_nearest(query, 7), read.k_nearest(query, 7));
+            }
+        }
+        let scan = FuzzyIndex::linear_scan(Levenshtein, vec!["a".to_string()]).to_bytes();
+        assert_eq!(
+            Some(StrSimError::MalformedIndex),
+            VpTree::from_bytes(Levenshtein, &scan).err()
+        );
+    }
+
+    #[test]
+    fn serialized_metric_mismatch() {
+        let metric = |step, version| Scaled { step, version };
+        let words = words();
+        let bytes = VpTree::new(Levenshtein, words.clone()).to_bytes();
+        assert_eq!(
+            Some(StrSimError::MetricMismatch),
+            VpTree::from_bytes(DamerauLevenshtein, &bytes).err()
+        );
+        let bytes = FuzzyIndex::linear_scan(metric(2, "1.0.0"), words).to_bytes();
+        assert!(FuzzyIndex::from_bytes(metric(2, "1.3.0"), &bytes).is_ok());
+        for other in [metric(3, "1.0.0"), metric(2, "2.0.0"), metric(2, "0.1.0")] {
+            assert_eq!(
+                Some(StrSimError::MetricMismatch),
+                FuzzyIndex::from_bytes(other, &bytes).err()
+            );
+        }
+    }
+
+    #[test]
+    fn malformed_serialized_index() {
+        let words: Vec<String> = ["", "ab", "öঙ香", "kitten", "sitting"]
+            .iter()
+            .map(|word| word.to_string())
+            .collect();
+        let bytes = VpTree::new(Levenshtein, words).to_bytes();
+        for len in 0..bytes.len() {
+            assert_eq!(
+                Some(StrSimError::MalformedIndex),
+                VpTree::from_bytes(Levenshtein, &bytes[..len]).err()
+            );
+        }
+        let mut longer = bytes.clone();
+        longer.push(b'a');
+        assert_eq!(
+            Some(StrSimError::MalformedIndex),
+            VpTree::from_bytes(Levenshtein, &longer).err()
+        );
+        /* corrupted bytes either fail or still give a tree which can be
+        queried */
+        for i in 0..bytes.len() {
+            for byte in [0, 1, 0x80, 0xff] {
+                let mut corrupted = bytes.clone();
+                corrupted[i] = byte;
+                if let Ok(tree) = VpTree::from_bytes(Levenshtein, &corrupted) {
+                    tree.find_within("kitten", 3);
+                    tree.k_nearest("kitten", 3);
+                }
+                if let Ok(view) = VpTreeView::new(Levenshtein, &corrupted) {
+                    view.find_within("kitten", 3);
+                    view.k_nearest("kitten", 3);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn osa_violates_triangle_inequality() {
+        assert!(Osa.distance("ca", "abc") > Osa.distance("ca", "ac") + Osa.distance("ac", "abc"));
+        assert_eq!(osa_distance("ab", "ba"), Osa.distance("ab", "ba"));
+    }
+}
@@ -0,0 +1,339 @@
+//! Phonetic encoders, which map names that sound alike to the same code.
+//! Comparing the codes instead of the spelling finds matches like `Robert`
+//! and `Rupert`.
+//!
+//! Every encoder is available as a function and as a type implementing
+//! `PhoneticEncoder`, so the encoder used for a dataset can be chosen at
+//! runtime.
+
+/// A phonetic encoding algorithm.
+///
+/// ```
+/// use strsim::phonetic::{PhoneticEncoder, Phonex, RefinedSoundex, Soundex};
+///
+/// let encoders: [&dyn PhoneticEncoder; 3] = [&Soundex, &RefinedSoundex, &Phonex];
+/// for encoder in encoders {
+///     assert!(encoder.sounds_alike("Smith", "Smyth"));
+/// }
+/// ```
+pub trait PhoneticEncoder {
+    /// Encodes `s`. Strings without letters result in an empty code.
+    fn encode(&self, s: &str) -> String;
+
+    /// Whether both strings have the same code. Strings without letters
+    /// never sound alike.
+    fn sounds_alike(&self, a: &str, b: &str) -> bool {
+        let a = self.encode(a);
+        !a.is_empty() && a == self.encode(b)
+    }
+}
+
+/* The uppercase ASCII letters of a string. Like in the reference
+implementations all other characters are ignored */
+fn ascii_letters(s: &str) -> Vec<u8> {
+    s.bytes()
+        .filter(u8::is_ascii_alphabetic)
+        .map(|b| b.to_ascii_uppercase())
+        .collect()
+}
+
+/* The Soundex digit of an uppercase ASCII letter. Vowels and `Y` map to 0,
+`H` and `W` to `None` since they don't separate equal digits */
+fn soundex_digit(c: char) -> Option<u8> {
+    match c {
+        'B' | 'F' | 'P' | 'V' => Some(1),
+        'C' | 'G' | 'J' | 'K' | 'Q' | 'S' | 'X' | 'Z' => Some(2),
+        'D' | 'T' => Some(3),
+        'L' => Some(4),
+        'M' | 'N' => Some(5),
+        'R' => Some(6),
+        'H' | 'W' => None,
+        _ => Some(0),
+    }
+}
+
+/// Encodes a name with American Soundex: the first letter followed by three
+/// digits for the following consonants. Characters other than ASCII letters
+/// are ignored, and a string without letters results in an empty code.
+///
+/// ```
+/// use strsim::phonetic::soundex;
+///
+/// assert_eq!("R163", soundex("Robert"));
+/// assert_eq!("R163", soundex("Rupert"));
+/// assert_eq!("T522", soundex("Tymczak"));
+/// assert_eq!("L000", soundex("Lee"));
+/// ```
+pub fn soundex(s: &str) -> String {
+    let mut letters = s
+        .chars()
+        .filter(char::is_ascii_alphabetic)
+        .map(|c| c.to_ascii_uppercase());
+
+    let first = match letters.next() {
+        Some(first) => first,
+        None => return String::new(),
+    };
+
+    let mut code = String::with_capacity(4);
+    code.push(first);
+    let mut last_digit = soundex_digit(first).unwrap_or(0);
+
+    for c in letters {
+        if code.len() == 4 {
+            break;
+        }
+        match soundex_digit(c) {
+            None => {}
+            Some(0) => last_digit = 0,
+            Some(digit) => {
+                if digit != last_digit {
+                    code.push(char::from(b'0' + digit));
+                }
+                last_digit = digit;
+            }
+        }
+    }
+
+    while code.len() < 4 {
+        code.push('0');
+    }
+    code
+}
+
+/// American Soundex, see `soundex`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Soundex;
+
+impl PhoneticEncoder for Soundex {
+    fn encode(&self, s: &str) -> String {
+        soundex(s)
+    }
+}
+
+/* The Refined Soundex digits of the letters A to Z */
+const REFINED_SOUNDEX_DIGITS: &[u8; 26] = b"01360240043788015936020505";
+
+/// Encodes a name with Refined Soundex, as implemented by Apache Commons
+/// Codec: the first letter followed by a digit for every letter, including
+/// the vowels, where repeated digits are collapsed. The code is not truncated,
+/// and it separates more names than `soundex` does.
+///
+/// ```
+/// use strsim::phonetic::refined_soundex;
+///
+/// assert_eq!("T6036084", refined_soundex("testing"));
+/// assert_eq!("B1908", refined_soundex("Brown"));
+/// assert_eq!(refined_soundex("Smith"), refined_soundex("Smyth"));
+/// ```
+pub fn refined_soundex(s: &str) -> String {
+    let letters = ascii_letters(s);
+    let first = match letters.first() {
+        Some(&first) => first,
+        None => return String::new(),
+    };
+
+    let mut code = String::with_capacity(letters.len() + 1);
+    code.push(char::from(first));
+    let mut last_digit = None;
+    for letter in letters {
+        let digit = REFINED_SOUNDEX_DIGITS[usize::from(letter - b'A')];
+        if last_digit != Some(digit) {
+            code.push(char::from(digit));
+            last_digit = Some(digit);
+        }
+    }
+    code
+}
+
+/// Refined Soundex, see `refined_soundex`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RefinedSoundex;
+
+impl PhoneticEncoder for RefinedSoundex {
+    fn encode(&self, s: &str) -> String {
+        refined_soundex(s)
+    }
+}
+
+fn is_phonex_vowel(letter: Option<&u8>) -> bool {
+    matches!(letter, Some(b'A' | b'E' | b'I' | b'O' | b'U' | b'Y'))
+}
+
+/// Encodes a name with Phonex (Lait and Randell, 1996), which combines
+/// Soundex with preprocessing rules from Phonix: trailing `S`es are removed,
+/// the leading letters `KN`, `PH` and `WR` and a leading `H` are simplified, and
+/// similar sounding first letters like `P` and `B` are merged. Like in the
+/// original C implementation, `L` and `R` are only coded when followed by a
+/// vowel or at the end of the name. The code consists of a letter and three
+/// digits.
+///
+/// ```
+/// use strsim::phonetic::phonex;
+///
+/// assert_eq!("C214", phonex("Cachpole"));
+/// assert_eq!("N100", phonex("Kneves"));
+/// assert_eq!(phonex("Philips"), phonex("Filip"));
+/// ```
+pub fn phonex(s: &str) -> String {
+    let mut name = ascii_letters(s);
+    while name.last() == Some(&b'S') {
+        name.pop();
+    }
+    if name.starts_with(b"KN") || name.starts_with(b"WR") {
+        name.remove(0);
+    } else if name.starts_with(b"PH") {
+        name[1] = b'F';
+        name.remove(0);
+    }
+    if name.first() == Some(&b'H') {
+        name.remove(0);
+    }
+
+    let first = match name.first() {
+        Some(b'A' | b'E' | b'I' | b'O' | b'U' | b'Y') => b'A',
+        Some(b'P') => b'B',
+        Some(b'V') => b'F',
+        Some(b'K' | b'Q') => b'C',
+        Some(b'J') => b'G',
+        Some(b'Z') => b'S',
+        Some(&first) => first,
+        None => return String::new(),
+    };
+
+    let mut code = String::with_capacity(4);
+    code.push(char::from(first));
+    let mut last = first;
+    let mut i = 1;
+    while i < name.len() && code.len() < 4 {
+        let next = name.get(i + 1);
+        let digit = match name[i] {
+            b'B' | b'F' | b'P' | b'V' => b'1',
+            b'C' | b'G' | b'J' | b'K' | b'Q' | b'S' | b'X' | b'Z' => b'2',
+            b'D' | b'T' if next != Some(&b'C') => b'3',
+            b'L' if next.is_none() || is_phonex_vowel(next) => b'4',
+            b'M' | b'N' => {
+                // a following D or G is silent, which the reference
+                // implementation expresses by repeating the M or N
+                if let Some(b'D' | b'G') = next {
+                    name[i + 1] = name[i];
+                }
+                b'5'
+            }
+            b'R' if next.is_none() || is_phonex_vowel(next) => b'6',
+            _ => b'0',
+        };
+        if digit != b'0' && digit != last {
+            code.push(char::from(digit));
+            last = digit;
+        }
+        i += 1;
+    }
+
+    while code.len() < 4 {
+        code.push('0');
+    }
+    code
+}
+
+/// Phonex, see `phonex`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Phonex;
+
+impl PhoneticEncoder for Phonex {
+    fn encode(&self, s: &str) -> String {
+        phonex(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn soundex_empty() {
+        assert_eq!("", soundex(""));
+        assert_eq!("", soundex("123 -"));
+    }
+
+    #[test]
+    fn soundex_reference_codes() {
+        let codes = [
+            ("Robert", "R163"),
+            ("Rupert", "R163"),
+            ("Rubin", "R150"),
+            ("Ashcraft", "A261"),
+            ("Ashcroft", "A261"),
+            ("Tymczak", "T522"),
+            ("Pfister", "P236"),
+            ("Honeyman", "H555"),
+            ("Gutierrez", "G362"),
+            ("Jackson", "J250"),
+        ];
+        for (name, code) in codes {
+            assert_eq!(code, soundex(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn soundex_ignores_case_and_other_characters() {
+        assert_eq!(soundex("O'Brien"), soundex("obrien"));
+        assert_eq!("A000", soundex("a"));
+        assert_eq!("M460", soundex("Müller"));
+    }
+
+    #[test]
+    fn refined_soundex_reference_codes() {
+        // test cases taken from Apache Commons Codec
+        let codes = [
+            ("testing", "T6036084"),
+            ("TESTING", "T6036084"),
+            ("The", "T60"),
+            ("quick", "Q503"),
+            ("brown", "B1908"),
+            ("fox", "F205"),
+            ("jumped", "J408106"),
+            ("over", "O0209"),
+            ("lazy", "L7050"),
+            ("dogs", "D6043"),
+        ];
+        for (name, code) in codes {
+            assert_eq!(code, refined_soundex(name), "{}", name);
+        }
+        assert_eq!("", refined_soundex("42"));
+    }
+
+    #[test]
+    fn phonex_reference_codes() {
+        // test cases taken from the Abydos implementation
+        let codes = [
+            ("Ewell", "A400"),
+            ("Filp", "F100"),
+            ("Heames", "A500"),
+            ("Kneves", "N100"),
+            ("River", "R160"),
+            ("Corley", "C400"),
+            ("Carton", "C350"),
+            ("Cachpole", "C214"),
+        ];
+        for (name, code) in codes {
+            assert_eq!(code, phonex(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn phonex_silent_letters() {
+        assert_eq!(phonex("Sandy"), phonex("Sany"));
+        assert_eq!("S560", phonex("Singer"));
+        assert_eq!("", phonex("sss"));
+        assert_eq!("", phonex(""));
+    }
+
+    #[test]
+    fn encoders_sound_alike() {
+        assert!(Soundex.sounds_alike("Robert", "Rupert"));
+        assert!(RefinedSoundex.sounds_alike("Robert", "Rupert"));
+        assert!(!RefinedSoundex.sounds_alike("Robert", "Rubin"));
+        assert!(!Phonex.sounds_alike("", "!"));
+    }
+}
@@ -0,0 +1,824 @@
+//! Preprocessing applied to strings before they are compared, for scripts
+//! where comparing the Unicode scalar values gives poor results.
+
+const HANGUL_SYLLABLES_START: u32 = 0xAC00;
+const HANGUL_SYLLABLES_COUNT: u32 = 11172;
+const LEADING_START: u32 = 0x1100;
+const VOWEL_START: u32 = 0x1161;
+const TRAILING_START: u32 = 0x11A7;
+const VOWEL_COUNT: u32 = 21;
+const TRAILING_COUNT: u32 = 28;
+
+/* Appends the jamo of a precomposed Hangul syllable to `out`, or returns
+false if `c` is no such syllable */
+fn push_jamo(c: char, out: &mut String) -> bool {
+    let index = match u32::from(c).checked_sub(HANGUL_SYLLABLES_START) {
+        Some(index) if index < HANGUL_SYLLABLES_COUNT => index,
+        _ => return false,
+    };
+
+    let leading = LEADING_START + index / (VOWEL_COUNT * TRAILING_COUNT);
+    let vowel = VOWEL_START + index % (VOWEL_COUNT * TRAILING_COUNT) / TRAILING_COUNT;
+    let trailing = index % TRAILING_COUNT;
+    // all of these are assigned code points, so the conversions can't fail
+    out.extend(char::from_u32(leading));
+    out.extend(char::from_u32(vowel));
+    if trailing != 0 {
+        out.extend(char::from_u32(TRAILING_START + trailing));
+    }
+    true
+}
+
+/// Decomposes the precomposed Hangul syllables of a string into their
+/// conjoining jamo, like the canonical decomposition of Unicode does. This
+/// way a typo in a single vowel or consonant only changes one of the two or
+/// three jamo of a syllable instead of the whole syllable. Other characters,
+/// including the compatibility jamo like `ㄱ`, are kept.
+///
+/// ```
+/// use strsim::normalized_levenshtein;
+/// use strsim::preprocess::decompose_hangul;
+///
+/// assert_eq!("\u{1112}\u{1161}\u{11AB}", decompose_hangul("한"));
+///
+/// // the first syllables only differ in their vowel
+/// assert_eq!(0.5, normalized_levenshtein("학교", "핵교"));
+/// assert_eq!(0.8, normalized_levenshtein(&decompose_hangul("학교"), &decompose_hangul("핵교")));
+/// ```
+pub fn decompose_hangul(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() * 3);
+    for c in s.chars() {
+        if !push_jamo(c, &mut out) {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/* The full-width forms of the half-width katakana and punctuation from
+U+FF61 to U+FF9F */
+const HALF_WIDTH_KATAKANA: &str =
+    "。「」、・ヲァィゥェォャュョッーアイウエオカキクケコサシスセソタチツテトナニヌネノハヒフヘホマミムメモヤユヨラリルレロワン゛゜";
+
+/* The regular forms of the full-width signs from U+FFE0 to U+FFE6 and of the
+half-width symbols from U+FFE8 to U+FFEE */
+const FULL_WIDTH_SIGNS: &str = "¢£¬¯¦¥₩";
+const HALF_WIDTH_SYMBOLS: &str = "│←↑→↓■○";
+
+/* Combines a katakana with a following voiced (dakuten) or semi-voiced
+(handakuten) sound mark, like `カ` and `゛` to `ガ` */
+fn combine_sound_mark(kana: char, mark: char) -> Option<char> {
+    let offset = match (kana, mark) {
+        ('ウ', '゛') => return Some('ヴ'),
+        ('ワ', '゛') => return Some('ヷ'),
+        ('ヲ', '゛') => return Some('ヺ'),
+        (
+            'カ' | 'キ' | 'ク' | 'ケ' | 'コ' | 'サ' | 'シ' | 'ス' | 'セ' | 'ソ' | 'タ' | 'チ'
+            | 'ツ' | 'テ' | 'ト' | 'ハ' | 'ヒ' | 'フ' | 'ヘ' | 'ホ',
+            '゛',
+        ) => 1,
+        ('ハ' | 'ヒ' | 'フ' | 'ヘ' | 'ホ', '゜') => 2,
+        _ => return None,
+    };
+    char::from_u32(u32::from(kana) + offset)
+}
+
+/// Folds full-width and half-width forms: full-width ASCII characters like
+/// `Ａ` and the ideographic space are replaced with their ASCII counterparts,
+/// half-width katakana like `ｶﾞ` with full-width katakana like `ガ`, and the
+/// remaining forms of the Halfwidth and Fullwidth Forms block except for
+/// Hangul, like `￥`, with their regular forms.
+///
+/// ```
+/// use strsim::preprocess::fold_width;
+///
+/// assert_eq!("ABC 123", fold_width("ＡＢＣ\u{3000}１２３"));
+/// assert_eq!("ガイド", fold_width("ｶﾞｲﾄﾞ"));
+/// ```
+pub fn fold_width(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        let code = u32::from(c);
+        let folded = match code {
+            0x3000 => ' ',
+            0xFF01..=0xFF5E => char::from_u32(code - 0xFEE0).unwrap_or(c),
+            0xFF61..=0xFF9F => HALF_WIDTH_KATAKANA
+                .chars()
+                .nth((code - 0xFF61) as usize)
+                .unwrap_or(c),
+            0xFFE0..=0xFFE6 => FULL_WIDTH_SIGNS
+                .chars()
+                .nth((code - 0xFFE0) as usize)
+                .unwrap_or(c),
+            0xFFE8..=0xFFEE => HALF_WIDTH_SYMBOLS
+                .chars()
+                .nth((code - 0xFFE8) as usize)
+                .unwrap_or(c),
+            _ => c,
+        };
+
+        let combined = match (out.chars().last(), folded) {
+            (Some(kana), '゛' | '゜') if (0xFF9E..=0xFF9F).contains(&code) => {
+                combine_sound_mark(kana, folded)
+            }
+            _ => None,
+        };
+        match combined {
+            Some(combined) => {
+                out.pop();
+                out.push(combined);
+            }
+            None => out.push(folded),
+        }
+    }
+    out
+}
+
+/* Appends the characters a compatibility character stands for to `out`, or
+returns false if `c` is not one of the supported compatibility characters */
+fn push_compatibility_form(c: char, out: &mut String) -> bool {
+    let code = u32::from(c);
+    let number = |start: u32, first: u32| code - start + first;
+    let letter = |start: u32, first: u8| char::from(first + (code - start) as u8);
+
+    match code {
+        // spaces of various widths
+        0x00A0 | 0x2000..=0x200A | 0x202F | 0x205F => out.push(' '),
+        0x00B2 => out.push('2'),
+        0x00B3 => out.push('3'),
+        0x00B9 => out.push('1'),
+        0x2070 => out.push('0'),
+        0x2074..=0x2079 => out.push_str(&number(0x2074, 4).to_string()),
+        0x2080..=0x2089 => out.push_str(&number(0x2080, 0).to_string()),
+        0x2103 => out.push_str("°C"),
+        0x2109 => out.push_str("°F"),
+        0x2116 => out.push_str("No"),
+        0x2121 => out.push_str("TEL"),
+        0x2122 => out.push_str("TM"),
+        0x2160..=0x216F => out.push_str(ROMAN_NUMERALS[(code - 0x2160) as usize]),
+        0x2170..=0x217F => out.push_str(&ROMAN_NUMERALS[(code - 0x2170) as usize].to_lowercase()),
+        0x2460..=0x2473 => out.push_str(&number(0x2460, 1).to_string()),
+        0x2474..=0x2487 => out.push_str(&format!("({})", number(0x2474, 1))),
+        0x2488..=0x249B => out.push_str(&format!("{}.", number(0x2488, 1))),
+        0x249C..=0x24B5 => out.push_str(&format!("({})", letter(0x249C, b'a'))),
+        0x24B6..=0x24CF => out.push(letter(0x24B6, b'A')),
+        0x24D0..=0x24E9 => out.push(letter(0x24D0, b'a')),
+        0x24EA => out.push('0'),
+        0x3231 => out.push_str("(株)"),
+        0x3232 => out.push_str("(有)"),
+        0x337F => out.push_str("株式会社"),
+        0x3380..=0x33DF => match SQUARED_UNITS.iter().find(|(unit, _)| *unit == c) {
+            Some((_, text)) => out.push_str(text),
+            None => return false,
+        },
+        0xFB00 => out.push_str("ff"),
+        0xFB01 => out.push_str("fi"),
+        0xFB02 => out.push_str("fl"),
+        0xFB03 => out.push_str("ffi"),
+        0xFB04 => out.push_str("ffl"),
+        0xFB05 | 0xFB06 => out.push_str("st"),
+        _ => return false,
+    }
+    true
+}
+
+/* The Roman numerals from U+2160 to U+216F, which are repeated in lowercase
+from U+2170 to U+217F */
+const ROMAN_NUMERALS: [&str; 16] = [
+    "I", "II", "III", "IV", "V", "VI", "VII", "VIII", "IX", "X", "XI", "XII", "L", "C", "D", "M",
+];
+
+/* Common units of the CJK Compatibility block */
+const SQUARED_UNITS: [(char, &str); 24] = [
+    ('㎎', "mg"),
+    ('㎏', "kg"),
+    ('㎐', "Hz"),
+    ('㎑', "kHz"),
+    ('㎒', "MHz"),
+    ('㎓', "GHz"),
+    ('㎖', "ml"),
+    ('㎗', "dl"),
+    ('㎘', "kl"),
+    ('㎜', "mm"),
+    ('㎝', "cm"),
+    ('㎞', "km"),
+    ('㎠', "cm2"),
+    ('㎡', "m2"),
+    ('㎢', "km2"),
+    ('㎥', "m3"),
+    ('㎳', "ms"),
+    ('㎸', "kV"),
+    ('㎾', "kW"),
+    ('㎿', "MW"),
+    ('㏄', "cc"),
+    ('㏊', "ha"),
+    ('㏎', "KM"),
+    ('㏕', "mil"),
+];
+
+/// Replaces compatibility characters common in mixed CJK and Latin data entry
+/// with the characters they stand for, like the compatibility decomposition
+/// (NFKC) of Unicode does: enclosed numbers and letters like `①` and `Ⓐ`,
+/// Roman numerals like `Ⅻ`, superscript and subscript digits, squared units
+/// like `㎏`, company marks like `㈱`, Latin ligatures like `ﬁ`, and spaces
+/// of other widths. Width folding is done separately by `fold_width`.
+///
+/// Only these groups of characters are folded, since the complete Unicode
+/// decomposition tables are outside of the scope of this crate.
+///
+/// ```
+/// use strsim::preprocess::fold_compatibility;
+///
+/// assert_eq!("Chapter XII: 5kg of flour for 2 (株)", fold_compatibility("Chapter Ⅻ: 5㎏ of ﬂour for ② ㈱"));
+/// ```
+pub fn fold_compatibility(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if !push_compatibility_form(c, &mut out) {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/* The vowel of a pinyin vowel with a tone mark */
+fn pinyin_vowel(c: char) -> Option<char> {
+    let vowel = match c {
+        'ā' | 'á' | 'ǎ' | 'à' => 'a',
+        'ē' | 'é' | 'ě' | 'è' => 'e',
+        'ī' | 'í' | 'ǐ' | 'ì' => 'i',
+        'ō' | 'ó' | 'ǒ' | 'ò' => 'o',
+        'ū' | 'ú' | 'ǔ' | 'ù' => 'u',
+        'ǖ' | 'ǘ' | 'ǚ' | 'ǜ' | 'v' => 'ü',
+        'Ā' | 'Á' | 'Ǎ' | 'À' => 'A',
+        'Ē' | 'É' | 'Ě' | 'È' => 'E',
+        'Ī' | 'Í' | 'Ǐ' | 'Ì' => 'I',
+        'Ō' | 'Ó' | 'Ǒ' | 'Ò' => 'O',
+        'Ū' | 'Ú' | 'Ǔ' | 'Ù' => 'U',
+        'Ǖ' | 'Ǘ' | 'Ǚ' | 'Ǜ' | 'V' => 'Ü',
+        _ => return None,
+    };
+    Some(vowel)
+}
+
+/// Removes the tones from pinyin, so names typed with tone marks, with tone
+/// numbers or without tones compare equal: `Lǐ Xiǎolóng`, `Li3 Xiao3long2`
+/// and `Li Xiaolong` all become `Li Xiaolong`. The spellings `v` and `u:` of
+/// `ü` are replaced with `ü`.
+///
+/// Tone numbers are the digits 1 to 5 directly following a letter, so this
+/// should only be applied to pinyin. Han characters are kept, since their
+/// transliteration requires the pinyin readings of the Unihan database.
+///
+/// ```
+/// use strsim::preprocess::fold_pinyin_tones;
+///
+/// assert_eq!("Li Xiaolong", fold_pinyin_tones("Lǐ Xiǎolóng"));
+/// assert_eq!("Li Xiaolong", fold_pinyin_tones("Li3 Xiao3long2"));
+/// assert_eq!("lü", fold_pinyin_tones("lu:4"));
+/// ```
+pub fn fold_pinyin_tones(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        let after_letter = out.chars().last().map_or(false, char::is_alphabetic);
+        match c {
+            '1'..='5' if after_letter => {}
+            'u' | 'U' if chars.peek() == Some(&':') => {
+                chars.next();
+                out.push(if c == 'u' { 'ü' } else { 'Ü' });
+            }
+            _ => out.push(pinyin_vowel(c).unwrap_or(c)),
+        }
+    }
+    out
+}
+
+/// Folds katakana into hiragana, so `カタカナ` equals `かたかな`. Katakana
+/// without a hiragana counterpart, like `ヷ`, are kept.
+///
+/// ```
+/// use strsim::preprocess::fold_kana;
+///
+/// assert_eq!("とうきょう", fold_kana("トウキョウ"));
+/// assert_eq!("らーめん", fold_kana("ラーメン"));
+/// ```
+pub fn fold_kana(s: &str) -> String {
+    s.chars()
+        .map(|c| match u32::from(c) {
+            code @ (0x30A1..=0x30F6 | 0x30FD..=0x30FE) => char::from_u32(code - 0x60).unwrap_or(c),
+            _ => c,
+        })
+        .collect()
+}
+
+/// The language specific rules used by `fold_case`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Locale {
+    /// The language independent rules of Unicode.
+    Default,
+    /// The rules of Turkish and Azerbaijani, where `I` is the uppercase form
+    /// of the dotless `ı` and `İ` the uppercase form of `i`.
+    Turkish,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale::Default
+    }
+}
+
+/// Folds the case of a string for case-insensitive comparisons, following
+/// the rules of `locale`. Unlike `str::to_lowercase` this also folds the
+/// final sigma `ς` into `σ` and `ß` into `ss`, which are the same letter in
+/// different forms.
+///
+/// ```
+/// use strsim::preprocess::{fold_case, Locale};
+///
+/// assert_eq!("straße", "Straße".to_lowercase());
+/// assert_eq!("strasse", fold_case("Straße", Locale::Default));
+/// assert_eq!("ıi", fold_case("Iİ", Locale::Turkish));
+/// assert_eq!("diyarbakır", fold_case("DİYARBAKIR", Locale::Turkish));
+/// ```
+pub fn fold_case(s: &str, locale: Locale) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match (c, locale) {
+            ('I', Locale::Turkish) => out.push('ı'),
+            ('İ', Locale::Turkish) => out.push('i'),
+            ('ß' | 'ẞ', _) => out.push_str("ss"),
+            ('ς', _) => out.push('σ'),
+            _ => out.extend(c.to_lowercase().map(|c| if c == 'ς' { 'σ' } else { c })),
+        }
+    }
+    out
+}
+
+/// Transliterates strings into another script before they are compared, so
+/// names spelled in different scripts or conventions can be matched.
+///
+/// This crate includes `LatinTransliterator`. Other scripts can be supported
+/// by implementing this trait, which is also implemented for closures.
+///
+/// ```
+/// use strsim::normalized_levenshtein;
+/// use strsim::preprocess::Transliterator;
+///
+/// let greek = |s: &str| s.replace('α', "a").replace('β', "b");
+/// assert_eq!("ab", greek.transliterate("αβ"));
+/// assert_eq!(1.0, greek.compare("αβ", "ab", normalized_levenshtein));
+/// ```
+pub trait Transliterator {
+    /// Transliterates `s`.
+    fn transliterate(&self, s: &str) -> String;
+
+    /// Compares the transliterated strings with `metric`.
+    fn compare<T, F>(&self, a: &str, b: &str, metric: F) -> T
+    where
+        F: FnOnce(&str, &str) -> T,
+    {
+        metric(&self.transliterate(a), &self.transliterate(b))
+    }
+}
+
+impl<F: Fn(&str) -> String> Transliterator for F {
+    fn transliterate(&self, s: &str) -> String {
+        self(s)
+    }
+}
+
+/* The ASCII base letters of the characters from U+00C0 to U+017F. Characters
+marked with `*` are transliterated to several letters or kept */
+const LATIN_BASE_LETTERS: &[u8] = b"AAAAAA*CEEEEIIIIDNOOOOO*OUUUUY**aaaaaa*ceeeeiiiidnooooo*ouuuuy*y\
+    AaAaAaCcCcCcCcDdDdEeEeEeEeEeGgGgGgGgHhHhIiIiIiIiIi**JjKk*LlLlLlLlLlNnNnNn*NnOoOoOo**RrRrRrSsSsSs\
+    SsTtTtTtUuUuUuUuUuUuWwYyYZzZzZzs";
+
+/// Transliterates Latin letters with diacritics into ASCII, so that `Łódź`
+/// equals `Lodz`. Letters without a base letter are spelled out, like `ß` as
+/// `ss` and `Æ` as `Ae`, combining diacritical marks are removed and all
+/// other characters are kept. This covers the Latin-1 Supplement and Latin
+/// Extended-A blocks, which include the letters of most European languages.
+///
+/// With `german_umlauts` the umlauts are spelled out the German way, so that
+/// `Müller` equals `Mueller`.
+///
+/// ```
+/// use strsim::preprocess::{LatinTransliterator, Transliterator};
+///
+/// let latin = LatinTransliterator::default();
+/// assert_eq!("Lodz", latin.transliterate("Łódź"));
+/// assert_eq!("Muller", latin.transliterate("Müller"));
+///
+/// let german = LatinTransliterator::default().german_umlauts(true);
+/// assert_eq!("Mueller", german.transliterate("Müller"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct LatinTransliterator {
+    german_umlauts: bool,
+}
+
+impl LatinTransliterator {
+    /// Whether `ä`, `ö` and `ü` are transliterated as `ae`, `oe` and `ue`
+    /// instead of dropping the diaeresis. Disabled by default.
+    pub fn german_umlauts(mut self, enabled: bool) -> Self {
+        self.german_umlauts = enabled;
+        self
+    }
+}
+
+impl Transliterator for LatinTransliterator {
+    fn transliterate(&self, s: &str) -> String {
+        let mut out = String::with_capacity(s.len());
+        for c in s.chars() {
+            let spelled_out = match c {
+                'Ä' | 'Ö' | 'Ü' | 'ä' | 'ö' | 'ü' if self.german_umlauts => match c {
+                    'Ä' => "Ae",
+                    'Ö' => "Oe",
+                    'Ü' => "Ue",
+                    'ä' => "ae",
+                    'ö' => "oe",
+                    _ => "ue",
+                },
+                'Æ' => "Ae",
+                'æ' => "ae",
+                'Œ' => "Oe",
+                'œ' => "oe",
+                'Ĳ' => "IJ",
+                'ĳ' => "ij",
+                'Þ' => "Th",
+                'þ' => "th",
+                'ß' => "ss",
+                'ŉ' => "'n",
+                '\u{300}'..='\u{36F}' => "",
+                _ => {
+                    let base = u32::from(c)
+                        .checked_sub(0xC0)
+                        .and_then(|index| LATIN_BASE_LETTERS.get(index as usize))
+                        .filter(|&&base| base != b'*');
+                    out.push(base.map_or(c, |&base| char::from(base)));
+                    continue;
+                }
+            };
+            out.push_str(spelled_out);
+        }
+        out
+    }
+}
+
+/// Transliterates Latin letters into ASCII with the default settings of
+/// `LatinTransliterator`.
+///
+/// ```
+/// use strsim::preprocess::transliterate_latin;
+///
+/// assert_eq!("Sao Paulo", transliterate_latin("São Paulo"));
+/// assert_eq!("Encyclopaedia", transliterate_latin("Encyclopædia"));
+/// ```
+pub fn transliterate_latin(s: &str) -> String {
+    LatinTransliterator::default().transliterate(s)
+}
+
+/// A configurable preprocessing of strings, which is applied to both strings
+/// before comparing them with any of the metrics of this crate. All steps are
+/// disabled by default.
+///
+/// ```
+/// use strsim::normalized_levenshtein;
+/// use strsim::preprocess::Preprocessor;
+///
+/// let jamo = Preprocessor::new().decompose_hangul(true);
+/// assert_eq!(0.0, normalized_levenshtein("밤", "밥"));
+/// assert!(jamo.compare("밤", "밥", normalized_levenshtein) > 0.6);
+///
+/// let japanese = Preprocessor::new().fold_width(true).fold_kana(true);
+/// assert_eq!(1.0, japanese.compare("ｶﾞｲﾄﾞ", "がいど", normalized_levenshtein));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Preprocessor {
+    fold_compatibility: bool,
+    fold_width: bool,
+    transliterate_latin: Option<LatinTransliterator>,
+    fold_case: Option<Locale>,
+    fold_pinyin_tones: bool,
+    fold_kana: bool,
+    decompose_hangul: bool,
+}
+
+impl Preprocessor {
+    /// Creates a preprocessor which keeps strings unchanged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether compatibility characters are folded, see `fold_compatibility`.
+    pub fn fold_compatibility(mut self, enabled: bool) -> Self {
+        self.fold_compatibility = enabled;
+        self
+    }
+
+    /// Whether full-width and half-width forms are folded, see `fold_width`.
+    pub fn fold_width(mut self, enabled: bool) -> Self {
+        self.fold_width = enabled;
+        self
+    }
+
+    /// Transliterates Latin letters into ASCII with `transliterator`, see
+    /// `LatinTransliterator`, or keeps them for `None`. Other transliterators
+    /// can be applied before the preprocessor with `Transliterator::compare`.
+    pub fn transliterate_latin(mut self, transliterator: Option<LatinTransliterator>) -> Self {
+        self.transliterate_latin = transliterator;
+        self
+    }
+
+    /// Folds the case with the rules of `locale`, see `fold_case`, or keeps
+    /// the case for `None`.
+    pub fn fold_case(mut self, locale: Option<Locale>) -> Self {
+        self.fold_case = locale;
+        self
+    }
+
+    /// Whether the tones are removed from pinyin, see `fold_pinyin_tones`.
+    pub fn fold_pinyin_tones(mut self, enabled: bool) -> Self {
+        self.fold_pinyin_tones = enabled;
+        self
+    }
+
+    /// Whether katakana are folded into hiragana, see `fold_kana`. Combined
+    /// with `fold_width` this makes half-width katakana equal hiragana.
+    pub fn fold_kana(mut self, enabled: bool) -> Self {
+        self.fold_kana = enabled;
+        self
+    }
+
+    /// Whether Hangul syllables are decomposed into jamo, see
+    /// `decompose_hangul`.
+    pub fn decompose_hangul(mut self, enabled: bool) -> Self {
+        self.decompose_hangul = enabled;
+        self
+    }
+
+    /// Applies the enabled steps to `s`, in the order compatibility folding,
+    /// width folding, case folding, Latin transliteration, pinyin tone
+    /// folding, kana folding and Hangul decomposition. Case folding comes
+    /// first so that locale specific letters like the Turkish `İ` are folded
+    /// before transliteration removes their diacritics.
+    pub fn apply(&self, s: &str) -> String {
+        let mut s = s.to_string();
+        if self.fold_compatibility {
+            s = fold_compatibility(&s);
+        }
+        if self.fold_width {
+            s = fold_width(&s);
+        }
+        if let Some(locale) = self.fold_case {
+            s = fold_case(&s, locale);
+        }
+        if let Some(transliterator) = self.transliterate_latin {
+            s = transliterator.transliterate(&s);
+        }
+        if self.fold_pinyin_tones {
+            s = fold_pinyin_tones(&s);
+        }
+        if self.fold_kana {
+            s = fold_kana(&s);
+        }
+        if self.decompose_hangul {
+            s = decompose_hangul(&s);
+        }
+        s
+    }
+
+    /// Compares the preprocessed strings with `metric`.
+    pub fn compare<T, F>(&self, a: &str, b: &str, metric: F) -> T
+    where
+        F: FnOnce(&str, &str) -> T,
+    {
+        metric(&self.apply(a), &self.apply(b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::levenshtein;
+
+    #[test]
+    fn decompose_hangul_syllables() {
+        assert_eq!("\u{1100}\u{1161}", decompose_hangul("가"));
+        assert_eq!("\u{1112}\u{1161}\u{11AB}", decompose_hangul("한"));
+        assert_eq!("\u{1112}\u{1175}\u{11C2}", decompose_hangul("힣"));
+    }
+
+    #[test]
+    fn decompose_hangul_keeps_other_characters() {
+        assert_eq!("abc ㄱ 香", decompose_hangul("abc ㄱ 香"));
+        assert_eq!("", decompose_hangul(""));
+        assert_eq!("\u{ABFF}\u{D7A4}", decompose_hangul("\u{ABFF}\u{D7A4}"));
+    }
+
+    #[test]
+    fn decompose_hangul_typos() {
+        // ㅏ and ㅐ in the first syllable
+        assert_eq!(
+            1,
+            levenshtein(&decompose_hangul("학교"), &decompose_hangul("핵교"))
+        );
+        // a missing final consonant
+        assert_eq!(
+            1,
+            levenshtein(&decompose_hangul("학교"), &decompose_hangul("하교"))
+        );
+    }
+
+    #[test]
+    fn half_width_katakana_table() {
+        assert_eq!(0xFF9F - 0xFF61 + 1, HALF_WIDTH_KATAKANA.chars().count());
+        assert_eq!("アン", fold_width("ｱﾝ"));
+        assert_eq!("「ヲ」", fold_width("｢ｦ｣"));
+    }
+
+    #[test]
+    fn fold_width_sound_marks() {
+        assert_eq!("ガギグゲゴ", fold_width("ｶﾞｷﾞｸﾞｹﾞｺﾞ"));
+        assert_eq!("ダヂヅデド", fold_width("ﾀﾞﾁﾞﾂﾞﾃﾞﾄﾞ"));
+        assert_eq!("バパピプペポ", fold_width("ﾊﾞﾊﾟﾋﾟﾌﾟﾍﾟﾎﾟ"));
+        assert_eq!("ヴヷヺ", fold_width("ｳﾞﾜﾞｦﾞ"));
+        // marks which can't be combined are kept
+        assert_eq!("ア゛ッ゛ナ゜", fold_width("ｱﾞｯﾞﾅﾟ"));
+        assert_eq!("゛", fold_width("ﾞ"));
+        // only half-width marks are combined
+        assert_eq!("カ゛", fold_width("カ゛"));
+    }
+
+    #[test]
+    fn fold_width_ascii() {
+        assert_eq!("!~ Aa", fold_width("！～\u{3000}Ａａ"));
+        assert_eq!("abc 香", fold_width("abc 香"));
+    }
+
+    #[test]
+    fn fold_width_signs() {
+        assert_eq!("¥100 ₩", fold_width("￥１００ ￦"));
+        assert_eq!("←○", fold_width("￩￮"));
+        assert_eq!("\u{FFE7}", fold_width("\u{FFE7}"));
+    }
+
+    #[test]
+    fn fold_compatibility_enclosed() {
+        assert_eq!("1 20 (3) (20) 4. 0", fold_compatibility("① ⑳ ⑶ ⒇ ⒋ ⓪"));
+        assert_eq!("(a) Z z", fold_compatibility("⒜ Ⓩ ⓩ"));
+    }
+
+    #[test]
+    fn fold_compatibility_numerals() {
+        assert_eq!("IV xii M", fold_compatibility("Ⅳ ⅻ Ⅿ"));
+        assert_eq!("x2 H2O 10", fold_compatibility("x² H₂O ¹⁰"));
+    }
+
+    #[test]
+    fn fold_compatibility_units_and_marks() {
+        assert_eq!("3m2 10km 20°C", fold_compatibility("3㎡ 10㎞ 20℃"));
+        assert_eq!("株式会社 (有) No 1", fold_compatibility("㍿ ㈲ № 1"));
+        // units without a replacement are kept
+        assert_eq!("㎀", fold_compatibility("㎀"));
+    }
+
+    #[test]
+    fn fold_compatibility_ligatures_and_spaces() {
+        assert_eq!("office staff", fold_compatibility("oﬃce\u{00A0}staﬀ"));
+        assert_eq!("a b", fold_compatibility("a\u{2009}b"));
+        assert_eq!("abc 香\u{3000}", fold_compatibility("abc 香\u{3000}"));
+    }
+
+    #[test]
+    fn fold_case_default() {
+        assert_eq!("abc äöü", fold_case("ABC ÄÖü", Locale::Default));
+        assert_eq!("i\u{307}ı", fold_case("İı", Locale::Default));
+        assert_eq!("σοφοσ", fold_case("ΣΟΦΟΣ", Locale::Default));
+        assert_eq!("σοφοσ", fold_case("σοφος", Locale::Default));
+        assert_eq!("ss", fold_case("ẞ", Locale::Default));
+    }
+
+    #[test]
+    fn fold_case_turkish() {
+        assert_eq!("ıiiı", fold_case("Iİiı", Locale::Turkish));
+        assert_eq!(
+            fold_case("ISPARTA", Locale::Turkish),
+            fold_case("ısparta", Locale::Turkish)
+        );
+        assert_ne!(fold_case("İSTANBUL", Locale::Default), "istanbul");
+        assert_eq!(fold_case("İSTANBUL", Locale::Turkish), "istanbul");
+    }
+
+    #[test]
+    fn fold_pinyin_tones_marks() {
+        assert_eq!("ma ma ma ma", fold_pinyin_tones("mā má mǎ mà"));
+        assert_eq!("nü lü Lü", fold_pinyin_tones("nǚ lǜ Lǘ"));
+        assert_eq!("Beijing", fold_pinyin_tones("Běijīng"));
+    }
+
+    #[test]
+    fn fold_pinyin_tones_numbers() {
+        assert_eq!("Beijing", fold_pinyin_tones("Bei3jing1"));
+        assert_eq!("nü LÜ", fold_pinyin_tones("nv3 LU:4"));
+        assert_eq!("ma ma", fold_pinyin_tones("ma5 ma"));
+        // digits which aren't tones are kept
+        assert_eq!("ma6 2 北京", fold_pinyin_tones("ma6 2 北京"));
+    }
+
+    #[test]
+    fn fold_kana_range() {
+        assert_eq!("ぁゖゝゞ", fold_kana("ァヶヽヾ"));
+        assert_eq!("ヷー・", fold_kana("ヷー・"));
+        assert_eq!("abc", fold_kana("abc"));
+    }
+
+    #[test]
+    fn preprocessor_compatibility() {
+        let preprocessor = Preprocessor::new()
+            .fold_compatibility(true)
+            .fold_width(true);
+        assert_eq!("No 1ABC", preprocessor.apply("№\u{3000}①ＡＢＣ"));
+    }
+
+    #[test]
+    fn preprocessor_case() {
+        let preprocessor = Preprocessor::new().fold_case(Some(Locale::Turkish));
+        assert_eq!(1.0, preprocessor.compare("KIRMIZI", "kırmızı", crate::jaro));
+        let width_and_case = preprocessor.fold_width(true);
+        assert_eq!("ıstanbul", width_and_case.apply("ＩＳＴＡＮＢＵＬ"));
+        assert_eq!("ABC", preprocessor.fold_case(None).apply("ABC"));
+    }
+
+    #[test]
+    fn transliterate_latin_table() {
+        assert_eq!(0x17F - 0xC0 + 1, LATIN_BASE_LETTERS.len());
+        assert_eq!("AC×÷y", transliterate_latin("ÀÇ×÷ÿ"));
+        assert_eq!("AaZzs", transliterate_latin("ĀāŽžſ"));
+    }
+
+    #[test]
+    fn transliterate_latin_letters() {
+        assert_eq!("Lodz", transliterate_latin("Łódź"));
+        assert_eq!("Dvorak", transliterate_latin("Dvořák"));
+        assert_eq!("Oresund Thorshavn", transliterate_latin("Øresund Þórshavn"));
+        assert_eq!("Strasse IJssel", transliterate_latin("Straße Ĳssel"));
+        // combining marks are removed, other scripts are kept
+        assert_eq!("Muller", transliterate_latin("Mu\u{308}ller"));
+        assert_eq!("Zurich 香 Ω", transliterate_latin("Zürich 香 Ω"));
+    }
+
+    #[test]
+    fn transliterate_german_umlauts() {
+        let german = LatinTransliterator::default().german_umlauts(true);
+        assert_eq!("Mueller", german.transliterate("Müller"));
+        assert_eq!("Aerger Oel Uebel", german.transliterate("Ärger Öl Übel"));
+        assert_eq!("Gross", german.transliterate("Groß"));
+    }
+
+    #[test]
+    fn preprocessor_transliteration() {
+        let preprocessor = Preprocessor::new()
+            .transliterate_latin(Some(LatinTransliterator::default().german_umlauts(true)))
+            .fold_case(Some(Locale::Default));
+        assert_eq!("mueller", preprocessor.apply("MÜLLER"));
+        assert_eq!(preprocessor.apply("Łódź"), preprocessor.apply("LODZ"));
+    }
+
+    #[test]
+    fn preprocessor_turkish_transliteration() {
+        let preprocessor = Preprocessor::new()
+            .transliterate_latin(Some(LatinTransliterator::default()))
+            .fold_case(Some(Locale::Turkish));
+        assert_eq!("istanbul", preprocessor.apply("İstanbul"));
+        assert_eq!("diyarbakir", preprocessor.apply("DİYARBAKIR"));
+        assert_eq!(preprocessor.apply("İZMİR"), preprocessor.apply("izmir"));
+    }
+
+    #[test]
+    fn preprocessor_pinyin() {
+        let preprocessor = Preprocessor::new()
+            .fold_case(Some(Locale::Default))
+            .fold_pinyin_tones(true);
+        assert_eq!("li xiaolong", preprocessor.apply("LǏ Xiǎolóng"));
+        assert_eq!(
+            preprocessor.apply("Zhang1 Wei3"),
+            preprocessor.apply("zhāng wěi")
+        );
+    }
+
+    #[test]
+    fn preprocessor_order() {
+        let preprocessor = Preprocessor::new().fold_width(true).fold_kana(true);
+        assert_eq!("がいど", preprocessor.apply("ｶﾞｲﾄﾞ"));
+        assert_eq!("ガイド", Preprocessor::new().fold_width(true).apply("ｶﾞｲﾄﾞ"));
+    }
+
+    #[test]
+    fn preprocessor_disabled() {
+        let preprocessor = Preprocessor::new();
+        assert_eq!("한국", preprocessor.apply("한국"));
+        assert_eq!(1, preprocessor.compare("밤", "밥", levenshtein));
+    }
+}
@@ -0,0 +1,2838 @@
+//! Similarity of records consisting of several string fields, as used for
+//! record linkage and deduplication.
+//!
+//! A `Schema` describes the fields of a record, the metric comparing each
+//! field, its weight and how missing values are treated. The score of a
+//! record pair is the weighted mean of the field similarities.
+//!
+//! ```
+//! use strsim::record::{Field, MissingValue, Schema};
+//! use strsim::{jaro_winkler, normalized_levenshtein};
+//!
+//! let schema = Schema::new()
+//!     .field(Field::new("name", jaro_winkler).weight(2.0))
+//!     .field(Field::new("city", normalized_levenshtein))
+//!     .field(Field::new("phone", normalized_levenshtein).missing(MissingValue::Score(0.0)));
+//!
+//! let a = [Some("Jon Smith"), Some("Boston"), Some("555-1234")];
+//! let b = [Some("John Smith"), Some("Boston"), None];
+//!
+//! let comparison = schema.compare(&a, &b).unwrap();
+//! assert_eq!(Some(1.0), comparison.field_scores[1]);
+//! assert_eq!(Some(0.0), comparison.field_scores[2]);
+//! assert!((comparison.score - 0.74).abs() < 0.01);
+//! ```
+//!
+//! For probabilistic record linkage, `FellegiSunter` estimates how much the
+//! agreement of every field says about two records being a match, using
+//! unlabeled record pairs.
+//!
+//! Comparing every record with every other record is rarely feasible. With
+//! blocking, only records sharing a `BlockingKey` are compared:
+//!
+//! ```
+//! use strsim::record::{block_by, candidate_pairs, BlockingKey};
+//!
+//! let names = ["Robert Smith", "Rupert Smith", "Alice Jones", "Rob Smith"];
+//! let blocks = block_by(&names, |name| Some(BlockingKey::Soundex.key(name)));
+//!
+//! assert_eq!(vec![(0, 1)], candidate_pairs(blocks.values()));
+//! ```
+//!
+//! The `sorted_neighborhood` method compares records which are close to each
+//! other after sorting them by a key instead. The matches among the
+//! candidate pairs can be inspected as a `SimilarityGraph`, and small sets of
+//! records, like the records of a block, can be grouped with `cluster`.
+//!
+//! To choose a threshold, the `ScoreDistribution` of `random_pairs` shows
+//! how similar records are by chance, compared to a sample of the candidate
+//! pairs. With a small set of pairs labeled by hand, `precision_recall` and
+//! `suggest_threshold` estimate how well the thresholds separate the matches.
+
+use std::cmp::{max, min, Ordering};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::extract::{NoCache, ScoreCache};
+use crate::json::{self, Value};
+use crate::phonetic::soundex;
+use crate::{Control, MetricId, StrSimError};
+
+/* The agreement threshold of a field unless it is set */
+const DEFAULT_AGREEMENT_THRESHOLD: f64 = 0.85;
+
+/// How a field is scored when it is missing in at least one of the records.
+/// A value is missing if it is `None` or consists only of whitespace.
+///
+/// The normalized metrics consider two empty strings identical, which is
+/// rarely right for data matching, where both values are unknown.
+/// `MissingValue::score` applies a policy to any metric:
+///
+/// ```
+/// use strsim::normalized_levenshtein;
+/// use strsim::record::MissingValue;
+///
+/// assert_eq!(Some(1.0), MissingValue::Compare.score("", "", normalized_levenshtein));
+/// assert_eq!(None, MissingValue::Ignore.score("", "", normalized_levenshtein));
+/// assert_eq!(Some(0.5), MissingValue::Score(0.5).score(" ", "Bob", normalized_levenshtein));
+/// assert_eq!(Some(1.0), MissingValue::Ignore.score("Bob", "Bob", normalized_levenshtein));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MissingValue {
+    /// Leave the field out of the score, so only the fields present in both
+    /// records count.
+    Ignore,
+    /// Use the given similarity for the field, e.g. `0.0` to treat a missing
+    /// value like a mismatch.
+    Score(f64),
+    /// Compare the values with the metric anyway, with `None` as an empty
+    /// string, which is what the metrics do on their own.
+    Compare,
+}
+
+impl MissingValue {
+    /// Compares `a` and `b` with `metric` if both are present, and scores
+    /// them according to the policy otherwise. Returns `None` if they are
+    /// ignored.
+    pub fn score<F>(self, a: &str, b: &str, metric: F) -> Option<f64>
+    where
+        F: FnOnce(&str, &str) -> f64,
+    {
+        if self == MissingValue::Compare || (is_present(a) && is_present(b)) {
+            return Some(metric(a, b));
+        }
+        match self {
+            MissingValue::Score(score) => Some(score),
+            _ => None,
+        }
+    }
+}
+
+/// A field of a record together with the way it is compared.
+#[derive(Clone)]
+pub struct Field {
+    name: String,
+    metric: fn(&str, &str) -> f64,
+    metric_name: Option<&'static str>,
+    metric_version: &'static str,
+    metric_fingerprint: Option<u64>,
+    weight: f64,
+    missing: MissingValue,
+    agreement_threshold: f64,
+}
+
+impl Field {
+    /// Creates a field compared with `metric`, which has to return a
+    /// similarity between 0.0 and 1.0. The field has a weight of 1.0 and is
+    /// ignored when it is missing.
+    pub fn new(name: &str, metric: fn(&str, &str) -> f64) -> Self {
+        Self {
+            name: name.to_string(),
+            metric,
+            metric_name: None,
+            metric_version: env!("CARGO_PKG_VERSION"),
+            metric_fingerprint: None,
+            weight: 1.0,
+            missing: MissingValue::Ignore,
+            agreement_threshold: DEFAULT_AGREEMENT_THRESHOLD,
+        }
+    }
+
+    /// Names the metric in the `MatchReport` of a comparison, like
+    /// `"jaro_winkler"`, since the metric itself can't be serialized.
+    pub fn metric_name(mut self, name: &'static str) -> Self {
+        self.metric_name = Some(name);
+        self
+    }
+
+    /// Sets the version of the metric in the `MatchReport` of a comparison,
+    /// which is the version of this crate by default. Custom metrics should
+    /// change it whenever their scores change, so audits can tell which
+    /// decisions were made with the old scores.
+    pub fn metric_version(mut self, version: &'static str) -> Self {
+        self.metric_version = version;
+        self
+    }
+
+    /// Sets the name, version and parameter fingerprint of the metric in the
+    /// `MatchReport` of a comparison at once.
+    ///
+    /// ```
+    /// use strsim::record::{Field, Schema};
+    /// use strsim::{jaro_winkler, MetricId};
+    ///
+    /// let id = MetricId::new("jaro_winkler", "1.0.0");
+    /// let schema = Schema::new().field(Field::new("name", jaro_winkler).metric_id(id));
+    /// let report = schema.report(&[Some("Jon")], &[Some("John")], 0.9).unwrap();
+    /// assert_eq!(Some(id), report.fields[0].metric_id());
+    /// ```
+    pub fn metric_id(mut self, id: MetricId) -> Self {
+        self.metric_name = Some(id.name());
+        self.metric_version = id.version();
+        self.metric_fingerprint = Some(id.fingerprint());
+        self
+    }
+
+    /// Sets the weight of the field relative to the other fields.
+    pub fn weight(mut self, weight: f64) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Sets how the field is scored when it is missing.
+    pub fn missing(mut self, missing: MissingValue) -> Self {
+        self.missing = missing;
+        self
+    }
+
+    /// Sets the similarity from which two values are considered to agree,
+    /// which is 0.85 by default. Only used for the agreement patterns of the
+    /// Fellegi-Sunter model.
+    pub fn agreement_threshold(mut self, threshold: f64) -> Self {
+        self.agreement_threshold = threshold;
+        self
+    }
+
+    /// The name of the field.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Compares the values of the field in two records. Returns `None` when a
+    /// value is missing and the field is ignored in that case.
+    pub fn compare(&self, a: Option<&str>, b: Option<&str>) -> Option<f64> {
+        self.missing
+            .score(a.unwrap_or(""), b.unwrap_or(""), self.metric)
+    }
+}
+
+fn is_present(value: &str) -> bool {
+    !value.trim().is_empty()
+}
+
+/// The fields of a record, in the order their values are passed to
+/// `Schema::compare`.
+#[derive(Clone, Default)]
+pub struct Schema {
+    fields: Vec<Field>,
+}
+
+impl Schema {
+    /// Creates a schema without fields.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a field after the existing fields.
+    pub fn field(mut self, field: Field) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    /// The fields of the schema.
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    /// Compares two records, given as the values of their fields in the order
+    /// of the schema. Returns an error if a record doesn't have one value per
+    /// field.
+    pub fn compare(
+        &self,
+        a: &[Option<&str>],
+        b: &[Option<&str>],
+    ) -> Result<Comparison, StrSimError> {
+        if a.len() != self.fields.len() || b.len() != self.fields.len() {
+            return Err(StrSimError::DifferentLengthArgs);
+        }
+
+        let field_scores: Vec<Option<f64>> = self
+            .fields
+            .iter()
+            .zip(a.iter().zip(b))
+            .map(|(field, (&a, &b))| field.compare(a, b))
+            .collect();
+
+        let mut weighted_sum = 0.0;
+        let mut weight_sum = 0.0;
+        for (field, score) in self.fields.iter().zip(&field_scores) {
+            if let Some(score) = score {
+                weighted_sum += field.weight * score;
+                weight_sum += field.weight;
+            }
+        }
+        let score = if weight_sum > 0.0 {
+            weighted_sum / weight_sum
+        } else {
+            0.0
+        };
+
+        Ok(Comparison {
+            field_scores,
+            score,
+        })
+    }
+
+    /// Compares two records like `compare` and decides whether they match,
+    /// which is the case when the score is at least `threshold`. The report
+    /// records the values, the settings and the similarity of every field
+    /// to audit the decision later, for example as JSON.
+    ///
+    /// ```
+    /// use strsim::record::{Field, Schema};
+    /// use strsim::normalized_levenshtein;
+    ///
+    /// let schema = Schema::new().field(Field::new("name", normalized_levenshtein));
+    /// let report = schema.report(&[Some("Jon")], &[Some("John")], 0.7).unwrap();
+    /// assert!(report.is_match);
+    /// assert_eq!("name", report.fields[0].name);
+    /// assert_eq!(Some(0.75), report.fields[0].score);
+    /// ```
+    pub fn report(
+        &self,
+        a: &[Option<&str>],
+        b: &[Option<&str>],
+        threshold: f64,
+    ) -> Result<MatchReport, StrSimError> {
+        let comparison = self.compare(a, b)?;
+        let fields = self
+            .fields
+            .iter()
+            .zip(a.iter().zip(b))
+            .zip(comparison.field_scores)
+            .map(|((field, (a, b)), score)| FieldReport {
+                name: field.name.clone(),
+                metric: field.metric_name,
+                version: field.metric_version,
+                fingerprint: field.metric_fingerprint,
+                a: a.map(str::to_string),
+                b: b.map(str::to_string),
+                weight: field.weight,
+                missing: field.missing,
+                score,
+            })
+            .collect();
+        Ok(MatchReport {
+            fields,
+            score: comparison.score,
+            threshold,
+            is_match: comparison.score >= threshold,
+        })
+    }
+
+    /// Compares two records like `compare`, but only returns whether every
+    /// field agrees, i.e. whether its similarity reaches the agreement
+    /// threshold of the field. Ignored missing values are `None`.
+    pub fn agreement(
+        &self,
+        a: &[Option<&str>],
+        b: &[Option<&str>],
+    ) -> Result<Vec<Option<bool>>, StrSimError> {
+        let comparison = self.compare(a, b)?;
+        Ok(self
+            .fields
+            .iter()
+            .zip(comparison.field_scores)
+            .map(|(field, score)| score.map(|score| score >= field.agreement_threshold))
+            .collect())
+    }
+}
+
+/* A similarity between 0.0 and 1.0 */
+type Similarity = fn(&str, &str) -> f64;
+
+/// Similarity metrics by name, to build a `Schema` from a `SchemaConfig`.
+///
+/// `MetricRegistry::standard` contains the similarities between 0.0 and 1.0
+/// of this crate, named like their function, e.g. `"jaro_winkler"`. Custom
+/// metrics are added with `register`. Every field built from the registry
+/// records the `MetricId` of its metric in its reports.
+///
+/// ```
+/// use strsim::record::{MetricRegistry, SchemaConfig};
+/// use strsim::MetricId;
+///
+/// fn same_initial(a: &str, b: &str) -> f64 {
+///     if a.chars().next() == b.chars().next() {
+///         1.0
+///     } else {
+///         0.0
+///     }
+/// }
+///
+/// let registry = MetricRegistry::standard()
+///     .register(MetricId::new("same_initial", "1.0.0"), same_initial);
+/// let config = SchemaConfig::from_json(
+///     r#"{
+///         "threshold": 0.8,
+///         "fields": [
+///             {"name": "last_name", "metric": "jaro_winkler", "weight": 2},
+///             {"name": "first_name", "metric": "same_initial", "missing": {"score": 0}}
+///         ]
+///     }"#,
+/// )
+/// .unwrap();
+/// let schema = registry.schema(&config).unwrap();
+///
+/// let a = [Some("Smith"), Some("Jon")];
+/// let b = [Some("Smith"), Some("John")];
+/// let report = schema.report(&a, &b, config.threshold.unwrap()).unwrap();
+/// assert!(report.is_match);
+/// assert_eq!(Some("same_initial"), report.fields[1].metric);
+/// ```
+#[derive(Clone, Default)]
+pub struct MetricRegistry {
+    metrics: BTreeMap<&'static str, (Similarity, MetricId)>,
+}
+
+impl MetricRegistry {
+    /// Creates a registry without metrics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry of the similarities of this crate.
+    pub fn standard() -> Self {
+        let metrics: [(&'static str, Similarity); 20] = [
+            ("jaro", crate::jaro),
+            ("jaro_winkler", crate::jaro_winkler),
+            ("normalized_levenshtein", crate::normalized_levenshtein),
+            (
+                "partial_normalized_levenshtein",
+                crate::partial_normalized_levenshtein,
+            ),
+            (
+                "normalized_damerau_levenshtein",
+                crate::normalized_damerau_levenshtein,
+            ),
+            ("normalized_osa", crate::normalized_osa),
+            ("normalized_indel", crate::normalized_indel),
+            ("normalized_lcs", crate::normalized_lcs),
+            ("ratcliff_obershelp", crate::ratcliff_obershelp),
+            ("sorensen_dice", crate::sorensen_dice),
+            ("overlap_coefficient", crate::overlap_coefficient),
+            ("cosine", crate::cosine),
+            ("token_sort_similarity", crate::token_sort_similarity),
+            ("token_set_similarity", crate::token_set_similarity),
+            ("token_match_similarity", crate::token_match_similarity),
+            ("address_similarity", crate::matchers::address_similarity),
+            ("company_similarity", crate::matchers::company_similarity),
+            ("email_similarity", crate::matchers::email_similarity),
+            ("phone_similarity", crate::matchers::phone_similarity),
+            ("url_similarity", crate::matchers::url_similarity),
+        ];
+        metrics
+            .iter()
+            .fold(Self::new(), |registry, &(name, metric)| {
+                registry.register(MetricId::new(name, "1.0.0"), metric)
+            })
+    }
+
+    /// Adds `metric` under the name of `id`, replacing a metric of the same
+    /// name. It has to return a similarity between 0.0 and 1.0.
+    pub fn register(mut self, id: MetricId, metric: fn(&str, &str) -> f64) -> Self {
+        self.metrics.insert(id.name(), (metric, id));
+        self
+    }
+
+    /// The identity of the metric named `name`, if it is registered.
+    pub fn metric_id(&self, name: &str) -> Option<MetricId> {
+        self.metrics.get(name).map(|&(_, id)| id)
+    }
+
+    /// Creates the field `name` compared with the metric named `metric`, like
+    /// `Field::new` followed by `Field::metric_id`. Returns
+    /// `StrSimError::UnknownMetric` if the metric isn't registered.
+    pub fn field(&self, name: &str, metric: &str) -> Result<Field, StrSimError> {
+        match self.metrics.get(metric) {
+            Some(&(metric, id)) => Ok(Field::new(name, metric).metric_id(id)),
+            None => Err(StrSimError::UnknownMetric),
+        }
+    }
+
+    /// Builds the schema described by `config`. Returns
+    /// `StrSimError::UnknownMetric` if one of its metrics isn't registered.
+    pub fn schema(&self, config: &SchemaConfig) -> Result<Schema, StrSimError> {
+        config
+            .fields
+            .iter()
+            .try_fold(Schema::new(), |schema, field| {
+                Ok(schema.field(
+                    self.field(&field.name, &field.metric)?
+                        .weight(field.weight)
+                        .missing(field.missing)
+                        .agreement_threshold(field.agreement_threshold),
+                ))
+            })
+    }
+}
+
+/// The description of a `Schema` by the names of the metrics of its fields,
+/// to keep it in a configuration file. `MetricRegistry::schema` builds the
+/// schema.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SchemaConfig {
+    /// The fields in the order of the values of a record.
+    pub fields: Vec<FieldConfig>,
+    /// The score from which two records are considered to match, for
+    /// `Schema::report`, if it is configured.
+    pub threshold: Option<f64>,
+}
+
+impl SchemaConfig {
+    /// Reads the configuration from a JSON object with the array `"fields"`
+    /// and the optional number `"threshold"`. Every field is an object with
+    /// the strings `"name"` and `"metric"`, and the optional `"weight"`,
+    /// `"missing"` and `"agreement_threshold"`, which default to the values of
+    /// `FieldConfig::new`. `"missing"` is written like in
+    /// `MatchReport::to_json`: `"ignore"`, `"compare"` or `{"score": 0.5}`.
+    ///
+    /// Returns `StrSimError::MalformedConfig` for invalid JSON, unknown or
+    /// duplicate keys, values of the wrong type and numbers which aren't
+    /// finite, so typos don't go unnoticed.
+    ///
+    /// ```
+    /// use strsim::record::{FieldConfig, MissingValue, SchemaConfig};
+    /// use strsim::StrSimError;
+    ///
+    /// let config = SchemaConfig::from_json(
+    ///     r#"{"fields": [{"name": "phone", "metric": "phone_similarity", "missing": "compare"}]}"#,
+    /// )
+    /// .unwrap();
+    /// let mut field = FieldConfig::new("phone", "phone_similarity");
+    /// field.missing = MissingValue::Compare;
+    /// assert_eq!(vec![field], config.fields);
+    /// assert_eq!(None, config.threshold);
+    ///
+    /// assert_eq!(
+    ///     Err(StrSimError::MalformedConfig),
+    ///     SchemaConfig::from_json(r#"{"fields": [{"name": "a", "metric": "jaro", "wieght": 2}]}"#)
+    /// );
+    /// ```
+    pub fn from_json(text: &str) -> Result<Self, StrSimError> {
+        let mut config = Self::default();
+        let mut fields = None;
+        for (key, value) in json_members(json::parse(text))? {
+            match (key.as_str(), value) {
+                ("fields", Value::Array(values)) => {
+                    fields = Some(
+                        values
+                            .into_iter()
+                            .map(FieldConfig::from_json)
+                            .collect::<Result<_, _>>()?,
+                    );
+                }
+                ("threshold", Value::Number(x)) => config.threshold = Some(json_finite(x)?),
+                _ => return Err(StrSimError::MalformedConfig),
+            }
+        }
+        config.fields = fields.ok_or(StrSimError::MalformedConfig)?;
+        Ok(config)
+    }
+}
+
+/// A field of a `SchemaConfig`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldConfig {
+    /// The name of the field.
+    pub name: String,
+    /// The name of the metric in the `MetricRegistry`.
+    pub metric: String,
+    /// The weight of the field, see `Field::weight`.
+    pub weight: f64,
+    /// How the field is scored when it is missing, see `Field::missing`.
+    pub missing: MissingValue,
+    /// The similarity from which two values agree, see
+    /// `Field::agreement_threshold`.
+    pub agreement_threshold: f64,
+}
+
+impl FieldConfig {
+    /// Creates the configuration of a field with the defaults of
+    /// `Field::new`.
+    pub fn new(name: &str, metric: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            metric: metric.to_string(),
+            weight: 1.0,
+            missing: MissingValue::Ignore,
+            agreement_threshold: DEFAULT_AGREEMENT_THRESHOLD,
+        }
+    }
+
+    fn from_json(value: Value) -> Result<Self, StrSimError> {
+        let mut field = Self::new("", "");
+        let (mut name, mut metric) = (None, None);
+        for (key, value) in json_members(Some(value))? {
+            match (key.as_str(), value) {
+                ("name", Value::String(value)) => name = Some(value),
+                ("metric", Value::String(value)) => metric = Some(value),
+                ("weight", Value::Number(x)) => field.weight = json_finite(x)?,
+                ("missing", Value::String(policy)) if policy == "ignore" => {
+                    field.missing = MissingValue::Ignore;
+                }
+                ("missing", Value::String(policy)) if policy == "compare" => {
+                    field.missing = MissingValue::Compare;
+                }
+                ("missing", value @ Value::Object(_)) => {
+                    let members = json_members(Some(value))?;
+                    match members.as_slice() {
+                        [(key, Value::Number(x))] if key == "score" => {
+                            field.missing = MissingValue::Score(json_finite(*x)?);
+                        }
+                        _ => return Err(StrSimError::MalformedConfig),
+                    }
+                }
+                ("agreement_threshold", Value::Number(x)) => {
+                    field.agreement_threshold = json_finite(x)?;
+                }
+                _ => return Err(StrSimError::MalformedConfig),
+            }
+        }
+        match (name, metric) {
+            (Some(name), Some(metric)) => {
+                field.name = name;
+                field.metric = metric;
+                Ok(field)
+            }
+            _ => Err(StrSimError::MalformedConfig),
+        }
+    }
+}
+
+/* The members of a JSON object without duplicate keys */
+fn json_members(value: Option<Value>) -> Result<Vec<(String, Value)>, StrSimError> {
+    match value {
+        Some(Value::Object(members)) => {
+            let keys: BTreeSet<&str> = members.iter().map(|(key, _)| key.as_str()).collect();
+            if keys.len() == members.len() {
+                Ok(members)
+            } else {
+                Err(StrSimError::MalformedConfig)
+            }
+        }
+        _ => Err(StrSimError::MalformedConfig),
+    }
+}
+
+/* Numbers too large for an f64 are parsed as infinity */
+fn json_finite(x: f64) -> Result<f64, StrSimError> {
+    if x.is_finite() {
+        Ok(x)
+    } else {
+        Err(StrSimError::MalformedConfig)
+    }
+}
+
+/// The result of comparing two records.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    /// The similarity of every field, or `None` for ignored missing values.
+    pub field_scores: Vec<Option<f64>>,
+    /// The weighted mean of the field similarities between 0.0 and 1.0. It is
+    /// 0.0 when every field is ignored.
+    pub score: f64,
+}
+
+/// The comparison of one field in a `MatchReport`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldReport {
+    /// The name of the field.
+    pub name: String,
+    /// The name of the metric comparing the field, if it was named with
+    /// `Field::metric_name`.
+    pub metric: Option<&'static str>,
+    /// The version of the metric, see `Field::metric_version`.
+    pub version: &'static str,
+    /// The fingerprint of the parameters of the metric, if it was identified
+    /// with `Field::metric_id`.
+    pub fingerprint: Option<u64>,
+    /// The value of the field in the first record.
+    pub a: Option<String>,
+    /// The value of the field in the second record.
+    pub b: Option<String>,
+    /// The weight of the field.
+    pub weight: f64,
+    /// How the field is scored when it is missing.
+    pub missing: MissingValue,
+    /// The similarity of the values, or `None` if the field was ignored.
+    pub score: Option<f64>,
+}
+
+/// The decision whether two records match together with everything it was
+/// based on, returned by `Schema::report`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchReport {
+    /// The comparison of every field.
+    pub fields: Vec<FieldReport>,
+    /// The weighted mean of the field similarities.
+    pub score: f64,
+    /// The score from which the records are considered to match.
+    pub threshold: f64,
+    /// Whether the score reaches the threshold.
+    pub is_match: bool,
+}
+
+impl MatchReport {
+    /// Serializes the report as a JSON object. Missing values, ignored
+    /// scores and scores which aren't finite are `null`.
+    ///
+    /// ```
+    /// use strsim::record::{Field, Schema};
+    /// use strsim::normalized_levenshtein;
+    ///
+    /// let field = Field::new("name", normalized_levenshtein)
+    ///     .metric_name("normalized_levenshtein")
+    ///     .metric_version("1");
+    /// let report = Schema::new().field(field).report(&[Some("Jon")], &[None], 0.5).unwrap();
+    /// assert_eq!(
+    ///     concat!(
+    ///         r#"{"fields":[{"name":"name","metric":"normalized_levenshtein","version":"1","#,
+    ///         r#""fingerprint":null,"a":"Jon","b":null,"weight":1,"missing":"ignore","#,
+    ///         r#""score":null}],"#,
+    ///         r#""score":0,"threshold":0.5,"is_match":false}"#
+    ///     ),
+    ///     report.to_json()
+    /// );
+    /// ```
+    pub fn to_json(&self) -> String {
+        let fields: Vec<String> = self.fields.iter().map(FieldReport::to_json).collect();
+        format!(
+            "{{\"fields\":[{}],\"score\":{},\"threshold\":{},\"is_match\":{}}}",
+            fields.join(","),
+            json_number(self.score),
+            json_number(self.threshold),
+            self.is_match
+        )
+    }
+}
+
+impl FieldReport {
+    /// The identity of the metric, if it was identified with
+    /// `Field::metric_id`.
+    pub fn metric_id(&self) -> Option<MetricId> {
+        let (name, fingerprint) = (self.metric?, self.fingerprint?);
+        Some(MetricId::from_parts(name, self.version, fingerprint))
+    }
+
+    fn to_json(&self) -> String {
+        let missing = match self.missing {
+            MissingValue::Ignore => json_string("ignore"),
+            MissingValue::Compare => json_string("compare"),
+            MissingValue::Score(score) => format!("{{\"score\":{}}}", json_number(score)),
+        };
+        let optional = |value: Option<&str>| value.map_or_else(|| "null".to_string(), json_string);
+        format!(
+            concat!(
+                "{{\"name\":{},\"metric\":{},\"version\":{},\"fingerprint\":{},",
+                "\"a\":{},\"b\":{},\"weight\":{},\"missing\":{},\"score\":{}}}"
+            ),
+            json_string(&self.name),
+            optional(self.metric),
+            json_string(self.version),
+            self.fingerprint
+                .map_or_else(|| "null".to_string(), |x| format!("\"{:016x}\"", x)),
+            optional(self.a.as_deref()),
+            optional(self.b.as_deref()),
+            json_number(self.weight),
+            missing,
+            self.score.map_or_else(|| "null".to_string(), json_number),
+        )
+    }
+}
+
+/// The decision whether two records match by their match weight together with
+/// everything it was based on, returned by `FellegiSunter::report`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightReport {
+    /// The comparison of every field.
+    pub fields: Vec<FieldReport>,
+    /// Whether every field agrees, or `None` for ignored missing values.
+    pub agreement: Vec<Option<bool>>,
+    /// The evidence of every field in bits, 0.0 for ignored missing values.
+    pub evidence: Vec<f64>,
+    /// The sum of the evidence.
+    pub match_weight: f64,
+    /// The probability of the records being a match.
+    pub match_probability: f64,
+    /// The match weight from which the records are considered to match.
+    pub threshold: f64,
+    /// Whether the match weight reaches the threshold.
+    pub is_match: bool,
+}
+
+impl WeightReport {
+    /// Serializes the report as a JSON object like `MatchReport::to_json`.
+    ///
+    /// ```
+    /// use strsim::record::{FellegiSunter, Field, Schema};
+    /// use strsim::normalized_levenshtein;
+    ///
+    /// let schema = Schema::new().field(Field::new("name", normalized_levenshtein));
+    /// let model = FellegiSunter::new(vec![0.5], vec![0.25], 0.5).unwrap();
+    /// let report = model.report(&schema, &[Some("Jon")], &[None], 0.5).unwrap();
+    /// assert!(report.to_json().ends_with(concat!(
+    ///     r#""agreement":[null],"evidence":[0],"match_weight":0,"#,
+    ///     r#""match_probability":0.5,"threshold":0.5,"is_match":false}"#
+    /// )));
+    /// ```
+    pub fn to_json(&self) -> String {
+        let fields: Vec<String> = self.fields.iter().map(FieldReport::to_json).collect();
+        let agreement: Vec<String> = self
+            .agreement
+            .iter()
+            .map(|agrees| agrees.map_or_else(|| "null".to_string(), |agrees| agrees.to_string()))
+            .collect();
+        let evidence: Vec<String> = self.evidence.iter().map(|&x| json_number(x)).collect();
+        format!(
+            concat!(
+                "{{\"fields\":[{}],\"agreement\":[{}],\"evidence\":[{}],",
+                "\"match_weight\":{},\"match_probability\":{},\"threshold\":{},",
+                "\"is_match\":{}}}"
+            ),
+            fields.join(","),
+            agreement.join(","),
+            evidence.join(","),
+            json_number(self.match_weight),
+            json_number(self.match_probability),
+            json_number(self.threshold),
+            self.is_match
+        )
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if u32::from(c) < 0x20 => json.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn json_number(x: f64) -> String {
+    if x.is_finite() {
+        x.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+/* Keeps probabilities away from 0 and 1, where the logarithms of the weights
+are undefined */
+fn clamp_probability(p: f64) -> f64 {
+    p.clamp(1e-6, 1.0 - 1e-6)
+}
+
+/* The binary logarithm. Unlike `f64::log2`, which calls the math library of
+the platform, it only uses basic arithmetic in a fixed order, so the result
+is the same on every platform */
+fn log2(x: f64) -> f64 {
+    if x.is_nan() || x < 0.0 {
+        return f64::NAN;
+    }
+    if x == 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if x.is_infinite() {
+        return x;
+    }
+
+    /* scale subnormal numbers into the normal range */
+    let (x, offset) = if x < f64::MIN_POSITIVE {
+        (x * 18_014_398_509_481_984.0, -54)
+    } else {
+        (x, 0)
+    };
+    let bits = x.to_bits();
+    let mut exponent = ((bits >> 52) & 0x7ff) as i64 - 1023 + offset;
+    let mut mantissa = f64::from_bits((bits & 0x000f_ffff_ffff_ffff) | (1023 << 52));
+    if mantissa > std::f64::consts::SQRT_2 {
+        mantissa /= 2.0;
+        exponent += 1;
+    }
+
+    /* ln(m) = 2 atanh(s) = 2 (s + s^3 / 3 + s^5 / 5 + ...) with |s| < 0.172 */
+    let s = (mantissa - 1.0) / (mantissa + 1.0);
+    let s2 = s * s;
+    let mut series = 0.0;
+    for k in (0..12).rev() {
+        series = series * s2 + 1.0 / f64::from(2 * k + 1);
+    }
+    exponent as f64 + 2.0 * s * series * std::f64::consts::LOG2_E
+}
+
+/* Two to the power of `x`, computed like `log2` to be the same on every
+platform */
+fn exp2(x: f64) -> f64 {
+    if x.is_nan() {
+        return x;
+    }
+    if x > 1024.0 {
+        return f64::INFINITY;
+    }
+    if x < -1080.0 {
+        return 0.0;
+    }
+
+    let n = x.round();
+    /* e^y = 1 + y + y^2 / 2! + ... with |y| <= ln(2) / 2 */
+    let y = (x - n) * std::f64::consts::LN_2;
+    let mut series = 1.0;
+    for k in (1..=18).rev() {
+        series = series * y / f64::from(k) + 1.0;
+    }
+
+    /* multiplying with powers of two is exact, split up to stay within the
+    range of the exponent */
+    let mut n = n as i64;
+    let mut result = series;
+    while n != 0 {
+        let step = n.clamp(-1000, 1000);
+        result *= f64::from_bits(((step + 1023) as u64) << 52);
+        n -= step;
+    }
+    result
+}
+
+/// The Fellegi-Sunter model of record linkage. For every field it holds the
+/// probability `m` that the field agrees for matching records and the
+/// probability `u` that it agrees for non-matching records. The evidence of
+/// a field is `log2(m / u)` when it agrees and `log2((1 - m) / (1 - u))` when
+/// it doesn't, and the match weight of a record pair is the sum over all
+/// fields.
+///
+/// ```
+/// use strsim::record::FellegiSunter;
+///
+/// let mut pairs = vec![vec![Some(true), Some(true)]; 20];
+/// pairs.extend(vec![vec![Some(false), Some(false)]; 70]);
+/// pairs.extend(vec![vec![Some(true), Some(false)]; 10]);
+///
+/// let model = FellegiSunter::estimate(&pairs, 100).unwrap();
+/// assert!(model.match_weight(&[Some(true), Some(true)]) > 0.0);
+/// assert!(model.match_weight(&[Some(false), Some(false)]) < 0.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct FellegiSunter {
+    m: Vec<f64>,
+    u: Vec<f64>,
+    match_proportion: f64,
+}
+
+impl FellegiSunter {
+    /// Creates a model from known `m` and `u` probabilities and the
+    /// proportion of matches among the compared pairs. Returns an error if
+    /// `m` and `u` have different lengths.
+    pub fn new(m: Vec<f64>, u: Vec<f64>, match_proportion: f64) -> Result<Self, StrSimError> {
+        if m.len() != u.len() {
+            return Err(StrSimError::DifferentLengthArgs);
+        }
+        Ok(Self {
+            m: m.into_iter().map(clamp_probability).collect(),
+            u: u.into_iter().map(clamp_probability).collect(),
+            match_proportion: clamp_probability(match_proportion),
+        })
+    }
+
+    /// Learns the model from the agreement patterns of unlabeled record pairs,
+    /// as returned by `Schema::agreement`, with the expectation-maximization
+    /// algorithm. Stops after `max_iterations` or once the probabilities
+    /// converge. Returns an error if the patterns have different lengths.
+    pub fn estimate(
+        patterns: &[Vec<Option<bool>>],
+        max_iterations: usize,
+    ) -> Result<Self, StrSimError> {
+        Self::estimate_with_control(patterns, max_iterations, &Control::new())
+    }
+
+    /// Learns the model like `estimate`, unless the estimation is cancelled
+    /// through `control`. The progress is the number of patterns processed
+    /// in all iterations, out of `max_iterations` times their number. It
+    /// stops short of the total when the probabilities converge early.
+    pub fn estimate_with_control(
+        patterns: &[Vec<Option<bool>>],
+        max_iterations: usize,
+        control: &Control<'_>,
+    ) -> Result<Self, StrSimError> {
+        let fields = patterns.first().map_or(0, Vec::len);
+        if patterns.iter().any(|pattern| pattern.len() != fields) {
+            return Err(StrSimError::DifferentLengthArgs);
+        }
+
+        // start from the assumption that agreement is a strong match signal
+        let mut model = Self::new(vec![0.9; fields], vec![0.1; fields], 0.1)?;
+        if patterns.is_empty() {
+            return Ok(model);
+        }
+
+        for iteration in 0..max_iterations {
+            // expectation: the probability of every pair being a match
+            let match_probabilities: Vec<f64> = patterns
+                .iter()
+                .map(|pattern| model.match_probability(pattern))
+                .collect();
+
+            // maximization: the probabilities explaining these best
+            let mut m_agree = vec![0.0; fields];
+            let mut m_total = vec![0.0; fields];
+            let mut u_agree = vec![0.0; fields];
+            let mut u_total = vec![0.0; fields];
+            for (pair, (pattern, &g)) in patterns.iter().zip(&match_probabilities).enumerate() {
+                control.step(
+                    iteration * patterns.len() + pair + 1,
+                    max_iterations * patterns.len(),
+                )?;
+                for (field, agreement) in pattern.iter().enumerate() {
+                    if let Some(agrees) = *agreement {
+                        m_total[field] += g;
+                        u_total[field] += 1.0 - g;
+                        if agrees {
+                            m_agree[field] += g;
+                            u_agree[field] += 1.0 - g;
+                        }
+                    }
+                }
+            }
+
+            let ratio = |agree: f64, total: f64, previous: f64| {
+                if total > 0.0 {
+                    clamp_probability(agree / total)
+                } else {
+                    previous
+                }
+            };
+            let next = Self {
+                m: (0..fields)
+                    .map(|field| ratio(m_agree[field], m_total[field], model.m[field]))
+                    .collect(),
+                u: (0..fields)
+                    .map(|field| ratio(u_agree[field], u_total[field], model.u[field]))
+                    .collect(),
+                match_proportion: clamp_probability(
+                    match_probabilities.iter().sum::<f64>() / patterns.len() as f64,
+                ),
+            };
+
+            let change = next
+                .m
+                .iter()
+                .zip(&model.m)
+                .chain(next.u.iter().zip(&model.u))
+                .map(|(x, y)| (x - y).abs())
+                .fold(
+                    (next.match_proportion - model.match_proportion).abs(),
+                    f64::max,
+                );
+            model = next;
+            if change < 1e-9 {
+                break;
+            }
+        }
+        Ok(model)
+    }
+
+    /// The probability of every field agreeing for matching records.
+    pub fn m_probabilities(&self) -> &[f64] {
+        &self.m
+    }
+
+    /// The probability of every field agreeing for non-matching records.
+    pub fn u_probabilities(&self) -> &[f64] {
+        &self.u
+    }
+
+    /// The estimated proportion of matches among the compared pairs.
+    pub fn match_proportion(&self) -> f64 {
+        self.match_proportion
+    }
+
+    /// The sum of the evidence of all fields in bits. Positive weights favor
+    /// a match. Missing values (`None`) don't contribute.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pattern doesn't have one entry per field.
+    pub fn match_weight(&self, pattern: &[Option<bool>]) -> f64 {
+        assert_eq!(self.m.len(), pattern.len(), "one entry per field required");
+        pattern
+            .iter()
+            .enumerate()
+            .map(|(field, &agreement)| self.evidence(field, agreement))
+            .sum()
+    }
+
+    /* The evidence of a field in bits */
+    fn evidence(&self, field: usize, agreement: Option<bool>) -> f64 {
+        let (m, u) = (self.m[field], self.u[field]);
+        match agreement {
+            Some(true) => log2(m / u),
+            Some(false) => log2((1.0 - m) / (1.0 - u)),
+            None => 0.0,
+        }
+    }
+
+    /// Compares two records with `schema` and decides whether they match,
+    /// which is the case when the match weight of their agreement pattern is
+    /// at least `threshold` bits. Like `Schema::report`, the report records
+    /// the values, the settings and the similarity of every field, together
+    /// with the evidence of every field. Returns an error if the schema
+    /// doesn't have one field per field of the model or a record doesn't
+    /// have one value per field.
+    ///
+    /// ```
+    /// use strsim::record::{FellegiSunter, Field, Schema};
+    /// use strsim::{jaro_winkler, normalized_levenshtein};
+    ///
+    /// let schema = Schema::new()
+    ///     .field(Field::new("name", jaro_winkler))
+    ///     .field(Field::new("city", normalized_levenshtein));
+    /// let model = FellegiSunter::new(vec![0.9, 0.8], vec![0.01, 0.2], 0.1).unwrap();
+    /// let report = model
+    ///     .report(&schema, &[Some("Jon"), Some("Boston")], &[Some("John"), Some("York")], 3.0)
+    ///     .unwrap();
+    /// assert_eq!(vec![Some(true), Some(false)], report.agreement);
+    /// assert!(report.evidence[0] > 0.0 && report.evidence[1] < 0.0);
+    /// assert!(report.is_match);
+    /// ```
+    pub fn report(
+        &self,
+        schema: &Schema,
+        a: &[Option<&str>],
+        b: &[Option<&str>],
+        threshold: f64,
+    ) -> Result<WeightReport, StrSimError> {
+        if schema.fields.len() != self.m.len() {
+            return Err(StrSimError::DifferentLengthArgs);
+        }
+        let report = schema.report(a, b, threshold)?;
+        let agreement: Vec<Option<bool>> = schema
+            .fields
+            .iter()
+            .zip(&report.fields)
+            .map(|(field, report)| report.score.map(|score| score >= field.agreement_threshold))
+            .collect();
+        let evidence: Vec<f64> = agreement
+            .iter()
+            .enumerate()
+            .map(|(field, &agreement)| self.evidence(field, agreement))
+            .collect();
+        let match_weight = evidence.iter().sum();
+        Ok(WeightReport {
+            fields: report.fields,
+            match_probability: self.match_probability(&agreement),
+            agreement,
+            evidence,
+            match_weight,
+            threshold,
+            is_match: match_weight >= threshold,
+        })
+    }
+
+    /// The probability of a record pair with the given agreement pattern
+    /// being a match, taking the proportion of matches into account.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pattern doesn't have one entry per field.
+    pub fn match_probability(&self, pattern: &[Option<bool>]) -> f64 {
+        let prior_weight = log2(self.match_proportion / (1.0 - self.match_proportion));
+        let weight = self.match_weight(pattern) + prior_weight;
+        1.0 / (1.0 + exp2(-weight))
+    }
+}
+
+/// Generates the keys used to group records before comparing them. The keys
+/// ignore case, and `block_by` doesn't group values with an empty key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockingKey {
+    /// The first `n` characters of the value, ignoring leading whitespace.
+    Prefix(usize),
+    /// The Soundex code of the value.
+    Soundex,
+    /// The alphabetically first `count` distinct character n-grams of length
+    /// `n`. Unlike a prefix, the key doesn't depend on the start of the
+    /// value, so a typo in the first characters doesn't necessarily change
+    /// it.
+    NgramPrefix {
+        /// The length of the n-grams.
+        n: usize,
+        /// The number of n-grams in the key.
+        count: usize,
+    },
+}
+
+impl BlockingKey {
+    /// Generates the key of a value.
+    ///
+    /// ```
+    /// use strsim::record::BlockingKey;
+    ///
+    /// assert_eq!("smi", BlockingKey::Prefix(3).key("Smith"));
+    /// assert_eq!("S530", BlockingKey::Soundex.key("Smith"));
+    /// assert_eq!("itmi", BlockingKey::NgramPrefix { n: 2, count: 2 }.key("Smith"));
+    /// ```
+    pub fn key(&self, value: &str) -> String {
+        match *self {
+            BlockingKey::Prefix(n) => value
+                .trim_start()
+                .chars()
+                .take(n)
+                .collect::<String>()
+                .to_lowercase(),
+            BlockingKey::Soundex => soundex(value),
+            BlockingKey::NgramPrefix { n, count } => {
+                let chars: Vec<char> = value.to_lowercase().chars().collect();
+                if n == 0 || chars.len() < n {
+                    return String::new();
+                }
+                let mut ngrams: Vec<&[char]> = chars.windows(n).collect();
+                ngrams.sort_unstable();
+                ngrams.dedup();
+                ngrams.into_iter().take(count).flatten().collect()
+            }
+        }
+    }
+}
+
+/// Groups the records by the key generated by `key`, omitting records
+/// without a key or with an empty key. Every group lists the indices of its
+/// records in increasing order.
+pub fn block_by<T, F>(records: &[T], mut key: F) -> BTreeMap<String, Vec<usize>>
+where
+    F: FnMut(&T) -> Option<String>,
+{
+    let mut blocks: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (i, record) in records.iter().enumerate() {
+        if let Some(key) = key(record) {
+            if !key.is_empty() {
+                blocks.entry(key).or_default().push(i);
+            }
+        }
+    }
+    blocks
+}
+
+/// Returns the pairs of records sharing at least one block, each pair once
+/// with the smaller index first. The blocks of several `block_by` calls can
+/// be chained to compare records sharing any of several keys.
+pub fn candidate_pairs<'a, I>(blocks: I) -> Vec<(usize, usize)>
+where
+    I: IntoIterator<Item = &'a Vec<usize>>,
+{
+    let mut pairs = Vec::new();
+    for block in blocks {
+        for (k, &i) in block.iter().enumerate() {
+            for &j in &block[k + 1..] {
+                pairs.push((min(i, j), max(i, j)));
+            }
+        }
+    }
+    pairs.sort_unstable();
+    pairs.dedup();
+    pairs
+}
+
+/// Returns the candidate pairs of the sorted-neighborhood method, as an
+/// alternative to blocking. The records are sorted by the key generated by
+/// `key`, and every record is paired with the `window - 1` records following
+/// it in this order. Records without a key or with an empty key are omitted,
+/// records with the same key keep their order.
+///
+/// Unlike blocking, similar records with slightly different keys can still
+/// be compared, while the number of pairs grows only linearly with the
+/// number of records. A typo near the start of the key can move a record
+/// far away from its duplicates, which is why the method is usually run with
+/// several keys and the pairs of all passes are merged. Each pair is
+/// returned once with the smaller index first.
+///
+/// ```
+/// use strsim::record::{sorted_neighborhood, BlockingKey};
+///
+/// let names = ["Smith", "Jones", "Smyth", "Smithe", "Johns"];
+/// let pairs = sorted_neighborhood(&names, 2, |name| Some(BlockingKey::Prefix(10).key(name)));
+/// // sorted: johns, jones, smith, smithe, smyth
+/// assert_eq!(vec![(0, 1), (0, 3), (1, 4), (2, 3)], pairs);
+/// ```
+pub fn sorted_neighborhood<T, F>(records: &[T], window: usize, mut key: F) -> Vec<(usize, usize)>
+where
+    F: FnMut(&T) -> Option<String>,
+{
+    let mut keyed: Vec<(String, usize)> = records
+        .iter()
+        .enumerate()
+        .filter_map(|(i, record)| key(record).map(|key| (key, i)))
+        .filter(|(key, _)| !key.is_empty())
+        .collect();
+    keyed.sort();
+
+    let mut pairs = Vec::new();
+    for (k, &(_, i)) in keyed.iter().enumerate() {
+        for &(_, j) in keyed.iter().skip(k + 1).take(window.saturating_sub(1)) {
+            pairs.push((min(i, j), max(i, j)));
+        }
+    }
+    pairs.sort_unstable();
+    pairs.dedup();
+    pairs
+}
+
+/// The graph of the record pairs with a similarity of at least a threshold,
+/// to inspect the matches before clustering them. The nodes are the indices
+/// of the records.
+///
+/// ```
+/// use strsim::record::{sorted_neighborhood, SimilarityGraph};
+/// use strsim::jaro_winkler;
+///
+/// let names = ["Martha", "Marhta", "Dwayne", "Duane"];
+/// let pairs = sorted_neighborhood(&names, 3, |name| Some(name.to_lowercase()));
+/// let graph = SimilarityGraph::new(&names, &pairs, 0.8, |a, b| jaro_winkler(a, b));
+///
+/// assert_eq!(2, graph.edges().len());
+/// assert_eq!(vec![1], graph.neighbors(0).iter().map(|&(j, _)| j).collect::<Vec<_>>());
+/// assert!(graph.neighbors(2).len() == 1);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimilarityGraph {
+    edges: Vec<(usize, usize, f64)>,
+    adjacency: Vec<Vec<(usize, f64)>>,
+}
+
+impl SimilarityGraph {
+    /// Scores the candidate `pairs` of `records` with `scorer` and keeps the
+    /// pairs with a similarity of at least `threshold` as edges. The pairs
+    /// are usually generated by `candidate_pairs` or `sorted_neighborhood`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a pair contains an index out of bounds of `records`.
+    pub fn new<T, F>(records: &[T], pairs: &[(usize, usize)], threshold: f64, scorer: F) -> Self
+    where
+        F: FnMut(&T, &T) -> f64,
+    {
+        match Self::with_control(records, pairs, threshold, scorer, &Control::new()) {
+            Ok(graph) => graph,
+            Err(_) => unreachable!("the scoring is never cancelled"),
+        }
+    }
+
+    /// Builds the graph like `new`, unless the scoring is cancelled through
+    /// `control`. The progress is the number of scored pairs.
+    ///
+    /// ```
+    /// use std::sync::atomic::AtomicBool;
+    /// use strsim::record::SimilarityGraph;
+    /// use strsim::{jaro_winkler, Control, StrSimError};
+    ///
+    /// let names = ["martha", "marhta", "dwayne"];
+    /// let cancelled = AtomicBool::new(true);
+    /// let control = Control::new().cancel_flag(&cancelled);
+    /// let graph = SimilarityGraph::with_control(&names, &[(0, 1)], 0.9, |a, b| jaro_winkler(a, b), &control);
+    /// assert_eq!(Some(StrSimError::Cancelled), graph.err());
+    /// ```
+    pub fn with_control<T, F>(
+        records: &[T],
+        pairs: &[(usize, usize)],
+        threshold: f64,
+        scorer: F,
+        control: &Control<'_>,
+    ) -> Result<Self, StrSimError>
+    where
+        F: FnMut(&T, &T) -> f64,
+    {
+        Self::with_cache(records, pairs, threshold, scorer, &mut NoCache, control)
+    }
+
+    /// Builds the graph like `with_control`, but looks the pairs up in
+    /// `cache` by the indices of their records before calling `scorer`, and
+    /// caches the similarities `scorer` returns. Pairs repeated in `pairs`,
+    /// e.g. because both records share several blocks, are scored once, and
+    /// passing the same cache again to build the graph with another
+    /// `threshold` doesn't score any pair again. The pairs `(i, j)` and
+    /// `(j, i)` are cached separately.
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use strsim::record::SimilarityGraph;
+    /// use strsim::{jaro_winkler, Control};
+    ///
+    /// let names = ["martha", "marhta", "dwayne"];
+    /// let pairs = [(0, 1), (0, 2), (0, 1)];
+    /// let mut cache = HashMap::new();
+    /// let mut calls = 0;
+    /// let mut scorer = |a: &&str, b: &&str| {
+    ///     calls += 1;
+    ///     jaro_winkler(a, b)
+    /// };
+    /// let control = Control::new();
+    /// let strict = SimilarityGraph::with_cache(&names, &pairs, 0.9, &mut scorer, &mut cache, &control).unwrap();
+    /// let loose = SimilarityGraph::with_cache(&names, &pairs, 0.0, &mut scorer, &mut cache, &control).unwrap();
+    /// assert_eq!(2, strict.edges().len());
+    /// assert_eq!(3, loose.edges().len());
+    /// assert_eq!(2, calls);
+    /// ```
+    pub fn with_cache<T, F, C>(
+        records: &[T],
+        pairs: &[(usize, usize)],
+        threshold: f64,
+        mut scorer: F,
+        cache: &mut C,
+        control: &Control<'_>,
+    ) -> Result<Self, StrSimError>
+    where
+        F: FnMut(&T, &T) -> f64,
+        C: ScoreCache<(usize, usize)> + ?Sized,
+    {
+        let mut edges = Vec::new();
+        let mut adjacency = vec![Vec::new(); records.len()];
+        for (scored, &(i, j)) in pairs.iter().enumerate() {
+            let similarity = match cache.get(&(i, j)) {
+                Some(similarity) => similarity,
+                None => {
+                    let similarity = scorer(&records[i], &records[j]);
+                    cache.insert((i, j), similarity);
+                    similarity
+                }
+            };
+            if similarity >= threshold {
+                edges.push((i, j, similarity));
+                adjacency[i].push((j, similarity));
+                if i != j {
+                    adjacency[j].push((i, similarity));
+                }
+            }
+            control.step(scored + 1, pairs.len())?;
+        }
+        Ok(Self { edges, adjacency })
+    }
+
+    /// The number of nodes, which is the number of records.
+    pub fn node_count(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    /// The edges as the indices of both records and their similarity, in the
+    /// order of the candidate pairs.
+    pub fn edges(&self) -> &[(usize, usize, f64)] {
+        &self.edges
+    }
+
+    /// The records connected to the record at `index` with their similarity,
+    /// which is the adjacency list of the node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn neighbors(&self, index: usize) -> &[(usize, f64)] {
+        &self.adjacency[index]
+    }
+
+    /// Exports the edges as CSV with the header `a,b,similarity`.
+    ///
+    /// ```
+    /// use strsim::record::SimilarityGraph;
+    ///
+    /// let graph = SimilarityGraph::new(&[1.0, 0.9, 0.1], &[(0, 1), (0, 2)], 0.5, |a, b| 1.0 - f64::abs(a - b));
+    /// assert_eq!("a,b,similarity\n0,1,0.9\n", graph.to_csv());
+    /// ```
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("a,b,similarity\n");
+        for &(i, j, similarity) in &self.edges {
+            csv.push_str(&format!("{},{},{}\n", i, j, similarity));
+        }
+        csv
+    }
+
+    /// Exports the graph in the DOT format of Graphviz, with the indices as
+    /// node labels and the similarities as edge labels. Nodes without edges
+    /// are included.
+    pub fn to_dot(&self) -> String {
+        let labels: Vec<String> = (0..self.node_count()).map(|i| i.to_string()).collect();
+        self.to_dot_with_labels(&labels)
+    }
+
+    /// Like `to_dot`, but labels the nodes with `labels`, usually the
+    /// records themselves. Nodes without a label are labeled with their
+    /// index.
+    ///
+    /// ```
+    /// use strsim::record::SimilarityGraph;
+    ///
+    /// let names = ["Jon", "John"];
+    /// let graph = SimilarityGraph::new(&names, &[(0, 1)], 0.0, |_, _| 0.75);
+    /// assert_eq!(
+    ///     "graph {\n  0 [label=\"Jon\"];\n  1 [label=\"John\"];\n  0 -- 1 [label=\"0.75\"];\n}\n",
+    ///     graph.to_dot_with_labels(&names)
+    /// );
+    /// ```
+    pub fn to_dot_with_labels<S: AsRef<str>>(&self, labels: &[S]) -> String {
+        let mut dot = String::from("graph {\n");
+        for i in 0..self.node_count() {
+            let label = match labels.get(i) {
+                Some(label) => escape_dot(label.as_ref()),
+                None => i.to_string(),
+            };
+            dot.push_str(&format!("  {} [label=\"{}\"];\n", i, label));
+        }
+        for &(i, j, similarity) in &self.edges {
+            dot.push_str(&format!("  {} -- {} [label=\"{}\"];\n", i, j, similarity));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// How `cluster` calculates the similarity of two clusters from the
+/// similarities of their records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Linkage {
+    /// The similarity of the most similar pair of records. A chain of similar
+    /// records can link dissimilar records into one cluster.
+    Single,
+    /// The similarity of the least similar pair of records, so every pair of
+    /// records in a cluster reaches the threshold.
+    Complete,
+    /// The mean similarity of all pairs of records.
+    Average,
+}
+
+impl Linkage {
+    /* The similarity of the merged clusters `i` and `j` to another cluster,
+    by the Lance-Williams update */
+    fn merge(self, i_similarity: f64, j_similarity: f64, i_size: usize, j_size: usize) -> f64 {
+        match self {
+            Linkage::Single => i_similarity.max(j_similarity),
+            Linkage::Complete => i_similarity.min(j_similarity),
+            Linkage::Average => {
+                (i_size as f64 * i_similarity + j_size as f64 * j_similarity)
+                    / (i_size + j_size) as f64
+            }
+        }
+    }
+}
+
+/// Groups the records with agglomerative hierarchical clustering. Every
+/// record starts in its own cluster, and the two most similar clusters are
+/// merged as long as their similarity by the `linkage` is at least
+/// `threshold`. Unlike the connected components of a `SimilarityGraph`,
+/// which link records like `Linkage::Single`, `Linkage::Complete` and
+/// `Linkage::Average` don't chain dissimilar records together.
+///
+/// Every pair of records is scored once, so it needs `O(n^2)` memory and
+/// calls of `scorer`, and `O(n^3)` time for `n` records. Blocking the records
+/// first and clustering every block keeps `n` small. Among equally similar
+/// clusters, the ones with the smallest indices are merged first. Returns the
+/// clusters as the indices of their records in increasing order, ordered by
+/// their first record.
+///
+/// ```
+/// use strsim::record::{cluster, Linkage};
+/// use strsim::normalized_levenshtein;
+///
+/// let names = ["abcd", "abce", "abxe", "axxe"];
+/// let scorer = |a: &&str, b: &&str| normalized_levenshtein(a, b);
+/// // every name is within one edit of the next one
+/// assert_eq!(vec![vec![0, 1, 2, 3]], cluster(&names, 0.75, Linkage::Single, scorer));
+/// assert_eq!(vec![vec![0, 1], vec![2, 3]], cluster(&names, 0.75, Linkage::Complete, scorer));
+/// ```
+pub fn cluster<T, F>(records: &[T], threshold: f64, linkage: Linkage, scorer: F) -> Vec<Vec<usize>>
+where
+    F: FnMut(&T, &T) -> f64,
+{
+    match cluster_with_control(records, threshold, linkage, scorer, &Control::new()) {
+        Ok(clusters) => clusters,
+        Err(_) => unreachable!("the clustering is never cancelled"),
+    }
+}
+
+/// Clusters the records like `cluster`, unless the clustering is cancelled
+/// through `control`. The progress counts the scored pairs followed by the
+/// merges of clusters, out of `n * (n - 1) / 2` pairs and `n - 1` merges for
+/// `n` records. It skips to the total once no clusters are similar enough to
+/// be merged.
+///
+/// ```
+/// use std::sync::atomic::AtomicBool;
+/// use strsim::record::{cluster_with_control, Linkage};
+/// use strsim::{normalized_levenshtein, Control, StrSimError};
+///
+/// let names = ["abcd", "abce", "abxe", "axxe"];
+/// let scorer = |a: &&str, b: &&str| normalized_levenshtein(a, b);
+/// let cancelled = AtomicBool::new(true);
+/// let control = Control::new().cancel_flag(&cancelled);
+/// assert_eq!(
+///     Some(StrSimError::Cancelled),
+///     cluster_with_control(&names, 0.75, Linkage::Single, scorer, &control).err()
+/// );
+/// ```
+pub fn cluster_with_control<T, F>(
+    records: &[T],
+    threshold: f64,
+    linkage: Linkage,
+    mut scorer: F,
+    control: &Control<'_>,
+) -> Result<Vec<Vec<usize>>, StrSimError>
+where
+    F: FnMut(&T, &T) -> f64,
+{
+    let n = records.len();
+    let pair_count = n * n.saturating_sub(1) / 2;
+    let total = pair_count + n.saturating_sub(1);
+    let mut similarities = vec![f64::NAN; n * n];
+    let mut scored = 0;
+    for i in 0..n {
+        for j in i + 1..n {
+            let similarity = scorer(&records[i], &records[j]);
+            similarities[i * n + j] = similarity;
+            similarities[j * n + i] = similarity;
+            scored += 1;
+            control.step(scored, total)?;
+        }
+    }
+    merge_clusters(similarities, n, threshold, linkage, control, pair_count)
+}
+
+/// Clusters `record_count` records like `cluster`, using the precomputed
+/// `similarities` of the records instead of scoring them. The similarity of
+/// the records `i` and `j` is `similarities[i * record_count + j]` for
+/// `i < j`; the diagonal and the lower triangle are ignored. Distances are
+/// clustered by negating them along with the threshold.
+///
+/// # Panics
+///
+/// Panics if `similarities` doesn't have `record_count * record_count`
+/// elements.
+///
+/// ```
+/// use strsim::record::{cluster_matrix, Linkage};
+///
+/// let distances = [
+///     0.0, 1.0, 4.0,
+///     1.0, 0.0, 2.0,
+///     4.0, 2.0, 0.0,
+/// ];
+/// let similarities: Vec<f64> = distances.iter().map(|distance| -distance).collect();
+/// assert_eq!(vec![vec![0, 1, 2]], cluster_matrix(&similarities, 3, -2.0, Linkage::Single));
+/// assert_eq!(vec![vec![0, 1], vec![2]], cluster_matrix(&similarities, 3, -2.0, Linkage::Complete));
+/// ```
+pub fn cluster_matrix(
+    similarities: &[f64],
+    record_count: usize,
+    threshold: f64,
+    linkage: Linkage,
+) -> Vec<Vec<usize>> {
+    match cluster_matrix_with_control(
+        similarities,
+        record_count,
+        threshold,
+        linkage,
+        &Control::new(),
+    ) {
+        Ok(clusters) => clusters,
+        Err(_) => unreachable!("the clustering is never cancelled"),
+    }
+}
+
+/// Clusters the records like `cluster_matrix`, unless the clustering is
+/// cancelled through `control`. The progress is the number of merges of
+/// clusters, out of `record_count - 1`, and skips to the total once no
+/// clusters are similar enough to be merged.
+///
+/// # Panics
+///
+/// Panics if `similarities` doesn't have `record_count * record_count`
+/// elements.
+pub fn cluster_matrix_with_control(
+    similarities: &[f64],
+    record_count: usize,
+    threshold: f64,
+    linkage: Linkage,
+    control: &Control<'_>,
+) -> Result<Vec<Vec<usize>>, StrSimError> {
+    let n = record_count;
+    assert_eq!(
+        n * n,
+        similarities.len(),
+        "the similarities of {} records are no square matrix",
+        n
+    );
+    let mut symmetric = similarities.to_vec();
+    for i in 0..n {
+        for j in 0..i {
+            symmetric[i * n + j] = symmetric[j * n + i];
+        }
+    }
+    merge_clusters(symmetric, n, threshold, linkage, control, 0)
+}
+
+/* Merges the most similar clusters of the `n` records with the symmetric
+matrix of `similarities`, reporting the merges as progress after the first
+`done` steps */
+fn merge_clusters(
+    mut similarities: Vec<f64>,
+    n: usize,
+    threshold: f64,
+    linkage: Linkage,
+    control: &Control<'_>,
+    mut done: usize,
+) -> Result<Vec<Vec<usize>>, StrSimError> {
+    let total = done + n.saturating_sub(1);
+    /* the clusters are stored at the index of their first record, merged
+    clusters are None */
+    let mut clusters: Vec<Option<Vec<usize>>> = (0..n).map(|i| Some(vec![i])).collect();
+    let mut active: Vec<usize> = (0..n).collect();
+    loop {
+        let mut best: Option<(usize, usize, f64)> = None;
+        for (k, &i) in active.iter().enumerate() {
+            for &j in &active[k + 1..] {
+                let similarity = similarities[i * n + j];
+                if similarity >= threshold && best.map_or(true, |(_, _, best)| similarity > best) {
+                    best = Some((i, j, similarity));
+                }
+            }
+        }
+        let (i, j) = match best {
+            Some((i, j, _)) => (i, j),
+            None => break,
+        };
+
+        let merged = clusters[j].take().unwrap_or_default();
+        let cluster = clusters[i].get_or_insert_with(Vec::new);
+        let (i_size, j_size) = (cluster.len(), merged.len());
+        cluster.extend(merged);
+        active.retain(|&k| k != j);
+        for &k in &active {
+            if k != i {
+                let similarity = linkage.merge(
+                    similarities[i * n + k],
+                    similarities[j * n + k],
+                    i_size,
+                    j_size,
+                );
+                similarities[i * n + k] = similarity;
+                similarities[k * n + i] = similarity;
+            }
+        }
+        done += 1;
+        control.step(done, total)?;
+    }
+    if done < total {
+        control.step(total, total)?;
+    }
+
+    Ok(clusters
+        .into_iter()
+        .flatten()
+        .map(|mut cluster| {
+            cluster.sort_unstable();
+            cluster
+        })
+        .collect())
+}
+
+/// Returns up to `count` distinct pairs of records, drawn uniformly at random
+/// from all pairs of `record_count` records. Their scores show how similar
+/// records which are usually no duplicates are. The same `seed` always
+/// returns the same pairs. Each pair is returned once with the smaller index
+/// first, and the pairs are sorted.
+///
+/// ```
+/// use strsim::record::random_pairs;
+///
+/// let pairs = random_pairs(100, 10, 42);
+/// assert_eq!(10, pairs.len());
+/// assert!(pairs.iter().all(|&(i, j)| i < j && j < 100));
+/// assert_eq!(pairs, random_pairs(100, 10, 42));
+/// assert_eq!(vec![(0, 1), (0, 2), (1, 2)], random_pairs(3, 10, 42));
+/// ```
+pub fn random_pairs(record_count: usize, count: usize, seed: u64) -> Vec<(usize, usize)> {
+    let total = record_count.saturating_mul(record_count.saturating_sub(1)) / 2;
+    if count.saturating_mul(2) >= total {
+        /* drawing most of the pairs is faster by picking from all of them */
+        let all: Vec<(usize, usize)> = (0..record_count)
+            .flat_map(|i| (i + 1..record_count).map(move |j| (i, j)))
+            .collect();
+        return sample_pairs(&all, count, seed);
+    }
+
+    let mut rng = SplitMix64(seed);
+    let mut pairs = BTreeSet::new();
+    while pairs.len() < count {
+        let i = rng.below(record_count);
+        let j = rng.below(record_count);
+        if i != j {
+            pairs.insert((min(i, j), max(i, j)));
+        }
+    }
+    pairs.into_iter().collect()
+}
+
+/// Returns up to `count` of the `pairs`, drawn uniformly at random without
+/// replacement, to score a sample of the candidate pairs of
+/// `candidate_pairs` or `sorted_neighborhood`. The same `seed` always
+/// returns the same pairs. The sampled pairs are sorted.
+///
+/// ```
+/// use strsim::record::sample_pairs;
+///
+/// let pairs = [(0, 1), (0, 2), (1, 2), (2, 3)];
+/// let sample = sample_pairs(&pairs, 2, 7);
+/// assert_eq!(2, sample.len());
+/// assert!(sample.iter().all(|pair| pairs.contains(pair)));
+/// ```
+pub fn sample_pairs(pairs: &[(usize, usize)], count: usize, seed: u64) -> Vec<(usize, usize)> {
+    let mut pairs = pairs.to_vec();
+    let count = min(count, pairs.len());
+    let mut rng = SplitMix64(seed);
+    /* a partial Fisher-Yates shuffle */
+    for k in 0..count {
+        let swap = k + rng.below(pairs.len() - k);
+        pairs.swap(k, swap);
+    }
+    pairs.truncate(count);
+    pairs.sort_unstable();
+    pairs
+}
+
+/// The scores of a sample of record pairs, to compare the scores of random
+/// pairs with the scores of blocked candidate pairs before choosing a
+/// threshold. Random pairs are mostly no duplicates, so a threshold which
+/// many of them reach leads to many false matches.
+///
+/// ```
+/// use strsim::record::{block_by, candidate_pairs, random_pairs, BlockingKey, ScoreDistribution};
+/// use strsim::jaro_winkler;
+///
+/// let names = ["Robert Smith", "Rupert Smith", "Alice Jones", "Rob Smith", "Alicia Jones"];
+/// let scorer = |a: &&str, b: &&str| jaro_winkler(a, b);
+///
+/// let random = ScoreDistribution::new(&names, &random_pairs(names.len(), 4, 1), scorer);
+/// let blocks = block_by(&names, |name| Some(BlockingKey::Soundex.key(name)));
+/// let blocked = ScoreDistribution::new(&names, &candidate_pairs(blocks.values()), scorer);
+///
+/// assert_eq!(4, random.len());
+/// assert!(blocked.quantile(0.5).unwrap() > 0.8);
+/// assert!(blocked.fraction_at_least(0.8) >= random.fraction_at_least(0.8));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreDistribution {
+    scores: Vec<f64>,
+}
+
+impl ScoreDistribution {
+    /// Scores the `pairs` of `records` with `scorer`. Scores which are NaN
+    /// are left out.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a pair contains an index out of bounds of `records`.
+    pub fn new<T, F>(records: &[T], pairs: &[(usize, usize)], mut scorer: F) -> Self
+    where
+        F: FnMut(&T, &T) -> f64,
+    {
+        Self::from_scores(
+            pairs
+                .iter()
+                .map(|&(i, j)| scorer(&records[i], &records[j]))
+                .collect(),
+        )
+    }
+
+    /// Creates the distribution of already calculated scores. Scores which
+    /// are NaN are left out.
+    pub fn from_scores(mut scores: Vec<f64>) -> Self {
+        scores.retain(|score| !score.is_nan());
+        scores.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        Self { scores }
+    }
+
+    /// The scores in increasing order.
+    pub fn scores(&self) -> &[f64] {
+        &self.scores
+    }
+
+    /// The number of scores.
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    /// Whether there are no scores.
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    /// The score below which a fraction of `q` of the scores lie, as the
+    /// nearest score without interpolation. `q` is clamped between 0.0 and
+    /// 1.0. Returns `None` without scores.
+    ///
+    /// ```
+    /// use strsim::record::ScoreDistribution;
+    ///
+    /// let distribution = ScoreDistribution::from_scores(vec![0.4, 0.1, 0.3, 0.2]);
+    /// assert_eq!(Some(0.1), distribution.quantile(0.0));
+    /// assert_eq!(Some(0.3), distribution.quantile(0.5));
+    /// assert_eq!(Some(0.4), distribution.quantile(1.0));
+    /// ```
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if self.scores.is_empty() {
+            return None;
+        }
+        let q = if q.is_nan() { 0.0 } else { q.clamp(0.0, 1.0) };
+        let last = self.scores.len() - 1;
+        let index = (q * last as f64).round() as usize;
+        Some(self.scores[min(index, last)])
+    }
+
+    /// The fraction of the scores which reach `threshold`, or 0.0 without
+    /// scores. For random pairs, it estimates how often a threshold matches
+    /// two records by chance.
+    pub fn fraction_at_least(&self, threshold: f64) -> f64 {
+        if self.scores.is_empty() {
+            return 0.0;
+        }
+        let below = self.scores.partition_point(|&score| score < threshold);
+        (self.scores.len() - below) as f64 / self.scores.len() as f64
+    }
+
+    /// Counts the scores in `bins` equally wide bins between 0.0 and 1.0,
+    /// where the last bin includes 1.0. Scores outside of this range are
+    /// counted in the first or last bin. Returns no bins if `bins` is 0.
+    ///
+    /// ```
+    /// use strsim::record::ScoreDistribution;
+    ///
+    /// let distribution = ScoreDistribution::from_scores(vec![0.1, 0.2, 0.9, 1.0]);
+    /// assert_eq!(vec![2, 0, 2], distribution.histogram(3));
+    /// ```
+    pub fn histogram(&self, bins: usize) -> Vec<usize> {
+        let mut counts = vec![0; bins];
+        if bins == 0 {
+            return counts;
+        }
+        for &score in &self.scores {
+            let bin = (score.max(0.0) * bins as f64) as usize;
+            counts[min(bin, bins - 1)] += 1;
+        }
+        counts
+    }
+}
+
+/// How well a threshold separates the matches from the non-matches of a
+/// labeled set of record pairs, returned by `precision_recall` and
+/// `suggest_threshold`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThresholdEstimate {
+    /// The score from which pairs are considered to match.
+    pub threshold: f64,
+    /// The number of matches reaching the threshold.
+    pub true_positives: usize,
+    /// The number of non-matches reaching the threshold.
+    pub false_positives: usize,
+    /// The number of matches below the threshold.
+    pub false_negatives: usize,
+    /// The fraction of the pairs reaching the threshold which are matches.
+    pub precision: f64,
+    /// The fraction of the matches reaching the threshold.
+    pub recall: f64,
+    /// The harmonic mean of precision and recall.
+    pub f1: f64,
+}
+
+/// Estimates precision and recall for every score of the `labeled` pairs
+/// used as threshold, in increasing order of the threshold. Every labeled
+/// pair is its score and whether the records are a match, as decided by
+/// hand for a small sample of the pairs. Pairs scored NaN are left out.
+/// Returns no estimates if there are no labeled matches.
+///
+/// With a sample of a few dozen pairs, the estimates are rough, so the
+/// labeled pairs are best sampled near the thresholds in question.
+///
+/// ```
+/// use strsim::record::precision_recall;
+///
+/// let labeled = [(0.95, true), (0.9, false), (0.85, true), (0.5, false)];
+/// let estimates = precision_recall(&labeled);
+///
+/// assert_eq!(4, estimates.len());
+/// assert_eq!(0.85, estimates[1].threshold);
+/// assert!((estimates[1].precision - 2.0 / 3.0).abs() < 1e-12);
+/// assert_eq!(1.0, estimates[1].recall);
+/// ```
+pub fn precision_recall(labeled: &[(f64, bool)]) -> Vec<ThresholdEstimate> {
+    let mut labeled: Vec<(f64, bool)> = labeled
+        .iter()
+        .copied()
+        .filter(|(score, _)| !score.is_nan())
+        .collect();
+    let matches = labeled.iter().filter(|&&(_, is_match)| is_match).count();
+    if matches == 0 {
+        return Vec::new();
+    }
+    labeled.sort_unstable_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+
+    /* lower the threshold one distinct score at a time */
+    let mut estimates = Vec::new();
+    let (mut true_positives, mut false_positives) = (0, 0);
+    for (k, &(score, is_match)) in labeled.iter().enumerate() {
+        if is_match {
+            true_positives += 1;
+        } else {
+            false_positives += 1;
+        }
+        if labeled.get(k + 1).map_or(false, |&(next, _)| next == score) {
+            continue;
+        }
+        let precision = true_positives as f64 / (true_positives + false_positives) as f64;
+        let recall = true_positives as f64 / matches as f64;
+        let f1 = if true_positives == 0 {
+            0.0
+        } else {
+            2.0 * precision * recall / (precision + recall)
+        };
+        estimates.push(ThresholdEstimate {
+            threshold: score,
+            true_positives,
+            false_positives,
+            false_negatives: matches - true_positives,
+            precision,
+            recall,
+            f1,
+        });
+    }
+    estimates.reverse();
+    estimates
+}
+
+/// Suggests the threshold with the best F1 score on the `labeled` pairs,
+/// the highest one if several are equally good. See `precision_recall` for
+/// the labeled pairs. Returns `None` if there are no labeled matches.
+///
+/// ```
+/// use strsim::record::suggest_threshold;
+///
+/// let labeled = [(0.97, true), (0.93, true), (0.9, false), (0.88, true), (0.7, false), (0.6, false)];
+/// let estimate = suggest_threshold(&labeled).unwrap();
+///
+/// assert_eq!(0.88, estimate.threshold);
+/// assert_eq!(1, estimate.false_positives);
+/// assert_eq!(1.0, estimate.recall);
+/// ```
+pub fn suggest_threshold(labeled: &[(f64, bool)]) -> Option<ThresholdEstimate> {
+    precision_recall(labeled).into_iter().rev().fold(
+        None,
+        |best: Option<ThresholdEstimate>, estimate| match best {
+            Some(best) if best.f1 >= estimate.f1 => Some(best),
+            _ => Some(estimate),
+        },
+    )
+}
+
+/* SplitMix64, a small pseudo random number generator whose output only
+depends on the seed, for reproducible samples */
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /* a number below `n`, which must not be 0 */
+    fn below(&mut self, n: usize) -> usize {
+        ((u128::from(self.next()) * n as u128) >> 64) as usize
+    }
+}
+
+/* Escapes a label for a quoted DOT string */
+fn escape_dot(label: &str) -> String {
+    let mut escaped = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            '"' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{jaro_winkler, normalized_levenshtein};
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Mutex;
+
+    #[test]
+    fn portable_log2_and_exp2() {
+        let mut x = 1e-310;
+        while x < 1e300 {
+            let relative = (log2(x) - x.log2()).abs() / x.log2().abs().max(1.0);
+            assert!(relative < 1e-15, "log2({})", x);
+            x *= 1.37;
+        }
+        assert_eq!(0.0, log2(1.0));
+        assert_eq!(10.0, log2(1024.0));
+        assert_eq!(f64::NEG_INFINITY, log2(0.0));
+        assert!(log2(-1.0).is_nan());
+
+        let mut x = -1070.0;
+        while x < 1023.0 {
+            let relative = (exp2(x) - x.exp2()).abs() / x.exp2();
+            assert!(relative < 1e-15, "exp2({})", x);
+            x += 0.731;
+        }
+        assert_eq!(1024.0, exp2(10.0));
+        assert_eq!(0.0, exp2(-2000.0));
+        assert_eq!(f64::INFINITY, exp2(2000.0));
+    }
+
+    fn schema() -> Schema {
+        Schema::new()
+            .field(Field::new("name", jaro_winkler).weight(3.0))
+            .field(Field::new("city", normalized_levenshtein))
+    }
+
+    #[test]
+    fn compare_weighted_mean() {
+        let comparison = schema()
+            .compare(
+                &[Some("martha"), Some("york")],
+                &[Some("marhta"), Some("cork")],
+            )
+            .unwrap();
+        let name = jaro_winkler("martha", "marhta");
+        assert_delta!(name, comparison.field_scores[0].unwrap());
+        assert_delta!(0.75, comparison.field_scores[1].unwrap());
+        assert_delta!((3.0 * name + 0.75) / 4.0, comparison.score);
+    }
+
+    #[test]
+    fn compare_ignores_missing_values() {
+        let comparison = schema()
+            .compare(
+                &[Some("martha"), Some("  ")],
+                &[Some("martha"), Some("york")],
+            )
+            .unwrap();
+        assert_eq!(vec![Some(1.0), None], comparison.field_scores);
+        assert_delta!(1.0, comparison.score);
+    }
+
+    #[test]
+    fn compare_scores_missing_values() {
+        let schema = Schema::new()
+            .field(Field::new("name", jaro_winkler))
+            .field(Field::new("city", jaro_winkler).missing(MissingValue::Score(0.5)));
+        let comparison = schema
+            .compare(&[Some("martha"), None], &[Some("martha"), None])
+            .unwrap();
+        assert_eq!(vec![Some(1.0), Some(0.5)], comparison.field_scores);
+        assert_delta!(0.75, comparison.score);
+    }
+
+    #[test]
+    fn compare_missing_values_with_metric() {
+        let schema = Schema::new()
+            .field(Field::new("name", normalized_levenshtein).missing(MissingValue::Compare));
+        assert_eq!(
+            vec![Some(1.0)],
+            schema.compare(&[None], &[Some("")]).unwrap().field_scores
+        );
+        assert_eq!(
+            vec![Some(0.0)],
+            schema
+                .compare(&[Some(" ")], &[Some("ab")])
+                .unwrap()
+                .field_scores
+        );
+        let report = schema.report(&[None], &[None], 0.5).unwrap();
+        assert!(report.to_json().contains(r#""missing":"compare""#));
+    }
+
+    #[test]
+    fn missing_value_policies() {
+        let metric = |_: &str, _: &str| 0.25;
+        for (a, b) in [("", "a"), ("a", " "), ("\t", "")] {
+            assert_eq!(None, MissingValue::Ignore.score(a, b, metric));
+            assert_eq!(Some(0.5), MissingValue::Score(0.5).score(a, b, metric));
+            assert_eq!(Some(0.25), MissingValue::Compare.score(a, b, metric));
+        }
+        assert_eq!(Some(0.25), MissingValue::Ignore.score("a", "b", metric));
+        assert_eq!(Some(0.25), MissingValue::Score(0.5).score("a", "b", metric));
+    }
+
+    #[test]
+    fn compare_everything_missing() {
+        let comparison = schema().compare(&[None, None], &[None, None]).unwrap();
+        assert_eq!(vec![None, None], comparison.field_scores);
+        assert_delta!(0.0, comparison.score);
+    }
+
+    #[test]
+    fn compare_wrong_number_of_values() {
+        assert_eq!(
+            Err(StrSimError::DifferentLengthArgs),
+            schema().compare(&[Some("martha")], &[Some("martha"), None])
+        );
+    }
+
+    #[test]
+    fn agreement_thresholds() {
+        let schema = Schema::new()
+            .field(Field::new("name", jaro_winkler))
+            .field(Field::new("city", normalized_levenshtein).agreement_threshold(0.7))
+            .field(Field::new("zip", normalized_levenshtein));
+        let agreement = schema
+            .agreement(
+                &[Some("martha"), Some("york"), None],
+                &[Some("marhta"), Some("cork"), Some("02134")],
+            )
+            .unwrap();
+        assert_eq!(vec![Some(true), Some(true), None], agreement);
+    }
+
+    fn synthetic_pairs() -> Vec<Vec<Option<bool>>> {
+        let mut pairs = Vec::new();
+        pairs.extend(vec![vec![Some(true), Some(true), Some(true)]; 18]);
+        pairs.extend(vec![vec![Some(true), Some(false), Some(true)]; 2]);
+        pairs.extend(vec![vec![Some(false), Some(false), Some(false)]; 60]);
+        pairs.extend(vec![vec![Some(true), Some(false), Some(false)]; 10]);
+        pairs.extend(vec![vec![Some(false), Some(true), Some(false)]; 10]);
+        pairs
+    }
+
+    #[test]
+    fn estimate_separates_matches() {
+        let model = FellegiSunter::estimate(&synthetic_pairs(), 100).unwrap();
+        assert_delta!(0.2, model.match_proportion(), 0.05);
+        for (&m, &u) in model.m_probabilities().iter().zip(model.u_probabilities()) {
+            assert!(m > 0.8, "m = {}", m);
+            assert!(u < 0.3, "u = {}", u);
+        }
+        assert!(model.match_probability(&[Some(true), Some(true), Some(true)]) > 0.95);
+        assert!(model.match_probability(&[Some(false), Some(false), Some(false)]) < 0.05);
+    }
+
+    #[test]
+    fn estimate_without_pairs() {
+        let model = FellegiSunter::estimate(&[], 10).unwrap();
+        assert!(model.m_probabilities().is_empty());
+        assert_delta!(0.0, model.match_weight(&[]));
+    }
+
+    #[test]
+    fn estimate_different_lengths() {
+        let pairs = vec![vec![Some(true)], vec![Some(true), None]];
+        assert_eq!(
+            Err(StrSimError::DifferentLengthArgs),
+            FellegiSunter::estimate(&pairs, 10)
+        );
+    }
+
+    #[test]
+    fn estimate_cancelled() {
+        let iterations = std::sync::atomic::AtomicUsize::new(0);
+        let cancelled = || iterations.fetch_add(1, std::sync::atomic::Ordering::Relaxed) >= 3;
+        let control = Control::new().cancel_when(&cancelled);
+        assert_eq!(
+            Err(StrSimError::Cancelled),
+            FellegiSunter::estimate_with_control(&synthetic_pairs(), 100, &control)
+        );
+        assert_eq!(4, iterations.into_inner());
+    }
+
+    #[test]
+    fn match_weight_known_probabilities() {
+        let model = FellegiSunter::new(vec![0.8, 0.9], vec![0.1, 0.6], 0.5).unwrap();
+        assert_delta!(3.0, model.match_weight(&[Some(true), None]));
+        assert_delta!(-2.0, model.match_weight(&[None, Some(false)]));
+        assert_delta!(0.5, model.match_probability(&[None, None]));
+    }
+
+    #[test]
+    fn blocking_key_prefix() {
+        assert_eq!("ab", BlockingKey::Prefix(2).key("  ABC"));
+        assert_eq!("a", BlockingKey::Prefix(2).key("a"));
+        assert_eq!("", BlockingKey::Prefix(0).key("abc"));
+    }
+
+    #[test]
+    fn blocking_key_ngram_prefix() {
+        let key = BlockingKey::NgramPrefix { n: 2, count: 3 };
+        // the n-grams of smith are sm, mi, it, th
+        assert_eq!("itmism", key.key("Smith"));
+        assert_eq!(key.key("Smith"), key.key("SMITH"));
+        assert_eq!("", key.key("a"));
+    }
+
+    #[test]
+    fn block_by_skips_empty_keys() {
+        let values = [Some("Smith"), None, Some("Smyth"), Some("!!")];
+        let blocks = block_by(&values, |value| value.map(|v| BlockingKey::Soundex.key(v)));
+        assert_eq!(1, blocks.len());
+        assert_eq!(vec![0, 2], blocks["S530"]);
+    }
+
+    #[test]
+    fn candidate_pairs_of_several_keys() {
+        let names = ["smith", "smyth", "smithe", "jones"];
+        let soundex = block_by(&names, |name| Some(BlockingKey::Soundex.key(name)));
+        let prefix = block_by(&names, |name| Some(BlockingKey::Prefix(4).key(name)));
+        assert_eq!(
+            vec![(0, 1), (0, 2), (1, 2)],
+            candidate_pairs(soundex.values().chain(prefix.values()))
+        );
+    }
+
+    #[test]
+    fn sorted_neighborhood_window() {
+        let values = ["d", "a", "c", "b"];
+        let key = |value: &&str| Some(value.to_string());
+        assert!(sorted_neighborhood(&values, 0, key).is_empty());
+        assert!(sorted_neighborhood(&values, 1, key).is_empty());
+        assert_eq!(
+            vec![(0, 2), (1, 3), (2, 3)],
+            sorted_neighborhood(&values, 2, key)
+        );
+        assert_eq!(6, sorted_neighborhood(&values, 10, key).len());
+    }
+
+    #[test]
+    fn sorted_neighborhood_skips_empty_keys() {
+        let values = [Some("Smith"), None, Some("Smyth"), Some("!!")];
+        let pairs = sorted_neighborhood(&values, 3, |value| {
+            value.map(|v| BlockingKey::Soundex.key(v))
+        });
+        assert_eq!(vec![(0, 2)], pairs);
+    }
+
+    #[test]
+    fn sorted_neighborhood_equal_keys() {
+        let values = ["x", "y", "x", "x"];
+        let pairs = sorted_neighborhood(&values, 2, |value| Some(value.to_string()));
+        // sorted: x (0), x (2), x (3), y (1)
+        assert_eq!(vec![(0, 2), (1, 3), (2, 3)], pairs);
+    }
+
+    #[test]
+    fn similarity_graph_threshold() {
+        let names = ["martha", "marhta", "martha", "dwayne"];
+        let pairs = [(0, 1), (0, 2), (0, 3), (1, 2)];
+        let graph = SimilarityGraph::new(&names, &pairs, 0.99, |a, b| jaro_winkler(a, b));
+        assert_eq!(4, graph.node_count());
+        assert_eq!(vec![(0, 2, 1.0)], graph.edges());
+        assert_eq!(&[(2, 1.0)], graph.neighbors(0));
+        assert_eq!(&[(0, 1.0)], graph.neighbors(2));
+        assert!(graph.neighbors(3).is_empty());
+    }
+
+    #[test]
+    fn similarity_graph_cache() {
+        let names = ["martha", "marhta", "martha", "dwayne"];
+        let pairs = [(0, 1), (0, 2), (0, 3), (1, 2), (0, 2)];
+        let mut cache = BTreeMap::new();
+        cache.insert((0, 3), 1.0);
+        let mut scored = Vec::new();
+        let graph = SimilarityGraph::with_cache(
+            &names,
+            &pairs,
+            0.99,
+            |a, b| {
+                scored.push((*a, *b));
+                jaro_winkler(a, b)
+            },
+            &mut cache,
+            &Control::new(),
+        )
+        .unwrap();
+        assert_eq!(vec![(0, 2, 1.0), (0, 3, 1.0), (0, 2, 1.0)], graph.edges());
+        assert_eq!(
+            vec![
+                ("martha", "marhta"),
+                ("martha", "martha"),
+                ("marhta", "martha")
+            ],
+            scored
+        );
+        assert_eq!(4, cache.len());
+    }
+
+    #[test]
+    fn similarity_graph_cancelled() {
+        let names = vec!["martha"; 100];
+        let pairs: Vec<(usize, usize)> = (1..100).map(|j| (0, j)).collect();
+        let mut scored = 0;
+        let flag = std::sync::atomic::AtomicBool::new(false);
+        let control = Control::new().cancel_flag(&flag);
+        let graph = SimilarityGraph::with_control(
+            &names,
+            &pairs,
+            0.9,
+            |a, b| {
+                scored += 1;
+                flag.store(scored >= 10, std::sync::atomic::Ordering::Relaxed);
+                jaro_winkler(a, b)
+            },
+            &control,
+        );
+        assert_eq!(Err(StrSimError::Cancelled), graph);
+        // the flag is checked every 64 pairs
+        assert_eq!(64, scored);
+    }
+
+    #[test]
+    fn cluster_linkages() {
+        // points on a line, with their closeness as similarity
+        let points = [0.0, 1.0, 2.0, 3.0, 10.0, 10.5];
+        let scorer = |a: &f64, b: &f64| 1.0 - (a - b).abs() / 10.0;
+        assert_eq!(
+            vec![vec![0, 1, 2, 3], vec![4, 5]],
+            cluster(&points, 0.9, Linkage::Single, scorer)
+        );
+        assert_eq!(
+            vec![vec![0, 1], vec![2, 3], vec![4, 5]],
+            cluster(&points, 0.8, Linkage::Complete, scorer)
+        );
+        // the mean distance of {0, 1} and {2, 3} is 2
+        assert_eq!(
+            vec![vec![0, 1, 2, 3], vec![4, 5]],
+            cluster(&points, 0.8, Linkage::Average, scorer)
+        );
+        assert_eq!(
+            vec![vec![0, 1], vec![2, 3], vec![4, 5]],
+            cluster(&points, 0.81, Linkage::Average, scorer)
+        );
+        assert_eq!(
+            vec![vec![0, 1, 2, 3, 4, 5]],
+            cluster(&points, -1.0, Linkage::Complete, scorer)
+        );
+    }
+
+    #[test]
+    fn cluster_edge_cases() {
+        let none: [f64; 0] = [];
+        assert!(cluster(&none, 0.5, Linkage::Average, |_, _| 1.0).is_empty());
+        assert_eq!(
+            vec![vec![0], vec![1]],
+            cluster(&[1, 2], 0.5, Linkage::Single, |_, _| f64::NAN)
+        );
+        assert_eq!(
+            vec![vec![0, 2], vec![1, 3]],
+            cluster(&[0, 1, 2, 3], 0.5, Linkage::Complete, |a, b| {
+                if (a + b) % 2 == 0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            })
+        );
+        // ties merge the clusters with the smallest indices first, and the
+        // merged cluster of 0 and 1 is not similar enough to 2
+        assert_eq!(
+            vec![vec![0, 1], vec![2]],
+            cluster(&[0, 1, 2], 0.5, Linkage::Complete, |a, b| {
+                if a + b == 2 {
+                    0.0
+                } else {
+                    1.0
+                }
+            })
+        );
+        let mut scored = 0;
+        cluster(&[1, 2, 3, 4], 0.5, Linkage::Single, |_, _| {
+            scored += 1;
+            0.0
+        });
+        assert_eq!(6, scored);
+    }
+
+    #[test]
+    fn cluster_matrix_matches_cluster() {
+        let points = [0.0, 1.0, 2.0, 3.0, 10.0, 10.5];
+        let scorer = |a: &f64, b: &f64| 1.0 - (a - b).abs() / 10.0;
+        // the lower triangle is ignored
+        let similarities: Vec<f64> = (0..36)
+            .map(|k| {
+                let (i, j) = (k / 6, k % 6);
+                if i < j {
+                    scorer(&points[i], &points[j])
+                } else {
+                    f64::NAN
+                }
+            })
+            .collect();
+        for &linkage in &[Linkage::Single, Linkage::Complete, Linkage::Average] {
+            for &threshold in &[0.8, 0.81, 0.9] {
+                assert_eq!(
+                    cluster(&points, threshold, linkage, scorer),
+                    cluster_matrix(&similarities, 6, threshold, linkage)
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "no square matrix")]
+    fn cluster_matrix_checks_size() {
+        cluster_matrix(&[1.0, 0.5, 0.5], 2, 0.5, Linkage::Single);
+    }
+
+    #[test]
+    fn cluster_with_control_reports_merges() {
+        let reports = Mutex::new(Vec::new());
+        let progress = |done, total| reports.lock().unwrap().push((done, total));
+        let control = Control::new().on_progress(&progress);
+        let points: Vec<f64> = (0..20).map(f64::from).collect();
+        let scorer = |a: &f64, b: &f64| 1.0 - (a - b).abs() / 10.0;
+        // 190 pairs and up to 19 merges, of which 10 are done before the
+        // clusters are too far apart
+        let clusters = cluster_with_control(&points, 0.9, Linkage::Complete, scorer, &control);
+        assert_eq!(10, clusters.unwrap().len());
+        assert_eq!(
+            vec![(64, 209), (128, 209), (192, 209), (209, 209)],
+            *reports.lock().unwrap()
+        );
+
+        // the merges are checked for cancellation as well
+        let cancelled = AtomicBool::new(true);
+        let control = Control::new().cancel_flag(&cancelled);
+        assert_eq!(
+            Some(StrSimError::Cancelled),
+            cluster_matrix_with_control(&[1.0; 4], 2, 0.5, Linkage::Single, &control).err()
+        );
+    }
+
+    #[test]
+    fn random_pairs_are_distinct_and_reproducible() {
+        for &(record_count, count) in &[(0, 5), (1, 5), (2, 5), (10, 3), (10, 40), (1000, 500)] {
+            let pairs = random_pairs(record_count, count, 3);
+            let total = record_count * record_count.saturating_sub(1) / 2;
+            assert_eq!(min(count, total), pairs.len());
+            assert!(pairs.windows(2).all(|w| w[0] < w[1]));
+            assert!(pairs.iter().all(|&(i, j)| i < j && j < record_count));
+            assert_eq!(pairs, random_pairs(record_count, count, 3));
+        }
+        assert_ne!(random_pairs(1000, 10, 1), random_pairs(1000, 10, 2));
+
+        // every record is drawn roughly equally often
+        let mut counts = [0; 10];
+        for (i, j) in random_pairs(10_000, 5_000, 9) {
+            counts[i % 10] += 1;
+            counts[j % 10] += 1;
+        }
+        assert!(counts.iter().all(|&count| count > 800 && count < 1200));
+    }
+
+    #[test]
+    fn sample_pairs_without_replacement() {
+        let pairs: Vec<(usize, usize)> = (0..20).map(|i| (i, i + 1)).collect();
+        assert_eq!(pairs, sample_pairs(&pairs, 30, 5));
+        let sample = sample_pairs(&pairs, 8, 5);
+        assert_eq!(8, sample.len());
+        assert!(sample.windows(2).all(|w| w[0] < w[1]));
+        assert!(sample.iter().all(|pair| pairs.contains(pair)));
+        assert!(sample_pairs(&[], 3, 5).is_empty());
+    }
+
+    #[test]
+    fn score_distribution() {
+        let distribution = ScoreDistribution::new(
+            &[0.0_f64, 0.5, 0.9, 1.0],
+            &[(0, 1), (1, 2), (2, 3), (0, 3)],
+            |a, b| {
+                if a == &0.0 && b == &1.0 {
+                    f64::NAN
+                } else {
+                    1.0 - (a - b).abs()
+                }
+            },
+        );
+        assert_eq!(3, distribution.len());
+        assert_delta!(0.5, distribution.scores()[0]);
+        assert_delta!(0.9, distribution.quantile(1.0).unwrap());
+        assert_delta!(0.6, distribution.quantile(0.5).unwrap());
+        assert_delta!(2.0 / 3.0, distribution.fraction_at_least(0.55));
+        assert_eq!(vec![0, 2, 1], distribution.histogram(3));
+        assert!(distribution.histogram(0).is_empty());
+
+        let empty = ScoreDistribution::from_scores(vec![f64::NAN]);
+        assert!(empty.is_empty());
+        assert_eq!(None, empty.quantile(0.5));
+        assert_eq!(0.0, empty.fraction_at_least(0.5));
+        assert_eq!(
+            vec![1, 0, 1],
+            ScoreDistribution::from_scores(vec![-0.5, 1.5]).histogram(3)
+        );
+    }
+
+    #[test]
+    fn precision_recall_of_labeled_pairs() {
+        let labeled = [
+            (0.9, true),
+            (0.8, true),
+            (0.8, false),
+            (f64::NAN, true),
+            (0.4, false),
+            (0.6, true),
+        ];
+        let estimates = precision_recall(&labeled);
+        let thresholds: Vec<f64> = estimates.iter().map(|e| e.threshold).collect();
+        assert_eq!(vec![0.4, 0.6, 0.8, 0.9], thresholds);
+        // equal scores are counted together
+        assert_eq!(2, estimates[2].true_positives);
+        assert_eq!(1, estimates[2].false_positives);
+        assert_eq!(1, estimates[2].false_negatives);
+        assert_delta!(2.0 / 3.0, estimates[2].precision);
+        assert_delta!(2.0 / 3.0, estimates[2].recall);
+        assert_eq!(0, estimates[3].false_positives);
+        assert_delta!(0.5, estimates[3].f1);
+
+        let best = suggest_threshold(&labeled).unwrap();
+        assert_eq!(0.6, best.threshold);
+        assert_delta!(6.0 / 7.0, best.f1);
+
+        assert!(precision_recall(&[(0.5, false)]).is_empty());
+        assert_eq!(None, suggest_threshold(&[]));
+    }
+
+    #[test]
+    fn suggest_threshold_prefers_higher_threshold() {
+        // both thresholds have an F1 score of 2/3
+        let labeled = [(0.9, true), (0.8, false), (0.7, false), (0.6, true)];
+        assert_eq!(0.9, suggest_threshold(&labeled).unwrap().threshold);
+        let with_non_match = [(0.9, false), (0.2, true)];
+        let best = suggest_threshold(&with_non_match).unwrap();
+        assert_eq!(0.2, best.threshold);
+        assert_delta!(0.5, best.precision);
+    }
+
+    #[test]
+    fn similarity_graph_dot_escapes_labels() {
+        let labels = ["say \"hi\"", "a\\b\nc"];
+        let graph = SimilarityGraph::new(&labels, &[(0, 1)], 0.0, |_, _| 0.5);
+        let dot = graph.to_dot_with_labels(&labels[..1]);
+        assert!(dot.contains("0 [label=\"say \\\"hi\\\"\"];"));
+        assert!(dot.contains("1 [label=\"1\"];"));
+        assert_eq!("a\\\\b\\nc", escape_dot(labels[1]));
+        assert_eq!(
+            "graph {\n  0 [label=\"0\"];\n  1 [label=\"1\"];\n  0 -- 1 [label=\"0.5\"];\n}\n",
+            graph.to_dot()
+        );
+    }
+
+    #[test]
+    fn report_fields() {
+        let schema = schema()
+            .field(Field::new("phone", normalized_levenshtein).missing(MissingValue::Score(0.0)));
+        let report = schema
+            .report(
+                &[Some("martha"), Some("york"), None],
+                &[Some("marhta"), Some("cork"), Some("555")],
+                0.9,
+            )
+            .unwrap();
+        let comparison = schema
+            .compare(
+                &[Some("martha"), Some("york"), None],
+                &[Some("marhta"), Some("cork"), Some("555")],
+            )
+            .unwrap();
+        assert_delta!(comparison.score, report.score);
+        assert!(!report.is_match);
+        assert_eq!(Some(0.0), report.fields[2].score);
+        assert_eq!(None, report.fields[2].a);
+        assert_eq!(Some("555".to_string()), report.fields[2].b);
+        assert!(report
+            .to_json()
+            .contains(r#""missing":{"score":0},"score":0}"#));
+    }
+
+    #[test]
+    fn report_wrong_length() {
+        assert_eq!(
+            Err(StrSimError::DifferentLengthArgs),
+            schema().report(&[None], &[None], 0.5)
+        );
+    }
+
+    #[test]
+    fn report_metric_versions() {
+        let schema = Schema::new()
+            .field(Field::new("name", jaro_winkler).metric_name("jaro_winkler"))
+            .field(Field::new("city", normalized_levenshtein).metric_version("2"));
+        let report = schema
+            .report(&[Some("a"), Some("b")], &[Some("a"), Some("b")], 0.5)
+            .unwrap();
+        assert_eq!(Some("jaro_winkler"), report.fields[0].metric);
+        assert_eq!(env!("CARGO_PKG_VERSION"), report.fields[0].version);
+        assert_eq!(None, report.fields[1].metric);
+        assert_eq!("2", report.fields[1].version);
+        assert!(report
+            .to_json()
+            .contains(r#"{"name":"city","metric":null,"version":"2","fingerprint":null,"a":"b""#));
+        assert_eq!(None, report.fields[0].metric_id());
+
+        let id = MetricId::new("prefix", "0.2.0").parameter(&[3]);
+        let schema = Schema::new().field(Field::new("name", jaro_winkler).metric_id(id));
+        let report = schema.report(&[Some("a")], &[Some("a")], 0.5).unwrap();
+        assert_eq!(Some(id), report.fields[0].metric_id());
+        assert!(report.to_json().contains(&format!(
+            r#""metric":"prefix","version":"0.2.0","fingerprint":"{:016x}""#,
+            id.fingerprint()
+        )));
+    }
+
+    #[test]
+    fn weight_report_matches_model() {
+        let schema = schema()
+            .field(Field::new("phone", normalized_levenshtein).missing(MissingValue::Score(0.0)));
+        let model = FellegiSunter::new(vec![0.9, 0.8, 0.7], vec![0.1, 0.3, 0.01], 0.2).unwrap();
+        let a = [Some("martha"), Some("york"), None];
+        let b = [Some("marhta"), Some("cork"), Some("555")];
+        let pattern = schema.agreement(&a, &b).unwrap();
+        let report = model.report(&schema, &a, &b, 1.0).unwrap();
+        assert_eq!(pattern, report.agreement);
+        assert_eq!(model.match_weight(&pattern), report.match_weight);
+        assert_eq!(model.match_probability(&pattern), report.match_probability);
+        assert_delta!(report.match_weight, report.evidence.iter().sum::<f64>());
+        assert_eq!(report.match_weight >= 1.0, report.is_match);
+        assert_eq!(schema.report(&a, &b, 1.0).unwrap().fields, report.fields);
+        assert!(report.to_json().contains(r#""agreement":[true,"#));
+
+        assert_eq!(
+            Err(StrSimError::DifferentLengthArgs),
+            model.report(&Schema::new(), &[], &[], 1.0)
+        );
+    }
+
+    #[test]
+    fn json_escapes() {
+        assert_eq!(r#""a\"b\\c\nd\u0001é""#, json_string("a\"b\\c\nd\u{1}é"));
+        assert_eq!("null", json_number(f64::NAN));
+        assert_eq!("0.25", json_number(0.25));
+    }
+
+    #[test]
+    fn field_names() {
+        let schema = schema();
+        let names: Vec<&str> = schema.fields().iter().map(Field::name).collect();
+        assert_eq!(vec!["name", "city"], names);
+    }
+
+    #[test]
+    fn registry_builds_configured_schema() {
+        let config = SchemaConfig::from_json(
+            r#"{
+                "fields": [
+                    {"name": "name", "metric": "jaro_winkler", "weight": 2,
+                     "agreement_threshold": 0.9, "missing": "ignore"},
+                    {"name": "city", "metric": "normalized_levenshtein",
+                     "missing": {"score": 0.25}}
+                ],
+                "threshold": 0.5
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(Some(0.5), config.threshold);
+        assert_eq!(2.0, config.fields[0].weight);
+        assert_eq!(0.9, config.fields[0].agreement_threshold);
+        assert_eq!(MissingValue::Score(0.25), config.fields[1].missing);
+
+        let configured = MetricRegistry::standard().schema(&config).unwrap();
+        let expected = Schema::new()
+            .field(
+                Field::new("name", jaro_winkler)
+                    .weight(2.0)
+                    .agreement_threshold(0.9),
+            )
+            .field(Field::new("city", normalized_levenshtein).missing(MissingValue::Score(0.25)));
+        for (a, b) in [
+            ([Some("Jon"), Some("Boston")], [Some("John"), None]),
+            (
+                [Some("Martha"), Some("York")],
+                [Some("Marhta"), Some("Cork")],
+            ),
+        ] {
+            assert_eq!(expected.compare(&a, &b), configured.compare(&a, &b));
+            assert_eq!(expected.agreement(&a, &b), configured.agreement(&a, &b));
+        }
+        let report = configured
+            .report(&[None, None], &[None, None], 0.5)
+            .unwrap();
+        assert_eq!(
+            MetricRegistry::standard().metric_id("jaro_winkler"),
+            report.fields[0].metric_id()
+        );
+    }
+
+    #[test]
+    fn registry_rejects_unknown_metrics() {
+        let registry = MetricRegistry::new();
+        assert_eq!(None, registry.metric_id("jaro"));
+        assert_eq!(
+            Some(StrSimError::UnknownMetric),
+            registry.field("name", "jaro").err()
+        );
+        let config = SchemaConfig {
+            fields: vec![FieldConfig::new("name", "jaro")],
+            threshold: None,
+        };
+        assert_eq!(
+            Some(StrSimError::UnknownMetric),
+            registry.schema(&config).err()
+        );
+
+        let id = MetricId::new("jaro", "2.0.0");
+        let registry = MetricRegistry::standard().register(id, jaro_winkler);
+        assert_eq!(Some(id), registry.metric_id("jaro"));
+        assert!(registry.schema(&config).is_ok());
+    }
+
+    #[test]
+    fn malformed_configs() {
+        for json in [
+            "",
+            "[]",
+            "{}",
+            r#"{"fields": {}}"#,
+            r#"{"fields": [], "threshold": "high"}"#,
+            r#"{"fields": [], "threshold": 1e999}"#,
+            r#"{"fields": [], "fields": []}"#,
+            r#"{"fields": [], "extra": 1}"#,
+            r#"{"fields": [{"name": "a"}]}"#,
+            r#"{"fields": [{"name": "a", "metric": 1}]}"#,
+            r#"{"fields": [{"name": "a", "metric": "jaro", "name": "b"}]}"#,
+            r#"{"fields": [{"name": "a", "metric": "jaro", "missing": "skip"}]}"#,
+            r#"{"fields": [{"name": "a", "metric": "jaro", "missing": {"value": 0}}]}"#,
+            r#"{"fields": [{"name": "a", "metric": "jaro", "missing": {"score": 0, "x": 0}}]}"#,
+            r#"{"fields": [{"name": "a", "metric": "jaro", "weight": null}]}"#,
+        ] {
+            assert_eq!(
+                Err(StrSimError::MalformedConfig),
+                SchemaConfig::from_json(json),
+                "{}",
+                json
+            );
+        }
+        assert_eq!(
+            Ok(SchemaConfig::default()),
+            SchemaConfig::from_json(r#" {"fields": []} "#)
+        );
+    }
+}
@@ -0,0 +1,169 @@
+//! Compact q-gram sketches to rule out dissimilar strings before comparing
+//! them with an exact metric.
+
+use std::cmp::max;
+
+use crate::Denominator;
+
+/// A 64-bit sketch of the q-grams of a string, for cheaply ruling out pairs
+/// of strings which can't be within an edit distance.
+///
+/// Every q-gram of the string is hashed to one of 64 bits. An insertion,
+/// deletion or substitution changes at most `q` q-grams, so strings within
+/// a Levenshtein distance of `k` differ in at most `k * q` q-grams and in at
+/// most `k` characters of length. `may_be_within` checks these conditions on
+/// the sketches. It never rules out a pair within the distance, but can let
+/// through pairs which are not, since different q-grams can share a bit.
+///
+/// ```
+/// use strsim::{levenshtein, QgramSketch};
+///
+/// let words = ["kitten", "sitting", "mitten", "banana"];
+/// let sketches: Vec<QgramSketch> = words.iter().map(|word| QgramSketch::new(word)).collect();
+///
+/// let query = QgramSketch::new("kitten");
+/// let matches: Vec<&str> = words
+///     .iter()
+///     .zip(&sketches)
+///     .filter(|(_, sketch)| query.may_be_within(sketch, 1))
+///     .map(|(word, _)| *word)
+///     .filter(|word| levenshtein("kitten", word) <= 1)
+///     .collect();
+/// assert_eq!(vec!["kitten", "mitten"], matches);
+/// assert!(!query.may_be_within(&sketches[3], 1));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct QgramSketch {
+    bits: u64,
+    len: usize,
+    q: usize,
+}
+
+impl QgramSketch {
+    /// Creates the sketch of the bigrams of `s`.
+    pub fn new(s: &str) -> Self {
+        Self::with_q(s, 2)
+    }
+
+    /// Creates the sketch of the q-grams of length `q` of `s`. Longer q-grams
+    /// set fewer bits for short strings, but each edit changes more of them.
+    /// `q` is at least 1.
+    pub fn with_q(s: &str, q: usize) -> Self {
+        let q = max(q, 1);
+        let chars: Vec<char> = s.chars().collect();
+        let mut bits = 0;
+        for qgram in chars.windows(q) {
+            /* the high bits of the hash are mixed best */
+            bits |= 1 << (hash(qgram) >> 58);
+        }
+        Self {
+            bits,
+            len: chars.len(),
+            q,
+        }
+    }
+
+    /// The length of the sketched string in characters.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the sketched string is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns false if the Levenshtein distance of the sketched strings is
+    /// certainly greater than `max_distance`. Sketches with different q-gram
+    /// lengths can't be compared, so they always return true.
+    pub fn may_be_within(&self, other: &QgramSketch, max_distance: usize) -> bool {
+        if self.q != other.q {
+            return true;
+        }
+        if max(self.len, other.len) - self.len.min(other.len) > max_distance {
+            return false;
+        }
+
+        let changed = max_distance.saturating_mul(self.q);
+        let only_self = (self.bits & !other.bits).count_ones() as usize;
+        let only_other = (other.bits & !self.bits).count_ones() as usize;
+        only_self <= changed && only_other <= changed
+    }
+
+    /// Returns false if the normalized Levenshtein similarity of the sketched
+    /// strings is certainly below `min_similarity`.
+    ///
+    /// ```
+    /// use strsim::QgramSketch;
+    ///
+    /// let a = QgramSketch::new("new york");
+    /// assert!(a.may_reach(&QgramSketch::new("new yorc"), 0.8));
+    /// assert!(!a.may_reach(&QgramSketch::new("los angeles"), 0.8));
+    /// ```
+    pub fn may_reach(&self, other: &QgramSketch, min_similarity: f64) -> bool {
+        let max_distance = Denominator::Max.max_distance(min_similarity, self.len, other.len);
+        self.may_be_within(other, max_distance)
+    }
+}
+
+/* The FNV-1a hash of the q-gram */
+fn hash(qgram: &[char]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &c in qgram {
+        for byte in u32::from(c).to_le_bytes() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{levenshtein, normalized_levenshtein};
+
+    #[test]
+    fn sketch_never_rules_out_matches() {
+        let words = [
+            "", "a", "ab", "abc", "kitten", "sitting", "mitten", "knitting", "banana", "bandana",
+            "öঙ香", "abc香", "aaaaaaaa", "aaaabaaa",
+        ];
+        for q in 1..=3 {
+            for a in &words {
+                for b in &words {
+                    let distance = levenshtein(a, b);
+                    let (a_sketch, b_sketch) =
+                        (QgramSketch::with_q(a, q), QgramSketch::with_q(b, q));
+                    assert!(a_sketch.may_be_within(&b_sketch, distance), "{} {}", a, b);
+                    assert!(a_sketch.may_reach(&b_sketch, normalized_levenshtein(a, b)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sketch_rules_out_length_difference() {
+        let a = QgramSketch::new("abc");
+        assert!(!a.may_be_within(&QgramSketch::new("abcdef"), 2));
+        assert!(a.may_be_within(&QgramSketch::new("abcdef"), 3));
+        assert!(!a.may_reach(&QgramSketch::new(""), 0.1));
+    }
+
+    #[test]
+    fn sketch_rules_out_different_qgrams() {
+        let a = QgramSketch::new("abcdefgh");
+        let b = QgramSketch::new("stuvwxyz");
+        assert!(!a.may_be_within(&b, 2));
+        assert!(a.may_be_within(&b, 8));
+    }
+
+    #[test]
+    fn sketch_different_q() {
+        let a = QgramSketch::with_q("abcdefgh", 2);
+        let b = QgramSketch::with_q("stuvwxyz", 3);
+        assert!(a.may_be_within(&b, 0));
+        assert_eq!(8, a.len());
+        assert!(QgramSketch::with_q("", 0).is_empty());
+    }
+}
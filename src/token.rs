@@ -0,0 +1,853 @@
+//! Token based similarity metrics. Strings are split into tokens on whitespace
+//! and the tokens are compared with one of the character based metrics.
+
+use std::borrow::Cow;
+
+use crate::{normalized_levenshtein, with_buffer};
+
+/// Splits a string into tokens on whitespace. Punctuation at the start and end
+/// of every token is removed, so `"Apt. 12,"` results in `["Apt", "12"]`.
+///
+/// ```
+/// use strsim::tokenize;
+///
+/// assert_eq!(vec!["Apt", "12", "Main", "St"], tokenize("Apt. 12, Main St."));
+/// ```
+pub fn tokenize(s: &str) -> Vec<&str> {
+    tokens(s).collect()
+}
+
+fn tokens(s: &str) -> impl Iterator<Item = &str> + Clone {
+    s.split_whitespace()
+        .map(|token| token.trim_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|token| !token.is_empty())
+}
+
+/// Common English words, to be ignored with `TokenScorer::stopwords`.
+#[cfg(feature = "stopwords")]
+pub const ENGLISH_STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "from", "if", "in", "into",
+    "is", "it", "no", "not", "of", "on", "or", "so", "such", "that", "the", "their", "then",
+    "there", "these", "they", "this", "to", "was", "will", "with",
+];
+
+/// Common German words, to be ignored with `TokenScorer::stopwords`.
+#[cfg(feature = "stopwords")]
+pub const GERMAN_STOPWORDS: &[&str] = &[
+    "am", "an", "auf", "aus", "bei", "das", "dem", "den", "der", "des", "die", "ein", "eine",
+    "einem", "einen", "einer", "eines", "für", "im", "in", "ist", "mit", "nach", "oder", "und",
+    "vom", "von", "zu", "zum", "zur",
+];
+
+/// Common French words, to be ignored with `TokenScorer::stopwords`.
+#[cfg(feature = "stopwords")]
+pub const FRENCH_STOPWORDS: &[&str] = &[
+    "à", "au", "aux", "avec", "ce", "ces", "dans", "de", "des", "du", "en", "et", "la", "le",
+    "les", "leur", "ou", "par", "pour", "sa", "se", "son", "sur", "un", "une",
+];
+
+/// Common Spanish words, to be ignored with `TokenScorer::stopwords`.
+#[cfg(feature = "stopwords")]
+pub const SPANISH_STOPWORDS: &[&str] = &[
+    "a", "al", "con", "de", "del", "el", "en", "es", "la", "las", "lo", "los", "o", "para", "por",
+    "se", "su", "sus", "un", "una", "y",
+];
+
+/// Normalizes every token before `TokenScorer` compares the tokens, for
+/// example to lowercase or stem them so `running` and `run` are equal. This
+/// is implemented for closures taking and returning a token.
+///
+/// ```
+/// use strsim::{TokenNormalizer, TokenScorer};
+///
+/// let stem = |token: &str| token.trim_end_matches("ning").to_string();
+/// assert_eq!("run", stem.normalize("running"));
+///
+/// let scorer = TokenScorer::default().normalizer(&stem);
+/// assert_eq!(1.0, scorer.set_similarity("running shoes", "run shoes"));
+/// ```
+pub trait TokenNormalizer {
+    /// Normalizes `token`.
+    fn normalize<'t>(&self, token: &'t str) -> Cow<'t, str>;
+}
+
+impl<F: Fn(&str) -> String> TokenNormalizer for F {
+    fn normalize<'t>(&self, token: &'t str) -> Cow<'t, str> {
+        Cow::Owned(self(token))
+    }
+}
+
+/// The `TokenNormalizer` keeping tokens unchanged, used by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct IdentityNormalizer;
+
+impl TokenNormalizer for IdentityNormalizer {
+    fn normalize<'t>(&self, token: &'t str) -> Cow<'t, str> {
+        Cow::Borrowed(token)
+    }
+}
+
+/// The `TokenNormalizer` lowercasing tokens, which only allocates for tokens
+/// with uppercase letters.
+///
+/// ```
+/// use strsim::{LowercaseNormalizer, TokenScorer};
+///
+/// let scorer = TokenScorer::default().normalizer(&LowercaseNormalizer);
+/// assert_eq!(1.0, scorer.sort_similarity("New York", "york new"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct LowercaseNormalizer;
+
+impl TokenNormalizer for LowercaseNormalizer {
+    fn normalize<'t>(&self, token: &'t str) -> Cow<'t, str> {
+        if token.chars().any(char::is_uppercase) {
+            Cow::Owned(token.to_lowercase())
+        } else {
+            Cow::Borrowed(token)
+        }
+    }
+}
+
+/* Parses tokens like `12`, `007` or `3.5`. Signs, exponents and special
+values are not treated as numbers, since they rarely are in real world data.
+Neither are tokens too long to be represented by a finite `f64` */
+fn parse_number(token: &str) -> Option<f64> {
+    let mut digits = 0;
+    let mut dots = 0;
+    for c in token.chars() {
+        match c {
+            '0'..='9' => digits += 1,
+            '.' => dots += 1,
+            _ => return None,
+        }
+    }
+
+    if digits == 0 || dots > 1 {
+        return None;
+    }
+    token.parse().ok().filter(|value: &f64| value.is_finite())
+}
+
+/* Compares two numbers by their relative difference */
+fn numeric_similarity(a: f64, b: f64) -> f64 {
+    if a == b {
+        return 1.0;
+    }
+    1.0 - (a - b).abs() / a.abs().max(b.abs())
+}
+
+/// Computes token based similarities using a configurable character based
+/// metric for the comparison of the tokens.
+///
+/// ```
+/// use strsim::{jaro_winkler, TokenScorer};
+///
+/// let scorer = TokenScorer::new(jaro_winkler).numeric_aware(true);
+///
+/// assert!(scorer.match_similarity("Apt 12", "Apt 13") > 0.9);
+/// assert!(scorer.match_similarity("Apt 12", "Apt 1200") < 0.6);
+/// ```
+#[derive(Clone, Copy)]
+pub struct TokenScorer<'s> {
+    metric: fn(&str, &str) -> f64,
+    numeric_aware: bool,
+    stopwords: &'s [&'s str],
+    normalizer: &'s (dyn TokenNormalizer + Sync),
+}
+
+impl<'s> TokenScorer<'s> {
+    /// Creates a scorer comparing tokens with `metric`, which has to return a
+    /// similarity between 0.0 and 1.0.
+    pub fn new(metric: fn(&str, &str) -> f64) -> Self {
+        Self {
+            metric,
+            numeric_aware: false,
+            stopwords: &[],
+            normalizer: &IdentityNormalizer,
+        }
+    }
+
+    /// When enabled, two numeric tokens are compared by their relative
+    /// difference `1 - |a - b| / max(|a|, |b|)` instead of by their digits.
+    /// This way `12` is close to `13` but far from `1200`. Only affects the
+    /// token by token comparison of `match_similarity`.
+    pub fn numeric_aware(mut self, enabled: bool) -> Self {
+        self.numeric_aware = enabled;
+        self
+    }
+
+    /// Ignores the tokens equal to one of `stopwords`, ignoring case, in all
+    /// similarities. This way `The Hilton Hotel` matches `Hilton Hotel`. A
+    /// string consisting only of stopwords keeps its tokens, so it still
+    /// differs from other such strings. With the `stopwords` feature lists of
+    /// common words like `ENGLISH_STOPWORDS` are available.
+    ///
+    /// ```
+    /// use strsim::TokenScorer;
+    ///
+    /// let scorer = TokenScorer::default().stopwords(&["the", "hotel"]);
+    /// assert_eq!(1.0, scorer.sort_similarity("The Hilton Hotel", "Hilton"));
+    /// assert!(scorer.sort_similarity("The Hotel", "Hilton") < 0.5);
+    /// ```
+    pub fn stopwords(mut self, stopwords: &'s [&'s str]) -> Self {
+        self.stopwords = stopwords;
+        self
+    }
+
+    /// Normalizes every token with `normalizer` before comparing the tokens
+    /// in all similarities. Stopwords are removed before the normalization.
+    /// Defaults to `IdentityNormalizer`. The normalizer is `Sync`, so the
+    /// scorer can be shared between threads.
+    pub fn normalizer(mut self, normalizer: &'s (dyn TokenNormalizer + Sync)) -> Self {
+        self.normalizer = normalizer;
+        self
+    }
+
+    fn is_stopword(&self, token: &str) -> bool {
+        self.stopwords.iter().any(|stopword| {
+            stopword
+                .chars()
+                .flat_map(char::to_lowercase)
+                .eq(token.chars().flat_map(char::to_lowercase))
+        })
+    }
+
+    /* The tokens of `s` without stopwords */
+    fn tokens<'t, 'a: 't>(&'t self, s: &'a str) -> impl Iterator<Item = &'a str> + 't {
+        let keep_stopwords =
+            self.stopwords.is_empty() || tokens(s).all(|token| self.is_stopword(token));
+        tokens(s).filter(move |token| keep_stopwords || !self.is_stopword(token))
+    }
+
+    /* The normalized tokens of `s` without stopwords */
+    fn normalized_tokens<'a>(&self, s: &'a str) -> Vec<Cow<'a, str>> {
+        self.tokens(s)
+            .map(|token| self.normalizer.normalize(token))
+            .collect()
+    }
+
+    /* The sorted and deduplicated normalized tokens of `s` */
+    fn token_set<'a>(&self, s: &'a str) -> Vec<Cow<'a, str>> {
+        let mut tokens = self.normalized_tokens(s);
+        tokens.sort_unstable();
+        tokens.dedup();
+        tokens
+    }
+
+    /* The similarity of `a_combined` and `b_combined` and the higher similarity
+    of their common prefix of `common_len` bytes with either of them */
+    fn set_similarities(
+        &self,
+        a_combined: &str,
+        b_combined: &str,
+        common_len: usize,
+    ) -> (f64, Option<f64>) {
+        let combined_similarity = (self.metric)(a_combined, b_combined);
+        if common_len == 0 {
+            return (combined_similarity, None);
+        }
+        let common = &a_combined[..common_len];
+        let common_similarity =
+            (self.metric)(common, a_combined).max((self.metric)(common, b_combined));
+        (combined_similarity, Some(common_similarity))
+    }
+
+    fn compare_tokens(&self, a: &str, b: &str) -> f64 {
+        if self.numeric_aware {
+            if let (Some(a_num), Some(b_num)) = (parse_number(a), parse_number(b)) {
+                return numeric_similarity(a_num, b_num);
+            }
+        }
+        (self.metric)(a, b)
+    }
+
+    /// Sorts the tokens of both strings alphabetically and compares the
+    /// rejoined strings, so the order of the tokens does not matter.
+    pub fn sort_similarity(&self, a: &str, b: &str) -> f64 {
+        let mut a_tokens = self.normalized_tokens(a);
+        let mut b_tokens = self.normalized_tokens(b);
+        a_tokens.sort_unstable();
+        b_tokens.sort_unstable();
+
+        (self.metric)(&a_tokens.join(" "), &b_tokens.join(" "))
+    }
+
+    /// Compares the sorted tokens both strings have in common with the sorted
+    /// common tokens followed by the remaining tokens of either string and
+    /// returns the best score. Neither the order nor duplicates of the tokens
+    /// nor additional tokens in one of the strings lower the score.
+    pub fn set_similarity(&self, a: &str, b: &str) -> f64 {
+        let a_tokens = self.token_set(a);
+        let b_tokens = self.token_set(b);
+        if a_tokens.is_empty() || b_tokens.is_empty() {
+            return empty_similarity(a_tokens.len(), b_tokens.len());
+        }
+
+        let (a_combined, b_combined, common_len) = combine_tokens(&a_tokens, &b_tokens);
+        match self.set_similarities(&a_combined, &b_combined, common_len) {
+            (combined_similarity, Some(common_similarity)) => {
+                combined_similarity.max(common_similarity)
+            }
+            (combined_similarity, None) => combined_similarity,
+        }
+    }
+
+    /// Like `set_similarity`, but also returns the compared strings and their
+    /// similarities.
+    ///
+    /// ```
+    /// use strsim::TokenScorer;
+    ///
+    /// let explanation = TokenScorer::default().explain_set_similarity("new york mets", "the mets of new york");
+    /// assert_eq!("mets new york", explanation.common);
+    /// assert_eq!("mets new york of the", explanation.b_combined);
+    /// assert_eq!(Some(1.0), explanation.common_similarity);
+    /// assert_eq!(1.0, explanation.similarity);
+    /// ```
+    pub fn explain_set_similarity(&self, a: &str, b: &str) -> TokenSetExplanation {
+        let a_tokens = self.token_set(a);
+        let b_tokens = self.token_set(b);
+        let (a_combined, b_combined, common_len) = combine_tokens(&a_tokens, &b_tokens);
+
+        let (combined_similarity, common_similarity) = if a_tokens.is_empty() || b_tokens.is_empty()
+        {
+            let similarity = empty_similarity(a_tokens.len(), b_tokens.len());
+            (similarity, None)
+        } else {
+            self.set_similarities(&a_combined, &b_combined, common_len)
+        };
+        let similarity = match common_similarity {
+            Some(common_similarity) => combined_similarity.max(common_similarity),
+            None => combined_similarity,
+        };
+
+        TokenSetExplanation {
+            common: a_combined[..common_len].to_string(),
+            a_combined,
+            b_combined,
+            combined_similarity,
+            common_similarity,
+            similarity,
+        }
+    }
+
+    /// Matches every token with the most similar token of the other string and
+    /// averages the similarities of these pairs (the Monge-Elkan similarity).
+    /// To keep the result symmetric the average is taken over the tokens of
+    /// both strings.
+    pub fn match_similarity(&self, a: &str, b: &str) -> f64 {
+        let a_tokens = self.normalized_tokens(a);
+        let b_tokens = self.normalized_tokens(b);
+        if a_tokens.is_empty() || b_tokens.is_empty() {
+            return empty_similarity(a_tokens.len(), b_tokens.len());
+        }
+
+        /* every pair is compared once, tracking the best match of the tokens of
+        `b` in a buffer while iterating over the tokens of `a` */
+        with_buffer(b_tokens.len(), |b_best: &mut [f64]| {
+            b_best.fill(f64::NEG_INFINITY);
+            let mut sum = 0.0;
+            for a_token in &a_tokens {
+                let mut a_best = f64::NEG_INFINITY;
+                for (b_token, b_best) in b_tokens.iter().zip(b_best.iter_mut()) {
+                    let similarity = self.compare_tokens(a_token, b_token);
+                    a_best = a_best.max(similarity);
+                    *b_best = b_best.max(similarity);
+                }
+                sum += a_best;
+            }
+            sum += b_best.iter().sum::<f64>();
+            sum / (a_tokens.len() + b_tokens.len()) as f64
+        })
+    }
+
+    /// Like `match_similarity`, but also returns the best match of every
+    /// token.
+    ///
+    /// ```
+    /// use strsim::TokenScorer;
+    ///
+    /// let explanation = TokenScorer::default().explain_match_similarity("Jon Smith", "Smith John");
+    /// assert_eq!("Jon", explanation.a_matches[0].token);
+    /// assert_eq!(Some("John"), explanation.a_matches[0].best_match);
+    /// assert_eq!(0.75, explanation.a_matches[0].similarity);
+    /// assert_eq!(0.875, explanation.similarity);
+    /// ```
+    pub fn explain_match_similarity<'a>(
+        &self,
+        a: &'a str,
+        b: &'a str,
+    ) -> TokenMatchExplanation<'a> {
+        let normalized = |s: &'a str| -> Vec<(&'a str, Cow<'a, str>)> {
+            self.tokens(s)
+                .map(|token| (token, self.normalizer.normalize(token)))
+                .collect()
+        };
+        let a_tokens = normalized(a);
+        let b_tokens = normalized(b);
+
+        type Tokens<'a> = [(&'a str, Cow<'a, str>)];
+        let best_matches = |from: &Tokens<'a>, to: &Tokens<'a>| -> Vec<TokenMatch<'a>> {
+            from.iter()
+                .map(|&(token, ref normalized)| {
+                    let mut best = TokenMatch {
+                        token,
+                        best_match: None,
+                        similarity: 0.0,
+                    };
+                    for &(other, ref other_normalized) in to {
+                        let similarity = self.compare_tokens(normalized, other_normalized);
+                        if best.best_match.is_none() || similarity > best.similarity {
+                            best.best_match = Some(other);
+                            best.similarity = similarity;
+                        }
+                    }
+                    best
+                })
+                .collect()
+        };
+        let a_matches = best_matches(&a_tokens, &b_tokens);
+        let b_matches = best_matches(&b_tokens, &a_tokens);
+
+        let similarity = if a_tokens.is_empty() || b_tokens.is_empty() {
+            empty_similarity(a_tokens.len(), b_tokens.len())
+        } else {
+            let sum = |matches: &[TokenMatch<'_>]| -> f64 {
+                matches
+                    .iter()
+                    .map(|token_match| token_match.similarity)
+                    .sum()
+            };
+            (sum(&a_matches) + sum(&b_matches)) / (a_tokens.len() + b_tokens.len()) as f64
+        };
+
+        TokenMatchExplanation {
+            a_matches,
+            b_matches,
+            similarity,
+        }
+    }
+}
+
+/* Strings without tokens are only similar to each other */
+fn empty_similarity(a_len: usize, b_len: usize) -> f64 {
+    if a_len == 0 && b_len == 0 {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/* Joins the common tokens of the sorted and deduplicated token lists followed
+by the remaining tokens of either list, and returns both strings and the
+length of the common tokens in bytes */
+fn combine_tokens(a_tokens: &[Cow<'_, str>], b_tokens: &[Cow<'_, str>]) -> (String, String, usize) {
+    let push = |combined: &mut String, token: &str| {
+        if !combined.is_empty() {
+            combined.push(' ');
+        }
+        combined.push_str(token);
+    };
+
+    let mut a_combined = String::new();
+    for token in a_tokens
+        .iter()
+        .filter(|token| b_tokens.binary_search(token).is_ok())
+    {
+        push(&mut a_combined, token);
+    }
+    let common_len = a_combined.len();
+    let mut b_combined = a_combined.clone();
+    for token in a_tokens
+        .iter()
+        .filter(|token| b_tokens.binary_search(token).is_err())
+    {
+        push(&mut a_combined, token);
+    }
+    for token in b_tokens
+        .iter()
+        .filter(|token| a_tokens.binary_search(token).is_err())
+    {
+        push(&mut b_combined, token);
+    }
+    (a_combined, b_combined, common_len)
+}
+
+/// The strings compared by `TokenScorer::set_similarity` and their
+/// similarities.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenSetExplanation {
+    /// The sorted tokens both strings have in common.
+    pub common: String,
+    /// The common tokens followed by the remaining sorted tokens of the first
+    /// string.
+    pub a_combined: String,
+    /// The common tokens followed by the remaining sorted tokens of the second
+    /// string.
+    pub b_combined: String,
+    /// The similarity of `a_combined` and `b_combined`.
+    pub combined_similarity: f64,
+    /// The higher similarity of `common` with `a_combined` or `b_combined`, or
+    /// `None` when the strings have no tokens in common.
+    pub common_similarity: Option<f64>,
+    /// The final similarity, the best of the other similarities.
+    pub similarity: f64,
+}
+
+/// The most similar token of the other string for one token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenMatch<'a> {
+    /// The token.
+    pub token: &'a str,
+    /// The first of the most similar tokens of the other string, or `None`
+    /// when the other string has no tokens.
+    pub best_match: Option<&'a str>,
+    /// The similarity of the token and its best match.
+    pub similarity: f64,
+}
+
+/// The best matches of the tokens of both strings, as averaged by
+/// `TokenScorer::match_similarity`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenMatchExplanation<'a> {
+    /// The best match for every token of the first string.
+    pub a_matches: Vec<TokenMatch<'a>>,
+    /// The best match for every token of the second string.
+    pub b_matches: Vec<TokenMatch<'a>>,
+    /// The final similarity, the average similarity of all matches.
+    pub similarity: f64,
+}
+
+impl Default for TokenScorer<'_> {
+    fn default() -> Self {
+        Self::new(normalized_levenshtein)
+    }
+}
+
+/// Calculates the normalized Levenshtein similarity of the strings after
+/// sorting their tokens. The returned value is between 0.0 and 1.0
+/// (inclusive), where 1.0 means the strings consist of the same tokens.
+///
+/// ```
+/// use strsim::token_sort_similarity;
+///
+/// assert_eq!(1.0, token_sort_similarity("new york mets", "mets new york"));
+/// ```
+pub fn token_sort_similarity(a: &str, b: &str) -> f64 {
+    TokenScorer::default().sort_similarity(a, b)
+}
+
+/// Calculates the normalized Levenshtein similarity based on the common tokens
+/// of the strings, see `TokenScorer::set_similarity`. The returned value is
+/// between 0.0 and 1.0 (inclusive), where 1.0 means all tokens of one string
+/// are part of the other string.
+///
+/// ```
+/// use strsim::token_set_similarity;
+///
+/// assert_eq!(1.0, token_set_similarity("new york mets", "new york mets vs atlanta braves"));
+/// ```
+pub fn token_set_similarity(a: &str, b: &str) -> f64 {
+    TokenScorer::default().set_similarity(a, b)
+}
+
+/// Calculates the Monge-Elkan similarity using the normalized Levenshtein
+/// similarity for the comparison of the tokens, see
+/// `TokenScorer::match_similarity`. The returned value is between 0.0 and 1.0
+/// (inclusive).
+///
+/// ```
+/// use strsim::token_match_similarity;
+///
+/// assert!((token_match_similarity("Jon Smith", "Smith John") - 0.875).abs() < 0.001);
+/// ```
+pub fn token_match_similarity(a: &str, b: &str) -> f64 {
+    TokenScorer::default().match_similarity(a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jaro_winkler;
+
+    #[test]
+    fn tokenize_strips_punctuation() {
+        assert_eq!(vec!["a", "b-c", "d"], tokenize("  a, (b-c) ... d! "));
+        assert!(tokenize(" ., ").is_empty());
+    }
+
+    #[test]
+    fn parse_number_accepts_plain_numbers() {
+        assert_eq!(Some(12.0), parse_number("12"));
+        assert_eq!(Some(7.0), parse_number("007"));
+        assert_eq!(Some(3.5), parse_number("3.5"));
+    }
+
+    #[test]
+    fn parse_number_rejects_other_tokens() {
+        assert_eq!(None, parse_number("12b"));
+        assert_eq!(None, parse_number("1.2.3"));
+        assert_eq!(None, parse_number("."));
+        assert_eq!(None, parse_number("inf"));
+        assert_eq!(None, parse_number("1e5"));
+    }
+
+    #[test]
+    fn token_sort_similarity_empty() {
+        assert_delta!(1.0, token_sort_similarity("", ""));
+        assert_delta!(0.0, token_sort_similarity("", "token"));
+    }
+
+    #[test]
+    fn token_sort_similarity_order() {
+        assert_delta!(
+            1.0,
+            token_sort_similarity("fuzzy wuzzy was a bear", "wuzzy fuzzy was a bear")
+        );
+        assert_delta!(
+            0.72727,
+            token_sort_similarity("fuzzy was a bear", "fuzzy fuzzy was a bear")
+        );
+    }
+
+    #[test]
+    fn token_set_similarity_empty() {
+        assert_delta!(1.0, token_set_similarity("", ""));
+        assert_delta!(0.0, token_set_similarity("token", " "));
+    }
+
+    #[test]
+    fn token_set_similarity_subset() {
+        assert_delta!(
+            1.0,
+            token_set_similarity("fuzzy was a bear", "fuzzy fuzzy was a bear")
+        );
+        assert_delta!(1.0, token_set_similarity("bear", "a fuzzy bear"));
+    }
+
+    #[test]
+    fn token_set_similarity_disjoint() {
+        assert_delta!(
+            normalized_levenshtein("abc", "xyz"),
+            token_set_similarity("abc", "xyz")
+        );
+    }
+
+    #[test]
+    fn token_match_similarity_empty() {
+        assert_delta!(1.0, token_match_similarity("", ""));
+        assert_delta!(0.0, token_match_similarity("", "token"));
+    }
+
+    #[test]
+    fn token_match_similarity_order() {
+        assert_delta!(1.0, token_match_similarity("John Smith", "Smith John"));
+        assert_delta!(0.875, token_match_similarity("Jon Smith", "Smith John"));
+    }
+
+    #[test]
+    fn token_match_similarity_symmetric() {
+        assert_delta!(
+            token_match_similarity("Main Street 5", "5 Main"),
+            token_match_similarity("5 Main", "Main Street 5")
+        );
+    }
+
+    #[test]
+    fn explain_match_similarity_tokens() {
+        let explanation = TokenScorer::default().explain_match_similarity("a b", "b");
+        assert_eq!(
+            vec![
+                TokenMatch {
+                    token: "a",
+                    best_match: Some("b"),
+                    similarity: 0.0,
+                },
+                TokenMatch {
+                    token: "b",
+                    best_match: Some("b"),
+                    similarity: 1.0,
+                },
+            ],
+            explanation.a_matches
+        );
+        assert_eq!(1, explanation.b_matches.len());
+        assert_delta!(2.0 / 3.0, explanation.similarity);
+    }
+
+    #[test]
+    fn explain_match_similarity_empty() {
+        let explanation = TokenScorer::default().explain_match_similarity("token", "");
+        assert_eq!(None, explanation.a_matches[0].best_match);
+        assert!(explanation.b_matches.is_empty());
+        assert_delta!(0.0, explanation.similarity);
+    }
+
+    #[test]
+    fn explain_set_similarity_without_common_tokens() {
+        let explanation = TokenScorer::default().explain_set_similarity("abc", "xyz");
+        assert_eq!("", explanation.common);
+        assert_eq!(None, explanation.common_similarity);
+        assert_delta!(explanation.combined_similarity, explanation.similarity);
+    }
+
+    #[test]
+    fn similarities_match_explanations() {
+        let scorer = TokenScorer::new(jaro_winkler).numeric_aware(true);
+        let pairs = [
+            ("new york mets", "the mets of new york"),
+            ("b a a c", "c d b"),
+            ("Apt 12 Main St", "Main Street Apt 13"),
+            ("", "abc"),
+            ("", ""),
+        ];
+        for &(a, b) in &pairs {
+            assert_eq!(
+                scorer.explain_set_similarity(a, b).similarity,
+                scorer.set_similarity(a, b)
+            );
+            assert_delta!(
+                scorer.explain_match_similarity(a, b).similarity,
+                scorer.match_similarity(a, b)
+            );
+        }
+    }
+
+    #[test]
+    fn numeric_similarity_relative_difference() {
+        assert_delta!(1.0, numeric_similarity(0.0, 0.0));
+        assert_delta!(0.92307, numeric_similarity(12.0, 13.0));
+        assert_delta!(0.01, numeric_similarity(12.0, 1200.0));
+        assert_delta!(0.0, numeric_similarity(0.0, 5.0));
+    }
+
+    #[test]
+    fn numeric_aware_match_similarity() {
+        let scorer = TokenScorer::default().numeric_aware(true);
+        assert_delta!(0.96153, scorer.match_similarity("Apt 12", "Apt 13"));
+        assert_delta!(0.505, scorer.match_similarity("Apt 12", "Apt 1200"));
+        assert_delta!(1.0, scorer.match_similarity("Apt 12", "Apt 12.0"));
+    }
+
+    #[test]
+    fn numeric_aware_huge_numbers() {
+        let scorer = TokenScorer::default().numeric_aware(true);
+        let huge = "9".repeat(400);
+        let other_huge = format!("8{}", "9".repeat(399));
+        assert_eq!(None, parse_number(&huge));
+        assert!(scorer.match_similarity(&huge, &other_huge) < 1.0);
+        let similarity = scorer.match_similarity(&huge, "12");
+        assert!((0.0..=1.0).contains(&similarity));
+    }
+
+    #[test]
+    fn numeric_aware_disabled() {
+        let scorer = TokenScorer::default();
+        assert_delta!(0.75, scorer.match_similarity("Apt 12", "Apt 13"));
+    }
+
+    #[test]
+    fn stopwords_ignore_case() {
+        let scorer = TokenScorer::default().stopwords(&["the", "ÜBER"]);
+        assert_eq!(
+            vec!["Hilton", "Hotel"],
+            scorer.tokens("The Hilton Hotel").collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec!["alles"],
+            scorer.tokens("über alles").collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec!["theater"],
+            scorer.tokens("the theater").collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn stopwords_only() {
+        let scorer = TokenScorer::default().stopwords(&["the", "a", "of"]);
+        assert_eq!(
+            vec!["The", "of"],
+            scorer.tokens("The of").collect::<Vec<_>>()
+        );
+        assert!(scorer.set_similarity("the", "a") < 1.0);
+        assert_delta!(1.0, scorer.set_similarity("", ""));
+    }
+
+    #[test]
+    fn stopwords_similarities() {
+        let scorer = TokenScorer::default().stopwords(&["the"]);
+        assert_delta!(
+            1.0,
+            scorer.sort_similarity("The Hilton Hotel", "Hilton Hotel")
+        );
+        assert_delta!(
+            1.0,
+            scorer.match_similarity("The Hilton Hotel", "Hilton Hotel")
+        );
+        assert_delta!(1.0, scorer.set_similarity("Hilton the Hotel", "Hotel"));
+        assert!(token_sort_similarity("The Hilton Hotel", "Hilton Hotel") < 0.9);
+    }
+
+    #[test]
+    fn lowercase_normalizer_borrows_lowercase_tokens() {
+        assert!(matches!(
+            LowercaseNormalizer.normalize("abc"),
+            Cow::Borrowed("abc")
+        ));
+        assert_eq!("straße", LowercaseNormalizer.normalize("STRAßE"));
+        assert_eq!("abc", IdentityNormalizer.normalize("abc"));
+    }
+
+    #[test]
+    fn normalizer_similarities() {
+        let scorer = TokenScorer::default().normalizer(&LowercaseNormalizer);
+        assert_delta!(1.0, scorer.sort_similarity("Hilton HOTEL", "hotel hilton"));
+        assert_delta!(1.0, scorer.set_similarity("Hilton", "the hilton hotel"));
+        assert_delta!(1.0, scorer.match_similarity("John Smith", "smith JOHN"));
+    }
+
+    #[test]
+    fn normalizer_keeps_original_tokens_in_explanation() {
+        let scorer = TokenScorer::default().normalizer(&LowercaseNormalizer);
+        let explanation = scorer.explain_match_similarity("JOHN", "john");
+        assert_eq!("JOHN", explanation.a_matches[0].token);
+        assert_eq!(Some("john"), explanation.a_matches[0].best_match);
+        assert_delta!(1.0, explanation.similarity);
+
+        let explanation = scorer.explain_set_similarity("New York", "NEW york");
+        assert_eq!("new york", explanation.common);
+    }
+
+    #[test]
+    fn normalizer_after_stopwords() {
+        let stem = |token: &str| token.trim_end_matches('s').to_string();
+        let scorer = TokenScorer::default()
+            .stopwords(&["this"])
+            .normalizer(&stem);
+        assert_eq!(vec!["hotel"], scorer.normalized_tokens("this hotels"));
+    }
+
+    #[cfg(feature = "stopwords")]
+    #[test]
+    fn stopword_lists_are_lowercase() {
+        for list in [
+            ENGLISH_STOPWORDS,
+            GERMAN_STOPWORDS,
+            FRENCH_STOPWORDS,
+            SPANISH_STOPWORDS,
+        ] {
+            assert!(list.iter().all(|word| word.to_lowercase() == *word));
+        }
+    }
+
+    #[test]
+    fn numeric_aware_mixed_tokens() {
+        let scorer = TokenScorer::new(jaro_winkler).numeric_aware(true);
+        assert_delta!(
+            TokenScorer::new(jaro_winkler).match_similarity("12b", "12"),
+            scorer.match_similarity("12b", "12")
+        );
+    }
+}
@@ -0,0 +1,423 @@
+//! Edit distance between ordered, labeled trees, like S-expressions, parsed
+//! XML documents or syntax trees.
+//!
+//! `tree_edit_distance` implements the algorithm of Zhang and Shasha. It is
+//! the smallest number of nodes to relabel, insert or delete to turn one tree
+//! into the other. Deleting a node moves its children up to its parent, and
+//! inserting a node can adopt a sequence of siblings as its children.
+//!
+//! Trees can be given in the bracket notation used by most tree edit
+//! distance tools, where every node is written as `{label children}`:
+//!
+//! ```
+//! use strsim::tree::bracket_tree_edit_distance;
+//!
+//! // relabel b as x and delete d, which moves e up to a
+//! assert_eq!(Ok(2), bracket_tree_edit_distance("{a{b}{c}{d{e}}}", "{a{x}{c}{e}}"));
+//! ```
+//!
+//! Any other tree type can be compared by implementing `TreeNode`.
+
+use std::cmp::min;
+use std::mem;
+use std::str::FromStr;
+
+use crate::StrSimError;
+
+/// A node of an ordered tree with a label, used by `tree_edit_distance`.
+///
+/// ```
+/// use strsim::tree::{tree_edit_distance, TreeNode};
+///
+/// struct Expr {
+///     op: char,
+///     args: Vec<Expr>,
+/// }
+///
+/// impl TreeNode for Expr {
+///     type Label = char;
+///
+///     fn label(&self) -> &char {
+///         &self.op
+///     }
+///
+///     fn children(&self) -> &[Expr] {
+///         &self.args
+///     }
+/// }
+///
+/// let leaf = |op| Expr { op, args: Vec::new() };
+/// let sum = Expr { op: '+', args: vec![leaf('x'), leaf('y')] };
+/// let product = Expr { op: '*', args: vec![leaf('x'), leaf('y')] };
+/// assert_eq!(1, tree_edit_distance(&sum, &product));
+/// ```
+pub trait TreeNode: Sized {
+    /// The type of the labels, which are equal or not.
+    type Label: PartialEq;
+
+    /// The label of the node.
+    fn label(&self) -> &Self::Label;
+
+    /// The children of the node, from left to right.
+    fn children(&self) -> &[Self];
+}
+
+/// A tree parsed from the bracket notation, where every node is written as
+/// `{label children}`, e.g. `{a{b}{c{d}}}`. Labels can be empty and contain
+/// any character, with `\`, `{` and `}` escaped by a backslash. Whitespace is
+/// part of the labels, except around the whole tree.
+///
+/// ```
+/// use strsim::tree::{BracketTree, TreeNode};
+///
+/// let tree: BracketTree = r"{html{head}{body{p\{x\}}}}".parse().unwrap();
+/// assert_eq!("html", tree.label());
+/// assert_eq!("p{x}", tree.children()[1].children()[0].label());
+/// assert!("{a}{b}".parse::<BracketTree>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BracketTree {
+    label: String,
+    children: Vec<BracketTree>,
+}
+
+impl BracketTree {
+    /// Creates a node with the given label and children.
+    pub fn new(label: &str, children: Vec<BracketTree>) -> Self {
+        Self {
+            label: label.to_string(),
+            children,
+        }
+    }
+}
+
+impl Drop for BracketTree {
+    fn drop(&mut self) {
+        /* drops the descendants one after another instead of recursively,
+        which could overflow the stack for deeply nested trees */
+        let mut descendants = mem::take(&mut self.children);
+        while let Some(mut node) = descendants.pop() {
+            descendants.append(&mut node.children);
+        }
+    }
+}
+
+impl TreeNode for BracketTree {
+    type Label = String;
+
+    fn label(&self) -> &String {
+        &self.label
+    }
+
+    fn children(&self) -> &[BracketTree] {
+        &self.children
+    }
+}
+
+impl FromStr for BracketTree {
+    type Err = StrSimError;
+
+    /// Parses a tree in the bracket notation. Fails with
+    /// `StrSimError::MalformedTree` unless the string is exactly one tree
+    /// with balanced brackets.
+    fn from_str(s: &str) -> Result<Self, StrSimError> {
+        /* the nodes whose closing bracket is still missing, innermost last.
+        Using a stack instead of recursion allows deeply nested trees */
+        let mut open: Vec<BracketTree> = Vec::new();
+        let mut root = None;
+        let mut chars = s.trim().chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if root.is_none() => {
+                    let mut label = String::new();
+                    while let Some(&c) = chars.peek() {
+                        match c {
+                            '{' | '}' => break,
+                            '\\' => {
+                                chars.next();
+                                label.push(chars.next().ok_or(StrSimError::MalformedTree)?);
+                            }
+                            c => {
+                                chars.next();
+                                label.push(c);
+                            }
+                        }
+                    }
+                    open.push(BracketTree {
+                        label,
+                        children: Vec::new(),
+                    });
+                }
+                '}' => {
+                    let node = open.pop().ok_or(StrSimError::MalformedTree)?;
+                    match open.last_mut() {
+                        Some(parent) => parent.children.push(node),
+                        None => root = Some(node),
+                    }
+                }
+                _ => return Err(StrSimError::MalformedTree),
+            }
+        }
+
+        if !open.is_empty() {
+            return Err(StrSimError::MalformedTree);
+        }
+        root.ok_or(StrSimError::MalformedTree)
+    }
+}
+
+/* The labels of the nodes in postorder, with the postorder index of the
+leftmost leaf below every node */
+struct Postorder<'a, L> {
+    labels: Vec<&'a L>,
+    leftmost: Vec<usize>,
+}
+
+impl<'a, L> Postorder<'a, L> {
+    fn new<T: TreeNode<Label = L>>(root: &'a T) -> Self {
+        let mut labels = Vec::new();
+        let mut leftmost = Vec::new();
+        /* the nodes on the path from the root, each with its next child to
+        visit and the leftmost leaf of its first child */
+        let mut path: Vec<(&'a T, usize, Option<usize>)> = vec![(root, 0, None)];
+
+        while let Some(&(node, next, first_leaf)) = path.last() {
+            if let Some(child) = node.children().get(next) {
+                let last = path.len() - 1;
+                path[last].1 += 1;
+                path.push((child, 0, None));
+                continue;
+            }
+            path.pop();
+            let leaf = first_leaf.unwrap_or(labels.len());
+            labels.push(node.label());
+            leftmost.push(leaf);
+            if let Some(parent) = path.last_mut() {
+                parent.2.get_or_insert(leaf);
+            }
+        }
+
+        Self { labels, leftmost }
+    }
+
+    /* The nodes which have a left sibling, and the root, in increasing
+    order. They are the last nodes with their leftmost leaf */
+    fn keyroots(&self) -> Vec<usize> {
+        let mut last_with_leaf = vec![None; self.leftmost.len()];
+        for (i, &leaf) in self.leftmost.iter().enumerate() {
+            last_with_leaf[leaf] = Some(i);
+        }
+        let mut keyroots: Vec<usize> = last_with_leaf.into_iter().flatten().collect();
+        keyroots.sort_unstable();
+        keyroots
+    }
+}
+
+/// Calculates the tree edit distance between two ordered trees with the
+/// algorithm of Zhang and Shasha: the smallest number of nodes to relabel,
+/// insert or delete to turn `a` into `b`, each costing 1.
+///
+/// It needs `O(|a| * |b|)` memory. The time is `O(|a| * |b|)` multiplied by
+/// the smaller of the depth and the number of leaves of each tree, so flat
+/// or narrow trees are faster than bushy and deep ones.
+///
+/// ```
+/// use strsim::tree::{tree_edit_distance, BracketTree};
+///
+/// let a: BracketTree = "{f{d{a}{c{b}}}{e}}".parse().unwrap();
+/// let b: BracketTree = "{f{c{d{a}{b}}}{e}}".parse().unwrap();
+/// assert_eq!(2, tree_edit_distance(&a, &b));
+/// ```
+pub fn tree_edit_distance<T: TreeNode>(a: &T, b: &T) -> usize {
+    let a = Postorder::new(a);
+    let b = Postorder::new(b);
+    let (a_len, b_len) = (a.labels.len(), b.labels.len());
+
+    /* the distances between all pairs of subtrees */
+    let mut tree_distances = vec![0; a_len * b_len];
+    /* the distances between the forests left of two nodes within the
+    subtrees of two keyroots */
+    let mut forest_distances = vec![0; (a_len + 1) * (b_len + 1)];
+
+    for &i in &a.keyroots() {
+        for &j in &b.keyroots() {
+            let (i_leaf, j_leaf) = (a.leftmost[i], b.leftmost[j]);
+            let width = j - j_leaf + 2;
+            let forest = |x: usize, y: usize| x * width + y;
+
+            for x in 1..=i - i_leaf + 1 {
+                forest_distances[forest(x, 0)] = x;
+            }
+            for y in 0..width {
+                forest_distances[forest(0, y)] = y;
+            }
+
+            for x in 1..=i - i_leaf + 1 {
+                let a_node = i_leaf + x - 1;
+                for y in 1..width {
+                    let b_node = j_leaf + y - 1;
+                    let edit = min(
+                        forest_distances[forest(x - 1, y)] + 1,
+                        forest_distances[forest(x, y - 1)] + 1,
+                    );
+                    let distance = if a.leftmost[a_node] == i_leaf && b.leftmost[b_node] == j_leaf {
+                        /* both forests are whole trees */
+                        let cost = usize::from(a.labels[a_node] != b.labels[b_node]);
+                        let distance = min(edit, forest_distances[forest(x - 1, y - 1)] + cost);
+                        tree_distances[a_node * b_len + b_node] = distance;
+                        distance
+                    } else {
+                        let x_rest = a.leftmost[a_node] - i_leaf;
+                        let y_rest = b.leftmost[b_node] - j_leaf;
+                        min(
+                            edit,
+                            forest_distances[forest(x_rest, y_rest)]
+                                + tree_distances[a_node * b_len + b_node],
+                        )
+                    };
+                    forest_distances[forest(x, y)] = distance;
+                }
+            }
+        }
+    }
+
+    tree_distances[a_len * b_len - 1]
+}
+
+/// Parses two trees in the bracket notation of `BracketTree` and calculates
+/// their `tree_edit_distance`. Fails with `StrSimError::MalformedTree` if
+/// either of them can't be parsed.
+///
+/// ```
+/// use strsim::tree::bracket_tree_edit_distance;
+/// use strsim::StrSimError;
+///
+/// assert_eq!(Ok(0), bracket_tree_edit_distance("{a{b}}", " {a{b}} "));
+/// assert_eq!(Ok(1), bracket_tree_edit_distance("{a{b}}", "{a}"));
+/// assert_eq!(Err(StrSimError::MalformedTree), bracket_tree_edit_distance("{a{b}", "{a}"));
+/// ```
+pub fn bracket_tree_edit_distance(a: &str, b: &str) -> Result<usize, StrSimError> {
+    let a: BracketTree = a.parse()?;
+    let b: BracketTree = b.parse()?;
+    Ok(tree_edit_distance(&a, &b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distance(a: &str, b: &str) -> usize {
+        bracket_tree_edit_distance(a, b).unwrap()
+    }
+
+    #[test]
+    fn parse_bracket_notation() {
+        let tree: BracketTree = "{a{b}{}{c{d}}}".parse().unwrap();
+        assert_eq!(
+            BracketTree::new(
+                "a",
+                vec![
+                    BracketTree::new("b", Vec::new()),
+                    BracketTree::new("", Vec::new()),
+                    BracketTree::new("c", vec![BracketTree::new("d", Vec::new())]),
+                ]
+            ),
+            tree
+        );
+        let escaped: BracketTree = r"{ a\\b\} }".parse().unwrap();
+        assert_eq!(r" a\b} ", escaped.label());
+    }
+
+    #[test]
+    fn parse_malformed_trees() {
+        for s in [
+            "", "a", "{a", "{a}}", "}", "{a}{b}", "{a}b", "{a{b}c}", r"{a\", "{a{b}",
+        ] {
+            assert_eq!(
+                Err(StrSimError::MalformedTree),
+                s.parse::<BracketTree>(),
+                "{}",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn parse_deep_trees() {
+        let depth = 100_000;
+        let deep = format!("{}{}", "{a".repeat(depth), "}".repeat(depth));
+        let tree: BracketTree = deep.parse().unwrap();
+        let postorder = Postorder::new(&tree);
+        assert_eq!(depth, postorder.labels.len());
+        assert!(postorder.leftmost.iter().all(|&leaf| leaf == 0));
+        assert_eq!(vec![depth - 1], postorder.keyroots());
+    }
+
+    #[test]
+    fn postorder_and_keyroots() {
+        let tree: BracketTree = "{f{d{a}{c{b}}}{e}}".parse().unwrap();
+        let postorder = Postorder::new(&tree);
+        let labels: Vec<&str> = postorder.labels.iter().map(|l| l.as_str()).collect();
+        assert_eq!(vec!["a", "b", "c", "d", "e", "f"], labels);
+        assert_eq!(vec![0, 1, 1, 0, 4, 0], postorder.leftmost);
+        assert_eq!(vec![2, 4, 5], postorder.keyroots());
+    }
+
+    #[test]
+    fn tree_edit_distance_operations() {
+        assert_eq!(0, distance("{a}", "{a}"));
+        assert_eq!(1, distance("{a}", "{b}"));
+        assert_eq!(1, distance("{a{b}}", "{a}"));
+        assert_eq!(1, distance("{a}", "{a{b}}"));
+        // deleting b moves its children up
+        assert_eq!(1, distance("{a{b{c}{d}}}", "{a{c}{d}}"));
+        // inserting b adopts c and d
+        assert_eq!(1, distance("{a{c}{d}{e}}", "{a{b{c}{d}}{e}}"));
+        // the order of the children matters
+        assert_eq!(2, distance("{a{b}{c}}", "{a{c}{b}}"));
+        assert_eq!(3, distance("{a{b}{c}}", "{x}"));
+        // the example of Zhang and Shasha
+        assert_eq!(2, distance("{f{d{a}{c{b}}}{e}}", "{f{c{d{a}{b}}}{e}}"));
+    }
+
+    #[test]
+    fn tree_edit_distance_symmetric() {
+        let trees = [
+            "{a}",
+            "{a{b}{c}}",
+            "{a{b{c}{d}}{e}}",
+            "{x{a{b}}{c{d}{e{f}}}}",
+            "{a{a{a}}{a}}",
+            "{f{d{a}{c{b}}}{e}}",
+        ];
+        for a in trees.iter() {
+            for b in trees.iter() {
+                assert_eq!(distance(a, b), distance(b, a), "{} {}", a, b);
+                assert_eq!(a == b, distance(a, b) == 0);
+            }
+        }
+    }
+
+    #[test]
+    fn tree_edit_distance_of_paths_is_levenshtein() {
+        // trees without siblings behave like strings
+        let path = |s: &str| {
+            s.chars()
+                .rev()
+                .fold(None, |child: Option<BracketTree>, c| {
+                    Some(BracketTree::new(
+                        &c.to_string(),
+                        child.into_iter().collect(),
+                    ))
+                })
+                .unwrap()
+        };
+        for (a, b) in [("kitten", "sitting"), ("ab", "ba"), ("abc", "c")] {
+            assert_eq!(
+                crate::levenshtein(a, b),
+                tree_edit_distance(&path(a), &path(b))
+            );
+        }
+    }
+}
@@ -1,8 +1,50 @@
 extern crate strsim;
 
+use strsim::autocomplete::Autocomplete;
+use strsim::extract::{
+    best_match, matches_above_with, score_metrics, score_nullable, top_k, top_k_with,
+    top_k_with_cache, Metric, NullPolicy, ScoreCmp, TieBreak,
+};
+use strsim::fuzz;
+use strsim::matchers::{
+    address_similarity, company_similarity, email_similarity, is_duplicate_title, path_similarity,
+    phone_similarity, url_similarity, version_similarity, AddressScorer, CompanyScorer,
+    EmailScorer, PathScorer, PhoneScorer, TitleScorer, UrlScorer, VersionScorer,
+};
+use strsim::metric_space::{
+    can_prune, distance_bounds, Distance, FuzzyIndex, IndexKind, Levenshtein, Pivots, QueryBudget,
+    TrueMetric, VpTree, VpTreeView,
+};
+use strsim::phonetic::{soundex, PhoneticEncoder, Phonex, RefinedSoundex};
+use strsim::preprocess::{
+    transliterate_latin, LatinTransliterator, Locale, Preprocessor, Transliterator,
+};
+use strsim::record::{
+    block_by, candidate_pairs, cluster, cluster_matrix, random_pairs, sorted_neighborhood,
+    suggest_threshold, BlockingKey, FellegiSunter, Field, Linkage, MetricRegistry, Schema,
+    SchemaConfig, ScoreDistribution, SimilarityGraph,
+};
+use strsim::tree::{bracket_tree_edit_distance, tree_edit_distance, BracketTree};
 use strsim::{
-    damerau_levenshtein, hamming, jaro, jaro_winkler, levenshtein, normalized_damerau_levenshtein,
-    normalized_levenshtein, osa_distance,
+    bit_hamming, bounded_levenshtein, bounded_levenshtein_chars, common_prefix_len,
+    common_suffix_len, consensus, cosine, cyclic_levenshtein, damerau_levenshtein,
+    explain_jaro_winkler, generic_lcs_seq, hamming, hamming_positions, highlight_spans,
+    indel_distance, jaccard, jaro, jaro_bytes, jaro_winkler, jaro_winkler_bytes, jaro_winkler_f32,
+    jaro_winkler_long_strings, jaro_winkler_ppm, jaro_winkler_suffix, jaro_winkler_with_boost,
+    lcs_seq, levenshtein, levenshtein_alignment, levenshtein_chars, levenshtein_edit_counts,
+    levenshtein_editops, needleman_wunsch, normalized_damerau_levenshtein,
+    normalized_damerau_levenshtein_cutoff, normalized_indel, normalized_indel_with, normalized_lcs,
+    normalized_lcs_with, normalized_levenshtein, normalized_levenshtein_chars,
+    normalized_levenshtein_cutoff, normalized_levenshtein_f32, normalized_levenshtein_many,
+    normalized_levenshtein_many_cutoff, normalized_levenshtein_ppm, normalized_levenshtein_triage,
+    normalized_levenshtein_with, normalized_levenshtein_with_cutoff,
+    normalized_levenshtein_with_triage, normalized_osa, normalized_osa_with, osa_distance,
+    osa_distance_window, osa_edit_counts, overlap_coefficient, partial_normalized_levenshtein,
+    prefix_similarity, ratcliff_obershelp, sorensen_dice, suffix_similarity, tanimoto, to_ppm,
+    token_match_similarity, token_set_similarity, token_sort_similarity, tversky,
+    weighted_edit_distance, weighted_levenshtein, CharWeightTable, ConfusionMatrix, Control,
+    CostModel, Denominator, EditCounts, EditKind, LevenshteinEstimator, LowercaseNormalizer,
+    MergePolicy, MetricId, QgramSketch, StrSimError, TokenScorer, Triage, WinklerBoost,
 };
 
 macro_rules! assert_delta {
@@ -28,21 +70,171 @@ fn hamming_works() {
     }
 }
 
+#[test]
+fn hamming_positions_works() {
+    assert_eq!(Ok(vec![4, 5, 6]), hamming_positions("hamming", "hammers"));
+}
+
 #[test]
 fn levenshtein_works() {
     assert_eq!(3, levenshtein("kitten", "sitting"));
 }
 
+#[test]
+fn lcs_seq_works() {
+    assert_eq!(4, lcs_seq("kitten", "sitting"));
+    assert_eq!(3, generic_lcs_seq(&[1, 2, 3], &[1, 4, 2, 3]));
+}
+
+#[test]
+fn normalized_lcs_works() {
+    assert_delta!(0.57142, normalized_lcs("kitten", "sitting"));
+}
+
+#[test]
+fn normalized_with_denominators_works() {
+    assert_delta!(
+        0.66666,
+        normalized_lcs_with("kitten", "sitting", Denominator::Min)
+    );
+    assert_delta!(
+        normalized_indel("kitten", "sitting"),
+        normalized_indel_with("kitten", "sitting", Denominator::Sum)
+    );
+    assert_delta!(
+        normalized_osa("ab", "bca"),
+        normalized_osa_with("ab", "bca", Denominator::Max)
+    );
+}
+
+#[test]
+fn cyclic_levenshtein_works() {
+    assert_eq!(0, cyclic_levenshtein("SN-2024-0042", "2024-0042SN-"));
+    assert_eq!(1, cyclic_levenshtein("SN-2024-0042", "2024-0043SN-"));
+}
+
+#[test]
+fn threshold_tuning_works() {
+    let names = [
+        "Jonathan",
+        "Jonathon",
+        "Maria",
+        "Marie",
+        "Peter",
+        "Petra",
+        "Alexander",
+        "Alexandra",
+    ];
+    let scorer = |a: &&str, b: &&str| normalized_levenshtein(a, b);
+    let random = ScoreDistribution::new(&names, &random_pairs(names.len(), 10, 0), scorer);
+    assert_eq!(10, random.len());
+    assert!(random.quantile(0.5).unwrap() < 0.5);
+
+    let labeled: Vec<(f64, bool)> = [(0, 1, true), (2, 3, true), (4, 5, false), (6, 7, true)]
+        .iter()
+        .map(|&(i, j, is_match)| (scorer(&names[i], &names[j]), is_match))
+        .collect();
+    let estimate = suggest_threshold(&labeled).unwrap();
+    assert_delta!(7.0 / 9.0, estimate.threshold);
+    assert_eq!(1.0, estimate.precision);
+    assert_eq!(1.0, estimate.recall);
+}
+
+#[test]
+fn cluster_works() {
+    let names = ["Jon Smith", "John Smith", "Jonathan Smith", "Jane Doe"];
+    let scorer = |a: &&str, b: &&str| jaro_winkler(a, b);
+    assert_eq!(
+        vec![vec![0, 1, 2], vec![3]],
+        cluster(&names, 0.84, Linkage::Single, scorer)
+    );
+    assert_eq!(
+        vec![vec![0, 1], vec![2], vec![3]],
+        cluster(&names, 0.84, Linkage::Complete, scorer)
+    );
+
+    let similarities: Vec<f64> = names
+        .iter()
+        .flat_map(|a| names.iter().map(move |b| jaro_winkler(a, b)))
+        .collect();
+    assert_eq!(
+        vec![vec![0, 1, 2], vec![3]],
+        cluster_matrix(&similarities, names.len(), 0.84, Linkage::Single)
+    );
+}
+
+#[test]
+fn tree_edit_distance_works() {
+    assert_eq!(
+        Ok(1),
+        bracket_tree_edit_distance("{html{head}{body{p}}}", "{html{body{p}}}")
+    );
+    let a: BracketTree = "{+{x}{*{y}{z}}}".parse().unwrap();
+    let b: BracketTree = "{+{x}{*{y}{w}}}".parse().unwrap();
+    assert_eq!(1, tree_edit_distance(&a, &b));
+    assert_eq!(
+        Err(StrSimError::MalformedTree),
+        "{+{x}".parse::<BracketTree>()
+    );
+}
+
+#[test]
+fn indel_distance_works() {
+    assert_eq!(5, indel_distance("kitten", "sitting"));
+    assert_delta!(0.61538, normalized_indel("kitten", "sitting"));
+}
+
+#[test]
+fn needleman_wunsch_works() {
+    assert_eq!(0, needleman_wunsch("GATTACA", "GCATGCU", 1, 1, 1));
+    assert_eq!(-3, needleman_wunsch("kitten", "sitting", 0, 1, 1));
+}
+
+#[test]
+fn ratcliff_obershelp_works() {
+    assert_delta!(
+        0.6,
+        ratcliff_obershelp("GESTALT PATTERN MATCHING", "GESTALT PRACTICE")
+    );
+    assert_delta!(0.5, ratcliff_obershelp("ab", "ba"));
+}
+
 #[test]
 fn normalized_levenshtein_works() {
     assert_delta!(0.57142, normalized_levenshtein("kitten", "sitting"));
 }
 
+#[test]
+fn partial_normalized_levenshtein_works() {
+    assert_delta!(
+        1.0,
+        partial_normalized_levenshtein("Hobbit", "The Hobbit, or There and Back Again")
+    );
+    assert_delta!(0.66666, partial_normalized_levenshtein("kitten", "sitting"));
+}
+
+#[test]
+fn f32_variants_work() {
+    assert_delta!(0.57142_f32, normalized_levenshtein_f32("kitten", "sitting"));
+    assert_delta!(
+        0.866_f32,
+        jaro_winkler_f32("cheeseburger", "cheese fries"),
+        0.001
+    );
+}
+
 #[test]
 fn osa_distance_works() {
     assert_eq!(3, osa_distance("ac", "cba"));
 }
 
+#[test]
+fn osa_distance_window_works() {
+    assert_eq!(3, osa_distance_window("ac", "cba", 1));
+    assert_eq!(1, osa_distance_window("receipt", "reciept", 1));
+    assert_eq!(1, osa_distance_window("recipe", "repice", 2));
+}
+
 #[test]
 fn damerau_levenshtein_works() {
     assert_eq!(2, damerau_levenshtein("ac", "cba"));
@@ -69,3 +261,957 @@ fn jaro_works() {
 fn jaro_winkler_works() {
     assert_delta!(0.866, jaro_winkler("cheeseburger", "cheese fries"), 0.001);
 }
+
+#[test]
+fn jaro_bytes_works() {
+    assert_delta!(0.944, jaro_bytes(b"martha", b"marhta"), 0.001);
+    assert_delta!(0.961, jaro_winkler_bytes(b"martha", b"marhta"), 0.001);
+}
+
+#[test]
+fn explain_jaro_winkler_works() {
+    let explanation = explain_jaro_winkler("cheeseburger", "cheese fries");
+    assert_eq!(4, explanation.prefix_length);
+    assert_delta!(0.866, explanation.similarity, 0.001);
+}
+
+#[test]
+fn jaro_winkler_suffix_works() {
+    assert_delta!(0.902, jaro_winkler_suffix("henderson", "anderson"), 0.001);
+}
+
+#[test]
+fn jaro_winkler_with_boost_works() {
+    let (a, b) = ("john f. kennedy", "john k. kennedy");
+    assert!(jaro_winkler_with_boost(a, b, WinklerBoost::Both) > jaro_winkler(a, b));
+    assert!(jaro_winkler_with_boost(a, b, WinklerBoost::Both) > jaro_winkler_suffix(a, b));
+}
+
+#[test]
+fn jaro_winkler_long_strings_works() {
+    assert_delta!(0.869, jaro_winkler_long_strings("dwayne", "duane"), 0.001);
+}
+
+#[test]
+fn common_affix_len_works() {
+    let (a, b) = ("préfixe", "préface");
+    assert_eq!(4, common_prefix_len(a, b).chars);
+    assert_eq!("préf", &a[..common_prefix_len(a, b).bytes]);
+    let suffix = common_suffix_len(a, b);
+    assert_eq!((1, 1), (suffix.chars, suffix.bytes));
+}
+
+#[test]
+fn prefix_similarity_works() {
+    assert_delta!(0.5, prefix_similarity("pre", "prefix", Denominator::Max));
+}
+
+#[test]
+fn suffix_similarity_works() {
+    assert_delta!(
+        0.4,
+        suffix_similarity("report.txt", "notes.txt", Denominator::Max)
+    );
+}
+
+#[test]
+fn token_sort_similarity_works() {
+    assert_delta!(1.0, token_sort_similarity("new york mets", "mets new york"));
+}
+
+#[test]
+fn token_set_similarity_works() {
+    assert_delta!(
+        1.0,
+        token_set_similarity("new york mets", "new york mets vs atlanta braves")
+    );
+}
+
+#[test]
+fn token_match_similarity_works() {
+    assert_delta!(0.875, token_match_similarity("Jon Smith", "Smith John"));
+}
+
+#[test]
+fn numeric_aware_token_scorer_works() {
+    let scorer = TokenScorer::default().numeric_aware(true);
+    assert!(
+        scorer.match_similarity("Apt 12", "Apt 13") > scorer.match_similarity("Apt 12", "Apt 1200")
+    );
+}
+
+#[test]
+fn stopwords_token_scorer_works() {
+    let scorer = TokenScorer::default().stopwords(&["the"]);
+    assert_delta!(
+        1.0,
+        scorer.sort_similarity("The Hilton Hotel", "Hilton Hotel")
+    );
+}
+
+#[test]
+fn token_normalizer_works() {
+    let scorer = TokenScorer::default().normalizer(&LowercaseNormalizer);
+    assert_delta!(1.0, scorer.set_similarity("The Hilton", "the hilton hotel"));
+
+    let stem = |token: &str| token.trim_end_matches("ning").to_string();
+    let scorer = TokenScorer::default().normalizer(&stem);
+    assert_delta!(1.0, scorer.sort_similarity("running late", "late run"));
+}
+
+#[cfg(feature = "stopwords")]
+#[test]
+fn stopword_lists_work() {
+    let scorer = TokenScorer::default().stopwords(strsim::ENGLISH_STOPWORDS);
+    assert_delta!(
+        1.0,
+        scorer.set_similarity("The Bank of England", "Bank England")
+    );
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_build_works() {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+
+    struct Unpark(std::thread::Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let words: Vec<String> = (0..100).map(|i| i.to_string()).collect();
+    let mut future = strsim::asynchronous::build_vp_tree(Levenshtein, words);
+    let waker = Waker::from(Arc::new(Unpark(std::thread::current())));
+    let tree = loop {
+        if let Poll::Ready(tree) = Pin::new(&mut future).poll(&mut Context::from_waker(&waker)) {
+            break tree.unwrap();
+        }
+        std::thread::park();
+    };
+    assert_eq!(vec![("7", 0)], tree.find_within("7", 0));
+}
+
+#[test]
+fn fuzz_ratio_works() {
+    assert_eq!(97, fuzz::ratio("this is a test", "this is a test!"));
+}
+
+#[test]
+fn fuzz_token_sort_ratio_works() {
+    assert_eq!(
+        100,
+        fuzz::token_sort_ratio("fuzzy wuzzy was a bear", "wuzzy fuzzy was a bear")
+    );
+}
+
+#[test]
+fn fuzz_token_set_ratio_works() {
+    assert_eq!(
+        100,
+        fuzz::token_set_ratio("fuzzy was a bear", "fuzzy fuzzy was a bear")
+    );
+}
+
+#[test]
+fn record_works() {
+    let schema = Schema::new()
+        .field(Field::new("name", jaro_winkler))
+        .field(Field::new("city", normalized_levenshtein));
+    let comparison = schema
+        .compare(&[Some("martha"), None], &[Some("martha"), Some("york")])
+        .unwrap();
+    assert_delta!(1.0, comparison.score);
+}
+
+#[test]
+fn soundex_works() {
+    assert_eq!("R163", soundex("Robert"));
+}
+
+#[test]
+fn blocking_works() {
+    let names = ["Robert", "Rupert", "Alice"];
+    let blocks = block_by(&names, |name| Some(BlockingKey::Soundex.key(name)));
+    assert_eq!(vec![(0, 1)], candidate_pairs(blocks.values()));
+}
+
+#[test]
+fn sorted_neighborhood_works() {
+    let names = ["Robert", "Alice", "Roberta", "Bob"];
+    let pairs = sorted_neighborhood(&names, 2, |name| Some(name.to_lowercase()));
+    assert_eq!(vec![(0, 2), (0, 3), (1, 3)], pairs);
+}
+
+#[test]
+fn similarity_graph_works() {
+    let names = ["Jon Smith", "John Smith", "Jane Doe"];
+    let pairs = candidate_pairs(std::iter::once(&vec![0, 1, 2]));
+    let graph = SimilarityGraph::new(&names, &pairs, 0.8, |a, b| jaro_winkler(a, b));
+    assert_eq!(1, graph.edges().len());
+    assert_eq!(1, graph.neighbors(1).len());
+    assert!(graph.to_csv().starts_with("a,b,similarity\n0,1,"));
+    assert!(graph
+        .to_dot_with_labels(&names)
+        .contains("label=\"Jane Doe\""));
+}
+
+#[test]
+fn score_cache_works() {
+    let names = ["Jon Smith", "John Smith", "Jane Doe"];
+    let mut cache = std::collections::HashMap::new();
+    let best = top_k_with_cache(
+        "Jon Smyth",
+        &names,
+        3,
+        |a, b, _| Some(jaro_winkler(a, b)),
+        &mut cache,
+    );
+    assert_eq!(&"Jon Smith", best[0].0);
+    assert_eq!(3, cache.len());
+
+    let pairs = [(0, 1), (1, 2), (0, 1)];
+    let mut cache = std::collections::HashMap::new();
+    let mut calls = 0;
+    let graph = SimilarityGraph::with_cache(
+        &names,
+        &pairs,
+        0.8,
+        |a, b| {
+            calls += 1;
+            jaro_winkler(a, b)
+        },
+        &mut cache,
+        &Control::new(),
+    )
+    .unwrap();
+    assert_eq!(2, graph.edges().len());
+    assert_eq!(2, calls);
+}
+
+#[test]
+fn match_report_works() {
+    let schema = Schema::new()
+        .field(Field::new("name", jaro_winkler).metric_name("jaro_winkler"))
+        .field(Field::new("city", normalized_levenshtein));
+    let report = schema
+        .report(
+            &[Some("Jon"), Some("Boston")],
+            &[Some("John"), Some("Boston")],
+            0.9,
+        )
+        .unwrap();
+    assert!(report.is_match);
+    assert_eq!(Some("jaro_winkler"), report.fields[0].metric);
+    let json = report.to_json();
+    assert!(json.contains(r#"{"name":"city","metric":null,"version":""#));
+    assert!(json.contains(r#""a":"Boston","b":"Boston""#));
+
+    let model = FellegiSunter::new(vec![0.9, 0.8], vec![0.1, 0.2], 0.1).unwrap();
+    let report = model
+        .report(
+            &schema,
+            &[Some("Jon"), Some("Boston")],
+            &[Some("John"), Some("Boston")],
+            0.0,
+        )
+        .unwrap();
+    assert!(report.is_match);
+    assert_eq!(
+        model.match_weight(&[Some(true), Some(true)]),
+        report.match_weight
+    );
+}
+
+#[test]
+fn schema_config_works() {
+    let config = SchemaConfig::from_json(
+        r#"{"fields": [{"name": "email", "metric": "email_similarity"}], "threshold": 0.9}"#,
+    )
+    .unwrap();
+    let schema = MetricRegistry::standard().schema(&config).unwrap();
+    let report = schema
+        .report(
+            &[Some("Jane.Doe+news@gmial.com")],
+            &[Some("jane.doe@gmail.com")],
+            config.threshold.unwrap(),
+        )
+        .unwrap();
+    assert!(report.is_match);
+    assert_eq!(Some("email_similarity"), report.fields[0].metric);
+    assert_eq!(
+        Err(StrSimError::UnknownMetric),
+        MetricRegistry::new().schema(&config).map(|_| ())
+    );
+}
+
+#[test]
+fn serialized_index_works() {
+    let words: Vec<String> = (0..1000).map(|i| format!("item{}", i)).collect();
+    let index = FuzzyIndex::new(Levenshtein, words);
+    let read = FuzzyIndex::from_bytes(Levenshtein, &index.to_bytes()).unwrap();
+    assert_eq!(IndexKind::VpTree, read.kind());
+    assert_eq!(index.k_nearest("item77", 3), read.k_nearest("item77", 3));
+
+    let bytes = VpTree::new(Levenshtein, (0..1000).map(|i| i.to_string()).collect()).to_bytes();
+    let view = VpTreeView::new(Levenshtein, &bytes).unwrap();
+    assert_eq!(vec![("77", 0), ("7", 1)], view.k_nearest("77", 2));
+    assert_eq!(
+        Some(StrSimError::MalformedIndex),
+        FuzzyIndex::from_bytes(Levenshtein, b"strsim").err()
+    );
+}
+
+#[test]
+fn metric_id_works() {
+    let id = Levenshtein.id();
+    assert_eq!("levenshtein", id.name());
+    assert!(id.is_compatible(&MetricId::new("levenshtein", "1.1.0")));
+    assert!(!id.is_compatible(&MetricId::new("levenshtein", "2.0.0")));
+    assert!(!id.is_compatible(&id.parameter(&[1])));
+
+    let schema = Schema::new().field(Field::new("name", normalized_levenshtein).metric_id(id));
+    let report = schema.report(&[Some("a")], &[Some("b")], 0.5).unwrap();
+    assert_eq!(Some(id), report.fields[0].metric_id());
+    assert!(report
+        .to_json()
+        .contains(r#""metric":"levenshtein","version":"1.0.0""#));
+}
+
+#[test]
+fn email_similarity_works() {
+    assert_eq!(
+        1.0,
+        email_similarity("Jane.Doe+news@gmial.com", "jane.doe@gmail.com")
+    );
+    let scorer = EmailScorer::default().weights(1.0, 0.0);
+    assert_eq!(1.0, scorer.similarity("jane@gmail.com", "jane@yahoo.com"));
+}
+
+#[test]
+fn url_similarity_works() {
+    assert_eq!(
+        1.0,
+        url_similarity(
+            "HTTP://Example.com:80/a?b=2&a=1",
+            "http://example.com/a?a=1&b=2"
+        )
+    );
+    let path_only = UrlScorer::default().weights(0.0, 0.0, 1.0, 0.0);
+    assert_eq!(
+        1.0,
+        path_only.similarity("https://a.com/x", "http://b.org/x?q")
+    );
+}
+
+#[test]
+fn path_similarity_works() {
+    assert_eq!(1.0, path_similarity("src/Main.rs", ".\\src\\main.rs"));
+    let query = "util/strings.py";
+    assert!(
+        path_similarity(query, "lib/util/strings.py") > path_similarity(query, "util/string.c")
+    );
+    let directories_only = PathScorer::default().weights(0.0, 1.0);
+    assert_eq!(1.0, directories_only.similarity("a/b/x.txt", "a/b/y.txt"));
+}
+
+#[test]
+fn version_similarity_works() {
+    assert_eq!(1.0, version_similarity("v1.0.210", "1.0.210+build"));
+    let versions = ["2.0.210", "1.0.209", "1.1.0"];
+    let best = versions
+        .iter()
+        .max_by(|a, b| {
+            version_similarity("1.0.210", a)
+                .partial_cmp(&version_similarity("1.0.210", b))
+                .unwrap()
+        })
+        .unwrap();
+    assert_eq!("1.0.209", *best);
+    let flat = VersionScorer::default().decay(1.0);
+    assert_eq!(2.5 / 3.0, flat.similarity("1.2.3", "1.2.4"));
+}
+
+#[test]
+fn duplicate_title_works() {
+    assert!(is_duplicate_title(
+        "The Hitchhiker's Guide to the Galaxy",
+        "Hitchhikers Guide To The Galaxy, The"
+    ));
+    assert!(!is_duplicate_title(
+        "The Godfather",
+        "The Godfather Part II"
+    ));
+    let strict = TitleScorer::default().stopwords(&[]).threshold(1.0);
+    assert!(is_duplicate_title("The Matrix", "A Matrix"));
+    assert!(!strict.is_duplicate("The Matrix", "A Matrix"));
+    assert_eq!(1.0, strict.similarity("The Matrix", "THE MATRIX!"));
+}
+
+#[test]
+fn phone_similarity_works() {
+    assert_eq!(1.0, phone_similarity("+1 (555) 010-4477", "555.010.4477"));
+    let scorer = PhoneScorer::default().country_code(44);
+    assert_eq!(1.0, scorer.similarity("020 7946 0958", "+44 20 7946 0958"));
+}
+
+#[test]
+fn address_similarity_works() {
+    assert_eq!(1.0, address_similarity("12 Main St", "12 main street"));
+    let scorer = AddressScorer::default().house_number_weight(1.0);
+    assert_eq!(0.0, scorer.similarity("12 Main St", "13 Main St"));
+}
+
+#[test]
+fn company_similarity_works() {
+    assert_eq!(
+        1.0,
+        company_similarity("Smith & Sons Ltd.", "Smith and Sons")
+    );
+    let strict = CompanyScorer::default().keep_legal_forms(true);
+    assert!(strict.similarity("Acme GmbH", "Acme Inc") < 1.0);
+}
+
+#[test]
+fn cost_model_works() {
+    struct Transpositions;
+
+    impl CostModel for Transpositions {
+        fn transpose_cost(&self, _a: char, _b: char, _position: usize) -> Option<usize> {
+            Some(1)
+        }
+    }
+
+    assert_eq!(1, weighted_edit_distance("ab", "ba", &Transpositions));
+    assert_eq!(
+        3,
+        weighted_edit_distance("kitten", "sitting", &CharWeightTable::default())
+    );
+}
+
+#[test]
+fn levenshtein_alignment_works() {
+    let edits = levenshtein_alignment("cat", "cut")
+        .filter(|step| step.kind != EditKind::Keep)
+        .count();
+    assert_eq!(1, edits);
+    assert_eq!(3, levenshtein_editops("kitten", "sitting").len());
+}
+
+#[test]
+fn tanimoto_works() {
+    assert_eq!(Ok(0.5), tanimoto(&[0b11], &[0b01]));
+    assert_eq!(Ok(1), bit_hamming(&[0b11], &[0b01]));
+}
+
+#[test]
+fn overlap_coefficient_works() {
+    assert_delta!(1.0, overlap_coefficient("sealed", "resealed"));
+    assert_delta!(0.0, overlap_coefficient("a", "b"));
+}
+
+#[test]
+fn cosine_works() {
+    assert_delta!(0.8, cosine("healed", "sealed"));
+    assert_delta!(0.0, cosine("a", "b"));
+}
+
+#[test]
+fn tversky_works() {
+    assert_delta!(
+        sorensen_dice("healed", "sealed"),
+        tversky("healed", "sealed", 0.5, 0.5)
+    );
+    assert_delta!(1.0, tversky("sealed", "resealed", 1.0, 0.0));
+}
+
+#[test]
+fn jaccard_works() {
+    assert_delta!(2.0 / 3.0, jaccard("healed", "sealed", 2));
+    assert_delta!(0.6, jaccard("healed", "sealed", 3));
+}
+
+#[test]
+fn phonetic_encoders_work() {
+    assert_eq!("T6036084", RefinedSoundex.encode("testing"));
+    assert!(Phonex.sounds_alike("Philips", "Filip"));
+}
+
+#[test]
+fn preprocessor_works() {
+    let jamo = Preprocessor::new().decompose_hangul(true);
+    assert_eq!(1, jamo.compare("학교", "핵교", levenshtein));
+    assert_eq!(5, jamo.apply("학교").chars().count());
+
+    let japanese = Preprocessor::new().fold_width(true).fold_kana(true);
+    assert_eq!("がいど abc", japanese.apply("ｶﾞｲﾄﾞ ａｂｃ"));
+
+    let compatibility = Preprocessor::new().fold_compatibility(true);
+    assert_eq!("5kg (1)", compatibility.apply("5㎏ ⑴"));
+
+    let turkish = Preprocessor::new().fold_case(Some(Locale::Turkish));
+    assert_eq!(0, turkish.compare("İZMİR", "izmir", levenshtein));
+}
+
+#[test]
+fn transliteration_works() {
+    let german = LatinTransliterator::default().german_umlauts(true);
+    assert_eq!(0, german.compare("Müller", "Mueller", levenshtein));
+    assert_eq!("Lodz", transliterate_latin("Łódź"));
+
+    let preprocessor =
+        Preprocessor::new().transliterate_latin(Some(LatinTransliterator::default()));
+    assert_eq!(
+        1.0,
+        preprocessor.compare("Łódź", "Lodz", normalized_levenshtein)
+    );
+}
+
+#[test]
+fn autocomplete_works() {
+    let mut words = Autocomplete::new();
+    words.insert("strsim", 3);
+    words.insert("string", 10);
+    words.insert("strength", 1);
+
+    let completions = words.complete("strim", 1, 2);
+    assert_eq!(2, completions.len());
+    assert_eq!("string", completions[0].word);
+    assert_eq!("strsim", completions[1].word);
+}
+
+#[test]
+fn matches_above_works() {
+    let words = ["apple", "apply", "maple", "banana"];
+    let matches: Vec<&&str> =
+        matches_above_with("appel", &words, 0.6, normalized_levenshtein_cutoff)
+            .map(|(word, _)| word)
+            .collect();
+    assert_eq!(vec![&"apple", &"apply"], matches);
+}
+
+#[test]
+fn levenshtein_estimator_works() {
+    let a = "lorem ipsum dolor sit amet ".repeat(400);
+    let b = a.replace("dolor", "dolar");
+    let estimate = LevenshteinEstimator::new(500, 10).estimate(&a, &b);
+    let exact = normalized_levenshtein(&a, &b);
+    assert!(estimate.lower <= exact && exact <= estimate.upper);
+}
+
+#[test]
+fn qgram_sketch_works() {
+    let query = QgramSketch::new("levenshtein");
+    assert!(query.may_be_within(&QgramSketch::new("levenstein"), 1));
+    assert!(!query.may_be_within(&QgramSketch::new("jaro winkler"), 2));
+    assert!(query.may_reach(&QgramSketch::new("levenshtien"), 0.8));
+}
+
+#[test]
+fn metric_space_works() {
+    fn pivots<M: TrueMetric>(metric: M) -> Pivots<M> {
+        Pivots::new(metric, vec!["abc".to_string()])
+    }
+    let pivots = pivots(Levenshtein);
+    let (a, b) = (pivots.distances("abcdef"), pivots.distances("a"));
+    assert!(can_prune(&a, &b, 0));
+    assert!(!can_prune(&a, &b, 1));
+    assert_eq!((1, 5), distance_bounds(3, 2));
+}
+
+#[test]
+fn vp_tree_works() {
+    let names = ["Jon", "John", "Joan", "Jane", "Bob"];
+    let tree = VpTree::new(
+        Levenshtein,
+        names.iter().map(|name| name.to_string()).collect(),
+    );
+    assert_eq!(
+        vec![("Jon", 0), ("John", 1), ("Joan", 1)],
+        tree.find_within("Jon", 1)
+    );
+    assert_eq!(vec![("Jane", 0), ("Jon", 2)], tree.k_nearest("Jane", 2));
+}
+
+#[test]
+fn fuzzy_index_works() {
+    let names = ["Jon", "John", "Joan", "Jane", "Bob"];
+    let index = FuzzyIndex::new(
+        Levenshtein,
+        names.iter().map(|name| name.to_string()).collect(),
+    );
+    assert_eq!(IndexKind::LinearScan, index.kind());
+    assert_eq!(5, index.len());
+    assert_eq!(
+        vec![("Jon", 0), ("John", 1), ("Joan", 1)],
+        index.find_within("Jon", 1)
+    );
+    assert_eq!(vec![("Jane", 0), ("Jon", 2)], index.k_nearest("Jane", 2));
+}
+
+#[test]
+fn query_budget_works() {
+    let words: Vec<String> = (0..1000).map(|i| format!("user{}", i)).collect();
+    let index = FuzzyIndex::new(Levenshtein, words);
+    let result = index.k_nearest_with_budget("user42", 3, QueryBudget::new().max_candidates(20));
+    assert!(!result.complete);
+    assert_eq!(3, result.matches.len());
+    let result = index.k_nearest_with_budget("user42", 3, QueryBudget::new());
+    assert!(result.complete);
+    assert_eq!(("user42", 0), result.matches[0]);
+}
+
+#[test]
+fn fuzzy_index_insert_and_remove_works() {
+    let mut index = FuzzyIndex::new(Levenshtein, vec!["Jon".to_string(), "Jane".to_string()]);
+    index.insert("John".to_string());
+    assert!(index.remove("Jon"));
+    assert!(!index.remove("Jon"));
+    assert_eq!(2, index.len());
+    assert_eq!(vec![("John", 1)], index.find_within("Jon", 1));
+}
+
+#[test]
+fn cancellation_works() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    let names = ["Jon", "John", "Joan"];
+    let flag = AtomicBool::new(false);
+    let control = Control::new().cancel_flag(&flag);
+    let graph =
+        SimilarityGraph::with_control(&names, &[(0, 1)], 0.5, |a, b| jaro(a, b), &control).unwrap();
+    assert_eq!(1, graph.edges().len());
+
+    flag.store(true, Ordering::Relaxed);
+    let index = FuzzyIndex::with_control(
+        Levenshtein,
+        (0..1000).map(|i| i.to_string()).collect(),
+        0,
+        &control,
+    );
+    assert!(matches!(index, Err(StrSimError::Cancelled)));
+}
+
+#[test]
+fn progress_works() {
+    use std::sync::Mutex;
+
+    let words: Vec<String> = (0..1000).map(|i| i.to_string()).collect();
+    let last = Mutex::new((0, 0));
+    let progress = |done, total| *last.lock().unwrap() = (done, total);
+    let control = Control::new().on_progress(&progress);
+    VpTree::with_control(Levenshtein, words, &control).unwrap();
+    assert_eq!((1000, 1000), *last.lock().unwrap());
+}
+
+#[test]
+fn thread_safety_works() {
+    use std::sync::Arc;
+    use std::thread;
+    use strsim::metric_space::DamerauLevenshtein;
+    use strsim::preprocess::Preprocessor;
+    use strsim::record::FellegiSunter;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Autocomplete>();
+    assert_send_sync::<Control<'_>>();
+    assert_send_sync::<FuzzyIndex<Levenshtein>>();
+    assert_send_sync::<VpTree<DamerauLevenshtein>>();
+    assert_send_sync::<Pivots<Levenshtein>>();
+    assert_send_sync::<QgramSketch>();
+    assert_send_sync::<LevenshteinEstimator>();
+    assert_send_sync::<TokenScorer<'_>>();
+    assert_send_sync::<TitleScorer<'_>>();
+    assert_send_sync::<Preprocessor>();
+    assert_send_sync::<Schema>();
+    assert_send_sync::<FellegiSunter>();
+    assert_send_sync::<SimilarityGraph>();
+
+    let words: Vec<String> = (0..2000).map(|i| format!("w{:x}", i * 7919)).collect();
+    let index = Arc::new(FuzzyIndex::new(Levenshtein, words.clone()));
+    assert_eq!(IndexKind::VpTree, index.kind());
+    let expected: Vec<Vec<(String, usize)>> = words[..8]
+        .iter()
+        .map(|word| {
+            index
+                .find_within(word, 1)
+                .into_iter()
+                .map(|(found, distance)| (found.to_string(), distance))
+                .collect()
+        })
+        .collect();
+
+    let workers: Vec<_> = expected
+        .into_iter()
+        .enumerate()
+        .map(|(i, expected)| {
+            let index = Arc::clone(&index);
+            let query = words[i].clone();
+            thread::spawn(move || {
+                (0..50).all(|_| {
+                    let found: Vec<(String, usize)> = index
+                        .find_within(&query, 1)
+                        .into_iter()
+                        .map(|(found, distance)| (found.to_string(), distance))
+                        .collect();
+                    found == expected
+                })
+            })
+        })
+        .collect();
+    assert!(workers.into_iter().all(|worker| worker.join().unwrap()));
+}
+
+#[test]
+fn score_cmp_works() {
+    let candidates = ["ab", "ba", "abc"];
+    // "ab" and "ba" are equally far from "xx"
+    let best = best_match("xx", &candidates, normalized_levenshtein).unwrap();
+    assert_eq!("ab", *best.0);
+    let cmp = ScoreCmp::default().tie_break(TieBreak::Alphabetical);
+    let reversed = ["ba", "ab", "abc"];
+    assert_eq!(
+        "ab",
+        *cmp.best_match("xx", &reversed, normalized_levenshtein)
+            .unwrap()
+            .0
+    );
+    let top = top_k("abcx", &candidates, 2, normalized_levenshtein);
+    assert_eq!(
+        vec!["abc", "ab"],
+        top.iter().map(|(c, _)| **c).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        top,
+        top_k_with("abcx", &candidates, 2, normalized_levenshtein_cutoff)
+    );
+}
+
+#[test]
+fn normalized_levenshtein_many_works() {
+    let candidates = vec![
+        "apple".to_string(),
+        "apply".to_string(),
+        "pineapple".to_string(),
+    ];
+    let similarities = normalized_levenshtein_many("appel", &candidates);
+    assert_eq!(3, similarities.len());
+    assert!((similarities[0] - 0.6).abs() < 0.00001);
+    let pruned = normalized_levenshtein_many_cutoff("appel", &candidates, 0.6);
+    assert_eq!(Some(similarities[0]), pruned[0]);
+    assert_eq!(None, pruned[2]);
+}
+
+#[test]
+fn score_metrics_works() {
+    let candidates = vec!["apple".to_string(), "maple".to_string()];
+    let scores = score_metrics(
+        "appel",
+        &candidates,
+        &[Metric::Jaro, Metric::NormalizedLevenshtein],
+    );
+    assert_eq!(2, scores.len());
+    assert_eq!(
+        normalized_levenshtein_many("appel", &candidates).as_slice(),
+        scores.get(Metric::NormalizedLevenshtein).unwrap()
+    );
+    assert_eq!(jaro("appel", "maple"), scores.get(Metric::Jaro).unwrap()[1]);
+}
+
+#[test]
+fn missing_value_works() {
+    use strsim::record::MissingValue;
+
+    assert_eq!(Some(1.0), MissingValue::Compare.score("", "", jaro_winkler));
+    assert_eq!(None, MissingValue::Ignore.score("", "", jaro_winkler));
+    assert_eq!(
+        Some(0.0),
+        MissingValue::Score(0.0).score("", "", normalized_damerau_levenshtein)
+    );
+}
+
+#[test]
+fn score_nullable_works() {
+    let names = vec![Some("Jon"), None, Some("John")];
+    let scores = score_nullable(Some("John"), &names, NullPolicy::Propagate, jaro_winkler);
+    assert_eq!(None, scores[1]);
+    assert_eq!(Some(1.0), scores[2]);
+    let skipped: Vec<f64> = scores.into_iter().flatten().collect();
+    assert_eq!(2, skipped.len());
+}
+
+#[test]
+fn levenshtein_chars_works() {
+    let candidates: Vec<Vec<char>> = ["Berlin", "Bern", "Bergen"]
+        .iter()
+        .map(|city| city.chars().collect())
+        .collect();
+    let distances: Vec<usize> = candidates
+        .iter()
+        .map(|candidate| levenshtein_chars(candidate, "Berln"))
+        .collect();
+    assert_eq!(vec![1, 1, 2], distances);
+    assert_eq!(None, bounded_levenshtein_chars(&candidates[2], "Berln", 1));
+    assert!((normalized_levenshtein_chars(&candidates[0], "Berln") - 0.83333).abs() < 0.00001);
+}
+
+#[test]
+fn normalized_cutoff_works() {
+    assert_eq!(
+        Some(normalized_levenshtein_with(
+            "kitten",
+            "sitting",
+            Denominator::Mean
+        )),
+        normalized_levenshtein_with_cutoff("kitten", "sitting", Denominator::Mean, 0.5)
+    );
+    assert_eq!(
+        None,
+        normalized_damerau_levenshtein_cutoff("ca", "abc", 0.5)
+    );
+    assert_eq!(
+        Some(1.0),
+        normalized_damerau_levenshtein_cutoff("", "", 1.0)
+    );
+}
+
+#[test]
+fn triage_works() {
+    let triage = |b| normalized_levenshtein_triage("Jonathan Smith", b, 0.7, 0.9);
+    assert_eq!(Triage::Accept, triage("Jonathon Smith"));
+    assert_eq!(Triage::Reject, triage("Jon Smith"));
+    assert_eq!(
+        Triage::Review(normalized_levenshtein("Jonathan Smith", "Jonathan Smyth-X")),
+        triage("Jonathan Smyth-X")
+    );
+    assert_eq!(
+        Triage::Review(0.5),
+        normalized_levenshtein_with_triage("kitten", "sitting", Denominator::Min, 0.5, 0.6)
+    );
+}
+
+#[test]
+fn max_distance_works() {
+    let (a, b) = ("Levenshtein", "Levenstein");
+    let limit = Denominator::Max.max_distance(0.9, 11, 10);
+    assert_eq!(1, limit);
+    let distance = bounded_levenshtein(a, b, limit).unwrap();
+    assert_eq!(
+        normalized_levenshtein(a, b),
+        Denominator::Max.similarity(distance, 11, 10)
+    );
+}
+
+#[test]
+fn determinism_works() {
+    use strsim::record::FellegiSunter;
+
+    let model = FellegiSunter::new(vec![0.9, 0.8], vec![0.1, 0.3], 0.05).unwrap();
+    let scores = [
+        jaro("Friedrich Nietzsche", "Jean-Paul Sartre"),
+        jaro_winkler("cheeseburger", "cheese fries"),
+        normalized_levenshtein("kitten", "sitting"),
+        normalized_damerau_levenshtein("levenshtein", "löwenbräu"),
+        sorensen_dice("healed", "sealed"),
+        model.match_weight(&[Some(true), Some(false)]),
+        model.match_probability(&[Some(true), Some(true)]),
+    ];
+    // the exact bits are part of the guarantee, not just the values
+    let bits: Vec<u64> = scores.iter().map(|score| score.to_bits()).collect();
+    assert_eq!(
+        vec![
+            0x3fd9_14a8_dec3_d14b,
+            0x3feb_bbbb_bbbb_bbbb,
+            0x3fe2_4924_9249_2492,
+            0x3fd1_745d_1745_d174,
+            0x3fe9_9999_9999_999a,
+            0x3ff5_cd16_4897_7275,
+            0x3fe1_dc47_711d_c477,
+        ],
+        bits
+    );
+}
+
+#[test]
+fn ppm_works() {
+    assert_eq!(571_429, normalized_levenshtein_ppm("kitten", "sitting"));
+    assert_eq!(
+        to_ppm(jaro_winkler("dixon", "dicksonx")),
+        jaro_winkler_ppm("dixon", "dicksonx")
+    );
+    assert_eq!(666_667, Denominator::Sum.similarity_ppm(1, 1, 2));
+}
+
+#[test]
+fn edit_counts_works() {
+    // an OCR result with a swapped pair and a missing letter
+    let (truth, ocr) = ("transposition", "tarnsposiion");
+    let counts = osa_edit_counts(truth, ocr);
+    assert_eq!(1, counts.transpositions);
+    assert_eq!(1, counts.deletions);
+    assert_eq!(osa_distance(truth, ocr), counts.distance());
+    let counts: EditCounts = levenshtein_edit_counts(truth, ocr);
+    assert_eq!(levenshtein(truth, ocr), counts.distance());
+    assert_eq!(0, counts.transpositions);
+}
+
+#[test]
+fn confusion_matrix_works() {
+    let mut confusions = ConfusionMatrix::new();
+    for (truth, asr) in [("their", "there"), ("their car", "there car")] {
+        confusions.add_steps(truth, asr, levenshtein_editops(truth, asr));
+    }
+    let costs = confusions.costs(4);
+    assert!(weighted_levenshtein("their", "there", &costs) < 2 * 4);
+}
+
+#[test]
+fn consensus_works() {
+    let records = vec![
+        "Acme Corporation".to_string(),
+        "Acme Corporaton".to_string(),
+        "Acme Corp.oration".to_string(),
+    ];
+    assert_eq!(
+        "Acme Corporation",
+        consensus(&records, MergePolicy::Majority)
+    );
+    assert_eq!(
+        "Anna",
+        consensus(&["Anna", "Ana"], MergePolicy::PreferLonger)
+    );
+}
+
+#[test]
+fn highlight_spans_works() {
+    let (a, b) = ("Straße 5", "Strasse 15");
+    let spans = highlight_spans(a, b, levenshtein_alignment(a, b));
+    let kept: Vec<&str> = spans.a_kept.iter().map(|range| &a[range.clone()]).collect();
+    assert_eq!(vec!["Stra", "e 5"], kept);
+    let edited: Vec<&str> = spans
+        .b_edited
+        .iter()
+        .map(|range| &b[range.clone()])
+        .collect();
+    assert_eq!(vec!["ss", "1"], edited);
+}
+
+#[test]
+fn alignment_byte_offsets_works() {
+    let (a, b) = ("Grüße", "Grusse");
+    for step in levenshtein_alignment(a, b) {
+        assert_eq!(
+            a.chars().skip(step.a_index).collect::<String>(),
+            a[step.a_byte..]
+        );
+        assert_eq!(
+            b.chars().skip(step.b_index).collect::<String>(),
+            b[step.b_byte..]
+        );
+    }
+}